@@ -6,9 +6,17 @@
 //! - Create and add a blocking filter for IPv4 outbound connections
 //! - Use transactions to ensure atomic operations
 
+#[cfg(target_os = "windows")]
 use std::io;
+#[cfg(target_os = "windows")]
 use wfp::{ActionType, FilterBuilder, FilterEngineBuilder, Layer, SubLayerBuilder, Transaction};
 
+#[cfg(not(target_os = "windows"))]
+fn main() {
+    eprintln!("This example requires Windows");
+}
+
+#[cfg(target_os = "windows")]
 fn main() -> io::Result<()> {
     println!("Creating WFP filter engine...");
 
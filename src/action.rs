@@ -2,6 +2,12 @@
 
 use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWP_ACTION_BLOCK;
 use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWP_ACTION_PERMIT;
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWP_ACTION_TYPE;
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWP_ACTION_FLAG_CALLOUT, FWP_ACTION_FLAG_NON_TERMINATING, FWP_ACTION_FLAG_TERMINATING,
+};
+
+use crate::util::flags_type;
 
 /// Specifies the action to take when a filter matches network traffic.
 ///
@@ -25,3 +31,31 @@ pub enum ActionType {
     /// Allow the network traffic that matches the filter to proceed.
     Permit = FWP_ACTION_PERMIT,
 }
+
+impl ActionType {
+    /// Returns the action type that corresponds to a raw `FWP_ACTION_TYPE` value, if any.
+    ///
+    /// Callout actions have no corresponding action type.
+    pub fn from_raw(raw: FWP_ACTION_TYPE) -> Option<Self> {
+        [Self::Block, Self::Permit]
+            .into_iter()
+            .find(|action| *action as u32 == raw)
+    }
+}
+
+flags_type! {
+    /// A mask of action type bits, used to select filters by their action in a
+    /// [`FilterEnumTemplate`](crate::FilterEnumTemplate).
+    ///
+    /// A filter matches if its `FWP_ACTION_TYPE` has at least one bit in common with the mask.
+    /// Raw action type bits can be included using [`ActionMask::from_bits_retain`].
+    pub struct ActionMask {
+        /// Actions that always return a terminating decision, such as [`ActionType::Block`] and
+        /// [`ActionType::Permit`] (`FWP_ACTION_FLAG_TERMINATING`).
+        const TERMINATING = FWP_ACTION_FLAG_TERMINATING;
+        /// Actions that never return a terminating decision (`FWP_ACTION_FLAG_NON_TERMINATING`).
+        const NON_TERMINATING = FWP_ACTION_FLAG_NON_TERMINATING;
+        /// Actions that invoke a callout (`FWP_ACTION_FLAG_CALLOUT`).
+        const CALLOUT = FWP_ACTION_FLAG_CALLOUT;
+    }
+}
@@ -0,0 +1,416 @@
+//! Backend that uses the Base Filtering Engine through the `Fwpm*` API.
+
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{
+    ERROR_NO_MORE_ITEMS, ERROR_NOT_SUPPORTED, ERROR_SUCCESS, HANDLE,
+};
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWP_EMPTY, FWP_FLOAT, FWP_INT8, FWP_INT16, FWP_INT32, FWP_UINT8, FWP_UINT16, FWP_UINT32,
+    FWP_VALUE0, FWPM_CALLOUT0, FWPM_ENGINE_OPTION, FWPM_FILTER_ENUM_TEMPLATE0, FWPM_FILTER0,
+    FWPM_LAYER0, FWPM_PROVIDER_CONTEXT0, FWPM_PROVIDER0, FWPM_SESSION0, FWPM_SUBLAYER0,
+    FwpmCalloutCreateEnumHandle0, FwpmCalloutDestroyEnumHandle0, FwpmCalloutEnum0,
+    FwpmEngineClose0, FwpmEngineGetOption0, FwpmEngineOpen0, FwpmEngineSetOption0, FwpmFilterAdd0,
+    FwpmFilterCreateEnumHandle0, FwpmFilterDeleteById0, FwpmFilterDeleteByKey0,
+    FwpmFilterDestroyEnumHandle0, FwpmFilterEnum0, FwpmFilterGetById0, FwpmFilterGetByKey0,
+    FwpmFreeMemory0, FwpmLayerCreateEnumHandle0, FwpmLayerDestroyEnumHandle0, FwpmLayerEnum0,
+    FwpmLayerGetByKey0, FwpmProviderAdd0, FwpmProviderContextCreateEnumHandle0,
+    FwpmProviderContextDestroyEnumHandle0, FwpmProviderContextEnum0, FwpmProviderCreateEnumHandle0,
+    FwpmProviderDeleteByKey0, FwpmProviderDestroyEnumHandle0, FwpmProviderEnum0,
+    FwpmProviderGetByKey0, FwpmSessionCreateEnumHandle0, FwpmSessionDestroyEnumHandle0,
+    FwpmSessionEnum0, FwpmSubLayerAdd0, FwpmSubLayerCreateEnumHandle0, FwpmSubLayerDeleteByKey0,
+    FwpmSubLayerDestroyEnumHandle0, FwpmSubLayerEnum0, FwpmSubLayerGetByKey0,
+    FwpmTransactionAbort0, FwpmTransactionBegin0, FwpmTransactionCommit0,
+};
+use windows_sys::Win32::System::Rpc::RPC_C_AUTHN_DEFAULT;
+use windows_sys::core::GUID;
+
+use super::{Backend, EnumBatch, FetchedObject};
+use crate::error::{Result, WfpError};
+use crate::util::guid_to_u128;
+
+/// A session with the Base Filtering Engine.
+pub(crate) struct FwpmBackend {
+    handle: HANDLE,
+    session_key: GUID,
+}
+
+// SAFETY: Crossing thread-boundaries is fine
+unsafe impl Send for FwpmBackend {}
+
+impl FwpmBackend {
+    /// Opens a session using [`FwpmEngineOpen0`].
+    ///
+    /// The session key must be set, so that the session can be identified later.
+    ///
+    /// [`FwpmEngineOpen0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmengineopen0
+    pub(crate) fn open(session: &FWPM_SESSION0) -> Result<Self> {
+        let mut handle: HANDLE = ptr::null_mut();
+
+        // SAFETY:
+        // - All parameters are valid: null pointers are acceptable for serverName and authInfo
+        // - RPC_C_AUTHN_DEFAULT is a valid authentication service constant
+        // - session is a properly initialized FWPM_SESSION0 structure
+        // - handle is a valid mutable pointer to receive the engine handle
+        let status = unsafe {
+            FwpmEngineOpen0(
+                ptr::null_mut(),
+                RPC_C_AUTHN_DEFAULT as u32,
+                ptr::null_mut(),
+                session,
+                &mut handle,
+            )
+        };
+        check(status)?;
+        Ok(Self {
+            handle,
+            session_key: session.sessionKey,
+        })
+    }
+}
+
+impl Drop for FwpmBackend {
+    fn drop(&mut self) {
+        // SAFETY:
+        // - self.handle is a valid engine handle obtained from FwpmEngineOpen0
+        // - We are the sole owner of this handle (FwpmBackend is not Clone)
+        // - This is called exactly once during drop, preventing double-free
+        unsafe { FwpmEngineClose0(self.handle) };
+    }
+}
+
+impl Backend for FwpmBackend {
+    fn raw_handle(&self) -> HANDLE {
+        self.handle
+    }
+
+    fn session_key(&self) -> GUID {
+        self.session_key
+    }
+
+    fn session_create_enum_handle(&self) -> Result<HANDLE> {
+        // SAFETY: A null template enumerates all sessions
+        create_enum_handle(|enum_handle| unsafe {
+            FwpmSessionCreateEnumHandle0(self.handle, ptr::null(), enum_handle)
+        })
+    }
+
+    fn session_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_SESSION0>> {
+        // SAFETY: The engine and enumeration handles are valid
+        enum_batch(|entries, num_returned| unsafe {
+            FwpmSessionEnum0(self.handle, enum_handle, num_entries, entries, num_returned)
+        })
+    }
+
+    fn session_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        // SAFETY: The engine and enumeration handles are valid
+        check(unsafe { FwpmSessionDestroyEnumHandle0(self.handle, enum_handle) })
+    }
+
+    fn engine_get_option(&self, option: FWPM_ENGINE_OPTION) -> Result<FWP_VALUE0> {
+        let mut value: *mut FWP_VALUE0 = ptr::null_mut();
+        // SAFETY:
+        // - self.handle is a valid engine handle
+        // - value is a valid pointer to receive the value
+        check(unsafe { FwpmEngineGetOption0(self.handle, option, &mut value) })?;
+
+        // SAFETY: On success, value points to a valid FWP_VALUE0
+        let copy = unsafe { *value };
+        // SAFETY: The value was allocated by FwpmEngineGetOption0 and is no longer used
+        unsafe { FwpmFreeMemory0((&mut value) as *mut _ as *mut _) };
+
+        // Other types refer to memory that has just been freed
+        match copy.r#type {
+            FWP_EMPTY | FWP_UINT8 | FWP_UINT16 | FWP_UINT32 | FWP_INT8 | FWP_INT16 | FWP_INT32
+            | FWP_FLOAT => Ok(copy),
+            _ => Err(WfpError::Other(ERROR_NOT_SUPPORTED as i32)),
+        }
+    }
+
+    unsafe fn engine_set_option(
+        &self,
+        option: FWPM_ENGINE_OPTION,
+        value: &FWP_VALUE0,
+    ) -> Result<()> {
+        // SAFETY:
+        // - self.handle is a valid engine handle
+        // - The caller guarantees that all pointers in value are valid
+        check(unsafe { FwpmEngineSetOption0(self.handle, option, value) })
+    }
+
+    fn transaction_begin(&self, flags: u32) -> Result<()> {
+        // SAFETY: self.handle is a valid engine handle
+        check(unsafe { FwpmTransactionBegin0(self.handle, flags) })
+    }
+
+    fn transaction_commit(&self) -> Result<()> {
+        // SAFETY: self.handle is a valid engine handle
+        check(unsafe { FwpmTransactionCommit0(self.handle) })
+    }
+
+    fn transaction_abort(&self) -> Result<()> {
+        // SAFETY: self.handle is a valid engine handle
+        check(unsafe { FwpmTransactionAbort0(self.handle) })
+    }
+
+    unsafe fn filter_add(&self, filter: &FWPM_FILTER0) -> Result<(u64, GUID)> {
+        let mut id = 0;
+        // SAFETY:
+        // - self.handle is a valid engine handle
+        // - The caller guarantees that all pointers in `filter` are valid
+        // - A NULL security descriptor is acceptable
+        check(unsafe { FwpmFilterAdd0(self.handle, filter, ptr::null_mut(), &mut id) })?;
+
+        if guid_to_u128(&filter.filterKey) != 0 {
+            return Ok((id, filter.filterKey));
+        }
+
+        // The key was generated by BFE, so look it up
+        let key = self.filter_get_by_id(id)?.get().filterKey;
+        Ok((id, key))
+    }
+
+    fn filter_delete_by_id(&self, id: u64) -> Result<()> {
+        // SAFETY: The handle and ID are valid
+        check(unsafe { FwpmFilterDeleteById0(self.handle, id) })
+    }
+
+    fn filter_delete_by_key(&self, key: &GUID) -> Result<()> {
+        // SAFETY: The handle and GUID are valid
+        check(unsafe { FwpmFilterDeleteByKey0(self.handle, key) })
+    }
+
+    fn filter_get_by_id(&self, id: u64) -> Result<FetchedObject<FWPM_FILTER0>> {
+        // SAFETY: The handle and ID are valid
+        fetch_object(|filter| unsafe { FwpmFilterGetById0(self.handle, id, filter) })
+    }
+
+    fn filter_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_FILTER0>> {
+        // SAFETY: The handle and GUID are valid
+        fetch_object(|filter| unsafe { FwpmFilterGetByKey0(self.handle, key, filter) })
+    }
+
+    unsafe fn filter_create_enum_handle(
+        &self,
+        template: Option<&FWPM_FILTER_ENUM_TEMPLATE0>,
+    ) -> Result<HANDLE> {
+        // SAFETY: A null template enumerates all filters. Otherwise, the caller guarantees
+        // that all pointers in the template are valid
+        create_enum_handle(|enum_handle| unsafe {
+            FwpmFilterCreateEnumHandle0(
+                self.handle,
+                template.map_or(ptr::null(), ptr::from_ref),
+                enum_handle,
+            )
+        })
+    }
+
+    fn filter_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_FILTER0>> {
+        // SAFETY: The engine and enumeration handles are valid
+        enum_batch(|entries, num_returned| unsafe {
+            FwpmFilterEnum0(self.handle, enum_handle, num_entries, entries, num_returned)
+        })
+    }
+
+    fn filter_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        // SAFETY: The engine and enumeration handles are valid
+        check(unsafe { FwpmFilterDestroyEnumHandle0(self.handle, enum_handle) })
+    }
+
+    unsafe fn provider_add(&self, provider: &FWPM_PROVIDER0) -> Result<()> {
+        // SAFETY:
+        // - self.handle is a valid engine handle
+        // - The caller guarantees that all pointers in `provider` are valid
+        // - A NULL security descriptor is acceptable
+        check(unsafe { FwpmProviderAdd0(self.handle, provider, ptr::null_mut()) })
+    }
+
+    fn provider_delete_by_key(&self, key: &GUID) -> Result<()> {
+        // SAFETY: The handle and GUID are valid
+        check(unsafe { FwpmProviderDeleteByKey0(self.handle, key) })
+    }
+
+    fn provider_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_PROVIDER0>> {
+        // SAFETY: The handle and GUID are valid
+        fetch_object(|provider| unsafe { FwpmProviderGetByKey0(self.handle, key, provider) })
+    }
+
+    fn provider_create_enum_handle(&self) -> Result<HANDLE> {
+        // SAFETY: A null template enumerates all providers
+        create_enum_handle(|enum_handle| unsafe {
+            FwpmProviderCreateEnumHandle0(self.handle, ptr::null(), enum_handle)
+        })
+    }
+
+    fn provider_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_PROVIDER0>> {
+        // SAFETY: The engine and enumeration handles are valid
+        enum_batch(|entries, num_returned| unsafe {
+            FwpmProviderEnum0(self.handle, enum_handle, num_entries, entries, num_returned)
+        })
+    }
+
+    fn provider_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        // SAFETY: The engine and enumeration handles are valid
+        check(unsafe { FwpmProviderDestroyEnumHandle0(self.handle, enum_handle) })
+    }
+
+    unsafe fn sublayer_add(&self, sublayer: &FWPM_SUBLAYER0) -> Result<()> {
+        // SAFETY:
+        // - self.handle is a valid engine handle
+        // - The caller guarantees that all pointers in `sublayer` are valid
+        // - A NULL security descriptor is acceptable
+        check(unsafe { FwpmSubLayerAdd0(self.handle, sublayer, ptr::null_mut()) })
+    }
+
+    fn sublayer_delete_by_key(&self, key: &GUID) -> Result<()> {
+        // SAFETY: The handle and GUID are valid
+        check(unsafe { FwpmSubLayerDeleteByKey0(self.handle, key) })
+    }
+
+    fn sublayer_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_SUBLAYER0>> {
+        // SAFETY: The handle and GUID are valid
+        fetch_object(|sublayer| unsafe { FwpmSubLayerGetByKey0(self.handle, key, sublayer) })
+    }
+
+    fn sublayer_create_enum_handle(&self) -> Result<HANDLE> {
+        // SAFETY: A null template enumerates all sublayers
+        create_enum_handle(|enum_handle| unsafe {
+            FwpmSubLayerCreateEnumHandle0(self.handle, ptr::null(), enum_handle)
+        })
+    }
+
+    fn sublayer_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_SUBLAYER0>> {
+        // SAFETY: The engine and enumeration handles are valid
+        enum_batch(|entries, num_returned| unsafe {
+            FwpmSubLayerEnum0(self.handle, enum_handle, num_entries, entries, num_returned)
+        })
+    }
+
+    fn sublayer_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        // SAFETY: The engine and enumeration handles are valid
+        check(unsafe { FwpmSubLayerDestroyEnumHandle0(self.handle, enum_handle) })
+    }
+
+    fn callout_create_enum_handle(&self) -> Result<HANDLE> {
+        // SAFETY: A null template enumerates all callouts
+        create_enum_handle(|enum_handle| unsafe {
+            FwpmCalloutCreateEnumHandle0(self.handle, ptr::null(), enum_handle)
+        })
+    }
+
+    fn callout_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_CALLOUT0>> {
+        // SAFETY: The engine and enumeration handles are valid
+        enum_batch(|entries, num_returned| unsafe {
+            FwpmCalloutEnum0(self.handle, enum_handle, num_entries, entries, num_returned)
+        })
+    }
+
+    fn callout_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        // SAFETY: The engine and enumeration handles are valid
+        check(unsafe { FwpmCalloutDestroyEnumHandle0(self.handle, enum_handle) })
+    }
+
+    fn layer_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_LAYER0>> {
+        // SAFETY: The handle and GUID are valid
+        fetch_object(|layer| unsafe { FwpmLayerGetByKey0(self.handle, key, layer) })
+    }
+
+    fn layer_create_enum_handle(&self) -> Result<HANDLE> {
+        // SAFETY: A null template enumerates all layers
+        create_enum_handle(|enum_handle| unsafe {
+            FwpmLayerCreateEnumHandle0(self.handle, ptr::null(), enum_handle)
+        })
+    }
+
+    fn layer_enum(&self, enum_handle: HANDLE, num_entries: u32) -> Result<EnumBatch<FWPM_LAYER0>> {
+        // SAFETY: The engine and enumeration handles are valid
+        enum_batch(|entries, num_returned| unsafe {
+            FwpmLayerEnum0(self.handle, enum_handle, num_entries, entries, num_returned)
+        })
+    }
+
+    fn layer_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        // SAFETY: The engine and enumeration handles are valid
+        check(unsafe { FwpmLayerDestroyEnumHandle0(self.handle, enum_handle) })
+    }
+
+    fn provider_context_create_enum_handle(&self) -> Result<HANDLE> {
+        // SAFETY: A null template enumerates all provider contexts
+        create_enum_handle(|enum_handle| unsafe {
+            FwpmProviderContextCreateEnumHandle0(self.handle, ptr::null(), enum_handle)
+        })
+    }
+
+    fn provider_context_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_PROVIDER_CONTEXT0>> {
+        // SAFETY: The engine and enumeration handles are valid
+        enum_batch(|entries, num_returned| unsafe {
+            FwpmProviderContextEnum0(self.handle, enum_handle, num_entries, entries, num_returned)
+        })
+    }
+
+    fn provider_context_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        // SAFETY: The engine and enumeration handles are valid
+        check(unsafe { FwpmProviderContextDestroyEnumHandle0(self.handle, enum_handle) })
+    }
+}
+
+/// Convert a status code returned by a `Fwpm*` function into a result.
+fn check(status: u32) -> Result<()> {
+    if status != ERROR_SUCCESS {
+        return Err(WfpError::from_code(status as i32));
+    }
+    Ok(())
+}
+
+/// Calls a `Fwpm*CreateEnumHandle0` function that writes a handle to its argument.
+fn create_enum_handle(f: impl FnOnce(*mut HANDLE) -> u32) -> Result<HANDLE> {
+    let mut enum_handle = HANDLE::default();
+    check(f(&mut enum_handle))?;
+    Ok(enum_handle)
+}
+
+/// Calls a `Fwpm*Enum0` function that writes an array of entries and its length to its
+/// arguments.
+fn enum_batch<T>(f: impl FnOnce(*mut *mut *mut T, *mut u32) -> u32) -> Result<EnumBatch<T>> {
+    let mut entries = ptr::null_mut();
+    let mut num_returned = 0;
+    let status = f(&mut entries, &mut num_returned);
+    if status == ERROR_NO_MORE_ITEMS {
+        return Ok(EnumBatch::empty());
+    }
+    check(status)?;
+    // SAFETY: The entries were returned by a `Fwpm*Enum0` function
+    Ok(unsafe { EnumBatch::from_fwpm(entries, num_returned) })
+}
+
+/// Calls a `Fwpm*GetByKey0` or `Fwpm*GetById0` function that writes an object to its argument.
+fn fetch_object<T>(f: impl FnOnce(*mut *mut T) -> u32) -> Result<FetchedObject<T>> {
+    let mut object = ptr::null_mut();
+    check(f(&mut object))?;
+    // SAFETY: The object was returned by a `Fwpm*GetByKey0` or `Fwpm*GetById0` function
+    Ok(unsafe { FetchedObject::from_fwpm(object) })
+}
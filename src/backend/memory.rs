@@ -0,0 +1,1296 @@
+//! In-memory stand-in for the Base Filtering Engine.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ptr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use windows_sys::Win32::Foundation::{ERROR_NOT_SUPPORTED, FWP_E_TYPE_MISMATCH, HANDLE};
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWP_ACTION_FLAG_CALLOUT, FWP_BYTE_BLOB, FWP_EMPTY, FWP_FILTER_ENUM_FLAG_BEST_TERMINATING_MATCH,
+    FWP_FILTER_ENUM_FLAG_BOOTTIME_ONLY, FWP_FILTER_ENUM_FLAG_INCLUDE_BOOTTIME,
+    FWP_FILTER_ENUM_FLAG_INCLUDE_DISABLED, FWP_FILTER_ENUM_FLAG_SORTED,
+    FWP_FILTER_ENUM_FULLY_CONTAINED, FWP_FILTER_ENUM_OVERLAPPING, FWP_UINT8, FWP_UINT32,
+    FWP_UINT64, FWP_VALUE0, FWPM_ACTION0, FWPM_CALLOUT0, FWPM_ENGINE_OPTION,
+    FWPM_ENGINE_OPTION_MAX, FWPM_FIELD_FLAGS, FWPM_FIELD_IP_ADDRESS, FWPM_FIELD_RAW_DATA,
+    FWPM_FIELD0, FWPM_FILTER_CONDITION0, FWPM_FILTER_ENUM_TEMPLATE0, FWPM_FILTER_FLAG_BOOTTIME,
+    FWPM_FILTER_FLAG_DISABLED, FWPM_FILTER_FLAG_PERSISTENT, FWPM_FILTER0, FWPM_FILTER0_0,
+    FWPM_LAYER_FLAG_BUILTIN, FWPM_LAYER_FLAG_KERNEL, FWPM_LAYER0, FWPM_PROVIDER_CONTEXT0,
+    FWPM_PROVIDER_FLAG_PERSISTENT, FWPM_PROVIDER0, FWPM_SESSION_FLAG_DYNAMIC, FWPM_SESSION0,
+    FWPM_SUBLAYER_FLAG_PERSISTENT, FWPM_SUBLAYER_UNIVERSAL, FWPM_SUBLAYER0, FWPM_TXN_READ_ONLY,
+};
+use windows_sys::core::GUID;
+
+use super::{Backend, EnumBatch, FetchedObject};
+use crate::condition::{Condition, ConditionField};
+use crate::error::{ConditionError, ObjectType, Result, WfpError};
+use crate::layer::Layer;
+use crate::util::{byte_blob_as_slice, generate_guid, guid_eq, guid_to_u128, wcslen};
+
+/// An in-memory stand-in for the Base Filtering Engine (BFE).
+///
+/// Sessions are opened using
+/// [`FilterEngineBuilder::open_in_memory`](crate::FilterEngineBuilder::open_in_memory). All
+/// sessions opened on the same `MemoryBfe` (or a clone of it) share the same set of objects,
+/// just like sessions opened on the real BFE.
+///
+/// It mimics the behavior of the BFE that is relevant when installing policies:
+///
+/// - Adding an object whose key is already in use fails with
+///   [`WfpError::AlreadyExists`](crate::WfpError::AlreadyExists).
+/// - Filters must reference a built-in layer and an existing sublayer and provider, and
+///   sublayers must reference an existing provider. Providers and sublayers that are still referenced cannot be deleted.
+/// - Only one session at a time can hold a write transaction, while any number of sessions can
+///   hold a read-only transaction. Changes made in a transaction are rolled back when it is
+///   aborted, or when the session is closed.
+/// - Objects added by a dynamic session are deleted when the session is closed.
+/// - Filter enumeration templates select filters by layer, provider, action, callout and flags.
+///   Templates with conditions, a provider context template or
+///   `FWP_FILTER_ENUM_FLAG_BEST_TERMINATING_MATCH` fail with `ERROR_NOT_SUPPORTED`.
+/// - Engine options are shared by all sessions. Every option is an `FWP_UINT32`, and is
+///   initially zero.
+/// - Every built-in [`Layer`](crate::Layer) can be enumerated, along with the fields that are
+///   available at it. Layers have no display data or runtime ID.
+///
+/// No traffic is ever filtered, and only the built-in universal sublayer is predefined. Callouts
+/// and provider contexts cannot be added, so enumerating them returns nothing.
+///
+/// # Example
+///
+/// ```
+/// use wfp::{FilterEngineBuilder, MemoryBfe, Transaction};
+///
+/// let bfe = MemoryBfe::new();
+/// let mut engine = FilterEngineBuilder::default()
+///     .dynamic()
+///     .open_in_memory(&bfe)
+///     .unwrap();
+/// let transaction = Transaction::new(&mut engine).unwrap();
+/// transaction.commit().unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct MemoryBfe {
+    state: Arc<Mutex<State>>,
+}
+
+impl MemoryBfe {
+    /// Creates a new engine that contains no objects except built-in ones.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a new session described by `session`.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `session` must be valid.
+    pub(crate) unsafe fn open_session(&self, session: &FWPM_SESSION0) -> MemorySession {
+        let mut state = self.lock();
+        state.next_session_id += 1;
+        let id = state.next_session_id;
+
+        // SAFETY: The caller guarantees that the pointers are valid
+        let stored = unsafe {
+            StoredSession {
+                id,
+                key: session.sessionKey,
+                name: copy_wide(session.displayData.name),
+                description: copy_wide(session.displayData.description),
+                flags: session.flags,
+                txn_wait_timeout: session.txnWaitTimeoutInMSec,
+            }
+        };
+        state.sessions.push(stored);
+
+        MemorySession {
+            bfe: self.clone(),
+            id,
+            key: session.sessionKey,
+            dynamic: session.flags & FWPM_SESSION_FLAG_DYNAMIC != 0,
+            enums: Mutex::default(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The state is always consistent between operations, so a poisoned lock is fine to use
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[derive(Default)]
+struct State {
+    objects: Objects,
+    transaction: Option<ActiveTransaction>,
+    /// Sessions that hold a read-only transaction
+    readers: Vec<u64>,
+    sessions: Vec<StoredSession>,
+    options: [u32; FWPM_ENGINE_OPTION_MAX as usize],
+    next_session_id: u64,
+    next_filter_id: u64,
+}
+
+struct ActiveTransaction {
+    session: u64,
+    /// The objects at the time the transaction began
+    snapshot: Objects,
+}
+
+#[derive(Clone)]
+struct Objects {
+    filters: Vec<StoredFilter>,
+    providers: Vec<StoredProvider>,
+    sublayers: Vec<StoredSublayer>,
+}
+
+impl Default for Objects {
+    fn default() -> Self {
+        Self {
+            filters: vec![],
+            providers: vec![],
+            sublayers: vec![StoredSublayer {
+                key: FWPM_SUBLAYER_UNIVERSAL,
+                name: None,
+                description: None,
+                flags: FWPM_SUBLAYER_FLAG_PERSISTENT,
+                provider_key: None,
+                provider_data: Box::default(),
+                weight: 0,
+                owner: None,
+            }],
+        }
+    }
+}
+
+impl Objects {
+    fn provider(&self, key: &GUID) -> Option<&StoredProvider> {
+        self.providers.iter().find(|p| guid_eq(&p.key, key))
+    }
+
+    fn sublayer(&self, key: &GUID) -> Option<&StoredSublayer> {
+        self.sublayers.iter().find(|s| guid_eq(&s.key, key))
+    }
+
+    /// Remove all objects added by the dynamic session `session`.
+    fn remove_owned_by(&mut self, session: u64) {
+        self.filters.retain(|f| f.owner != Some(session));
+        self.sublayers.retain(|s| s.owner != Some(session));
+        self.providers.retain(|p| p.owner != Some(session));
+    }
+}
+
+impl State {
+    /// Returns the objects that `session` can observe.
+    ///
+    /// Uncommitted changes are only visible to the session that made them.
+    fn visible_objects(&self, session: u64) -> &Objects {
+        match &self.transaction {
+            Some(txn) if txn.session != session => &txn.snapshot,
+            _ => &self.objects,
+        }
+    }
+
+    /// Returns the objects that `session` may modify.
+    ///
+    /// Fails if the session holds a read-only transaction, or if another session holds a
+    /// transaction.
+    fn writable_objects(&mut self, session: u64) -> Result<&mut Objects> {
+        if self.readers.contains(&session) {
+            return Err(WfpError::IncompatibleTxn);
+        }
+        match &self.transaction {
+            Some(txn) if txn.session != session => Err(WfpError::Timeout),
+            None if !self.readers.is_empty() => Err(WfpError::Timeout),
+            _ => Ok(&mut self.objects),
+        }
+    }
+
+    /// Returns whether `session` holds a transaction.
+    fn in_transaction(&self, session: u64) -> bool {
+        self.readers.contains(&session)
+            || self
+                .transaction
+                .as_ref()
+                .is_some_and(|txn| txn.session == session)
+    }
+}
+
+/// A session opened on a [`MemoryBfe`].
+pub(crate) struct MemorySession {
+    bfe: MemoryBfe,
+    id: u64,
+    key: GUID,
+    dynamic: bool,
+    enums: Mutex<Enumerations>,
+}
+
+#[derive(Default)]
+struct Enumerations {
+    next_handle: usize,
+    filters: HashMap<usize, VecDeque<StoredFilter>>,
+    sessions: HashMap<usize, VecDeque<StoredSession>>,
+    providers: HashMap<usize, VecDeque<StoredProvider>>,
+    sublayers: HashMap<usize, VecDeque<StoredSublayer>>,
+    layers: HashMap<usize, VecDeque<Layer>>,
+    /// Enumerations of objects that cannot be added, and are therefore always empty
+    callouts: HashSet<usize>,
+    provider_contexts: HashSet<usize>,
+}
+
+impl Enumerations {
+    /// Returns a new enumeration handle.
+    fn new_handle(&mut self) -> usize {
+        self.next_handle += 1;
+        self.next_handle
+    }
+}
+
+impl MemorySession {
+    /// Returns the owner to record for objects added by this session.
+    fn owner(&self) -> Option<u64> {
+        self.dynamic.then_some(self.id)
+    }
+
+    fn enums(&self) -> MutexGuard<'_, Enumerations> {
+        self.enums.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Fails if a persistent object is added by a dynamic session.
+    fn check_persistence(&self, persistent: bool) -> Result<()> {
+        if persistent && self.dynamic {
+            return Err(WfpError::DynamicSessionInProgress);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MemorySession {
+    fn drop(&mut self) {
+        let mut state = self.bfe.lock();
+        if let Some(txn) = state.transaction.take_if(|txn| txn.session == self.id) {
+            state.objects = txn.snapshot;
+        }
+        state.readers.retain(|&reader| reader != self.id);
+        state.sessions.retain(|session| session.id != self.id);
+        if self.dynamic {
+            state.objects.remove_owned_by(self.id);
+            if let Some(txn) = &mut state.transaction {
+                txn.snapshot.remove_owned_by(self.id);
+            }
+        }
+    }
+}
+
+impl Backend for MemorySession {
+    fn session_key(&self) -> GUID {
+        self.key
+    }
+
+    fn session_create_enum_handle(&self) -> Result<HANDLE> {
+        let sessions = self.bfe.lock().sessions.clone();
+        let mut enums = self.enums();
+        let handle = enums.new_handle();
+        enums.sessions.insert(handle, sessions.into());
+        Ok(ptr::without_provenance_mut(handle))
+    }
+
+    fn session_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_SESSION0>> {
+        let sessions = take_entries(&mut self.enums().sessions, enum_handle, num_entries)?;
+        let sessions: Vec<Box<RawSession>> = sessions.into_iter().map(RawSession::new).collect();
+        Ok(owned_batch(sessions, |session| &session.raw))
+    }
+
+    fn session_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        destroy_enumeration(&mut self.enums().sessions, enum_handle)
+    }
+
+    fn engine_get_option(&self, option: FWPM_ENGINE_OPTION) -> Result<FWP_VALUE0> {
+        let state = self.bfe.lock();
+        let val = *usize::try_from(option)
+            .ok()
+            .and_then(|option| state.options.get(option))
+            .ok_or(WfpError::InvalidParameter)?;
+        let mut value = FWP_VALUE0 {
+            r#type: FWP_UINT32,
+            ..Default::default()
+        };
+        value.Anonymous.uint32 = val;
+        Ok(value)
+    }
+
+    unsafe fn engine_set_option(
+        &self,
+        option: FWPM_ENGINE_OPTION,
+        value: &FWP_VALUE0,
+    ) -> Result<()> {
+        let mut state = self.bfe.lock();
+        let stored = usize::try_from(option)
+            .ok()
+            .and_then(|option| state.options.get_mut(option))
+            .ok_or(WfpError::InvalidParameter)?;
+        if value.r#type != FWP_UINT32 {
+            return Err(WfpError::from_code(FWP_E_TYPE_MISMATCH));
+        }
+        // SAFETY: The type is FWP_UINT32
+        *stored = unsafe { value.Anonymous.uint32 };
+        Ok(())
+    }
+
+    fn transaction_begin(&self, flags: u32) -> Result<()> {
+        let mut state = self.bfe.lock();
+        if state.in_transaction(self.id) {
+            return Err(WfpError::TxnInProgress);
+        }
+        // Read-only transactions may run concurrently, but not alongside a write transaction
+        if state.transaction.is_some() {
+            return Err(WfpError::Timeout);
+        }
+        if flags & FWPM_TXN_READ_ONLY != 0 {
+            state.readers.push(self.id);
+            return Ok(());
+        }
+        if !state.readers.is_empty() {
+            return Err(WfpError::Timeout);
+        }
+        let snapshot = state.objects.clone();
+        state.transaction = Some(ActiveTransaction {
+            session: self.id,
+            snapshot,
+        });
+        Ok(())
+    }
+
+    fn transaction_commit(&self) -> Result<()> {
+        let mut state = self.bfe.lock();
+        if !state.in_transaction(self.id) {
+            return Err(WfpError::NoTxnInProgress);
+        }
+        state.transaction.take_if(|txn| txn.session == self.id);
+        state.readers.retain(|&reader| reader != self.id);
+        Ok(())
+    }
+
+    fn transaction_abort(&self) -> Result<()> {
+        let mut state = self.bfe.lock();
+        if !state.in_transaction(self.id) {
+            return Err(WfpError::NoTxnInProgress);
+        }
+        if let Some(txn) = state.transaction.take_if(|txn| txn.session == self.id) {
+            state.objects = txn.snapshot;
+        }
+        state.readers.retain(|&reader| reader != self.id);
+        Ok(())
+    }
+
+    unsafe fn filter_add(&self, filter: &FWPM_FILTER0) -> Result<(u64, GUID)> {
+        // SAFETY: The caller guarantees that all pointers are valid
+        let mut stored = unsafe { StoredFilter::from_raw(filter) }?;
+        stored.owner = self.owner();
+
+        let persistent = stored.flags & FWPM_FILTER_FLAG_PERSISTENT != 0;
+        if persistent && stored.flags & FWPM_FILTER_FLAG_BOOTTIME != 0 {
+            return Err(WfpError::InvalidFlags);
+        }
+        self.check_persistence(persistent)?;
+
+        if guid_to_u128(&stored.key) == 0 {
+            stored.key = generate_guid();
+        }
+        if guid_to_u128(&stored.sublayer_key) == 0 {
+            stored.sublayer_key = FWPM_SUBLAYER_UNIVERSAL;
+        }
+
+        let mut state = self.bfe.lock();
+        let objects = state.writable_objects(self.id)?;
+        if objects.filters.iter().any(|f| guid_eq(&f.key, &stored.key)) {
+            return Err(WfpError::AlreadyExists);
+        }
+        if Layer::from_guid(&stored.layer_key).is_none() {
+            return Err(WfpError::NotFound(ObjectType::Layer));
+        }
+        if let Some(provider_key) = &stored.provider_key {
+            let provider = objects
+                .provider(provider_key)
+                .ok_or(WfpError::NotFound(ObjectType::Provider))?;
+            if persistent && provider.flags & FWPM_PROVIDER_FLAG_PERSISTENT == 0 {
+                return Err(WfpError::LifetimeMismatch);
+            }
+        }
+        let sublayer = objects
+            .sublayer(&stored.sublayer_key)
+            .ok_or(WfpError::NotFound(ObjectType::SubLayer))?;
+        if persistent && sublayer.flags & FWPM_SUBLAYER_FLAG_PERSISTENT == 0 {
+            return Err(WfpError::LifetimeMismatch);
+        }
+
+        state.next_filter_id += 1;
+        stored.id = state.next_filter_id;
+        let added = (stored.id, stored.key);
+        state.objects.filters.push(stored);
+        Ok(added)
+    }
+
+    fn filter_delete_by_id(&self, id: u64) -> Result<()> {
+        let mut state = self.bfe.lock();
+        let filters = &mut state.writable_objects(self.id)?.filters;
+        let index = filters
+            .iter()
+            .position(|f| f.id == id)
+            .ok_or(WfpError::NotFound(ObjectType::Filter))?;
+        filters.remove(index);
+        Ok(())
+    }
+
+    fn filter_delete_by_key(&self, key: &GUID) -> Result<()> {
+        let mut state = self.bfe.lock();
+        let filters = &mut state.writable_objects(self.id)?.filters;
+        let index = filters
+            .iter()
+            .position(|f| guid_eq(&f.key, key))
+            .ok_or(WfpError::NotFound(ObjectType::Filter))?;
+        filters.remove(index);
+        Ok(())
+    }
+
+    fn filter_get_by_id(&self, id: u64) -> Result<FetchedObject<FWPM_FILTER0>> {
+        let filter = self
+            .bfe
+            .lock()
+            .visible_objects(self.id)
+            .filters
+            .iter()
+            .find(|f| f.id == id)
+            .cloned()
+            .ok_or(WfpError::NotFound(ObjectType::Filter))?;
+        Ok(owned_object(RawFilter::new(filter), |filter| &filter.raw))
+    }
+
+    fn filter_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_FILTER0>> {
+        let filter = self
+            .bfe
+            .lock()
+            .visible_objects(self.id)
+            .filters
+            .iter()
+            .find(|f| guid_eq(&f.key, key))
+            .cloned()
+            .ok_or(WfpError::NotFound(ObjectType::Filter))?;
+        Ok(owned_object(RawFilter::new(filter), |filter| &filter.raw))
+    }
+
+    unsafe fn filter_create_enum_handle(
+        &self,
+        template: Option<&FWPM_FILTER_ENUM_TEMPLATE0>,
+    ) -> Result<HANDLE> {
+        let template = match template {
+            // SAFETY: The caller guarantees that the pointers are valid
+            Some(template) => Some(unsafe { FilterTemplate::from_raw(template) }?),
+            None => None,
+        };
+        let mut filters: Vec<StoredFilter> = {
+            let state = self.bfe.lock();
+            let filters = state.visible_objects(self.id).filters.iter();
+            match &template {
+                Some(template) => filters.filter(|f| template.matches(f)).cloned().collect(),
+                None => filters.cloned().collect(),
+            }
+        };
+        if template.is_some_and(|t| t.flags & FWP_FILTER_ENUM_FLAG_SORTED != 0) {
+            filters.sort_by_key(|f| Reverse(f.effective_weight()));
+        }
+        let mut enums = self.enums();
+        let handle = enums.new_handle();
+        enums.filters.insert(handle, filters.into());
+        Ok(ptr::without_provenance_mut(handle))
+    }
+
+    fn filter_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_FILTER0>> {
+        let filters = take_entries(&mut self.enums().filters, enum_handle, num_entries)?;
+        let filters: Vec<Box<RawFilter>> = filters.into_iter().map(RawFilter::new).collect();
+        Ok(owned_batch(filters, |filter| &filter.raw))
+    }
+
+    fn filter_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        destroy_enumeration(&mut self.enums().filters, enum_handle)
+    }
+
+    unsafe fn provider_add(&self, provider: &FWPM_PROVIDER0) -> Result<()> {
+        // SAFETY: The caller guarantees that all pointers are valid
+        let mut stored = unsafe {
+            StoredProvider {
+                key: provider.providerKey,
+                name: copy_wide(provider.displayData.name),
+                description: copy_wide(provider.displayData.description),
+                flags: provider.flags,
+                provider_data: byte_blob_as_slice(&provider.providerData).into(),
+                service_name: copy_wide(provider.serviceName),
+                owner: self.owner(),
+            }
+        };
+        self.check_persistence(stored.flags & FWPM_PROVIDER_FLAG_PERSISTENT != 0)?;
+
+        let mut state = self.bfe.lock();
+        let objects = state.writable_objects(self.id)?;
+        if guid_to_u128(&stored.key) == 0 {
+            stored.key = generate_guid();
+        }
+        if objects.provider(&stored.key).is_some() {
+            return Err(WfpError::AlreadyExists);
+        }
+        objects.providers.push(stored);
+        Ok(())
+    }
+
+    fn provider_delete_by_key(&self, key: &GUID) -> Result<()> {
+        let mut state = self.bfe.lock();
+        let objects = state.writable_objects(self.id)?;
+        let index = objects
+            .providers
+            .iter()
+            .position(|p| guid_eq(&p.key, key))
+            .ok_or(WfpError::NotFound(ObjectType::Provider))?;
+        let referenced = objects
+            .filters
+            .iter()
+            .any(|f| f.provider_key.is_some_and(|k| guid_eq(&k, key)))
+            || objects
+                .sublayers
+                .iter()
+                .any(|s| s.provider_key.is_some_and(|k| guid_eq(&k, key)));
+        if referenced {
+            return Err(WfpError::InUse);
+        }
+        objects.providers.remove(index);
+        Ok(())
+    }
+
+    fn provider_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_PROVIDER0>> {
+        let provider = self
+            .bfe
+            .lock()
+            .visible_objects(self.id)
+            .provider(key)
+            .cloned()
+            .ok_or(WfpError::NotFound(ObjectType::Provider))?;
+        Ok(owned_object(RawProvider::new(provider), |provider| {
+            &provider.raw
+        }))
+    }
+
+    fn provider_create_enum_handle(&self) -> Result<HANDLE> {
+        let providers = self.bfe.lock().visible_objects(self.id).providers.clone();
+        let mut enums = self.enums();
+        let handle = enums.new_handle();
+        enums.providers.insert(handle, providers.into());
+        Ok(ptr::without_provenance_mut(handle))
+    }
+
+    fn provider_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_PROVIDER0>> {
+        let providers = take_entries(&mut self.enums().providers, enum_handle, num_entries)?;
+        let providers: Vec<Box<RawProvider>> =
+            providers.into_iter().map(RawProvider::new).collect();
+        Ok(owned_batch(providers, |provider| &provider.raw))
+    }
+
+    fn provider_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        destroy_enumeration(&mut self.enums().providers, enum_handle)
+    }
+
+    unsafe fn sublayer_add(&self, sublayer: &FWPM_SUBLAYER0) -> Result<()> {
+        // SAFETY: The caller guarantees that all pointers are valid
+        let mut stored = unsafe {
+            StoredSublayer {
+                key: sublayer.subLayerKey,
+                name: copy_wide(sublayer.displayData.name),
+                description: copy_wide(sublayer.displayData.description),
+                flags: sublayer.flags,
+                provider_key: sublayer.providerKey.as_ref().copied(),
+                provider_data: byte_blob_as_slice(&sublayer.providerData).into(),
+                weight: sublayer.weight,
+                owner: self.owner(),
+            }
+        };
+        let persistent = stored.flags & FWPM_SUBLAYER_FLAG_PERSISTENT != 0;
+        self.check_persistence(persistent)?;
+
+        let mut state = self.bfe.lock();
+        let objects = state.writable_objects(self.id)?;
+        if guid_to_u128(&stored.key) == 0 {
+            stored.key = generate_guid();
+        }
+        if objects.sublayer(&stored.key).is_some() {
+            return Err(WfpError::AlreadyExists);
+        }
+        if let Some(provider_key) = &stored.provider_key {
+            let provider = objects
+                .provider(provider_key)
+                .ok_or(WfpError::NotFound(ObjectType::Provider))?;
+            if persistent && provider.flags & FWPM_PROVIDER_FLAG_PERSISTENT == 0 {
+                return Err(WfpError::LifetimeMismatch);
+            }
+        }
+        objects.sublayers.push(stored);
+        Ok(())
+    }
+
+    fn sublayer_delete_by_key(&self, key: &GUID) -> Result<()> {
+        let mut state = self.bfe.lock();
+        let objects = state.writable_objects(self.id)?;
+        let index = objects
+            .sublayers
+            .iter()
+            .position(|s| guid_eq(&s.key, key))
+            .ok_or(WfpError::NotFound(ObjectType::SubLayer))?;
+        if guid_eq(key, &FWPM_SUBLAYER_UNIVERSAL) {
+            return Err(WfpError::BuiltinObject);
+        }
+        if objects
+            .filters
+            .iter()
+            .any(|f| guid_eq(&f.sublayer_key, key))
+        {
+            return Err(WfpError::InUse);
+        }
+        objects.sublayers.remove(index);
+        Ok(())
+    }
+
+    fn sublayer_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_SUBLAYER0>> {
+        let sublayer = self
+            .bfe
+            .lock()
+            .visible_objects(self.id)
+            .sublayer(key)
+            .cloned()
+            .ok_or(WfpError::NotFound(ObjectType::SubLayer))?;
+        Ok(owned_object(RawSublayer::new(sublayer), |sublayer| {
+            &sublayer.raw
+        }))
+    }
+
+    fn sublayer_create_enum_handle(&self) -> Result<HANDLE> {
+        let sublayers = self.bfe.lock().visible_objects(self.id).sublayers.clone();
+        let mut enums = self.enums();
+        let handle = enums.new_handle();
+        enums.sublayers.insert(handle, sublayers.into());
+        Ok(ptr::without_provenance_mut(handle))
+    }
+
+    fn sublayer_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_SUBLAYER0>> {
+        let sublayers = take_entries(&mut self.enums().sublayers, enum_handle, num_entries)?;
+        let sublayers: Vec<Box<RawSublayer>> =
+            sublayers.into_iter().map(RawSublayer::new).collect();
+        Ok(owned_batch(sublayers, |sublayer| &sublayer.raw))
+    }
+
+    fn sublayer_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        destroy_enumeration(&mut self.enums().sublayers, enum_handle)
+    }
+
+    fn callout_create_enum_handle(&self) -> Result<HANDLE> {
+        let mut enums = self.enums();
+        let handle = enums.new_handle();
+        enums.callouts.insert(handle);
+        Ok(ptr::without_provenance_mut(handle))
+    }
+
+    fn callout_enum(
+        &self,
+        enum_handle: HANDLE,
+        _num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_CALLOUT0>> {
+        if !self.enums().callouts.contains(&enum_handle.addr()) {
+            return Err(WfpError::InvalidEnumerator);
+        }
+        Ok(EnumBatch::empty())
+    }
+
+    fn callout_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        self.enums()
+            .callouts
+            .remove(&enum_handle.addr())
+            .then_some(())
+            .ok_or(WfpError::InvalidEnumerator)
+    }
+
+    fn layer_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_LAYER0>> {
+        let layer = Layer::from_guid(key).ok_or(WfpError::NotFound(ObjectType::Layer))?;
+        Ok(owned_object(RawLayer::new(layer), |layer| &layer.raw))
+    }
+
+    fn layer_create_enum_handle(&self) -> Result<HANDLE> {
+        let mut enums = self.enums();
+        let handle = enums.new_handle();
+        enums
+            .layers
+            .insert(handle, Layer::ALL.iter().copied().collect());
+        Ok(ptr::without_provenance_mut(handle))
+    }
+
+    fn layer_enum(&self, enum_handle: HANDLE, num_entries: u32) -> Result<EnumBatch<FWPM_LAYER0>> {
+        let layers = take_entries(&mut self.enums().layers, enum_handle, num_entries)?;
+        let layers: Vec<Box<RawLayer>> = layers.into_iter().map(RawLayer::new).collect();
+        Ok(owned_batch(layers, |layer| &layer.raw))
+    }
+
+    fn layer_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        destroy_enumeration(&mut self.enums().layers, enum_handle)
+    }
+
+    fn provider_context_create_enum_handle(&self) -> Result<HANDLE> {
+        let mut enums = self.enums();
+        let handle = enums.new_handle();
+        enums.provider_contexts.insert(handle);
+        Ok(ptr::without_provenance_mut(handle))
+    }
+
+    fn provider_context_enum(
+        &self,
+        enum_handle: HANDLE,
+        _num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_PROVIDER_CONTEXT0>> {
+        if !self.enums().provider_contexts.contains(&enum_handle.addr()) {
+            return Err(WfpError::InvalidEnumerator);
+        }
+        Ok(EnumBatch::empty())
+    }
+
+    fn provider_context_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()> {
+        self.enums()
+            .provider_contexts
+            .remove(&enum_handle.addr())
+            .then_some(())
+            .ok_or(WfpError::InvalidEnumerator)
+    }
+}
+
+/// An owned copy of a filter.
+#[derive(Clone)]
+struct StoredFilter {
+    id: u64,
+    key: GUID,
+    name: Option<Box<[u16]>>,
+    description: Option<Box<[u16]>>,
+    flags: u32,
+    provider_key: Option<GUID>,
+    provider_data: Box<[u8]>,
+    layer_key: GUID,
+    sublayer_key: GUID,
+    weight: StoredWeight,
+    conditions: Vec<Condition>,
+    action: FWPM_ACTION0,
+    context: FWPM_FILTER0_0,
+    /// The dynamic session that added the filter
+    owner: Option<u64>,
+}
+
+#[derive(Clone, Copy)]
+enum StoredWeight {
+    Empty,
+    UInt8(u8),
+    UInt64(u64),
+}
+
+impl StoredFilter {
+    /// Deep-copies a raw filter.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `filter` must be valid.
+    unsafe fn from_raw(filter: &FWPM_FILTER0) -> Result<Self> {
+        // SAFETY: The caller guarantees that the pointer is valid for the given type
+        let weight = match filter.weight.r#type {
+            FWP_EMPTY => StoredWeight::Empty,
+            FWP_UINT8 => match unsafe { filter.weight.Anonymous.uint8 } {
+                weight @ 0..=15 => StoredWeight::UInt8(weight),
+                _ => return Err(WfpError::InvalidWeight),
+            },
+            FWP_UINT64 => StoredWeight::UInt64(unsafe { *filter.weight.Anonymous.uint64 }),
+            _ => return Err(WfpError::InvalidWeight),
+        };
+
+        let num_conditions = usize::try_from(filter.numFilterConditions).unwrap();
+        let mut conditions = Vec::with_capacity(num_conditions);
+        for i in 0..num_conditions {
+            // SAFETY: The caller guarantees that there are `numFilterConditions` valid conditions
+            let condition = unsafe { Condition::from_raw(&*filter.filterCondition.add(i)) }.ok_or(
+                WfpError::InvalidCondition(ConditionError::UnsupportedValueType),
+            )?;
+            conditions.push(condition);
+        }
+
+        // SAFETY: The caller guarantees that the pointers are valid
+        unsafe {
+            Ok(Self {
+                id: 0,
+                key: filter.filterKey,
+                name: copy_wide(filter.displayData.name),
+                description: copy_wide(filter.displayData.description),
+                flags: filter.flags,
+                provider_key: filter.providerKey.as_ref().copied(),
+                provider_data: byte_blob_as_slice(&filter.providerData).into(),
+                layer_key: filter.layerKey,
+                sublayer_key: filter.subLayerKey,
+                weight,
+                conditions,
+                action: filter.action,
+                context: filter.Anonymous,
+                owner: None,
+            })
+        }
+    }
+
+    /// Returns the weight that the filter is evaluated with.
+    fn effective_weight(&self) -> u64 {
+        match self.weight {
+            StoredWeight::Empty => 0,
+            StoredWeight::UInt8(weight) => u64::from(weight) << 60,
+            StoredWeight::UInt64(weight) => weight,
+        }
+    }
+}
+
+/// An owned copy of a filter enumeration template.
+struct FilterTemplate {
+    provider_key: Option<GUID>,
+    layer_key: GUID,
+    flags: u32,
+    action_mask: u32,
+    callout_key: Option<GUID>,
+}
+
+impl FilterTemplate {
+    /// Copies a raw template.
+    ///
+    /// Templates with conditions, a provider context template or
+    /// `FWP_FILTER_ENUM_FLAG_BEST_TERMINATING_MATCH` are not supported, since they depend on
+    /// how traffic is classified.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `template` must be valid.
+    unsafe fn from_raw(template: &FWPM_FILTER_ENUM_TEMPLATE0) -> Result<Self> {
+        if Layer::from_guid(&template.layerKey).is_none() {
+            return Err(WfpError::NotFound(ObjectType::Layer));
+        }
+        if !matches!(
+            template.enumType,
+            FWP_FILTER_ENUM_FULLY_CONTAINED | FWP_FILTER_ENUM_OVERLAPPING
+        ) {
+            return Err(WfpError::InvalidParameter);
+        }
+        if template.numFilterConditions != 0
+            || !template.providerContextTemplate.is_null()
+            || template.flags & FWP_FILTER_ENUM_FLAG_BEST_TERMINATING_MATCH != 0
+        {
+            return Err(WfpError::Other(ERROR_NOT_SUPPORTED as i32));
+        }
+        // SAFETY: The caller guarantees that the pointers are valid
+        unsafe {
+            Ok(Self {
+                provider_key: template.providerKey.as_ref().copied(),
+                layer_key: template.layerKey,
+                flags: template.flags,
+                action_mask: template.actionMask,
+                callout_key: template.calloutKey.as_ref().copied(),
+            })
+        }
+    }
+
+    /// Returns whether `filter` is selected by the template.
+    fn matches(&self, filter: &StoredFilter) -> bool {
+        let boottime = filter.flags & FWPM_FILTER_FLAG_BOOTTIME != 0;
+        let lifetime_matches = if self.flags & FWP_FILTER_ENUM_FLAG_BOOTTIME_ONLY != 0 {
+            boottime
+        } else {
+            !boottime || self.flags & FWP_FILTER_ENUM_FLAG_INCLUDE_BOOTTIME != 0
+        };
+        let disabled = filter.flags & FWPM_FILTER_FLAG_DISABLED != 0;
+        let callout_matches = self.callout_key.is_none_or(|key| {
+            filter.action.r#type & FWP_ACTION_FLAG_CALLOUT != 0
+                // SAFETY: `calloutKey` is the active union field for callout actions
+                && guid_eq(&key, &unsafe { filter.action.Anonymous.calloutKey })
+        });
+
+        guid_eq(&filter.layer_key, &self.layer_key)
+            && self
+                .provider_key
+                .is_none_or(|key| filter.provider_key.is_some_and(|k| guid_eq(&k, &key)))
+            && filter.action.r#type & self.action_mask != 0
+            && callout_matches
+            && lifetime_matches
+            && (!disabled || self.flags & FWP_FILTER_ENUM_FLAG_INCLUDE_DISABLED != 0)
+    }
+}
+
+/// A filter along with a raw `FWPM_FILTER0` that points into it.
+struct RawFilter {
+    stored: StoredFilter,
+    raw: FWPM_FILTER0,
+    conditions: Vec<FWPM_FILTER_CONDITION0>,
+    provider_key: GUID,
+    weight: u64,
+    effective_weight: u64,
+}
+
+impl RawFilter {
+    fn new(stored: StoredFilter) -> Box<Self> {
+        let mut filter = Box::new(RawFilter {
+            conditions: stored
+                .conditions
+                .iter()
+                .map(|c| *c.raw_condition())
+                .collect(),
+            provider_key: stored.provider_key.unwrap_or_default(),
+            weight: 0,
+            effective_weight: stored.effective_weight(),
+            raw: FWPM_FILTER0::default(),
+            stored,
+        });
+
+        // The box keeps all fields at a stable address
+        let RawFilter {
+            stored,
+            raw,
+            conditions,
+            provider_key,
+            weight,
+            effective_weight,
+        } = &mut *filter;
+
+        raw.filterKey = stored.key;
+        raw.displayData.name = wide_ptr(&stored.name);
+        raw.displayData.description = wide_ptr(&stored.description);
+        raw.flags = stored.flags;
+        if stored.provider_key.is_some() {
+            raw.providerKey = provider_key;
+        }
+        raw.providerData = byte_blob(&stored.provider_data);
+        raw.layerKey = stored.layer_key;
+        raw.subLayerKey = stored.sublayer_key;
+        raw.weight = match stored.weight {
+            StoredWeight::Empty => FWP_VALUE0::default(),
+            StoredWeight::UInt8(val) => {
+                let mut value = FWP_VALUE0 {
+                    r#type: FWP_UINT8,
+                    ..Default::default()
+                };
+                value.Anonymous.uint8 = val;
+                value
+            }
+            StoredWeight::UInt64(val) => {
+                *weight = val;
+                u64_value(weight)
+            }
+        };
+        if !conditions.is_empty() {
+            raw.numFilterConditions = u32::try_from(conditions.len()).unwrap();
+            raw.filterCondition = conditions.as_mut_ptr();
+        }
+        raw.action = stored.action;
+        raw.Anonymous = stored.context;
+        raw.filterId = stored.id;
+        raw.effectiveWeight = u64_value(effective_weight);
+
+        filter
+    }
+}
+
+/// An owned copy of a session.
+#[derive(Clone)]
+struct StoredSession {
+    id: u64,
+    key: GUID,
+    name: Option<Box<[u16]>>,
+    description: Option<Box<[u16]>>,
+    flags: u32,
+    txn_wait_timeout: u32,
+}
+
+/// A session materialized as an `FWPM_SESSION0`, which points into `stored`.
+struct RawSession {
+    stored: StoredSession,
+    raw: FWPM_SESSION0,
+}
+
+impl RawSession {
+    fn new(stored: StoredSession) -> Box<Self> {
+        let mut session = Box::new(RawSession {
+            stored,
+            raw: FWPM_SESSION0::default(),
+        });
+
+        // The box keeps all fields at a stable address
+        let RawSession { stored, raw } = &mut *session;
+
+        raw.sessionKey = stored.key;
+        raw.displayData.name = wide_ptr(&stored.name);
+        raw.displayData.description = wide_ptr(&stored.description);
+        raw.flags = stored.flags;
+        raw.txnWaitTimeoutInMSec = stored.txn_wait_timeout;
+        raw.processId = std::process::id();
+
+        session
+    }
+}
+
+/// An owned copy of a provider.
+#[derive(Clone)]
+struct StoredProvider {
+    key: GUID,
+    name: Option<Box<[u16]>>,
+    description: Option<Box<[u16]>>,
+    flags: u32,
+    provider_data: Box<[u8]>,
+    service_name: Option<Box<[u16]>>,
+    /// The dynamic session that added the provider
+    owner: Option<u64>,
+}
+
+/// A provider materialized as an `FWPM_PROVIDER0`, which points into `stored`.
+struct RawProvider {
+    stored: StoredProvider,
+    raw: FWPM_PROVIDER0,
+}
+
+impl RawProvider {
+    fn new(stored: StoredProvider) -> Box<Self> {
+        let mut provider = Box::new(RawProvider {
+            stored,
+            raw: FWPM_PROVIDER0::default(),
+        });
+
+        // The box keeps all fields at a stable address
+        let RawProvider { stored, raw } = &mut *provider;
+
+        raw.providerKey = stored.key;
+        raw.displayData.name = wide_ptr(&stored.name);
+        raw.displayData.description = wide_ptr(&stored.description);
+        raw.flags = stored.flags;
+        raw.providerData = byte_blob(&stored.provider_data);
+        raw.serviceName = wide_ptr(&stored.service_name);
+
+        provider
+    }
+}
+
+/// An owned copy of a sublayer.
+#[derive(Clone)]
+struct StoredSublayer {
+    key: GUID,
+    name: Option<Box<[u16]>>,
+    description: Option<Box<[u16]>>,
+    flags: u32,
+    provider_key: Option<GUID>,
+    provider_data: Box<[u8]>,
+    weight: u16,
+    /// The dynamic session that added the sublayer
+    owner: Option<u64>,
+}
+
+/// A sublayer materialized as an `FWPM_SUBLAYER0`, which points into `stored`.
+struct RawSublayer {
+    stored: StoredSublayer,
+    raw: FWPM_SUBLAYER0,
+    provider_key: GUID,
+}
+
+impl RawSublayer {
+    fn new(stored: StoredSublayer) -> Box<Self> {
+        let mut sublayer = Box::new(RawSublayer {
+            provider_key: stored.provider_key.unwrap_or_default(),
+            stored,
+            raw: FWPM_SUBLAYER0::default(),
+        });
+
+        // The box keeps all fields at a stable address
+        let RawSublayer {
+            stored,
+            raw,
+            provider_key,
+        } = &mut *sublayer;
+
+        raw.subLayerKey = stored.key;
+        raw.displayData.name = wide_ptr(&stored.name);
+        raw.displayData.description = wide_ptr(&stored.description);
+        raw.flags = stored.flags;
+        if stored.provider_key.is_some() {
+            raw.providerKey = provider_key;
+        }
+        raw.providerData = byte_blob(&stored.provider_data);
+        raw.weight = stored.weight;
+
+        sublayer
+    }
+}
+
+/// A built-in layer materialized as an `FWPM_LAYER0`.
+struct RawLayer {
+    raw: FWPM_LAYER0,
+    field_keys: Vec<GUID>,
+    fields: Vec<FWPM_FIELD0>,
+}
+
+impl RawLayer {
+    fn new(layer: Layer) -> Box<Self> {
+        let condition_fields = layer.condition_fields();
+        let mut raw_layer = Box::new(RawLayer {
+            raw: FWPM_LAYER0::default(),
+            field_keys: condition_fields.iter().map(|field| *field.guid()).collect(),
+            fields: vec![],
+        });
+
+        // The box keeps all fields at a stable address
+        let RawLayer {
+            raw,
+            field_keys,
+            fields,
+        } = &mut *raw_layer;
+
+        *fields = condition_fields
+            .iter()
+            .zip(field_keys.iter_mut())
+            .map(|(&field, key)| FWPM_FIELD0 {
+                fieldKey: key,
+                r#type: match field {
+                    ConditionField::RemoteAddress
+                    | ConditionField::LocalAddress
+                    | ConditionField::NextHopAddress => FWPM_FIELD_IP_ADDRESS,
+                    ConditionField::Flags => FWPM_FIELD_FLAGS,
+                    _ => FWPM_FIELD_RAW_DATA,
+                },
+                dataType: layer
+                    .field_type(field)
+                    .map_or(FWP_EMPTY, |data_type| data_type as i32),
+            })
+            .collect();
+
+        raw.layerKey = *layer.guid();
+        raw.flags = FWPM_LAYER_FLAG_BUILTIN;
+        if is_kernel_layer(layer) {
+            raw.flags |= FWPM_LAYER_FLAG_KERNEL;
+        }
+        raw.numFields = u32::try_from(fields.len()).unwrap();
+        raw.field = fields.as_mut_ptr();
+        raw.defaultSubLayerKey = FWPM_SUBLAYER_UNIVERSAL;
+
+        raw_layer
+    }
+}
+
+/// Returns whether `layer` is classified in kernel mode, rather than by a user-mode keying
+/// module or RPC.
+fn is_kernel_layer(layer: Layer) -> bool {
+    !matches!(
+        layer,
+        Layer::IkeExtV4
+            | Layer::IkeExtV6
+            | Layer::IpsecKmDemuxV4
+            | Layer::IpsecKmDemuxV6
+            | Layer::IpsecV4
+            | Layer::IpsecV6
+            | Layer::KmAuthorization
+            | Layer::RpcUm
+            | Layer::RpcEpMap
+            | Layer::RpcEpAdd
+            | Layer::RpcProxyConn
+            | Layer::RpcProxyIf
+    )
+}
+
+/// Removes up to `num_entries` objects from the enumeration identified by `enum_handle`.
+fn take_entries<T>(
+    enums: &mut HashMap<usize, VecDeque<T>>,
+    enum_handle: HANDLE,
+    num_entries: u32,
+) -> Result<Vec<T>> {
+    let remaining = enums
+        .get_mut(&enum_handle.addr())
+        .ok_or(WfpError::InvalidEnumerator)?;
+    let count = remaining.len().min(usize::try_from(num_entries).unwrap());
+    Ok(remaining.drain(..count).collect())
+}
+
+/// Removes the enumeration identified by `enum_handle`.
+fn destroy_enumeration<T>(
+    enums: &mut HashMap<usize, VecDeque<T>>,
+    enum_handle: HANDLE,
+) -> Result<()> {
+    enums
+        .remove(&enum_handle.addr())
+        .map(|_| ())
+        .ok_or(WfpError::InvalidEnumerator)
+}
+
+/// Creates a batch that owns `objects`. `raw` returns the raw object to hand out.
+fn owned_batch<O: 'static, T: 'static>(objects: Vec<Box<O>>, raw: fn(&O) -> &T) -> EnumBatch<T> {
+    let mut pointers: Vec<*mut T> = objects
+        .iter()
+        .map(|object| raw(object) as *const T as *mut T)
+        .collect();
+    let num_entries = u32::try_from(pointers.len()).unwrap();
+    let entries = pointers.as_mut_ptr();
+    // SAFETY: The pointers refer to boxed objects that are owned by the batch and never
+    //         mutated. Moving the vectors into the batch does not move their contents.
+    unsafe { EnumBatch::from_owned(entries, num_entries, Box::new((objects, pointers))) }
+}
+
+/// Creates a fetched object that owns `object`. `raw` returns the raw object to hand out.
+fn owned_object<O: 'static, T>(object: Box<O>, raw: fn(&O) -> &T) -> FetchedObject<T> {
+    let pointer = raw(&object) as *const T as *mut T;
+    // SAFETY: The pointer refers to a boxed object that is owned by the fetched object and
+    //         never mutated. Moving the box does not move its contents.
+    unsafe { FetchedObject::from_owned(pointer, object) }
+}
+
+/// Copies a null-terminated UTF-16 string, including the terminator.
+///
+/// # Safety
+///
+/// `s` must be null or point to a null-terminated string.
+unsafe fn copy_wide(s: *const u16) -> Option<Box<[u16]>> {
+    if s.is_null() {
+        return None;
+    }
+    // SAFETY: The caller guarantees that `s` is null-terminated
+    Some(unsafe { std::slice::from_raw_parts(s, wcslen(s) + 1) }.into())
+}
+
+fn wide_ptr(s: &Option<Box<[u16]>>) -> *mut u16 {
+    s.as_ref().map_or(ptr::null_mut(), |s| s.as_ptr() as *mut _)
+}
+
+fn byte_blob(data: &[u8]) -> FWP_BYTE_BLOB {
+    FWP_BYTE_BLOB {
+        size: u32::try_from(data.len()).unwrap(),
+        data: if data.is_empty() {
+            ptr::null_mut()
+        } else {
+            data.as_ptr() as *mut _
+        },
+    }
+}
+
+fn u64_value(value: &mut u64) -> FWP_VALUE0 {
+    let mut raw = FWP_VALUE0 {
+        r#type: FWP_UINT64,
+        ..Default::default()
+    };
+    raw.Anonymous.uint64 = value;
+    raw
+}
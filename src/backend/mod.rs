@@ -0,0 +1,377 @@
+//! Backends that carry out filter engine operations.
+//!
+//! A [`FilterEngine`](crate::FilterEngine) forwards every operation to a [`Backend`].
+//! On Windows, [`FilterEngineBuilder::open`](crate::FilterEngineBuilder::open) connects to the
+//! Base Filtering Engine (BFE) through the `Fwpm*` API. [`MemoryBfe`] is an in-memory stand-in
+//! for the BFE that is available on every target, and is mainly useful for tests.
+
+#[cfg(target_os = "windows")]
+mod fwpm;
+mod memory;
+
+#[cfg(target_os = "windows")]
+pub(crate) use fwpm::FwpmBackend;
+pub use memory::MemoryBfe;
+
+use std::any::Any;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::HANDLE;
+#[cfg(target_os = "windows")]
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FwpmFreeMemory0;
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWP_VALUE0, FWPM_CALLOUT0, FWPM_ENGINE_OPTION, FWPM_FILTER_ENUM_TEMPLATE0, FWPM_FILTER0,
+    FWPM_LAYER0, FWPM_PROVIDER_CONTEXT0, FWPM_PROVIDER0, FWPM_SESSION0, FWPM_SUBLAYER0,
+};
+use windows_sys::core::GUID;
+
+use crate::error::Result;
+
+/// The operations that a [`FilterEngine`](crate::FilterEngine) session performs.
+///
+/// Each method corresponds to a function in the WFP management API, operating on a single
+/// open session. Errors are reported the same way as the underlying API, i.e. as the
+/// [`WfpError`](crate::WfpError) that corresponds to the `FWP_E_*` status code.
+///
+/// Methods that take raw WFP structures are `unsafe`: every pointer in the structure must be
+/// valid for the duration of the call.
+pub trait Backend: Send {
+    /// Returns the raw engine handle, or null if the backend has none.
+    fn raw_handle(&self) -> HANDLE {
+        ptr::null_mut()
+    }
+
+    /// Returns the key that uniquely identifies the session.
+    fn session_key(&self) -> GUID;
+
+    /// Creates a handle for enumerating all sessions.
+    /// This corresponds to `FwpmSessionCreateEnumHandle0`.
+    fn session_create_enum_handle(&self) -> Result<HANDLE>;
+
+    /// Returns up to `num_entries` sessions from an enumeration. An empty batch means that the
+    /// enumeration is complete. This corresponds to `FwpmSessionEnum0`.
+    fn session_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_SESSION0>>;
+
+    /// Destroys a session enumeration handle.
+    /// This corresponds to `FwpmSessionDestroyEnumHandle0`.
+    fn session_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()>;
+
+    /// Returns the value of an engine option. This corresponds to `FwpmEngineGetOption0`.
+    ///
+    /// Only values that are stored inline in an `FWP_VALUE0`, such as `FWP_UINT32`, can be
+    /// returned.
+    fn engine_get_option(&self, option: FWPM_ENGINE_OPTION) -> Result<FWP_VALUE0>;
+
+    /// Sets the value of an engine option. This corresponds to `FwpmEngineSetOption0`.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `value` must be valid.
+    unsafe fn engine_set_option(
+        &self,
+        option: FWPM_ENGINE_OPTION,
+        value: &FWP_VALUE0,
+    ) -> Result<()>;
+
+    /// Begins an explicit transaction. This corresponds to `FwpmTransactionBegin0`.
+    fn transaction_begin(&self, flags: u32) -> Result<()>;
+
+    /// Commits the current transaction. This corresponds to `FwpmTransactionCommit0`.
+    fn transaction_commit(&self) -> Result<()>;
+
+    /// Aborts the current transaction. This corresponds to `FwpmTransactionAbort0`.
+    fn transaction_abort(&self) -> Result<()>;
+
+    /// Adds a filter and returns its runtime ID and key. This corresponds to `FwpmFilterAdd0`.
+    ///
+    /// If the key in `filter` is zero, the returned key is the one generated by the engine.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `filter` must be valid.
+    unsafe fn filter_add(&self, filter: &FWPM_FILTER0) -> Result<(u64, GUID)>;
+
+    /// Deletes a filter by its runtime ID. This corresponds to `FwpmFilterDeleteById0`.
+    fn filter_delete_by_id(&self, id: u64) -> Result<()>;
+
+    /// Deletes a filter by its key. This corresponds to `FwpmFilterDeleteByKey0`.
+    fn filter_delete_by_key(&self, key: &GUID) -> Result<()>;
+
+    /// Returns the filter with the given runtime ID. This corresponds to `FwpmFilterGetById0`.
+    fn filter_get_by_id(&self, id: u64) -> Result<FetchedObject<FWPM_FILTER0>>;
+
+    /// Returns the filter with the given key. This corresponds to `FwpmFilterGetByKey0`.
+    fn filter_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_FILTER0>>;
+
+    /// Creates a handle for enumerating the filters that match `template`, or all filters if
+    /// there is no template. This corresponds to `FwpmFilterCreateEnumHandle0`.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `template` must be valid.
+    unsafe fn filter_create_enum_handle(
+        &self,
+        template: Option<&FWPM_FILTER_ENUM_TEMPLATE0>,
+    ) -> Result<HANDLE>;
+
+    /// Returns up to `num_entries` filters from an enumeration. An empty batch means that the
+    /// enumeration is complete. This corresponds to `FwpmFilterEnum0`.
+    fn filter_enum(&self, enum_handle: HANDLE, num_entries: u32)
+    -> Result<EnumBatch<FWPM_FILTER0>>;
+
+    /// Destroys a filter enumeration handle.
+    /// This corresponds to `FwpmFilterDestroyEnumHandle0`.
+    fn filter_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()>;
+
+    /// Adds a provider. This corresponds to `FwpmProviderAdd0`.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `provider` must be valid.
+    unsafe fn provider_add(&self, provider: &FWPM_PROVIDER0) -> Result<()>;
+
+    /// Deletes a provider by its key. This corresponds to `FwpmProviderDeleteByKey0`.
+    fn provider_delete_by_key(&self, key: &GUID) -> Result<()>;
+
+    /// Returns the provider with the given key. This corresponds to `FwpmProviderGetByKey0`.
+    fn provider_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_PROVIDER0>>;
+
+    /// Creates a handle for enumerating all providers.
+    /// This corresponds to `FwpmProviderCreateEnumHandle0`.
+    fn provider_create_enum_handle(&self) -> Result<HANDLE>;
+
+    /// Returns up to `num_entries` providers from an enumeration. An empty batch means that the
+    /// enumeration is complete. This corresponds to `FwpmProviderEnum0`.
+    fn provider_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_PROVIDER0>>;
+
+    /// Destroys a provider enumeration handle.
+    /// This corresponds to `FwpmProviderDestroyEnumHandle0`.
+    fn provider_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()>;
+
+    /// Adds a sublayer. This corresponds to `FwpmSubLayerAdd0`.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `sublayer` must be valid.
+    unsafe fn sublayer_add(&self, sublayer: &FWPM_SUBLAYER0) -> Result<()>;
+
+    /// Deletes a sublayer by its key. This corresponds to `FwpmSubLayerDeleteByKey0`.
+    fn sublayer_delete_by_key(&self, key: &GUID) -> Result<()>;
+
+    /// Returns the sublayer with the given key. This corresponds to `FwpmSubLayerGetByKey0`.
+    fn sublayer_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_SUBLAYER0>>;
+
+    /// Creates a handle for enumerating all sublayers.
+    /// This corresponds to `FwpmSubLayerCreateEnumHandle0`.
+    fn sublayer_create_enum_handle(&self) -> Result<HANDLE>;
+
+    /// Returns up to `num_entries` sublayers from an enumeration. An empty batch means that the
+    /// enumeration is complete. This corresponds to `FwpmSubLayerEnum0`.
+    fn sublayer_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_SUBLAYER0>>;
+
+    /// Destroys a sublayer enumeration handle.
+    /// This corresponds to `FwpmSubLayerDestroyEnumHandle0`.
+    fn sublayer_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()>;
+
+    /// Creates a handle for enumerating all callouts.
+    /// This corresponds to `FwpmCalloutCreateEnumHandle0`.
+    fn callout_create_enum_handle(&self) -> Result<HANDLE>;
+
+    /// Returns up to `num_entries` callouts from an enumeration. An empty batch means that the
+    /// enumeration is complete. This corresponds to `FwpmCalloutEnum0`.
+    fn callout_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_CALLOUT0>>;
+
+    /// Destroys a callout enumeration handle.
+    /// This corresponds to `FwpmCalloutDestroyEnumHandle0`.
+    fn callout_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()>;
+
+    /// Returns the layer with the given key. This corresponds to `FwpmLayerGetByKey0`.
+    fn layer_get_by_key(&self, key: &GUID) -> Result<FetchedObject<FWPM_LAYER0>>;
+
+    /// Creates a handle for enumerating all layers.
+    /// This corresponds to `FwpmLayerCreateEnumHandle0`.
+    fn layer_create_enum_handle(&self) -> Result<HANDLE>;
+
+    /// Returns up to `num_entries` layers from an enumeration. An empty batch means that the
+    /// enumeration is complete. This corresponds to `FwpmLayerEnum0`.
+    fn layer_enum(&self, enum_handle: HANDLE, num_entries: u32) -> Result<EnumBatch<FWPM_LAYER0>>;
+
+    /// Destroys a layer enumeration handle.
+    /// This corresponds to `FwpmLayerDestroyEnumHandle0`.
+    fn layer_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()>;
+
+    /// Creates a handle for enumerating all provider contexts.
+    /// This corresponds to `FwpmProviderContextCreateEnumHandle0`.
+    fn provider_context_create_enum_handle(&self) -> Result<HANDLE>;
+
+    /// Returns up to `num_entries` provider contexts from an enumeration. An empty batch means that the
+    /// enumeration is complete. This corresponds to `FwpmProviderContextEnum0`.
+    fn provider_context_enum(
+        &self,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<FWPM_PROVIDER_CONTEXT0>>;
+
+    /// Destroys a provider context enumeration handle.
+    /// This corresponds to `FwpmProviderContextDestroyEnumHandle0`.
+    fn provider_context_destroy_enum_handle(&self, enum_handle: HANDLE) -> Result<()>;
+}
+
+/// A batch of objects returned by an enumeration.
+///
+/// The objects are stored as an array of pointers, which is how the `Fwpm*Enum0` functions
+/// return them.
+pub struct EnumBatch<T> {
+    entries: *mut *mut T,
+    num_entries: u32,
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    owner: Owner,
+}
+
+/// Where the objects returned by a backend are stored.
+enum Owner {
+    /// The objects were allocated by WFP and must be freed with `FwpmFreeMemory0`.
+    #[cfg(target_os = "windows")]
+    Fwpm,
+    /// The objects are kept alive by this value.
+    Owned(#[allow(dead_code)] Box<dyn Any>),
+}
+
+impl<T> EnumBatch<T> {
+    /// Returns a batch with no entries.
+    pub fn empty() -> Self {
+        Self {
+            entries: ptr::null_mut(),
+            num_entries: 0,
+            owner: Owner::Owned(Box::new(())),
+        }
+    }
+
+    /// Takes ownership of entries returned by a `Fwpm*Enum0` function.
+    ///
+    /// # Safety
+    ///
+    /// `entries` must point to `num_entries` valid pointers, and must be freed using
+    /// `FwpmFreeMemory0`.
+    #[cfg(target_os = "windows")]
+    pub(crate) unsafe fn from_fwpm(entries: *mut *mut T, num_entries: u32) -> Self {
+        Self {
+            entries,
+            num_entries,
+            owner: Owner::Fwpm,
+        }
+    }
+
+    /// Creates a batch from entries that are kept alive by `owner`.
+    ///
+    /// # Safety
+    ///
+    /// `entries` must point to `num_entries` valid pointers, and both the array and the
+    /// objects must remain valid and unchanged for as long as `owner` is alive.
+    pub unsafe fn from_owned(entries: *mut *mut T, num_entries: u32, owner: Box<dyn Any>) -> Self {
+        Self {
+            entries,
+            num_entries,
+            owner: Owner::Owned(owner),
+        }
+    }
+
+    /// Returns the number of entries in the batch.
+    pub fn len(&self) -> usize {
+        usize::try_from(self.num_entries).unwrap()
+    }
+
+    /// Returns whether the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.num_entries == 0
+    }
+
+    /// Returns the entry at `index`, if it exists.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        // SAFETY: `index` is in bounds, and the entries are valid for the lifetime of `self`
+        Some(unsafe { &**self.entries.add(index) })
+    }
+}
+
+impl<T> Drop for EnumBatch<T> {
+    fn drop(&mut self) {
+        #[cfg(target_os = "windows")]
+        if let Owner::Fwpm = self.owner
+            && !self.entries.is_null()
+        {
+            // SAFETY: The entries were allocated by WFP and have not been freed
+            unsafe { FwpmFreeMemory0((&mut self.entries) as *mut _ as *mut _) };
+        }
+    }
+}
+
+/// A single object returned by a backend, e.g. by `Fwpm*GetByKey0`.
+pub struct FetchedObject<T> {
+    object: *mut T,
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    owner: Owner,
+}
+
+impl<T> FetchedObject<T> {
+    /// Takes ownership of an object returned by a `Fwpm*GetByKey0` or `Fwpm*GetById0`
+    /// function.
+    ///
+    /// # Safety
+    ///
+    /// `object` must point to a valid object, and must be freed using `FwpmFreeMemory0`.
+    #[cfg(target_os = "windows")]
+    pub(crate) unsafe fn from_fwpm(object: *mut T) -> Self {
+        Self {
+            object,
+            owner: Owner::Fwpm,
+        }
+    }
+
+    /// Creates an object that is kept alive by `owner`.
+    ///
+    /// # Safety
+    ///
+    /// `object` must point to a valid object, which must remain valid and unchanged for as
+    /// long as `owner` is alive.
+    pub unsafe fn from_owned(object: *mut T, owner: Box<dyn Any>) -> Self {
+        Self {
+            object,
+            owner: Owner::Owned(owner),
+        }
+    }
+
+    /// Returns the object.
+    pub fn get(&self) -> &T {
+        // SAFETY: The object is valid for the lifetime of `self`
+        unsafe { &*self.object }
+    }
+}
+
+impl<T> Drop for FetchedObject<T> {
+    fn drop(&mut self) {
+        #[cfg(target_os = "windows")]
+        if let Owner::Fwpm = self.owner {
+            // SAFETY: The object was allocated by WFP and has not been freed
+            unsafe { FwpmFreeMemory0((&mut self.object) as *mut _ as *mut _) };
+        }
+    }
+}
@@ -1,12 +1,17 @@
-use std::{ffi::OsStr, io};
+#[cfg(target_os = "windows")]
+use std::ffi::OsStr;
 
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWP_BYTE_BLOB;
+#[cfg(target_os = "windows")]
 use windows_sys::Win32::{
     Foundation::ERROR_SUCCESS,
-    NetworkManagement::WindowsFilteringPlatform::{
-        FWP_BYTE_BLOB, FwpmFreeMemory0, FwpmGetAppIdFromFileName0,
-    },
+    NetworkManagement::WindowsFilteringPlatform::{FwpmFreeMemory0, FwpmGetAppIdFromFileName0},
 };
 
+#[cfg(target_os = "windows")]
+use crate::error::{Result, WfpError};
+use crate::util::byte_blob_as_slice;
+#[cfg(target_os = "windows")]
 use crate::util::string_to_null_terminated_utf16;
 
 /// An owned byte blob that is freed using `FwpmFreeMemory0`.
@@ -14,9 +19,8 @@ pub struct OwnedByteBlob {
     inner: InnerBlob,
 }
 enum InnerBlob {
-    Pointer {
-        blob: *mut FWP_BYTE_BLOB,
-    },
+    #[cfg(target_os = "windows")]
+    Pointer { blob: *mut FWP_BYTE_BLOB },
     Vec {
         blob: FWP_BYTE_BLOB,
         _buf: Box<[u8]>,
@@ -30,6 +34,7 @@ impl OwnedByteBlob {
     ///
     /// This must be a pointer that should be freed using `FwpmFreeMemory`,
     /// such as one returned by `FwpmGetAppIdFromFileName0`.
+    #[cfg(target_os = "windows")]
     pub unsafe fn from_raw(blob: *mut FWP_BYTE_BLOB) -> Self {
         Self {
             inner: InnerBlob::Pointer { blob },
@@ -39,10 +44,17 @@ impl OwnedByteBlob {
     /// Return pointer to the underlying byte blob
     pub fn as_ptr(&self) -> *const FWP_BYTE_BLOB {
         match &self.inner {
+            #[cfg(target_os = "windows")]
             InnerBlob::Pointer { blob } => *blob,
             InnerBlob::Vec { blob, .. } => blob,
         }
     }
+
+    /// Return the contents of the byte blob
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: The blob points to `size` bytes that live as long as `self`
+        unsafe { byte_blob_as_slice(&*self.as_ptr()) }
+    }
 }
 
 impl<T: AsRef<[u8]>> From<T> for OwnedByteBlob {
@@ -58,6 +70,7 @@ impl<T: AsRef<[u8]>> From<T> for OwnedByteBlob {
     }
 }
 
+#[cfg(target_os = "windows")]
 impl Drop for OwnedByteBlob {
     fn drop(&mut self) {
         if let InnerBlob::Pointer { mut blob } = self.inner {
@@ -72,14 +85,15 @@ impl Drop for OwnedByteBlob {
 /// The underlying function is [`FwpmGetAppIdFromFileName0`].
 ///
 /// [`FwpmGetAppIdFromFileName0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmgetappidfromfilename0
-pub fn app_id_from_filename(app_path: impl AsRef<OsStr>) -> io::Result<OwnedByteBlob> {
+#[cfg(target_os = "windows")]
+pub fn app_id_from_filename(app_path: impl AsRef<OsStr>) -> Result<OwnedByteBlob> {
     let path: Vec<u16> = string_to_null_terminated_utf16(app_path);
     let mut blob = std::ptr::null_mut();
 
     // SAFETY: We are passing a valid pointer to a pointer and null-terminated string
     let status = unsafe { FwpmGetAppIdFromFileName0(path.as_ptr(), &mut blob) };
     if status != ERROR_SUCCESS {
-        return Err(io::Error::from_raw_os_error(status as i32));
+        return Err(WfpError::from_code(status as i32));
     }
 
     // SAFETY: `blob` is a valid pointer returned by `FwpmGetAppIdFromFileName0`
@@ -0,0 +1,138 @@
+//! Callouts
+
+use std::fmt;
+
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWPM_CALLOUT_FLAG_PERSISTENT, FWPM_CALLOUT_FLAG_REGISTERED,
+    FWPM_CALLOUT_FLAG_USES_PROVIDER_CONTEXT, FWPM_CALLOUT0,
+};
+use windows_sys::core::GUID;
+
+use crate::error::Result;
+use crate::layer::Layer;
+use crate::util::{GuidDisplay, byte_blob_as_slice, display_string, flags_type};
+
+flags_type! {
+    /// Flags of a callout.
+    ///
+    /// These correspond to the `FWPM_CALLOUT_FLAG_*` values of the `flags` field in the
+    /// underlying [`FWPM_CALLOUT0`] structure.
+    ///
+    /// [`FWPM_CALLOUT0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_callout0
+    pub struct CalloutFlags {
+        /// The callout survives a BFE restart (`FWPM_CALLOUT_FLAG_PERSISTENT`).
+        const PERSISTENT = FWPM_CALLOUT_FLAG_PERSISTENT;
+        /// The callout needs a provider context (`FWPM_CALLOUT_FLAG_USES_PROVIDER_CONTEXT`).
+        const USES_PROVIDER_CONTEXT = FWPM_CALLOUT_FLAG_USES_PROVIDER_CONTEXT;
+        /// The callout driver has registered the callout, so it can be invoked
+        /// (`FWPM_CALLOUT_FLAG_REGISTERED`).
+        const REGISTERED = FWPM_CALLOUT_FLAG_REGISTERED;
+    }
+}
+
+/// An owned copy of a callout.
+///
+/// A callout is a set of functions, usually exposed by a driver, that filters can invoke as
+/// their action. This is returned by [`CalloutEnumerator`](crate::CalloutEnumerator).
+///
+/// This corresponds to the underlying [`FWPM_CALLOUT0`] structure.
+///
+/// [`FWPM_CALLOUT0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_callout0
+#[derive(Clone)]
+pub struct CalloutInfo {
+    key: GUID,
+    name: Option<String>,
+    description: Option<String>,
+    flags: CalloutFlags,
+    provider: Option<GUID>,
+    provider_data: Vec<u8>,
+    applicable_layer: GUID,
+    id: u32,
+}
+
+impl CalloutInfo {
+    /// Deep-copies a raw callout.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `callout` must be valid.
+    pub(crate) unsafe fn from_raw(callout: &FWPM_CALLOUT0) -> Result<Self> {
+        // SAFETY: The caller guarantees that the pointers are valid
+        unsafe {
+            Ok(Self {
+                key: callout.calloutKey,
+                name: display_string(callout.displayData.name, "invalid callout name")?,
+                description: display_string(
+                    callout.displayData.description,
+                    "invalid callout description",
+                )?,
+                flags: CalloutFlags::from_bits_retain(callout.flags),
+                provider: callout.providerKey.as_ref().copied(),
+                provider_data: byte_blob_as_slice(&callout.providerData).to_vec(),
+                applicable_layer: callout.applicableLayer,
+                id: callout.calloutId,
+            })
+        }
+    }
+
+    /// Return the callout GUID.
+    pub fn guid(&self) -> GUID {
+        self.key
+    }
+
+    /// Return the callout name, if set.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Return the callout description, if set.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return the callout flags.
+    pub fn flags(&self) -> CalloutFlags {
+        self.flags
+    }
+
+    /// Return the provider that the callout belongs to, if set.
+    pub fn provider(&self) -> Option<GUID> {
+        self.provider
+    }
+
+    /// Return the provider data of the callout. This is empty if none is set.
+    pub fn provider_data(&self) -> &[u8] {
+        &self.provider_data
+    }
+
+    /// Return the layer that the callout can be used at, or `None` if it is not a built-in
+    /// layer.
+    pub fn layer(&self) -> Option<Layer> {
+        Layer::from_guid(&self.applicable_layer)
+    }
+
+    /// Return the GUID of the layer that the callout can be used at.
+    pub fn applicable_layer(&self) -> GUID {
+        self.applicable_layer
+    }
+
+    /// Return the runtime ID of the callout.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl fmt::Debug for CalloutInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CalloutInfo")
+            .field("key", &GuidDisplay(&self.key))
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("flags", &self.flags)
+            .field("provider", &self.provider.as_ref().map(GuidDisplay))
+            .field("provider_data", &self.provider_data)
+            .field("applicable_layer", &GuidDisplay(&self.applicable_layer))
+            .field("id", &self.id)
+            .finish()
+    }
+}
@@ -1,27 +1,66 @@
 //! Filter condition creation and management.
 
 use std::ffi::OsStr;
-use std::io;
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 
-use windows_sys::Win32::Foundation::ERROR_SUCCESS;
-use windows_sys::Win32::NetworkManagement::IpHelper::ConvertInterfaceAliasToLuid;
-use windows_sys::Win32::NetworkManagement::Ndis::NET_LUID_LH;
+use windows_sys::Win32::NetworkManagement::IpHelper::{
+    IF_TYPE_ATM, IF_TYPE_ETHERNET_CSMACD, IF_TYPE_FDDI, IF_TYPE_IEEE1394, IF_TYPE_IEEE80211,
+    IF_TYPE_IEEE80216_WMAN, IF_TYPE_ISO88025_TOKENRING, IF_TYPE_OTHER, IF_TYPE_PPP,
+    IF_TYPE_SOFTWARE_LOOPBACK, IF_TYPE_TUNNEL, IF_TYPE_WWANPP, IF_TYPE_WWANPP2,
+};
+use windows_sys::Win32::NetworkManagement::Ndis::{
+    TUNNEL_TYPE_6TO4, TUNNEL_TYPE_DIRECT, TUNNEL_TYPE_IPHTTPS, TUNNEL_TYPE_ISATAP,
+    TUNNEL_TYPE_NONE, TUNNEL_TYPE_OTHER, TUNNEL_TYPE_TEREDO,
+};
 use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
-    FWP_BYTE_BLOB_TYPE, FWP_MATCH_EQUAL, FWP_MATCH_GREATER, FWP_MATCH_GREATER_OR_EQUAL,
-    FWP_MATCH_LESS, FWP_MATCH_LESS_OR_EQUAL, FWP_MATCH_RANGE, FWP_UINT8, FWP_UINT16, FWP_UINT32,
+    FWP_ACTRL_MATCH_FILTER, FWP_BYTE_ARRAY6_TYPE, FWP_BYTE_ARRAY16, FWP_BYTE_ARRAY16_TYPE,
+    FWP_BYTE_BLOB_TYPE, FWP_CONDITION_FLAG_IS_APPCONTAINER_LOOPBACK, FWP_CONDITION_FLAG_IS_AUTH_FW,
+    FWP_CONDITION_FLAG_IS_CONNECTION_REDIRECTED, FWP_CONDITION_FLAG_IS_FRAGMENT,
+    FWP_CONDITION_FLAG_IS_FRAGMENT_GROUP, FWP_CONDITION_FLAG_IS_HONORING_POLICY_AUTHORIZE,
+    FWP_CONDITION_FLAG_IS_IMPLICIT_BIND, FWP_CONDITION_FLAG_IS_INBOUND_PASS_THRU,
+    FWP_CONDITION_FLAG_IS_IPSEC_NATT_RECLASSIFY, FWP_CONDITION_FLAG_IS_IPSEC_SECURED,
+    FWP_CONDITION_FLAG_IS_LOOPBACK, FWP_CONDITION_FLAG_IS_NAME_APP_SPECIFIED,
+    FWP_CONDITION_FLAG_IS_NON_APPCONTAINER_LOOPBACK, FWP_CONDITION_FLAG_IS_OUTBOUND_PASS_THRU,
+    FWP_CONDITION_FLAG_IS_PROMISCUOUS, FWP_CONDITION_FLAG_IS_PROXY_CONNECTION,
+    FWP_CONDITION_FLAG_IS_RAW_ENDPOINT, FWP_CONDITION_FLAG_IS_REASSEMBLED,
+    FWP_CONDITION_FLAG_IS_REAUTHORIZE, FWP_CONDITION_FLAG_IS_RECLASSIFY,
+    FWP_CONDITION_FLAG_IS_RESERVED, FWP_CONDITION_FLAG_IS_WILDCARD_BIND,
+    FWP_CONDITION_FLAG_REQUIRES_ALE_CLASSIFY, FWP_CONDITION_VALUE0, FWP_DATA_TYPE, FWP_MATCH_EQUAL,
+    FWP_MATCH_FLAGS_ALL_SET, FWP_MATCH_FLAGS_ANY_SET, FWP_MATCH_FLAGS_NONE_SET, FWP_MATCH_GREATER,
+    FWP_MATCH_GREATER_OR_EQUAL, FWP_MATCH_LESS, FWP_MATCH_LESS_OR_EQUAL, FWP_MATCH_NOT_EQUAL,
+    FWP_MATCH_NOT_PREFIX, FWP_MATCH_PREFIX, FWP_MATCH_RANGE, FWP_MATCH_TYPE, FWP_RANGE_TYPE,
+    FWP_RANGE0, FWP_SECURITY_DESCRIPTOR_TYPE, FWP_SID, FWP_UINT8, FWP_UINT16, FWP_UINT32,
     FWP_UINT64, FWP_UNICODE_STRING_TYPE, FWP_V4_ADDR_AND_MASK, FWP_V4_ADDR_MASK,
-    FWP_V6_ADDR_AND_MASK, FWP_V6_ADDR_MASK, FWPM_CONDITION_ALE_APP_ID,
-    FWPM_CONDITION_IP_LOCAL_ADDRESS, FWPM_CONDITION_IP_LOCAL_INTERFACE,
-    FWPM_CONDITION_IP_LOCAL_PORT, FWPM_CONDITION_IP_PROTOCOL, FWPM_CONDITION_IP_REMOTE_ADDRESS,
-    FWPM_CONDITION_IP_REMOTE_PORT, FWPM_FILTER_CONDITION0,
+    FWP_V6_ADDR_AND_MASK, FWP_V6_ADDR_MASK, FWP_VALUE0, FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_ORIGINAL_APP_ID, FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_REMOTE_USER_ID, FWPM_CONDITION_ALE_USER_ID, FWPM_CONDITION_COMPARTMENT_ID,
+    FWPM_CONDITION_DIRECTION, FWPM_CONDITION_FLAGS, FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_INTERFACE_TYPE, FWPM_CONDITION_IP_ARRIVAL_INTERFACE,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE, FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE, FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_IP_LOCAL_PORT, FWPM_CONDITION_IP_NEXTHOP_ADDRESS,
+    FWPM_CONDITION_IP_NEXTHOP_INTERFACE, FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS, FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX, FWPM_CONDITION_TUNNEL_TYPE, FWPM_FILTER_CONDITION0,
 };
 
 use windows_sys::core::GUID;
 
-use crate::blob::{OwnedByteBlob, app_id_from_filename};
-use crate::util::string_to_null_terminated_utf16;
+use crate::blob::OwnedByteBlob;
+#[cfg(target_os = "windows")]
+use crate::blob::app_id_from_filename;
+use crate::error::{ConditionError, ParseError, Result, WfpError};
+use crate::layer::Direction;
+use crate::luid::NetLuid;
+use crate::network::{IpNetwork, Ipv4Network, Ipv6Network};
+use crate::security::SecurityDescriptor;
+use crate::sid::Sid;
+use crate::util::{
+    GuidDisplay, byte_blob_as_slice, flags_type, guid_eq, string_to_null_terminated_utf16, wcslen,
+};
 
 // In `fwpmu.h`, `FWPM_CONDITION_ICMP_TYPE` and `FWPM_CONDITION_ICMP_CODE` are
 // `#define`d as aliases for `FWPM_CONDITION_IP_LOCAL_PORT` and
@@ -39,10 +78,18 @@ const FWPM_CONDITION_ICMP_CODE: GUID = FWPM_CONDITION_IP_REMOTE_PORT;
 /// ```no_run
 /// use wfp::{PortConditionBuilder, ConditionField, MatchType};
 ///
+/// # fn main() -> wfp::Result<()> {
 /// // Block traffic to port 80
 /// let condition = PortConditionBuilder::remote()
 ///     .equal(80)
 ///     .build();
+///
+/// // Block traffic to all non-privileged ports
+/// let condition = PortConditionBuilder::remote()
+///     .range(1024..=65535)?
+///     .build();
+/// # Ok(())
+/// # }
 /// ```
 #[derive(Clone)]
 pub struct PortConditionBuilder<Value> {
@@ -52,10 +99,12 @@ pub struct PortConditionBuilder<Value> {
 
 /// Type-state marker indicating the port value has not been set.
 #[doc(hidden)]
+#[derive(Clone)]
 pub struct PortConditionBuilderMissingValue;
 
 /// Type-state marker indicating the port value has been set.
 #[doc(hidden)]
+#[derive(Clone)]
 pub struct PortConditionBuilderHasValue;
 
 impl PortConditionBuilder<PortConditionBuilderMissingValue> {
@@ -84,12 +133,37 @@ impl<Value> PortConditionBuilder<Value> {
             _pd: std::marker::PhantomData,
         }
     }
+
+    /// Creates a condition that matches any port except the given port number.
+    pub fn not_equal(self, port: u16) -> PortConditionBuilder<PortConditionBuilderHasValue> {
+        PortConditionBuilder {
+            builder: self.builder.match_type(MatchType::NotEqual).value_u16(port),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Creates a condition that matches any port in `ports`, including both endpoints.
+    ///
+    /// Fails with [`ConditionError::InvalidRange`] if `ports` is empty.
+    pub fn range(
+        self,
+        ports: RangeInclusive<u16>,
+    ) -> Result<PortConditionBuilder<PortConditionBuilderHasValue>> {
+        Ok(PortConditionBuilder {
+            builder: self.builder.value_range(
+                RangeEndpoint::UInt16(*ports.start()),
+                RangeEndpoint::UInt16(*ports.end()),
+            )?,
+            _pd: std::marker::PhantomData,
+        })
+    }
 }
 
 impl PortConditionBuilder<PortConditionBuilderHasValue> {
     /// Builds the condition.
     ///
-    /// This method is only available when a port value has been set with `equal()`.
+    /// This method is only available when a port value has been set with `equal()`,
+    /// `not_equal()` or `range()`.
     pub fn build(self) -> Condition {
         self.builder.build().expect("condition has value")
     }
@@ -224,6 +298,17 @@ impl<Value> IcmpConditionBuilder<Value> {
         }
     }
 
+    /// Creates a condition that matches any value except the given value.
+    pub fn not_equal(self, value: u8) -> IcmpConditionBuilder<IcmpConditionBuilderHasValue> {
+        IcmpConditionBuilder {
+            builder: self
+                .builder
+                .match_type(MatchType::NotEqual)
+                .value_u16(value.into()),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
     /// Creates a condition that matches values greater than the given value.
     pub fn greater(self, value: u8) -> IcmpConditionBuilder<IcmpConditionBuilderHasValue> {
         IcmpConditionBuilder {
@@ -267,6 +352,22 @@ impl<Value> IcmpConditionBuilder<Value> {
             _pd: std::marker::PhantomData,
         }
     }
+
+    /// Creates a condition that matches any value in `values`, including both endpoints.
+    ///
+    /// Fails with [`ConditionError::InvalidRange`] if `values` is empty.
+    pub fn range(
+        self,
+        values: RangeInclusive<u8>,
+    ) -> Result<IcmpConditionBuilder<IcmpConditionBuilderHasValue>> {
+        Ok(IcmpConditionBuilder {
+            builder: self.builder.value_range(
+                RangeEndpoint::UInt16((*values.start()).into()),
+                RangeEndpoint::UInt16((*values.end()).into()),
+            )?,
+            _pd: std::marker::PhantomData,
+        })
+    }
 }
 
 impl IcmpConditionBuilder<IcmpConditionBuilderHasValue> {
@@ -313,14 +414,27 @@ impl AppIdConditionBuilder<AppIdConditionBuilderMissingValue> {
             _pd: std::marker::PhantomData,
         }
     }
+
+    /// Creates a condition on the application that originated a proxied connection
+    /// (`FWPM_CONDITION_ALE_ORIGINAL_APP_ID`), rather than on the proxy itself.
+    pub fn original() -> Self {
+        Self {
+            builder: ConditionBuilder::default().field(ConditionField::OriginalAppId),
+            _pd: std::marker::PhantomData,
+        }
+    }
 }
 
 impl<Value> AppIdConditionBuilder<Value> {
     /// Creates a condition that matches the exact application path.
+    ///
+    /// The path is converted to an app ID using `FwpmGetAppIdFromFileName0`, so this is only
+    /// available on Windows.
+    #[cfg(target_os = "windows")]
     pub fn equal(
         self,
         app_path: impl AsRef<OsStr>,
-    ) -> io::Result<AppIdConditionBuilder<AppIdConditionBuilderHasValue>> {
+    ) -> Result<AppIdConditionBuilder<AppIdConditionBuilderHasValue>> {
         let byte_blob = app_id_from_filename(app_path)?;
 
         Ok(AppIdConditionBuilder {
@@ -348,10 +462,228 @@ impl Default for AppIdConditionBuilder<AppIdConditionBuilderMissingValue> {
     }
 }
 
+/// Typed builder for conditions on the user of a connection.
+///
+/// The condition matches if the security descriptor grants `FWP_ACTRL_MATCH_FILTER` (`CC` in
+/// SDDL) to the user. The security descriptor is either parsed from an SDDL string using
+/// [`sddl`](Self::sddl), or built from SIDs that are allowed or denied using
+/// [`allow`](Self::allow) and [`deny`](Self::deny). SIDs of groups match all of their members.
+///
+/// Unless an SDDL string is given, the security descriptor is owned by LocalSystem and only
+/// contains the ACEs that are added.
+///
+/// # Example
+///
+/// ```
+/// use wfp::{Sid, UserConditionBuilder};
+///
+/// # fn main() -> Result<(), wfp::ParseError> {
+/// // Match connections made by NetworkService or LocalService
+/// let condition = UserConditionBuilder::local()
+///     .sddl("D:(A;;CC;;;NS)(A;;CC;;;LS)")?
+///     .build();
+///
+/// // Match members of the Users group, except for one account
+/// let condition = UserConditionBuilder::local()
+///     .allow("S-1-5-32-545".parse::<Sid>()?)
+///     .deny("S-1-5-21-1-2-3-1001".parse::<Sid>()?)
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct UserConditionBuilder<Value> {
+    field: ConditionField,
+    descriptor: SecurityDescriptor,
+    _pd: std::marker::PhantomData<Value>,
+}
+
+/// Type-state marker indicating the users have not been set.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct UserConditionBuilderMissingValue;
+
+/// Type-state marker indicating the users have been set.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct UserConditionBuilderHasValue;
+
+impl UserConditionBuilder<UserConditionBuilderMissingValue> {
+    /// Creates a condition on the user of the local application (`FWPM_CONDITION_ALE_USER_ID`).
+    pub fn local() -> Self {
+        Self::new(ConditionField::UserId)
+    }
+
+    /// Creates a condition on the remote user, as authenticated by IPsec
+    /// (`FWPM_CONDITION_ALE_REMOTE_USER_ID`).
+    pub fn remote() -> Self {
+        Self::new(ConditionField::RemoteUserId)
+    }
+
+    fn new(field: ConditionField) -> Self {
+        Self {
+            field,
+            descriptor: SecurityDescriptor::system_owned(),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Uses the security descriptor described by `sddl`.
+    ///
+    /// See [`SecurityDescriptor`] for the supported syntax. SIDs can still be added using
+    /// [`allow`](Self::allow) and [`deny`](Self::deny).
+    pub fn sddl(
+        self,
+        sddl: &str,
+    ) -> std::result::Result<UserConditionBuilder<UserConditionBuilderHasValue>, ParseError> {
+        Ok(self.descriptor(sddl.parse()?))
+    }
+
+    /// Uses the given security descriptor.
+    pub fn descriptor(
+        self,
+        descriptor: SecurityDescriptor,
+    ) -> UserConditionBuilder<UserConditionBuilderHasValue> {
+        UserConditionBuilder {
+            field: self.field,
+            descriptor,
+            _pd: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<Value> UserConditionBuilder<Value> {
+    /// Matches the user or group identified by `sid`, unless it is also denied.
+    pub fn allow(mut self, sid: Sid) -> UserConditionBuilder<UserConditionBuilderHasValue> {
+        self.descriptor.allow(sid, FWP_ACTRL_MATCH_FILTER);
+        UserConditionBuilder {
+            field: self.field,
+            descriptor: self.descriptor,
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Never matches the user or group identified by `sid`, even if it is allowed.
+    pub fn deny(mut self, sid: Sid) -> UserConditionBuilder<UserConditionBuilderHasValue> {
+        self.descriptor.deny(sid, FWP_ACTRL_MATCH_FILTER);
+        UserConditionBuilder {
+            field: self.field,
+            descriptor: self.descriptor,
+            _pd: std::marker::PhantomData,
+        }
+    }
+}
+
+impl UserConditionBuilder<UserConditionBuilderHasValue> {
+    /// Builds the condition.
+    ///
+    /// This method is only available once a security descriptor or SID has been set.
+    pub fn build(self) -> Condition {
+        ConditionBuilder::default()
+            .field(self.field)
+            .match_type(MatchType::Equal)
+            .value_security_descriptor(self.descriptor.to_bytes())
+            .build()
+            .expect("all values are set")
+    }
+}
+
+/// Typed builder for conditions on the package of an application.
+///
+/// Builds a condition that matches traffic from apps running in a particular AppContainer,
+/// corresponding to `FWPM_CONDITION_ALE_PACKAGE_ID`. The AppContainer is identified either by
+/// its SID, or by the package family name from which its SID is derived. Traffic from
+/// applications that do not run in an AppContainer has the null SID (`S-1-0-0`) as package ID.
+///
+/// # Example
+///
+/// ```
+/// use wfp::{PackageConditionBuilder, Sid};
+///
+/// # fn main() -> Result<(), wfp::ParseError> {
+/// // Match the Calculator app
+/// let condition = PackageConditionBuilder::default()
+///     .family_name("Microsoft.WindowsCalculator_8wekyb3d8bbwe")?
+///     .build();
+///
+/// // Match the same app by its SID
+/// let condition = PackageConditionBuilder::default()
+///     .sid("S-1-15-2-466767348-3739614953-2700836392-1801644223-4227750657-1087833535-2488631167"
+///         .parse::<Sid>()?)
+///     .build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PackageConditionBuilder<Value> {
+    sid: Option<Sid>,
+    _pd: std::marker::PhantomData<Value>,
+}
+
+/// Type-state marker indicating the package has not been set.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct PackageConditionBuilderMissingValue;
+
+/// Type-state marker indicating the package has been set.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct PackageConditionBuilderHasValue;
+
+impl PackageConditionBuilder<PackageConditionBuilderMissingValue> {
+    /// Matches the AppContainer identified by `sid`.
+    pub fn sid(self, sid: Sid) -> PackageConditionBuilder<PackageConditionBuilderHasValue> {
+        PackageConditionBuilder {
+            sid: Some(sid),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Matches the AppContainer of the package with the given family name, e.g.
+    /// `Microsoft.WindowsCalculator_8wekyb3d8bbwe`.
+    ///
+    /// See [`Sid::from_app_container_name`] for how the SID is derived.
+    pub fn family_name(
+        self,
+        name: &str,
+    ) -> std::result::Result<PackageConditionBuilder<PackageConditionBuilderHasValue>, ParseError>
+    {
+        Ok(self.sid(Sid::from_app_container_name(name)?))
+    }
+}
+
+impl PackageConditionBuilder<PackageConditionBuilderHasValue> {
+    /// Builds the condition.
+    ///
+    /// This method is only available once the package has been set.
+    pub fn build(self) -> Condition {
+        ConditionBuilder::default()
+            .field(ConditionField::PackageId)
+            .match_type(MatchType::Equal)
+            .value_sid(self.sid.expect("set by the type state"))
+            .build()
+            .expect("all values are set")
+    }
+}
+
+impl Default for PackageConditionBuilder<PackageConditionBuilderMissingValue> {
+    fn default() -> Self {
+        Self {
+            sid: None,
+            _pd: std::marker::PhantomData,
+        }
+    }
+}
+
 /// Typed builder for interface (LUID) conditions.
 ///
 /// Builds a condition that matches the local interface a connection is bound to,
-/// corresponding to `FWPM_CONDITION_IP_LOCAL_INTERFACE`.
+/// corresponding to `FWPM_CONDITION_IP_LOCAL_INTERFACE`, the interface that a packet
+/// arrived on, corresponding to `FWPM_CONDITION_IP_ARRIVAL_INTERFACE`, or the interface that
+/// a packet will be sent on, corresponding to `FWPM_CONDITION_IP_NEXTHOP_INTERFACE`.
+///
+/// Interfaces are identified by their [`NetLuid`], which can be resolved from an alias, name,
+/// index or GUID on Windows.
 ///
 /// # Example
 ///
@@ -362,6 +694,11 @@ impl Default for AppIdConditionBuilder<AppIdConditionBuilderMissingValue> {
 /// let condition = InterfaceConditionBuilder::local()
 ///     .alias("Test")?
 ///     .build();
+///
+/// // Match traffic that will be sent on a VPN adapter
+/// let condition = InterfaceConditionBuilder::next_hop()
+///     .guid(&vpn_adapter_guid)?
+///     .build();
 /// ```
 pub struct InterfaceConditionBuilder<Value> {
     builder: ConditionBuilder,
@@ -384,38 +721,85 @@ impl InterfaceConditionBuilder<InterfaceConditionBuilderMissingValue> {
             _pd: std::marker::PhantomData,
         }
     }
+
+    /// Creates an arrival interface condition (`FWPM_CONDITION_IP_ARRIVAL_INTERFACE`).
+    ///
+    /// This differs from the local interface when traffic is forwarded, or when weak host
+    /// receives are enabled.
+    pub fn arrival() -> Self {
+        Self {
+            builder: ConditionBuilder::default().field(ConditionField::ArrivalInterface),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Creates a next hop interface condition (`FWPM_CONDITION_IP_NEXTHOP_INTERFACE`).
+    ///
+    /// This is the interface that outbound or forwarded traffic will be sent on.
+    pub fn next_hop() -> Self {
+        Self {
+            builder: ConditionBuilder::default().field(ConditionField::NextHopInterface),
+            _pd: std::marker::PhantomData,
+        }
+    }
 }
 
 impl<Value> InterfaceConditionBuilder<Value> {
-    /// Creates a condition that matches the interface with the given alias.
-    ///
-    /// The alias is resolved to a LUID using [`ConvertInterfaceAliasToLuid`].
-    /// Returns `Err` if the interface does not exist.
+    /// Creates a condition that matches the interface with the given alias, e.g. `Ethernet`.
     ///
-    /// [`ConvertInterfaceAliasToLuid`]: https://learn.microsoft.com/en-us/windows/win32/api/netioapi/nf-netioapi-convertinterfacealiastoluid
+    /// Returns `Err` if the interface does not exist. See [`NetLuid::from_alias`].
+    #[cfg(target_os = "windows")]
     pub fn alias(
         self,
         alias: impl AsRef<OsStr>,
-    ) -> io::Result<InterfaceConditionBuilder<InterfaceConditionBuilderHasValue>> {
-        let wide_alias: Vec<u16> = string_to_null_terminated_utf16(alias);
-        let mut luid = NET_LUID_LH::default();
-
-        // SAFETY: Passing a null-terminated UTF-16 string and a valid pointer to NET_LUID_LH.
-        let status = unsafe { ConvertInterfaceAliasToLuid(wide_alias.as_ptr(), &mut luid) };
-        if status != ERROR_SUCCESS {
-            return Err(io::Error::from_raw_os_error(status as i32));
-        }
+    ) -> Result<InterfaceConditionBuilder<InterfaceConditionBuilderHasValue>> {
+        Ok(self.luid(NetLuid::from_alias(alias)?))
+    }
+
+    /// Creates a condition that matches the interface with the given name, e.g.
+    /// `ethernet_32768`.
+    ///
+    /// Returns `Err` if the interface does not exist. See [`NetLuid::from_name`].
+    #[cfg(target_os = "windows")]
+    pub fn name(
+        self,
+        name: impl AsRef<OsStr>,
+    ) -> Result<InterfaceConditionBuilder<InterfaceConditionBuilderHasValue>> {
+        Ok(self.luid(NetLuid::from_name(name)?))
+    }
 
-        // SAFETY: NET_LUID_LH is a union of `Value: u64` and `Info` (also 8 bytes), so reading
-        // `Value` is always valid regardless of how the OS populated the union.
-        let luid_value = unsafe { luid.Value };
-        Ok(self.luid(luid_value))
+    /// Creates a condition that matches the interface with the given index.
+    ///
+    /// Returns `Err` if the interface does not exist. See [`NetLuid::from_index`].
+    #[cfg(target_os = "windows")]
+    pub fn index(
+        self,
+        index: u32,
+    ) -> Result<InterfaceConditionBuilder<InterfaceConditionBuilderHasValue>> {
+        Ok(self.luid(NetLuid::from_index(index)?))
+    }
+
+    /// Creates a condition that matches the interface with the given GUID.
+    ///
+    /// Returns `Err` if the interface does not exist. See [`NetLuid::from_guid`].
+    #[cfg(target_os = "windows")]
+    pub fn guid(
+        self,
+        guid: &GUID,
+    ) -> Result<InterfaceConditionBuilder<InterfaceConditionBuilderHasValue>> {
+        Ok(self.luid(NetLuid::from_guid(guid)?))
     }
 
-    /// Creates a condition that matches the given raw interface LUID.
-    pub fn luid(self, luid: u64) -> InterfaceConditionBuilder<InterfaceConditionBuilderHasValue> {
+    /// Creates a condition that matches the given interface LUID.
+    pub fn luid(
+        self,
+        luid: impl Into<NetLuid>,
+    ) -> InterfaceConditionBuilder<InterfaceConditionBuilderHasValue> {
         InterfaceConditionBuilder {
-            builder: self.builder.match_type(MatchType::Equal).value_u64(luid),
+            builder: self
+                .builder
+                .match_type(MatchType::Equal)
+                .value_u64(luid.into().value()),
             _pd: std::marker::PhantomData,
         }
     }
@@ -425,57 +809,61 @@ impl InterfaceConditionBuilder<InterfaceConditionBuilderHasValue> {
     /// Builds the condition.
     ///
     /// This method is only available when an interface value has been set with
-    /// `alias()` or `luid()`.
+    /// `alias()`, `name()`, `index()`, `guid()` or `luid()`.
     pub fn build(self) -> Condition {
         self.builder.build().expect("condition should be valid")
     }
 }
 
-/// Typed builder for IP address (subnet/prefix) conditions.
+/// Typed builder for IP address conditions.
 ///
-/// This builder produces conditions that match the local or remote IP address
-/// of a connection against an IPv4 or IPv6 contiguous prefix, using the
-/// [`FWP_V4_ADDR_MASK`] / [`FWP_V6_ADDR_MASK`] WFP value types. A single host
-/// match (e.g. a specific DNS server) is expressed as a `/32` or `/128`
-/// prefix.
+/// This builder produces conditions that match the local, remote or next hop IP address
+/// of a connection against a single address, an [`IpNetwork`] (a contiguous
+/// IPv4 or IPv6 prefix), or a range of addresses. Single addresses use the `FWP_UINT32` /
+/// `FWP_BYTE_ARRAY16_TYPE` WFP value types, and prefixes use the
+/// [`FWP_V4_ADDR_MASK`] / [`FWP_V6_ADDR_MASK`] value types.
 ///
-/// `subnet_v4` is intended for use on an IPv4 layer such as
-/// [`Layer::ConnectV4`](crate::Layer::ConnectV4); `subnet_v6` is intended
-/// for an IPv6 layer such as [`Layer::ConnectV6`](crate::Layer::ConnectV6).
+/// IPv4 values are intended for use on an IPv4 layer such as
+/// [`Layer::ConnectV4`](crate::Layer::ConnectV4), and IPv6 values on an IPv6
+/// layer such as [`Layer::ConnectV6`](crate::Layer::ConnectV6). Adding a
+/// condition whose address family does not match the layer fails with
+/// [`ConditionError::AddressFamilyMismatch`](crate::ConditionError::AddressFamilyMismatch).
 ///
 /// # Example
 ///
 /// Permit outbound IPv4 connections to the `192.168.0.0/16` private range.
 /// Conditions on the same field on a single filter are combined with
-/// logical OR, so additional `subnet_v4(...)` conditions can be chained
+/// logical OR, so additional `subnet(...)` conditions can be chained
 /// onto the same `FilterBuilder` to permit more prefixes.
 ///
 /// ```no_run
-/// use std::net::Ipv4Addr;
-/// use wfp::{ActionType, FilterBuilder, IpAddressConditionBuilder, Layer};
+/// use wfp::{ActionType, FilterBuilder, IpAddressConditionBuilder, IpNetwork, Layer};
 ///
+/// # fn main() -> Result<(), wfp::ParseError> {
+/// let network: IpNetwork = "192.168.0.0/16".parse()?;
 /// let filter = FilterBuilder::default()
 ///     .name("Permit 192.168.0.0/16")
 ///     .action(ActionType::Permit)
 ///     .layer(Layer::ConnectV4)
-///     .condition(
-///         IpAddressConditionBuilder::remote()
-///             .subnet_v4(Ipv4Addr::new(192, 168, 0, 0), 16)
-///             .build(),
-///     );
+///     .condition(IpAddressConditionBuilder::remote().subnet(network).build());
+/// # Ok(())
+/// # }
 /// ```
 #[derive(Clone)]
 pub struct IpAddressConditionBuilder<Value> {
     builder: ConditionBuilder,
+    unmap_v4: bool,
     _pd: std::marker::PhantomData<Value>,
 }
 
 /// Type-state marker indicating the IP address value has not been set.
 #[doc(hidden)]
+#[derive(Clone)]
 pub struct IpAddressConditionBuilderMissingValue;
 
 /// Type-state marker indicating the IP address value has been set.
 #[doc(hidden)]
+#[derive(Clone)]
 pub struct IpAddressConditionBuilderHasValue;
 
 impl IpAddressConditionBuilder<IpAddressConditionBuilderMissingValue> {
@@ -484,6 +872,7 @@ impl IpAddressConditionBuilder<IpAddressConditionBuilderMissingValue> {
     pub fn remote() -> Self {
         Self {
             builder: ConditionBuilder::default().field(ConditionField::RemoteAddress),
+            unmap_v4: false,
             _pd: std::marker::PhantomData,
         }
     }
@@ -493,92 +882,839 @@ impl IpAddressConditionBuilder<IpAddressConditionBuilderMissingValue> {
     pub fn local() -> Self {
         Self {
             builder: ConditionBuilder::default().field(ConditionField::LocalAddress),
+            unmap_v4: false,
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Match against the IP address of the next hop
+    /// (`FWPM_CONDITION_IP_NEXTHOP_ADDRESS`).
+    pub fn next_hop() -> Self {
+        Self {
+            builder: ConditionBuilder::default().field(ConditionField::NextHopAddress),
+            unmap_v4: false,
             _pd: std::marker::PhantomData,
         }
     }
+
+    /// Converts IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) that are passed to
+    /// [`address`](Self::address) or [`range`](Self::range) to IPv4 addresses.
+    ///
+    /// This is useful when addresses come from a dual-stack socket, but the condition is
+    /// added to an IPv4 layer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::net::{IpAddr, Ipv6Addr};
+    /// use wfp::IpAddressConditionBuilder;
+    ///
+    /// let addr = IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0x0a00, 0x0001));
+    /// let condition = IpAddressConditionBuilder::remote()
+    ///     .unmap_v4()
+    ///     .address(addr)
+    ///     .build();
+    /// // Matches 10.0.0.1
+    /// ```
+    pub fn unmap_v4(mut self) -> Self {
+        self.unmap_v4 = true;
+        self
+    }
+
+    /// Returns whether IPv4-mapped addresses are matched as IPv4 addresses.
+    pub(crate) fn unmaps_v4(&self) -> bool {
+        self.unmap_v4
+    }
 }
 
 impl<V> IpAddressConditionBuilder<V> {
-    /// Match an IPv4 prefix `addr/prefix_len`.
+    /// Match any address in `network`.
+    ///
+    /// IPv4 networks are matched as `FWP_V4_ADDR_MASK` values, and IPv6 networks as
+    /// `FWP_V6_ADDR_MASK` values. Host networks (`/32` and `/128`) are matched like
+    /// [`address`](Self::address).
+    pub fn subnet(
+        self,
+        network: impl Into<IpNetwork>,
+    ) -> IpAddressConditionBuilder<IpAddressConditionBuilderHasValue> {
+        let builder = self.builder.match_type(MatchType::Equal);
+        let builder = match network.into() {
+            IpNetwork::V4(network) if network.prefix_len() == Ipv4Network::MAX_PREFIX_LEN => {
+                builder.value_u32(u32::from(network.addr()))
+            }
+            IpNetwork::V6(network) if network.prefix_len() == Ipv6Network::MAX_PREFIX_LEN => {
+                builder.value_byte_array16(network.addr().octets())
+            }
+            IpNetwork::V4(network) => {
+                builder.value_v4_addr_mask(u32::from(network.addr()), u32::from(network.mask()))
+            }
+            IpNetwork::V6(network) => {
+                builder.value_v6_addr_mask(network.addr().octets(), network.prefix_len())
+            }
+        };
+        IpAddressConditionBuilder {
+            builder,
+            unmap_v4: self.unmap_v4,
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Match an IPv4 prefix `addr/prefix_len`. Host bits of `addr` are ignored.
     ///
     /// Use this on an IPv4 layer such as
     /// [`Layer::ConnectV4`](crate::Layer::ConnectV4).
     ///
-    /// # Panics
-    ///
-    /// Panics if `prefix_len > 32`.
+    /// Fails with [`ConditionError::InvalidNetMask`] if `prefix_len > 32`.
     pub fn subnet_v4(
         self,
         addr: Ipv4Addr,
         prefix_len: u8,
-    ) -> IpAddressConditionBuilder<IpAddressConditionBuilderHasValue> {
-        let mask = v4_prefix_to_mask(prefix_len);
-        IpAddressConditionBuilder {
-            builder: self
-                .builder
-                .match_type(MatchType::Equal)
-                .value_v4_addr_mask(u32::from(addr), mask),
-            _pd: std::marker::PhantomData,
-        }
+    ) -> Result<IpAddressConditionBuilder<IpAddressConditionBuilderHasValue>> {
+        let network = Ipv4Network::new(addr, prefix_len)
+            .map_err(|_| WfpError::InvalidCondition(ConditionError::InvalidNetMask))?;
+        Ok(self.subnet(network))
     }
 
-    /// Match an IPv6 prefix `addr/prefix_len`.
+    /// Match an IPv6 prefix `addr/prefix_len`. Host bits of `addr` are ignored.
     ///
     /// Use this on an IPv6 layer such as
     /// [`Layer::ConnectV6`](crate::Layer::ConnectV6).
     ///
-    /// # Panics
-    ///
-    /// Panics if `prefix_len > 128`.
+    /// Fails with [`ConditionError::InvalidNetMask`] if `prefix_len > 128`.
     pub fn subnet_v6(
         self,
         addr: Ipv6Addr,
         prefix_len: u8,
+    ) -> Result<IpAddressConditionBuilder<IpAddressConditionBuilderHasValue>> {
+        let network = Ipv6Network::new(addr, prefix_len)
+            .map_err(|_| WfpError::InvalidCondition(ConditionError::InvalidNetMask))?;
+        Ok(self.subnet(network))
+    }
+
+    /// Match the single address `addr`.
+    ///
+    /// IPv4 addresses are matched as `FWP_UINT32` values in host byte order, and IPv6
+    /// addresses as `FWP_BYTE_ARRAY16_TYPE` values, which is what WFP expects for exact
+    /// matches. The address family should match the IP version of the layer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::net::Ipv4Addr;
+    /// use wfp::IpAddressConditionBuilder;
+    ///
+    /// // Match a specific DNS server
+    /// let condition = IpAddressConditionBuilder::remote()
+    ///     .address(Ipv4Addr::new(9, 9, 9, 9))
+    ///     .build();
+    /// ```
+    pub fn address(
+        self,
+        addr: impl Into<IpAddr>,
     ) -> IpAddressConditionBuilder<IpAddressConditionBuilderHasValue> {
-        assert!(prefix_len <= 128, "IPv6 prefix length must be <= 128");
+        let addr = self.unmap(addr.into());
+        let builder = self.builder.match_type(MatchType::Equal);
+        let builder = match addr {
+            IpAddr::V4(addr) => builder.value_u32(u32::from(addr)),
+            IpAddr::V6(addr) => builder.value_byte_array16(addr.octets()),
+        };
         IpAddressConditionBuilder {
-            builder: self
-                .builder
-                .match_type(MatchType::Equal)
-                .value_v6_addr_mask(addr.octets(), prefix_len),
+            builder,
+            unmap_v4: self.unmap_v4,
             _pd: std::marker::PhantomData,
         }
     }
+
+    /// Match any address from `start` to `end`, including both endpoints.
+    ///
+    /// Both addresses must be of the same family, which should match the IP version of the
+    /// layer. Unlike a subnet, a range does not need to be aligned to a prefix.
+    ///
+    /// Fails with [`ConditionError::AddressFamilyMismatch`] if `start` and `end` are of
+    /// different families, or with [`ConditionError::InvalidRange`] if `start > end`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::net::Ipv4Addr;
+    /// use wfp::IpAddressConditionBuilder;
+    ///
+    /// # fn main() -> wfp::Result<()> {
+    /// let condition = IpAddressConditionBuilder::remote()
+    ///     .range(Ipv4Addr::new(10, 0, 0, 5), Ipv4Addr::new(10, 0, 0, 20))?
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn range(
+        self,
+        start: impl Into<IpAddr>,
+        end: impl Into<IpAddr>,
+    ) -> Result<IpAddressConditionBuilder<IpAddressConditionBuilderHasValue>> {
+        let (low, high) = match (self.unmap(start.into()), self.unmap(end.into())) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => (
+                RangeEndpoint::UInt32(u32::from(start)),
+                RangeEndpoint::UInt32(u32::from(end)),
+            ),
+            (IpAddr::V6(start), IpAddr::V6(end)) => (
+                RangeEndpoint::ByteArray16(Box::new(FWP_BYTE_ARRAY16 {
+                    byteArray16: start.octets(),
+                })),
+                RangeEndpoint::ByteArray16(Box::new(FWP_BYTE_ARRAY16 {
+                    byteArray16: end.octets(),
+                })),
+            ),
+            _ => {
+                return Err(WfpError::InvalidCondition(
+                    ConditionError::AddressFamilyMismatch,
+                ));
+            }
+        };
+        Ok(IpAddressConditionBuilder {
+            builder: self.builder.value_range(low, high)?,
+            unmap_v4: self.unmap_v4,
+            _pd: std::marker::PhantomData,
+        })
+    }
+
+    /// Converts `addr` to an IPv4 address if it is IPv4-mapped and [`unmap_v4`] is set.
+    ///
+    /// [`unmap_v4`]: IpAddressConditionBuilder::unmap_v4
+    fn unmap(&self, addr: IpAddr) -> IpAddr {
+        if self.unmap_v4 {
+            addr.to_canonical()
+        } else {
+            addr
+        }
+    }
 }
 
 impl IpAddressConditionBuilder<IpAddressConditionBuilderHasValue> {
     /// Builds the condition.
     ///
-    /// This method is only available once a subnet has been set with
-    /// [`subnet_v4`](Self::subnet_v4) or [`subnet_v6`](Self::subnet_v6).
+    /// This method is only available once an address, subnet or range has been set with
+    /// [`address`](Self::address), [`subnet`](Self::subnet), [`subnet_v4`](Self::subnet_v4),
+    /// [`subnet_v6`](Self::subnet_v6) or [`range`](Self::range).
     pub fn build(self) -> Condition {
         self.builder.build().expect("condition should be valid")
     }
 }
 
-/// Convert an IPv4 prefix length into a contiguous host-byte-order mask.
-fn v4_prefix_to_mask(prefix_len: u8) -> u32 {
-    assert!(prefix_len <= 32, "IPv4 prefix length must be <= 32");
-    if prefix_len == 0 {
-        0
-    } else {
-        u32::MAX << (32 - prefix_len)
+flags_type! {
+    /// Flags that describe the traffic being classified, matched by a
+    /// [`FlagsConditionBuilder`].
+    ///
+    /// These correspond to the `FWP_CONDITION_FLAG_*` values of the `FWPM_CONDITION_FLAGS`
+    /// field. Not every flag is set at every layer. See [Filtering Condition Flags] for
+    /// details.
+    ///
+    /// [Filtering Condition Flags]: https://learn.microsoft.com/en-us/windows/win32/fwp/filtering-condition-flags
+    pub struct ConditionFlags {
+        /// The traffic is loopback traffic (`FWP_CONDITION_FLAG_IS_LOOPBACK`).
+        const IS_LOOPBACK = FWP_CONDITION_FLAG_IS_LOOPBACK;
+        /// The traffic is protected by IPsec (`FWP_CONDITION_FLAG_IS_IPSEC_SECURED`).
+        const IS_IPSEC_SECURED = FWP_CONDITION_FLAG_IS_IPSEC_SECURED;
+        /// The classification is a reauthorization of a previously authorized connection
+        /// (`FWP_CONDITION_FLAG_IS_REAUTHORIZE`).
+        const IS_REAUTHORIZE = FWP_CONDITION_FLAG_IS_REAUTHORIZE;
+        /// The application bound to the wildcard address
+        /// (`FWP_CONDITION_FLAG_IS_WILDCARD_BIND`).
+        const IS_WILDCARD_BIND = FWP_CONDITION_FLAG_IS_WILDCARD_BIND;
+        /// The socket is a raw socket (`FWP_CONDITION_FLAG_IS_RAW_ENDPOINT`).
+        const IS_RAW_ENDPOINT = FWP_CONDITION_FLAG_IS_RAW_ENDPOINT;
+        /// The packet is a fragment (`FWP_CONDITION_FLAG_IS_FRAGMENT`).
+        const IS_FRAGMENT = FWP_CONDITION_FLAG_IS_FRAGMENT;
+        /// The packet is a fragment group (`FWP_CONDITION_FLAG_IS_FRAGMENT_GROUP`).
+        const IS_FRAGMENT_GROUP = FWP_CONDITION_FLAG_IS_FRAGMENT_GROUP;
+        /// The packet is being reclassified after IPsec NAT traversal
+        /// (`FWP_CONDITION_FLAG_IS_IPSEC_NATT_RECLASSIFY`).
+        const IS_IPSEC_NATT_RECLASSIFY = FWP_CONDITION_FLAG_IS_IPSEC_NATT_RECLASSIFY;
+        /// The traffic has not yet been classified at the application layer enforcement (ALE)
+        /// layers (`FWP_CONDITION_FLAG_REQUIRES_ALE_CLASSIFY`).
+        const REQUIRES_ALE_CLASSIFY = FWP_CONDITION_FLAG_REQUIRES_ALE_CLASSIFY;
+        /// The bind was implicit (`FWP_CONDITION_FLAG_IS_IMPLICIT_BIND`).
+        const IS_IMPLICIT_BIND = FWP_CONDITION_FLAG_IS_IMPLICIT_BIND;
+        /// The packet was reassembled (`FWP_CONDITION_FLAG_IS_REASSEMBLED`).
+        const IS_REASSEMBLED = FWP_CONDITION_FLAG_IS_REASSEMBLED;
+        /// The application specified the remote name
+        /// (`FWP_CONDITION_FLAG_IS_NAME_APP_SPECIFIED`).
+        const IS_NAME_APP_SPECIFIED = FWP_CONDITION_FLAG_IS_NAME_APP_SPECIFIED;
+        /// The socket is in promiscuous mode (`FWP_CONDITION_FLAG_IS_PROMISCUOUS`).
+        const IS_PROMISCUOUS = FWP_CONDITION_FLAG_IS_PROMISCUOUS;
+        /// The traffic is authenticated firewall traffic (`FWP_CONDITION_FLAG_IS_AUTH_FW`).
+        const IS_AUTH_FW = FWP_CONDITION_FLAG_IS_AUTH_FW;
+        /// The classification is a reclassification (`FWP_CONDITION_FLAG_IS_RECLASSIFY`).
+        const IS_RECLASSIFY = FWP_CONDITION_FLAG_IS_RECLASSIFY;
+        /// The outbound traffic is passing through the host
+        /// (`FWP_CONDITION_FLAG_IS_OUTBOUND_PASS_THRU`).
+        const IS_OUTBOUND_PASS_THRU = FWP_CONDITION_FLAG_IS_OUTBOUND_PASS_THRU;
+        /// The inbound traffic is passing through the host
+        /// (`FWP_CONDITION_FLAG_IS_INBOUND_PASS_THRU`).
+        const IS_INBOUND_PASS_THRU = FWP_CONDITION_FLAG_IS_INBOUND_PASS_THRU;
+        /// The connection was redirected (`FWP_CONDITION_FLAG_IS_CONNECTION_REDIRECTED`).
+        const IS_CONNECTION_REDIRECTED = FWP_CONDITION_FLAG_IS_CONNECTION_REDIRECTED;
+        /// The connection is a proxy connection (`FWP_CONDITION_FLAG_IS_PROXY_CONNECTION`).
+        const IS_PROXY_CONNECTION = FWP_CONDITION_FLAG_IS_PROXY_CONNECTION;
+        /// The traffic is loopback traffic involving an AppContainer
+        /// (`FWP_CONDITION_FLAG_IS_APPCONTAINER_LOOPBACK`).
+        const IS_APPCONTAINER_LOOPBACK = FWP_CONDITION_FLAG_IS_APPCONTAINER_LOOPBACK;
+        /// The traffic is loopback traffic not involving an AppContainer
+        /// (`FWP_CONDITION_FLAG_IS_NON_APPCONTAINER_LOOPBACK`).
+        const IS_NON_APPCONTAINER_LOOPBACK = FWP_CONDITION_FLAG_IS_NON_APPCONTAINER_LOOPBACK;
+        /// Reserved (`FWP_CONDITION_FLAG_IS_RESERVED`).
+        const IS_RESERVED = FWP_CONDITION_FLAG_IS_RESERVED;
+        /// The traffic is honoring a policy authorization
+        /// (`FWP_CONDITION_FLAG_IS_HONORING_POLICY_AUTHORIZE`).
+        const IS_HONORING_POLICY_AUTHORIZE = FWP_CONDITION_FLAG_IS_HONORING_POLICY_AUTHORIZE;
     }
 }
 
-/// Specifies how a condition value should be matched against network traffic.
+/// Typed builder for conditions on the [`ConditionFlags`] of the traffic.
 ///
-/// These correspond to the [`FWP_MATCH_TYPE`] enumeration values.
+/// # Example
 ///
-/// [`FWP_MATCH_TYPE`]: https://docs.microsoft.com/en-us/windows/win32/api/fwptypes/ne-fwptypes-fwp_match_type
-#[repr(i32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum MatchType {
-    /// The condition value must exactly match the network data.
-    Equal = FWP_MATCH_EQUAL,
-    /// The network data must be greater than the condition value.
-    Greater = FWP_MATCH_GREATER,
-    /// The network data must be less than the condition value.
+/// ```
+/// use wfp::{ConditionFlags, FlagsConditionBuilder};
+///
+/// // Match traffic that is neither loopback nor a reauthorization
+/// let condition = FlagsConditionBuilder::none_set(
+///     ConditionFlags::IS_LOOPBACK | ConditionFlags::IS_REAUTHORIZE,
+/// )
+/// .build();
+/// ```
+#[derive(Clone)]
+pub struct FlagsConditionBuilder {
+    builder: ConditionBuilder,
+}
+
+impl FlagsConditionBuilder {
+    /// Creates a condition that matches if all of `flags` are set.
+    pub fn all_set(flags: ConditionFlags) -> Self {
+        Self::new(MatchType::FlagsAllSet, flags)
+    }
+
+    /// Creates a condition that matches if any of `flags` is set.
+    pub fn any_set(flags: ConditionFlags) -> Self {
+        Self::new(MatchType::FlagsAnySet, flags)
+    }
+
+    /// Creates a condition that matches if none of `flags` is set.
+    pub fn none_set(flags: ConditionFlags) -> Self {
+        Self::new(MatchType::FlagsNoneSet, flags)
+    }
+
+    /// Creates a new flags condition builder.
+    fn new(match_type: MatchType, flags: ConditionFlags) -> Self {
+        Self {
+            builder: ConditionBuilder::default()
+                .field(ConditionField::Flags)
+                .match_type(match_type)
+                .value_u32(flags.bits()),
+        }
+    }
+
+    /// Builds the condition.
+    pub fn build(self) -> Condition {
+        self.builder.build().expect("all values are set")
+    }
+}
+
+/// Typed builder for conditions on the direction of traffic (`FWPM_CONDITION_DIRECTION`).
+///
+/// This is mainly useful at layers that see traffic in both directions, such as
+/// [`Layer::DatagramDataV4`](crate::Layer::DatagramDataV4) and
+/// [`Layer::FlowEstablishedV4`](crate::Layer::FlowEstablishedV4).
+///
+/// # Example
+///
+/// ```
+/// use wfp::{Direction, DirectionConditionBuilder};
+///
+/// // Match inbound datagrams only
+/// let condition = DirectionConditionBuilder::equal(Direction::Inbound).build();
+/// ```
+#[derive(Clone)]
+pub struct DirectionConditionBuilder {
+    builder: ConditionBuilder,
+}
+
+impl DirectionConditionBuilder {
+    /// Creates a condition that matches traffic in `direction`.
+    pub fn equal(direction: Direction) -> Self {
+        Self {
+            builder: ConditionBuilder::default()
+                .field(ConditionField::Direction)
+                .match_type(MatchType::Equal)
+                .value_u32(direction as u32),
+        }
+    }
+
+    /// Builds the condition.
+    pub fn build(self) -> Condition {
+        self.builder.build().expect("all values are set")
+    }
+}
+
+/// The type of an IP address.
+///
+/// These correspond to the `NL_ADDRESS_TYPE` enumeration values.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AddressType {
+    /// The unspecified address (`0.0.0.0` or `::`).
+    Unspecified = 0,
+    /// A unicast address.
+    Unicast = 1,
+    /// An anycast address.
+    Anycast = 2,
+    /// A multicast address.
+    Multicast = 3,
+    /// A broadcast address. This only applies to IPv4.
+    Broadcast = 4,
+    /// An address that is not valid as a source or destination.
+    Invalid = 5,
+}
+
+impl AddressType {
+    const ALL: &[AddressType] = &[
+        Self::Unspecified,
+        Self::Unicast,
+        Self::Anycast,
+        Self::Multicast,
+        Self::Broadcast,
+        Self::Invalid,
+    ];
+
+    /// Returns the address type that corresponds to a raw `NL_ADDRESS_TYPE` value, if any.
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ty| *ty as u8 == raw)
+    }
+}
+
+/// Typed builder for conditions on the [`AddressType`] of the local or destination address.
+///
+/// # Example
+///
+/// ```
+/// use wfp::{AddressType, AddressTypeConditionBuilder};
+///
+/// // Match traffic to multicast addresses
+/// let condition = AddressTypeConditionBuilder::destination()
+///     .equal(AddressType::Multicast)
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct AddressTypeConditionBuilder<Value> {
+    builder: ConditionBuilder,
+    _pd: std::marker::PhantomData<Value>,
+}
+
+/// Type-state marker indicating the address type has not been set.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct AddressTypeConditionBuilderMissingValue;
+
+/// Type-state marker indicating the address type has been set.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct AddressTypeConditionBuilderHasValue;
+
+impl AddressTypeConditionBuilder<AddressTypeConditionBuilderMissingValue> {
+    /// Match against the type of the local address (`FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE`).
+    pub fn local() -> Self {
+        Self {
+            builder: ConditionBuilder::default().field(ConditionField::LocalAddressType),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Match against the type of the destination address
+    /// (`FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE`).
+    pub fn destination() -> Self {
+        Self {
+            builder: ConditionBuilder::default().field(ConditionField::DestinationAddressType),
+            _pd: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<Value> AddressTypeConditionBuilder<Value> {
+    /// Creates a condition that matches addresses of type `address_type`.
+    pub fn equal(
+        self,
+        address_type: AddressType,
+    ) -> AddressTypeConditionBuilder<AddressTypeConditionBuilderHasValue> {
+        AddressTypeConditionBuilder {
+            builder: self
+                .builder
+                .match_type(MatchType::Equal)
+                .value_u8(address_type as u8),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Creates a condition that matches addresses of any type except `address_type`.
+    pub fn not_equal(
+        self,
+        address_type: AddressType,
+    ) -> AddressTypeConditionBuilder<AddressTypeConditionBuilderHasValue> {
+        AddressTypeConditionBuilder {
+            builder: self
+                .builder
+                .match_type(MatchType::NotEqual)
+                .value_u8(address_type as u8),
+            _pd: std::marker::PhantomData,
+        }
+    }
+}
+
+impl AddressTypeConditionBuilder<AddressTypeConditionBuilderHasValue> {
+    /// Builds the condition.
+    ///
+    /// This method is only available when an address type has been set with `equal()` or
+    /// `not_equal()`.
+    pub fn build(self) -> Condition {
+        self.builder.build().expect("condition has value")
+    }
+}
+
+/// The type of a network interface.
+///
+/// These are the interface types assigned by IANA (`ifType`) that Windows commonly reports.
+/// See [`IF_TYPE`] for details.
+///
+/// [`IF_TYPE`]: https://learn.microsoft.com/en-us/windows/win32/api/iptypes/ns-iptypes-ip_adapter_addresses_lh
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InterfaceType {
+    /// Some other type of interface (`IF_TYPE_OTHER`).
+    Other = IF_TYPE_OTHER,
+    /// An Ethernet interface (`IF_TYPE_ETHERNET_CSMACD`).
+    Ethernet = IF_TYPE_ETHERNET_CSMACD,
+    /// A token ring interface (`IF_TYPE_ISO88025_TOKENRING`).
+    TokenRing = IF_TYPE_ISO88025_TOKENRING,
+    /// A Fiber Distributed Data Interface (`IF_TYPE_FDDI`).
+    Fddi = IF_TYPE_FDDI,
+    /// A PPP interface (`IF_TYPE_PPP`).
+    Ppp = IF_TYPE_PPP,
+    /// A software loopback interface (`IF_TYPE_SOFTWARE_LOOPBACK`).
+    SoftwareLoopback = IF_TYPE_SOFTWARE_LOOPBACK,
+    /// An ATM interface (`IF_TYPE_ATM`).
+    Atm = IF_TYPE_ATM,
+    /// An IEEE 802.11 wireless interface (`IF_TYPE_IEEE80211`).
+    Ieee80211 = IF_TYPE_IEEE80211,
+    /// A tunnel interface (`IF_TYPE_TUNNEL`). See [`TunnelType`] for the kind of tunnel.
+    Tunnel = IF_TYPE_TUNNEL,
+    /// An IEEE 1394 (FireWire) interface (`IF_TYPE_IEEE1394`).
+    Ieee1394 = IF_TYPE_IEEE1394,
+    /// An IEEE 802.16 WiMAX interface (`IF_TYPE_IEEE80216_WMAN`).
+    Ieee80216Wman = IF_TYPE_IEEE80216_WMAN,
+    /// A GSM-based mobile broadband interface (`IF_TYPE_WWANPP`).
+    WwanPp = IF_TYPE_WWANPP,
+    /// A CDMA-based mobile broadband interface (`IF_TYPE_WWANPP2`).
+    WwanPp2 = IF_TYPE_WWANPP2,
+}
+
+impl InterfaceType {
+    const ALL: &[InterfaceType] = &[
+        Self::Other,
+        Self::Ethernet,
+        Self::TokenRing,
+        Self::Fddi,
+        Self::Ppp,
+        Self::SoftwareLoopback,
+        Self::Atm,
+        Self::Ieee80211,
+        Self::Tunnel,
+        Self::Ieee1394,
+        Self::Ieee80216Wman,
+        Self::WwanPp,
+        Self::WwanPp2,
+    ];
+
+    /// Returns the interface type that corresponds to a raw `IF_TYPE` value, if any.
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ty| *ty as u32 == raw)
+    }
+}
+
+/// Typed builder for conditions on the [`InterfaceType`] of the interface
+/// (`FWPM_CONDITION_INTERFACE_TYPE`).
+///
+/// # Example
+///
+/// ```
+/// use wfp::{InterfaceType, InterfaceTypeConditionBuilder};
+///
+/// // Match traffic on wireless interfaces
+/// let condition = InterfaceTypeConditionBuilder::equal(InterfaceType::Ieee80211).build();
+/// ```
+#[derive(Clone)]
+pub struct InterfaceTypeConditionBuilder {
+    builder: ConditionBuilder,
+}
+
+impl InterfaceTypeConditionBuilder {
+    /// Creates a condition that matches interfaces of type `interface_type`.
+    pub fn equal(interface_type: InterfaceType) -> Self {
+        Self::new(MatchType::Equal, interface_type)
+    }
+
+    /// Creates a condition that matches interfaces of any type except `interface_type`.
+    pub fn not_equal(interface_type: InterfaceType) -> Self {
+        Self::new(MatchType::NotEqual, interface_type)
+    }
+
+    /// Creates a new interface type condition builder.
+    fn new(match_type: MatchType, interface_type: InterfaceType) -> Self {
+        Self {
+            builder: ConditionBuilder::default()
+                .field(ConditionField::InterfaceType)
+                .match_type(match_type)
+                .value_u32(interface_type as u32),
+        }
+    }
+
+    /// Builds the condition.
+    pub fn build(self) -> Condition {
+        self.builder.build().expect("all values are set")
+    }
+}
+
+/// The type of a tunnel interface.
+///
+/// These correspond to the [`TUNNEL_TYPE`] enumeration values.
+///
+/// [`TUNNEL_TYPE`]: https://learn.microsoft.com/en-us/windows/win32/api/ifdef/ne-ifdef-tunnel_type
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TunnelType {
+    /// The interface is not a tunnel.
+    None = TUNNEL_TYPE_NONE as u32,
+    /// Some other type of tunnel.
+    Other = TUNNEL_TYPE_OTHER as u32,
+    /// Packets are encapsulated directly in IPv4 (IPv6-in-IPv4).
+    Direct = TUNNEL_TYPE_DIRECT as u32,
+    /// A 6to4 tunnel.
+    SixToFour = TUNNEL_TYPE_6TO4 as u32,
+    /// An ISATAP tunnel.
+    Isatap = TUNNEL_TYPE_ISATAP as u32,
+    /// A Teredo tunnel.
+    Teredo = TUNNEL_TYPE_TEREDO as u32,
+    /// An IP-HTTPS tunnel.
+    IpHttps = TUNNEL_TYPE_IPHTTPS as u32,
+}
+
+impl TunnelType {
+    const ALL: &[TunnelType] = &[
+        Self::None,
+        Self::Other,
+        Self::Direct,
+        Self::SixToFour,
+        Self::Isatap,
+        Self::Teredo,
+        Self::IpHttps,
+    ];
+
+    /// Returns the tunnel type that corresponds to a raw `TUNNEL_TYPE` value, if any.
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ty| *ty as u32 == raw)
+    }
+}
+
+/// Typed builder for conditions on the [`TunnelType`] of the interface
+/// (`FWPM_CONDITION_TUNNEL_TYPE`).
+///
+/// # Example
+///
+/// ```
+/// use wfp::{TunnelType, TunnelTypeConditionBuilder};
+///
+/// // Match traffic over Teredo
+/// let condition = TunnelTypeConditionBuilder::equal(TunnelType::Teredo).build();
+/// ```
+#[derive(Clone)]
+pub struct TunnelTypeConditionBuilder {
+    builder: ConditionBuilder,
+}
+
+impl TunnelTypeConditionBuilder {
+    /// Creates a condition that matches tunnels of type `tunnel_type`.
+    pub fn equal(tunnel_type: TunnelType) -> Self {
+        Self::new(MatchType::Equal, tunnel_type)
+    }
+
+    /// Creates a condition that matches tunnels of any type except `tunnel_type`.
+    ///
+    /// [`TunnelType::None`] can be used to match all tunnel interfaces.
+    pub fn not_equal(tunnel_type: TunnelType) -> Self {
+        Self::new(MatchType::NotEqual, tunnel_type)
+    }
+
+    /// Creates a new tunnel type condition builder.
+    fn new(match_type: MatchType, tunnel_type: TunnelType) -> Self {
+        Self {
+            builder: ConditionBuilder::default()
+                .field(ConditionField::TunnelType)
+                .match_type(match_type)
+                .value_u32(tunnel_type as u32),
+        }
+    }
+
+    /// Builds the condition.
+    pub fn build(self) -> Condition {
+        self.builder.build().expect("all values are set")
+    }
+}
+
+/// Typed builder for conditions on the index of an interface.
+///
+/// # Example
+///
+/// ```
+/// use wfp::InterfaceIndexConditionBuilder;
+///
+/// // Match traffic on the interface with index 12
+/// let condition = InterfaceIndexConditionBuilder::interface()
+///     .equal(12)
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct InterfaceIndexConditionBuilder<Value> {
+    builder: ConditionBuilder,
+    _pd: std::marker::PhantomData<Value>,
+}
+
+/// Type-state marker indicating the interface index has not been set.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct InterfaceIndexConditionBuilderMissingValue;
+
+/// Type-state marker indicating the interface index has been set.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct InterfaceIndexConditionBuilderHasValue;
+
+impl InterfaceIndexConditionBuilder<InterfaceIndexConditionBuilderMissingValue> {
+    /// Match against the index of the interface (`FWPM_CONDITION_INTERFACE_INDEX`).
+    pub fn interface() -> Self {
+        Self {
+            builder: ConditionBuilder::default().field(ConditionField::InterfaceIndex),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Match against the index of the sub-interface (`FWPM_CONDITION_SUB_INTERFACE_INDEX`).
+    pub fn sub_interface() -> Self {
+        Self {
+            builder: ConditionBuilder::default().field(ConditionField::SubInterfaceIndex),
+            _pd: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<Value> InterfaceIndexConditionBuilder<Value> {
+    /// Creates a condition that matches the exact index.
+    pub fn equal(
+        self,
+        index: u32,
+    ) -> InterfaceIndexConditionBuilder<InterfaceIndexConditionBuilderHasValue> {
+        InterfaceIndexConditionBuilder {
+            builder: self.builder.match_type(MatchType::Equal).value_u32(index),
+            _pd: std::marker::PhantomData,
+        }
+    }
+
+    /// Creates a condition that matches any index except `index`.
+    pub fn not_equal(
+        self,
+        index: u32,
+    ) -> InterfaceIndexConditionBuilder<InterfaceIndexConditionBuilderHasValue> {
+        InterfaceIndexConditionBuilder {
+            builder: self
+                .builder
+                .match_type(MatchType::NotEqual)
+                .value_u32(index),
+            _pd: std::marker::PhantomData,
+        }
+    }
+}
+
+impl InterfaceIndexConditionBuilder<InterfaceIndexConditionBuilderHasValue> {
+    /// Builds the condition.
+    ///
+    /// This method is only available when an index has been set with `equal()` or
+    /// `not_equal()`.
+    pub fn build(self) -> Condition {
+        self.builder.build().expect("condition has value")
+    }
+}
+
+/// Typed builder for conditions on the network compartment (`FWPM_CONDITION_COMPARTMENT_ID`).
+///
+/// Compartments isolate network stacks from each other, e.g. for containers. Most traffic is
+/// in [the default compartment](Self::DEFAULT_COMPARTMENT_ID).
+///
+/// # Example
+///
+/// ```
+/// use wfp::CompartmentIdConditionBuilder;
+///
+/// // Match traffic outside of the default compartment
+/// let condition =
+///     CompartmentIdConditionBuilder::not_equal(CompartmentIdConditionBuilder::DEFAULT_COMPARTMENT_ID)
+///         .build();
+/// ```
+#[derive(Clone)]
+pub struct CompartmentIdConditionBuilder {
+    builder: ConditionBuilder,
+}
+
+impl CompartmentIdConditionBuilder {
+    /// The ID of the default compartment (`DEFAULT_COMPARTMENT_ID`).
+    pub const DEFAULT_COMPARTMENT_ID: u32 = 1;
+
+    /// Creates a condition that matches traffic in compartment `id`.
+    pub fn equal(id: u32) -> Self {
+        Self::new(MatchType::Equal, id)
+    }
+
+    /// Creates a condition that matches traffic in any compartment except `id`.
+    pub fn not_equal(id: u32) -> Self {
+        Self::new(MatchType::NotEqual, id)
+    }
+
+    /// Creates a new compartment condition builder.
+    fn new(match_type: MatchType, id: u32) -> Self {
+        Self {
+            builder: ConditionBuilder::default()
+                .field(ConditionField::CompartmentId)
+                .match_type(match_type)
+                .value_u32(id),
+        }
+    }
+
+    /// Builds the condition.
+    pub fn build(self) -> Condition {
+        self.builder.build().expect("all values are set")
+    }
+}
+
+/// Specifies how a condition value should be matched against network traffic.
+///
+/// These correspond to the [`FWP_MATCH_TYPE`] enumeration values.
+///
+/// [`FWP_MATCH_TYPE`]: https://docs.microsoft.com/en-us/windows/win32/api/fwptypes/ne-fwptypes-fwp_match_type
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchType {
+    /// The condition value must exactly match the network data.
+    Equal = FWP_MATCH_EQUAL,
+    /// The network data must be greater than the condition value.
+    Greater = FWP_MATCH_GREATER,
+    /// The network data must be less than the condition value.
     Less = FWP_MATCH_LESS,
     /// The network data must be greater than or equal to the condition value.
     GreaterOrEqual = FWP_MATCH_GREATER_OR_EQUAL,
@@ -586,13 +1722,124 @@ pub enum MatchType {
     LessOrEqual = FWP_MATCH_LESS_OR_EQUAL,
     /// The network data must fall within a specified range.
     Range = FWP_MATCH_RANGE,
+    /// All of the flags in the condition value must be set in the network data.
+    FlagsAllSet = FWP_MATCH_FLAGS_ALL_SET,
+    /// At least one of the flags in the condition value must be set in the network data.
+    FlagsAnySet = FWP_MATCH_FLAGS_ANY_SET,
+    /// None of the flags in the condition value may be set in the network data.
+    FlagsNoneSet = FWP_MATCH_FLAGS_NONE_SET,
+    /// The network data must not match the condition value.
+    NotEqual = FWP_MATCH_NOT_EQUAL,
+    /// The network data must begin with the condition value. This applies to strings and
+    /// byte blobs.
+    Prefix = FWP_MATCH_PREFIX,
+    /// The network data must not begin with the condition value. This applies to strings and
+    /// byte blobs.
+    NotPrefix = FWP_MATCH_NOT_PREFIX,
+}
+
+impl MatchType {
+    const ALL: &[MatchType] = &[
+        Self::Equal,
+        Self::Greater,
+        Self::Less,
+        Self::GreaterOrEqual,
+        Self::LessOrEqual,
+        Self::Range,
+        Self::FlagsAllSet,
+        Self::FlagsAnySet,
+        Self::FlagsNoneSet,
+        Self::NotEqual,
+        Self::Prefix,
+        Self::NotPrefix,
+    ];
+
+    /// Returns the match type that corresponds to a raw `FWP_MATCH_TYPE` value, if any.
+    pub fn from_raw(raw: FWP_MATCH_TYPE) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ty| *ty as i32 == raw)
+    }
+}
+
+/// The data type of a condition field or value.
+///
+/// These correspond to the [`FWP_DATA_TYPE`] enumeration values. The data type of each field
+/// that is available at a layer is returned by [`Layer::field_type`](crate::Layer::field_type).
+///
+/// [`FWP_DATA_TYPE`]: https://learn.microsoft.com/en-us/windows/win32/api/fwptypes/ne-fwptypes-fwp_data_type
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DataType {
+    /// An 8-bit unsigned integer (`FWP_UINT8`).
+    UInt8 = FWP_UINT8,
+    /// A 16-bit unsigned integer (`FWP_UINT16`).
+    UInt16 = FWP_UINT16,
+    /// A 32-bit unsigned integer (`FWP_UINT32`). IPv4 addresses are stored this way.
+    UInt32 = FWP_UINT32,
+    /// A 64-bit unsigned integer (`FWP_UINT64`).
+    UInt64 = FWP_UINT64,
+    /// A 16-byte array (`FWP_BYTE_ARRAY16_TYPE`). IPv6 addresses are stored this way.
+    ByteArray16 = FWP_BYTE_ARRAY16_TYPE,
+    /// A variable-length byte array (`FWP_BYTE_BLOB_TYPE`).
+    ByteBlob = FWP_BYTE_BLOB_TYPE,
+    /// A security identifier (`FWP_SID`).
+    Sid = FWP_SID,
+    /// A security descriptor (`FWP_SECURITY_DESCRIPTOR_TYPE`).
+    SecurityDescriptor = FWP_SECURITY_DESCRIPTOR_TYPE,
+    /// A null-terminated UTF-16 string (`FWP_UNICODE_STRING_TYPE`).
+    UnicodeString = FWP_UNICODE_STRING_TYPE,
+    /// A 6-byte array (`FWP_BYTE_ARRAY6_TYPE`). MAC addresses are stored this way.
+    ByteArray6 = FWP_BYTE_ARRAY6_TYPE,
+    /// An IPv4 address and mask (`FWP_V4_ADDR_MASK`).
+    V4AddrMask = FWP_V4_ADDR_MASK,
+    /// An IPv6 address and prefix length (`FWP_V6_ADDR_MASK`).
+    V6AddrMask = FWP_V6_ADDR_MASK,
+    /// A range of values (`FWP_RANGE_TYPE`).
+    Range = FWP_RANGE_TYPE,
+}
+
+impl DataType {
+    const ALL: &[DataType] = &[
+        Self::UInt8,
+        Self::UInt16,
+        Self::UInt32,
+        Self::UInt64,
+        Self::ByteArray16,
+        Self::ByteBlob,
+        Self::Sid,
+        Self::SecurityDescriptor,
+        Self::UnicodeString,
+        Self::ByteArray6,
+        Self::V4AddrMask,
+        Self::V6AddrMask,
+        Self::Range,
+    ];
+
+    /// Returns the data type that corresponds to a raw `FWP_DATA_TYPE` value, if any.
+    pub fn from_raw(raw: FWP_DATA_TYPE) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ty| *ty as i32 == raw)
+    }
+
+    /// Returns whether a value of this type can be matched against a field of type `field`.
+    ///
+    /// Apart from values of the same type, address-and-mask values can be matched against
+    /// addresses of the same family.
+    pub fn is_compatible_with(self, field: DataType) -> bool {
+        self == field
+            || matches!(
+                (self, field),
+                (Self::V4AddrMask, Self::UInt32) | (Self::V6AddrMask, Self::ByteArray16)
+            )
+    }
 }
 
 /// Represents different types of filter conditions that can be applied to network traffic.
 ///
 /// Each condition type corresponds to a specific field in the network packet or connection
-/// that can be inspected and matched against.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// that can be inspected and matched against. The fields that are available at a layer are
+/// returned by [`Layer::condition_fields`](crate::Layer::condition_fields).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum ConditionField {
     /// Remote IP address of the connection.
     RemoteAddress,
@@ -615,9 +1862,84 @@ pub enum ConditionField {
     AppId,
     /// Local interface LUID for the connection.
     LocalInterface,
+    /// Flags that describe the traffic. See [`ConditionFlags`].
+    Flags,
+    /// The user of the local application. See [`UserConditionBuilder`].
+    UserId,
+    /// The remote user, as authenticated by IPsec. See [`UserConditionBuilder`].
+    RemoteUserId,
+    /// The AppContainer SID of the application. See [`PackageConditionBuilder`].
+    PackageId,
+    /// The direction of the traffic. See [`DirectionConditionBuilder`].
+    Direction,
+    /// The [`AddressType`] of the local address. See [`AddressTypeConditionBuilder`].
+    LocalAddressType,
+    /// The [`AddressType`] of the destination address. See [`AddressTypeConditionBuilder`].
+    DestinationAddressType,
+    /// The [`InterfaceType`] of the interface. See [`InterfaceTypeConditionBuilder`].
+    InterfaceType,
+    /// The [`TunnelType`] of the interface. See [`TunnelTypeConditionBuilder`].
+    TunnelType,
+    /// The index of the interface. See [`InterfaceIndexConditionBuilder`].
+    InterfaceIndex,
+    /// The index of the sub-interface. See [`InterfaceIndexConditionBuilder`].
+    SubInterfaceIndex,
+    /// The network compartment of the traffic. See [`CompartmentIdConditionBuilder`].
+    CompartmentId,
+    /// The IP address of the next hop. See [`IpAddressConditionBuilder::next_hop`].
+    NextHopAddress,
+    /// The application ID of the process that originated a proxied connection.
+    /// See [`AppIdConditionBuilder::original`].
+    OriginalAppId,
+    /// LUID of the interface that a packet arrived on.
+    /// See [`InterfaceConditionBuilder::arrival`].
+    ArrivalInterface,
+    /// LUID of the interface that a packet will be sent on.
+    /// See [`InterfaceConditionBuilder::next_hop`].
+    NextHopInterface,
 }
 
 impl ConditionField {
+    const ALL: &[ConditionField] = &[
+        Self::RemoteAddress,
+        Self::LocalAddress,
+        Self::RemotePort,
+        Self::LocalPort,
+        Self::Protocol,
+        Self::IcmpType,
+        Self::IcmpCode,
+        Self::AppId,
+        Self::LocalInterface,
+        Self::Flags,
+        Self::UserId,
+        Self::RemoteUserId,
+        Self::PackageId,
+        Self::Direction,
+        Self::LocalAddressType,
+        Self::DestinationAddressType,
+        Self::InterfaceType,
+        Self::TunnelType,
+        Self::InterfaceIndex,
+        Self::SubInterfaceIndex,
+        Self::CompartmentId,
+        Self::NextHopAddress,
+        Self::OriginalAppId,
+        Self::ArrivalInterface,
+        Self::NextHopInterface,
+    ];
+
+    /// Returns the field identified by `guid`, or `None` if it is not a known field.
+    ///
+    /// [`ConditionField::IcmpType`] and [`ConditionField::IcmpCode`] share their GUIDs with
+    /// [`ConditionField::LocalPort`] and [`ConditionField::RemotePort`], so those GUIDs are
+    /// always mapped to the port fields.
+    pub fn from_guid(guid: &GUID) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|field| guid_eq(field.guid(), guid))
+    }
+
     /// Returns the Windows GUID identifier for this condition field.
     pub fn guid(&self) -> &GUID {
         match self {
@@ -630,6 +1952,22 @@ impl ConditionField {
             Self::IcmpCode => &FWPM_CONDITION_ICMP_CODE,
             Self::AppId => &FWPM_CONDITION_ALE_APP_ID,
             Self::LocalInterface => &FWPM_CONDITION_IP_LOCAL_INTERFACE,
+            Self::Flags => &FWPM_CONDITION_FLAGS,
+            Self::UserId => &FWPM_CONDITION_ALE_USER_ID,
+            Self::RemoteUserId => &FWPM_CONDITION_ALE_REMOTE_USER_ID,
+            Self::PackageId => &FWPM_CONDITION_ALE_PACKAGE_ID,
+            Self::Direction => &FWPM_CONDITION_DIRECTION,
+            Self::LocalAddressType => &FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+            Self::DestinationAddressType => &FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE,
+            Self::InterfaceType => &FWPM_CONDITION_INTERFACE_TYPE,
+            Self::TunnelType => &FWPM_CONDITION_TUNNEL_TYPE,
+            Self::InterfaceIndex => &FWPM_CONDITION_INTERFACE_INDEX,
+            Self::SubInterfaceIndex => &FWPM_CONDITION_SUB_INTERFACE_INDEX,
+            Self::CompartmentId => &FWPM_CONDITION_COMPARTMENT_ID,
+            Self::NextHopAddress => &FWPM_CONDITION_IP_NEXTHOP_ADDRESS,
+            Self::OriginalAppId => &FWPM_CONDITION_ALE_ORIGINAL_APP_ID,
+            Self::ArrivalInterface => &FWPM_CONDITION_IP_ARRIVAL_INTERFACE,
+            Self::NextHopInterface => &FWPM_CONDITION_IP_NEXTHOP_INTERFACE,
         }
     }
 }
@@ -644,6 +1982,9 @@ impl ConditionField {
 /// - [`PortConditionBuilder`] for port-based conditions
 /// - [`ProtocolConditionBuilder`] for protocol-based conditions
 /// - [`AppIdConditionBuilder`] for application-based conditions
+/// - [`FlagsConditionBuilder`] for conditions on [`ConditionFlags`]
+/// - [`UserConditionBuilder`] for user-based conditions
+/// - [`PackageConditionBuilder`] for AppContainer-based conditions
 ///
 /// # Example
 ///
@@ -669,9 +2010,104 @@ enum ConditionValue {
     UInt16(u16),
     UInt8(u8),
     String(Vec<u16>),
-    ByteBlob { blob: OwnedByteBlob },
+    ByteBlob {
+        blob: OwnedByteBlob,
+    },
+    SecurityDescriptor {
+        blob: OwnedByteBlob,
+    },
+    Sid(Sid),
+    ByteArray16(FWP_BYTE_ARRAY16),
     V4AddrMask(FWP_V4_ADDR_AND_MASK),
     V6AddrMask(FWP_V6_ADDR_AND_MASK),
+    Range {
+        range: FWP_RANGE0,
+        // These keep the data that `range` points to valid
+        _low: RangeEndpoint,
+        _high: RangeEndpoint,
+    },
+}
+
+/// An endpoint of a range value. Values that `FWP_VALUE0` stores by pointer are boxed, so
+/// that the pointers remain valid when the endpoint is moved.
+enum RangeEndpoint {
+    UInt8(u8),
+    UInt16(u16),
+    UInt32(u32),
+    UInt64(Box<u64>),
+    ByteArray16(Box<FWP_BYTE_ARRAY16>),
+}
+
+impl RangeEndpoint {
+    /// Returns whether `self` and `high` are of the same type, and `self` does not exceed
+    /// `high`.
+    fn is_at_most(&self, high: &RangeEndpoint) -> bool {
+        match (self, high) {
+            (RangeEndpoint::UInt8(low), RangeEndpoint::UInt8(high)) => low <= high,
+            (RangeEndpoint::UInt16(low), RangeEndpoint::UInt16(high)) => low <= high,
+            (RangeEndpoint::UInt32(low), RangeEndpoint::UInt32(high)) => low <= high,
+            (RangeEndpoint::UInt64(low), RangeEndpoint::UInt64(high)) => low <= high,
+            (RangeEndpoint::ByteArray16(low), RangeEndpoint::ByteArray16(high)) => {
+                low.byteArray16 <= high.byteArray16
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns a raw value that points into this endpoint.
+    fn to_raw(&self) -> FWP_VALUE0 {
+        // SAFETY: This is a C struct
+        let mut value: FWP_VALUE0 = unsafe { std::mem::zeroed() };
+        match self {
+            RangeEndpoint::UInt8(val) => {
+                value.r#type = FWP_UINT8;
+                value.Anonymous.uint8 = *val;
+            }
+            RangeEndpoint::UInt16(val) => {
+                value.r#type = FWP_UINT16;
+                value.Anonymous.uint16 = *val;
+            }
+            RangeEndpoint::UInt32(val) => {
+                value.r#type = FWP_UINT32;
+                value.Anonymous.uint32 = *val;
+            }
+            RangeEndpoint::UInt64(val) => {
+                value.r#type = FWP_UINT64;
+                // SAFETY: The data is never mutated, and is boxed
+                value.Anonymous.uint64 = &**val as *const u64 as *mut u64;
+            }
+            RangeEndpoint::ByteArray16(val) => {
+                value.r#type = FWP_BYTE_ARRAY16_TYPE;
+                // SAFETY: The data is never mutated, and is boxed
+                value.Anonymous.byteArray16 = &**val as *const _ as *mut _;
+            }
+        }
+        value
+    }
+
+    /// Deep-copies a raw range endpoint.
+    ///
+    /// Returns `None` if the value type is not supported.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `value` must be valid for its type.
+    unsafe fn from_raw(value: &FWP_VALUE0) -> Option<Self> {
+        // SAFETY: The caller guarantees that the union field matching `type` is valid
+        let value = unsafe {
+            match value.r#type {
+                FWP_UINT8 => RangeEndpoint::UInt8(value.Anonymous.uint8),
+                FWP_UINT16 => RangeEndpoint::UInt16(value.Anonymous.uint16),
+                FWP_UINT32 => RangeEndpoint::UInt32(value.Anonymous.uint32),
+                FWP_UINT64 => RangeEndpoint::UInt64(Box::new(*value.Anonymous.uint64)),
+                FWP_BYTE_ARRAY16_TYPE => {
+                    RangeEndpoint::ByteArray16(Box::new(*value.Anonymous.byteArray16))
+                }
+                _ => return None,
+            }
+        };
+        Some(value)
+    }
 }
 
 impl ConditionBuilder {
@@ -694,7 +2130,6 @@ impl ConditionBuilder {
     }
 
     /// Sets a 32-bit unsigned integer value for the condition.
-    #[allow(dead_code)]
     pub fn value_u32(mut self, value: u32) -> Self {
         self.value = Some(ConditionValue::UInt32(value).into());
         self
@@ -735,11 +2170,32 @@ impl ConditionBuilder {
     ///     .value_byte_blob(app_id_data)
     ///     .build()?;
     /// ```
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
     pub fn value_byte_blob(mut self, blob: impl Into<OwnedByteBlob>) -> Self {
         self.value = Some(ConditionValue::ByteBlob { blob: blob.into() }.into());
         self
     }
 
+    /// Sets a self-relative security descriptor value for the condition.
+    pub fn value_security_descriptor(mut self, sd: impl Into<OwnedByteBlob>) -> Self {
+        self.value = Some(ConditionValue::SecurityDescriptor { blob: sd.into() }.into());
+        self
+    }
+
+    /// Sets a SID value for the condition.
+    pub fn value_sid(mut self, sid: Sid) -> Self {
+        self.value = Some(ConditionValue::Sid(sid).into());
+        self
+    }
+
+    /// Sets a 16-byte array value for the condition, such as an IPv6 address in network byte
+    /// order.
+    pub fn value_byte_array16(mut self, bytes: [u8; 16]) -> Self {
+        self.value =
+            Some(ConditionValue::ByteArray16(FWP_BYTE_ARRAY16 { byteArray16: bytes }).into());
+        self
+    }
+
     /// Sets an IPv4 address-and-mask value for the condition.
     ///
     /// `addr` and `mask` are in host byte order, matching the in-memory layout
@@ -764,17 +2220,355 @@ impl ConditionBuilder {
         self
     }
 
-    /// Builds the condition into the internal representation used by FilterBuilder.
-    pub fn build(self) -> Option<Condition> {
-        let field = self.field?;
-        let match_type = self.match_type?;
-        let value = self.value?;
+    /// Sets a range value for the condition, and sets the match type to
+    /// [`MatchType::Range`].
+    ///
+    /// Fails with [`ConditionError::InvalidRange`] if `low` and `high` are of different types,
+    /// or if `low > high`.
+    fn value_range(mut self, low: RangeEndpoint, high: RangeEndpoint) -> Result<Self> {
+        if !low.is_at_most(&high) {
+            return Err(WfpError::InvalidCondition(ConditionError::InvalidRange));
+        }
+        self.match_type = Some(MatchType::Range);
+        self.value = Some(ConditionValue::range(low, high).into());
+        Ok(self)
+    }
+
+    /// Builds the condition into the internal representation used by FilterBuilder.
+    pub fn build(self) -> Option<Condition> {
+        let field = self.field?;
+        let match_type = self.match_type?;
+        let value = self.value?;
+
+        Some(Condition::from_parts(
+            *field.guid(),
+            Some(field),
+            match_type as i32,
+            value,
+        ))
+    }
+}
+
+impl ConditionValue {
+    /// Creates a range value from `low` to `high`, inclusive.
+    fn range(low: RangeEndpoint, high: RangeEndpoint) -> Self {
+        let range = FWP_RANGE0 {
+            valueLow: low.to_raw(),
+            valueHigh: high.to_raw(),
+        };
+        ConditionValue::Range {
+            range,
+            _low: low,
+            _high: high,
+        }
+    }
+
+    /// Deep-copies a raw condition value.
+    ///
+    /// Returns `None` if the value type is not supported.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `value` must be valid for its type.
+    unsafe fn from_raw(value: &FWP_CONDITION_VALUE0) -> Option<Self> {
+        // SAFETY: The caller guarantees that the union field matching `type` is valid
+        let value = unsafe {
+            match value.r#type {
+                FWP_UINT8 => ConditionValue::UInt8(value.Anonymous.uint8),
+                FWP_UINT16 => ConditionValue::UInt16(value.Anonymous.uint16),
+                FWP_UINT32 => ConditionValue::UInt32(value.Anonymous.uint32),
+                FWP_UINT64 => ConditionValue::UInt64(*value.Anonymous.uint64),
+                FWP_UNICODE_STRING_TYPE => {
+                    let s = value.Anonymous.unicodeString;
+                    let len = wcslen(s);
+                    // Include the null terminator
+                    ConditionValue::String(std::slice::from_raw_parts(s, len + 1).to_vec())
+                }
+                FWP_BYTE_BLOB_TYPE => ConditionValue::ByteBlob {
+                    blob: OwnedByteBlob::from(byte_blob_as_slice(&*value.Anonymous.byteBlob)),
+                },
+                FWP_SECURITY_DESCRIPTOR_TYPE => ConditionValue::SecurityDescriptor {
+                    blob: OwnedByteBlob::from(byte_blob_as_slice(&*value.Anonymous.sd)),
+                },
+                FWP_SID => ConditionValue::Sid(Sid::from_raw(value.Anonymous.sid.cast())?),
+                FWP_BYTE_ARRAY16_TYPE => ConditionValue::ByteArray16(*value.Anonymous.byteArray16),
+                FWP_V4_ADDR_MASK => ConditionValue::V4AddrMask(*value.Anonymous.v4AddrMask),
+                FWP_V6_ADDR_MASK => ConditionValue::V6AddrMask(*value.Anonymous.v6AddrMask),
+                FWP_RANGE_TYPE => {
+                    let range = &*value.Anonymous.rangeValue;
+                    ConditionValue::range(
+                        RangeEndpoint::from_raw(&range.valueLow)?,
+                        RangeEndpoint::from_raw(&range.valueHigh)?,
+                    )
+                }
+                _ => return None,
+            }
+        };
+        Some(value)
+    }
+}
+
+/// The value of a [`Condition`], decoded according to its field.
+///
+/// Values of known fields are decoded into the types that the typed builders accept, e.g.
+/// [`IpAddr`] for addresses and [`Direction`] for the direction of traffic. Values of other
+/// fields, and values that do not fit the type of their field, are decoded by their data type
+/// only.
+///
+/// # Example
+///
+/// ```
+/// use std::net::Ipv4Addr;
+/// use wfp::{IpAddressConditionBuilder, PortConditionBuilder, TypedValue};
+///
+/// # fn main() -> wfp::Result<()> {
+/// let condition = IpAddressConditionBuilder::remote()
+///     .address(Ipv4Addr::new(10, 0, 0, 1))
+///     .build();
+/// assert_eq!(condition.value(), TypedValue::Address(Ipv4Addr::new(10, 0, 0, 1).into()));
+///
+/// let condition = PortConditionBuilder::local().range(1024..=2047)?.build();
+/// assert_eq!(
+///     condition.value(),
+///     TypedValue::Range(Box::new(TypedValue::Port(1024)), Box::new(TypedValue::Port(2047))),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TypedValue {
+    /// An IP address.
+    Address(IpAddr),
+    /// An IP network (`FWP_V4_ADDR_MASK` or `FWP_V6_ADDR_MASK`).
+    Network(IpNetwork),
+    /// A port number, or an ICMP type or code.
+    Port(u16),
+    /// An IP protocol number.
+    Protocol(u8),
+    /// An application ID, i.e. the device path of an executable.
+    AppId(String),
+    /// The LUID of an interface.
+    Interface(NetLuid),
+    /// Flags that describe the traffic.
+    Flags(ConditionFlags),
+    /// A security descriptor that matches users.
+    SecurityDescriptor(SecurityDescriptor),
+    /// A security identifier.
+    Sid(Sid),
+    /// The direction of traffic.
+    Direction(Direction),
+    /// The type of an IP address.
+    AddressType(AddressType),
+    /// The type of an interface.
+    InterfaceType(InterfaceType),
+    /// The type of a tunnel.
+    TunnelType(TunnelType),
+    /// An inclusive range of values.
+    Range(Box<TypedValue>, Box<TypedValue>),
+    /// An 8-bit unsigned integer.
+    UInt8(u8),
+    /// A 16-bit unsigned integer.
+    UInt16(u16),
+    /// A 32-bit unsigned integer.
+    UInt32(u32),
+    /// A 64-bit unsigned integer.
+    UInt64(u64),
+    /// A 16-byte array.
+    ByteArray16([u8; 16]),
+    /// A variable-length byte array. Security descriptors that cannot be decoded are also
+    /// returned this way.
+    ByteBlob(Vec<u8>),
+    /// A string.
+    String(String),
+    /// An IPv4 address and a mask that is not a prefix.
+    V4AddrMask(Ipv4Addr, Ipv4Addr),
+    /// An IPv6 address and a prefix length that is out of range.
+    V6AddrMask(Ipv6Addr, u8),
+}
+
+impl TypedValue {
+    /// Decodes `value` by its data type only.
+    fn untyped(value: &ConditionValue) -> Self {
+        match value {
+            ConditionValue::UInt64(val) => Self::UInt64(*val),
+            ConditionValue::UInt32(val) => Self::UInt32(*val),
+            ConditionValue::UInt16(val) => Self::UInt16(*val),
+            ConditionValue::UInt8(val) => Self::UInt8(*val),
+            ConditionValue::String(wide_str) => {
+                let len = wide_str
+                    .iter()
+                    .position(|&c| c == 0)
+                    .unwrap_or(wide_str.len());
+                Self::String(String::from_utf16_lossy(&wide_str[..len]))
+            }
+            ConditionValue::ByteBlob { blob } => Self::ByteBlob(blob.as_slice().to_vec()),
+            ConditionValue::SecurityDescriptor { blob } => {
+                SecurityDescriptor::from_bytes(blob.as_slice()).map_or_else(
+                    |_| Self::ByteBlob(blob.as_slice().to_vec()),
+                    Self::SecurityDescriptor,
+                )
+            }
+            ConditionValue::Sid(sid) => Self::Sid(sid.clone()),
+            ConditionValue::ByteArray16(bytes) => Self::ByteArray16(bytes.byteArray16),
+            ConditionValue::V4AddrMask(addr_and_mask) => {
+                let addr = Ipv4Addr::from(addr_and_mask.addr);
+                let mask = addr_and_mask.mask;
+                let prefix_len = u8::try_from(mask.leading_ones()).unwrap();
+                match Ipv4Network::new(addr, prefix_len) {
+                    Ok(network) if mask.count_ones() == mask.leading_ones() => {
+                        Self::Network(network.into())
+                    }
+                    _ => Self::V4AddrMask(addr, Ipv4Addr::from(mask)),
+                }
+            }
+            ConditionValue::V6AddrMask(addr_and_mask) => {
+                let addr = Ipv6Addr::from(addr_and_mask.addr);
+                let prefix_len = addr_and_mask.prefixLength;
+                Ipv6Network::new(addr, prefix_len)
+                    .map_or(Self::V6AddrMask(addr, prefix_len), |network| {
+                        Self::Network(network.into())
+                    })
+            }
+            ConditionValue::Range { _low, _high, .. } => {
+                Self::Range(Box::new(_low.into()), Box::new(_high.into()))
+            }
+        }
+    }
+
+    /// Decodes `self` into the type of `field`, if it has the data type of that field.
+    fn typed(self, field: Option<ConditionField>) -> Self {
+        let Some(field) = field else {
+            return self;
+        };
+        match (field, self) {
+            (
+                ConditionField::LocalAddress
+                | ConditionField::RemoteAddress
+                | ConditionField::NextHopAddress,
+                Self::UInt32(addr),
+            ) => Self::Address(Ipv4Addr::from(addr).into()),
+            (
+                ConditionField::LocalAddress
+                | ConditionField::RemoteAddress
+                | ConditionField::NextHopAddress,
+                Self::ByteArray16(addr),
+            ) => Self::Address(Ipv6Addr::from(addr).into()),
+            (
+                ConditionField::LocalPort
+                | ConditionField::RemotePort
+                | ConditionField::IcmpType
+                | ConditionField::IcmpCode,
+                Self::UInt16(port),
+            ) => Self::Port(port),
+            (ConditionField::Protocol, Self::UInt8(protocol)) => Self::Protocol(protocol),
+            (ConditionField::AppId | ConditionField::OriginalAppId, Self::ByteBlob(blob)) => {
+                match app_id_from_blob(&blob) {
+                    Some(app_id) => Self::AppId(app_id),
+                    None => Self::ByteBlob(blob),
+                }
+            }
+            (
+                ConditionField::LocalInterface
+                | ConditionField::ArrivalInterface
+                | ConditionField::NextHopInterface,
+                Self::UInt64(luid),
+            ) => Self::Interface(NetLuid::from_value(luid)),
+            (ConditionField::Flags, Self::UInt32(flags)) => {
+                Self::Flags(ConditionFlags::from_bits_retain(flags))
+            }
+            (ConditionField::Direction, Self::UInt32(val)) => i32::try_from(val)
+                .ok()
+                .and_then(Direction::from_raw)
+                .map_or(Self::UInt32(val), Self::Direction),
+            (
+                ConditionField::LocalAddressType | ConditionField::DestinationAddressType,
+                Self::UInt8(val),
+            ) => AddressType::from_raw(val).map_or(Self::UInt8(val), Self::AddressType),
+            (ConditionField::InterfaceType, Self::UInt32(val)) => {
+                InterfaceType::from_raw(val).map_or(Self::UInt32(val), Self::InterfaceType)
+            }
+            (ConditionField::TunnelType, Self::UInt32(val)) => {
+                TunnelType::from_raw(val).map_or(Self::UInt32(val), Self::TunnelType)
+            }
+            (_, Self::Range(low, high)) => Self::Range(
+                Box::new(low.typed(Some(field))),
+                Box::new(high.typed(Some(field))),
+            ),
+            (_, value) => value,
+        }
+    }
+}
+
+impl From<&RangeEndpoint> for TypedValue {
+    fn from(endpoint: &RangeEndpoint) -> Self {
+        match endpoint {
+            RangeEndpoint::UInt8(val) => Self::UInt8(*val),
+            RangeEndpoint::UInt16(val) => Self::UInt16(*val),
+            RangeEndpoint::UInt32(val) => Self::UInt32(*val),
+            RangeEndpoint::UInt64(val) => Self::UInt64(**val),
+            RangeEndpoint::ByteArray16(val) => Self::ByteArray16(val.byteArray16),
+        }
+    }
+}
+
+/// Decodes an application ID, which is a null-terminated UTF-16 string.
+fn app_id_from_blob(blob: &[u8]) -> Option<String> {
+    if !blob.len().is_multiple_of(2) {
+        return None;
+    }
+    let wide: Vec<u16> = blob
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect();
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+    String::from_utf16(&wide[..len]).ok()
+}
+
+/// Internal representation of a built condition.
+///
+/// This can be added to a [`FilterBuilder`](crate::FilterBuilder).
+#[derive(Clone)]
+pub struct Condition {
+    raw_condition: FWPM_FILTER_CONDITION0,
+    field: Option<ConditionField>,
+    // This keeps underlying pointers and data valid
+    _value: Arc<ConditionValue>,
+}
 
+// SAFETY: The pointers in `raw_condition` only refer to the immutable data in `_value`
+unsafe impl Send for Condition {}
+// SAFETY: The pointers in `raw_condition` only refer to the immutable data in `_value`
+unsafe impl Sync for Condition {}
+
+impl fmt::Debug for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Condition");
+        match self.field {
+            Some(field) => debug.field("field", &field),
+            None => debug.field("field_key", &GuidDisplay(&self.raw_condition.fieldKey)),
+        };
+        match self.match_type() {
+            Some(match_type) => debug.field("match_type", &match_type),
+            None => debug.field("match_type", &self.raw_condition.matchType),
+        };
+        debug.field("value", &self.value()).finish()
+    }
+}
+
+impl Condition {
+    /// Creates a condition from its parts, pointing the raw value at the data in `value`.
+    fn from_parts(
+        field_key: GUID,
+        field: Option<ConditionField>,
+        match_type: i32,
+        value: Arc<ConditionValue>,
+    ) -> Self {
         // SAFETY: This is a C struct
         let mut raw_condition: FWPM_FILTER_CONDITION0 = unsafe { std::mem::zeroed() };
 
-        raw_condition.fieldKey = *field.guid();
-        raw_condition.matchType = match_type as i32;
+        raw_condition.fieldKey = field_key;
+        raw_condition.matchType = match_type;
 
         match &*value {
             ConditionValue::UInt64(val) => {
@@ -805,6 +2599,21 @@ impl ConditionBuilder {
                 // SAFETY: The data is never mutated, and is tied to the lifetime of Condition
                 raw_condition.conditionValue.Anonymous.byteBlob = blob.as_ptr() as _;
             }
+            ConditionValue::SecurityDescriptor { blob } => {
+                raw_condition.conditionValue.r#type = FWP_SECURITY_DESCRIPTOR_TYPE;
+                // SAFETY: The data is never mutated, and is tied to the lifetime of Condition
+                raw_condition.conditionValue.Anonymous.sd = blob.as_ptr() as _;
+            }
+            ConditionValue::Sid(sid) => {
+                raw_condition.conditionValue.r#type = FWP_SID;
+                // SAFETY: The data is never mutated, and is tied to the lifetime of Condition
+                raw_condition.conditionValue.Anonymous.sid = sid.as_bytes().as_ptr() as _;
+            }
+            ConditionValue::ByteArray16(bytes) => {
+                raw_condition.conditionValue.r#type = FWP_BYTE_ARRAY16_TYPE;
+                // SAFETY: The data is never mutated, and is tied to the lifetime of Condition
+                raw_condition.conditionValue.Anonymous.byteArray16 = bytes as *const _ as *mut _;
+            }
             ConditionValue::V4AddrMask(addr_and_mask) => {
                 raw_condition.conditionValue.r#type = FWP_V4_ADDR_MASK;
                 // SAFETY: The data is never mutated, and is tied to the lifetime of Condition
@@ -821,26 +2630,71 @@ impl ConditionBuilder {
                 raw_condition.conditionValue.Anonymous.v6AddrMask =
                     addr_and_mask as *const _ as *mut _;
             }
+            ConditionValue::Range { range, .. } => {
+                raw_condition.conditionValue.r#type = FWP_RANGE_TYPE;
+                // SAFETY: The data is never mutated, and is tied to the lifetime of Condition
+                raw_condition.conditionValue.Anonymous.rangeValue = range as *const _ as *mut _;
+            }
         }
 
-        Some(Condition {
+        Condition {
             raw_condition,
+            field,
             _value: value,
-        })
+        }
     }
-}
 
-/// Internal representation of a built condition.
-///
-/// This can be added to a [`FilterBuilder`](crate::FilterBuilder).
-#[derive(Clone)]
-pub struct Condition {
-    raw_condition: FWPM_FILTER_CONDITION0,
-    // This keeps underlying pointers and data valid
-    _value: Arc<ConditionValue>,
-}
+    /// Deep-copies a raw filter condition.
+    ///
+    /// Returns `None` if the condition value type is not supported.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `raw` must be valid.
+    pub(crate) unsafe fn from_raw(raw: &FWPM_FILTER_CONDITION0) -> Option<Self> {
+        // SAFETY: The caller guarantees that the pointers are valid
+        let value = unsafe { ConditionValue::from_raw(&raw.conditionValue) }?;
+        Some(Self::from_parts(
+            raw.fieldKey,
+            ConditionField::from_guid(&raw.fieldKey),
+            raw.matchType,
+            value.into(),
+        ))
+    }
+
+    /// Return the field that the condition matches, or `None` if it is not a known field.
+    pub fn field(&self) -> Option<ConditionField> {
+        self.field
+    }
+
+    /// Return the GUID of the field that the condition matches.
+    pub fn field_key(&self) -> GUID {
+        self.raw_condition.fieldKey
+    }
+
+    /// Return how the value is matched against the field, or `None` if the match type is not
+    /// known.
+    pub fn match_type(&self) -> Option<MatchType> {
+        MatchType::from_raw(self.raw_condition.matchType)
+    }
+
+    /// Return the value that is compared against the field, decoded according to the field.
+    pub fn value(&self) -> TypedValue {
+        TypedValue::untyped(&self._value).typed(self.field)
+    }
+
+    /// Return the type of the value that is compared against the field.
+    ///
+    /// For ranges, this is the type of the endpoints.
+    pub(crate) fn value_type(&self) -> Option<DataType> {
+        let value = &self.raw_condition.conditionValue;
+        if value.r#type == FWP_RANGE_TYPE {
+            // SAFETY: The type is FWP_RANGE_TYPE, and the range is kept alive by `_value`
+            return DataType::from_raw(unsafe { (*value.Anonymous.rangeValue).valueLow.r#type });
+        }
+        DataType::from_raw(value.r#type)
+    }
 
-impl Condition {
     /// Return the underlying FWPM_FILTER_CONDITION0 structure.
     pub(crate) fn raw_condition(&self) -> &FWPM_FILTER_CONDITION0 {
         &self.raw_condition
@@ -851,6 +2705,8 @@ impl Condition {
 mod test {
     use std::str::FromStr;
 
+    use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWP_DIRECTION_INBOUND;
+
     use super::*;
 
     fn assert_field_key_eq(actual: &GUID, expected: &GUID) {
@@ -956,24 +2812,19 @@ mod test {
     }
 
     #[test]
-    fn test_v4_prefix_to_mask() {
-        assert_eq!(v4_prefix_to_mask(0), 0x00000000);
-        assert_eq!(v4_prefix_to_mask(8), 0xFF000000);
-        assert_eq!(v4_prefix_to_mask(16), 0xFFFF0000);
-        assert_eq!(v4_prefix_to_mask(24), 0xFFFFFF00);
-        assert_eq!(v4_prefix_to_mask(32), 0xFFFFFFFF);
-    }
-
-    #[test]
-    #[should_panic(expected = "IPv4 prefix length must be <= 32")]
-    fn test_v4_prefix_to_mask_too_large() {
-        let _ = v4_prefix_to_mask(33);
+    fn test_subnet_v4_prefix_too_large() {
+        let result = IpAddressConditionBuilder::remote().subnet_v4(Ipv4Addr::UNSPECIFIED, 33);
+        assert!(matches!(
+            result,
+            Err(WfpError::InvalidCondition(ConditionError::InvalidNetMask))
+        ));
     }
 
     #[test]
     fn test_subnet_v4_remote() {
         let condition = IpAddressConditionBuilder::remote()
             .subnet_v4(Ipv4Addr::new(192, 168, 0, 0), 16)
+            .unwrap()
             .build();
 
         assert_field_key_eq(
@@ -994,7 +2845,8 @@ mod test {
     #[test]
     fn test_subnet_v4_local() {
         let condition = IpAddressConditionBuilder::local()
-            .subnet_v4(Ipv4Addr::new(127, 0, 0, 0), 8)
+            .subnet_v4(Ipv4Addr::new(127, 0, 0, 1), 8)
+            .unwrap()
             .build();
 
         assert_field_key_eq(
@@ -1010,6 +2862,7 @@ mod test {
     fn test_subnet_v6_remote() {
         let condition = IpAddressConditionBuilder::remote()
             .subnet_v6(Ipv6Addr::from_str("fe80::").unwrap(), 10)
+            .unwrap()
             .build();
 
         assert_field_key_eq(
@@ -1032,10 +2885,498 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "IPv6 prefix length must be <= 128")]
     fn test_subnet_v6_prefix_too_large() {
-        let _ = IpAddressConditionBuilder::remote()
-            .subnet_v6(Ipv6Addr::UNSPECIFIED, 129)
+        let result = IpAddressConditionBuilder::remote().subnet_v6(Ipv6Addr::UNSPECIFIED, 129);
+        assert!(matches!(
+            result,
+            Err(WfpError::InvalidCondition(ConditionError::InvalidNetMask))
+        ));
+    }
+
+    #[test]
+    fn test_subnet_network() {
+        let network: IpNetwork = "10.1.2.3/8".parse().unwrap();
+        let condition = IpAddressConditionBuilder::remote().subnet(network).build();
+        let v4 = unsafe { &*condition.raw_condition.conditionValue.Anonymous.v4AddrMask };
+        assert_eq!(v4.addr, 0x0A000000);
+        assert_eq!(v4.mask, 0xFF000000);
+
+        // Host networks are matched as single addresses
+        let network: IpNetwork = "2001:db8::1/128".parse().unwrap();
+        let condition = IpAddressConditionBuilder::remote().subnet(network).build();
+        assert_eq!(
+            condition.raw_condition.conditionValue.r#type,
+            FWP_BYTE_ARRAY16_TYPE
+        );
+        let network: IpNetwork = "10.0.0.1".parse().unwrap();
+        let condition = IpAddressConditionBuilder::remote().subnet(network).build();
+        assert_eq!(condition.raw_condition.conditionValue.r#type, FWP_UINT32);
+    }
+
+    /// Return the raw range value of `condition`.
+    fn raw_range(condition: &Condition) -> &FWP_RANGE0 {
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_RANGE);
+        assert_eq!(
+            condition.raw_condition.conditionValue.r#type,
+            FWP_RANGE_TYPE
+        );
+        unsafe { &*condition.raw_condition.conditionValue.Anonymous.rangeValue }
+    }
+
+    #[test]
+    fn test_port_range() {
+        let condition = PortConditionBuilder::local()
+            .range(1024..=65535)
+            .unwrap()
+            .build();
+
+        assert_field_key_eq(
+            &condition.raw_condition.fieldKey,
+            &FWPM_CONDITION_IP_LOCAL_PORT,
+        );
+        let range = raw_range(&condition);
+        assert_eq!(range.valueLow.r#type, FWP_UINT16);
+        assert_eq!(range.valueHigh.r#type, FWP_UINT16);
+        assert_eq!(unsafe { range.valueLow.Anonymous.uint16 }, 1024);
+        assert_eq!(unsafe { range.valueHigh.Anonymous.uint16 }, 65535);
+        assert_eq!(condition.value_type(), Some(DataType::UInt16));
+    }
+
+    #[test]
+    fn test_port_range_empty() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let result = PortConditionBuilder::local().range(443..=80);
+        assert!(matches!(
+            result,
+            Err(WfpError::InvalidCondition(ConditionError::InvalidRange))
+        ));
+        #[allow(clippy::reversed_empty_ranges)]
+        let result = IcmpConditionBuilder::r#type().range(137..=133);
+        assert!(matches!(
+            result,
+            Err(WfpError::InvalidCondition(ConditionError::InvalidRange))
+        ));
+    }
+
+    #[test]
+    fn test_icmp_range() {
+        let condition = IcmpConditionBuilder::r#type()
+            .range(133..=137)
+            .unwrap()
+            .build();
+
+        let range = raw_range(&condition);
+        assert_eq!(range.valueLow.r#type, FWP_UINT16);
+        assert_eq!(unsafe { range.valueLow.Anonymous.uint16 }, 133);
+        assert_eq!(unsafe { range.valueHigh.Anonymous.uint16 }, 137);
+    }
+
+    #[test]
+    fn test_ip_address_range() {
+        let condition = IpAddressConditionBuilder::remote()
+            .range(Ipv4Addr::new(10, 0, 0, 5), Ipv4Addr::new(10, 0, 0, 20))
+            .unwrap()
+            .build();
+        let range = raw_range(&condition);
+        assert_eq!(range.valueLow.r#type, FWP_UINT32);
+        assert_eq!(unsafe { range.valueLow.Anonymous.uint32 }, 0x0A000005);
+        assert_eq!(unsafe { range.valueHigh.Anonymous.uint32 }, 0x0A000014);
+
+        let start = Ipv6Addr::from_str("2001:db8::").unwrap();
+        let end = Ipv6Addr::from_str("2001:db8::ffff").unwrap();
+        let condition = IpAddressConditionBuilder::local()
+            .range(start, end)
+            .unwrap()
+            .build();
+        let range = raw_range(&condition);
+        assert_eq!(range.valueHigh.r#type, FWP_BYTE_ARRAY16_TYPE);
+        let low = unsafe { &*range.valueLow.Anonymous.byteArray16 };
+        let high = unsafe { &*range.valueHigh.Anonymous.byteArray16 };
+        assert_eq!(low.byteArray16, start.octets());
+        assert_eq!(high.byteArray16, end.octets());
+
+        // The pointers must remain valid in clones and deep copies
+        let copy = unsafe { Condition::from_raw(condition.clone().raw_condition()) }.unwrap();
+        drop(condition);
+        let range = raw_range(&copy);
+        let high = unsafe { &*range.valueHigh.Anonymous.byteArray16 };
+        assert_eq!(high.byteArray16, end.octets());
+        assert_eq!(copy.value_type(), Some(DataType::ByteArray16));
+    }
+
+    #[test]
+    fn test_ip_address_range_invalid() {
+        let result =
+            IpAddressConditionBuilder::remote().range(Ipv4Addr::LOCALHOST, Ipv6Addr::LOCALHOST);
+        assert!(matches!(
+            result,
+            Err(WfpError::InvalidCondition(
+                ConditionError::AddressFamilyMismatch
+            ))
+        ));
+
+        let result = IpAddressConditionBuilder::remote()
+            .range(Ipv4Addr::new(10, 0, 0, 9), Ipv4Addr::new(10, 0, 0, 1));
+        assert!(matches!(
+            result,
+            Err(WfpError::InvalidCondition(ConditionError::InvalidRange))
+        ));
+    }
+
+    #[test]
+    fn test_ip_address_single() {
+        let condition = IpAddressConditionBuilder::remote()
+            .address(Ipv4Addr::new(192, 168, 1, 1))
+            .build();
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_EQUAL);
+        assert_eq!(condition.raw_condition.conditionValue.r#type, FWP_UINT32);
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint32 },
+            0xC0A80101
+        );
+
+        let addr = Ipv6Addr::from_str("2001:db8::1").unwrap();
+        let condition = IpAddressConditionBuilder::local().address(addr).build();
+        assert_eq!(
+            condition.raw_condition.conditionValue.r#type,
+            FWP_BYTE_ARRAY16_TYPE
+        );
+        let copy = unsafe { Condition::from_raw(condition.clone().raw_condition()) }.unwrap();
+        drop(condition);
+        let bytes = unsafe { &*copy.raw_condition.conditionValue.Anonymous.byteArray16 };
+        assert_eq!(bytes.byteArray16, addr.octets());
+        assert_eq!(copy.value_type(), Some(DataType::ByteArray16));
+    }
+
+    #[test]
+    fn test_ip_address_unmap_v4() {
+        let mapped = Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped();
+
+        // Mapped addresses are kept as is by default
+        let condition = IpAddressConditionBuilder::remote().address(mapped).build();
+        assert_eq!(condition.value_type(), Some(DataType::ByteArray16));
+
+        let condition = IpAddressConditionBuilder::remote()
+            .unmap_v4()
+            .address(mapped)
+            .build();
+        assert_eq!(condition.value_type(), Some(DataType::UInt32));
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint32 },
+            0x0A000001
+        );
+
+        let condition = IpAddressConditionBuilder::remote()
+            .unmap_v4()
+            .range(mapped, Ipv4Addr::new(10, 0, 0, 9))
+            .unwrap()
+            .build();
+        assert_eq!(condition.value_type(), Some(DataType::UInt32));
+    }
+
+    #[test]
+    fn test_port_not_equal() {
+        let condition = PortConditionBuilder::remote().not_equal(53).build();
+
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_NOT_EQUAL);
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint16 },
+            53
+        );
+    }
+
+    #[test]
+    fn test_flags_condition() {
+        let flags = ConditionFlags::IS_LOOPBACK | ConditionFlags::IS_REAUTHORIZE;
+        let condition = FlagsConditionBuilder::none_set(flags).build();
+
+        assert_field_key_eq(&condition.raw_condition.fieldKey, &FWPM_CONDITION_FLAGS);
+        assert_eq!(condition.field(), Some(ConditionField::Flags));
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_FLAGS_NONE_SET);
+        assert_eq!(condition.raw_condition.conditionValue.r#type, FWP_UINT32);
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint32 },
+            FWP_CONDITION_FLAG_IS_LOOPBACK | FWP_CONDITION_FLAG_IS_REAUTHORIZE
+        );
+
+        let condition = FlagsConditionBuilder::any_set(ConditionFlags::IS_IPSEC_SECURED).build();
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_FLAGS_ANY_SET);
+        let condition = FlagsConditionBuilder::all_set(ConditionFlags::IS_WILDCARD_BIND).build();
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_FLAGS_ALL_SET);
+
+        assert_eq!(
+            format!("{flags:?}"),
+            "ConditionFlags(IS_LOOPBACK | IS_REAUTHORIZE)"
+        );
+    }
+
+    #[test]
+    fn test_user_condition() {
+        let condition = UserConditionBuilder::local()
+            .allow("S-1-5-32-545".parse().unwrap())
+            .deny("S-1-5-21-1-2-3-1001".parse().unwrap())
+            .build();
+
+        assert_field_key_eq(
+            &condition.raw_condition.fieldKey,
+            &FWPM_CONDITION_ALE_USER_ID,
+        );
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_EQUAL);
+        assert_eq!(
+            condition.raw_condition.conditionValue.r#type,
+            FWP_SECURITY_DESCRIPTOR_TYPE
+        );
+
+        let expected =
+            SecurityDescriptor::from_sddl("O:SYG:SYD:(D;;CC;;;S-1-5-21-1-2-3-1001)(A;;CC;;;BU)")
+                .unwrap()
+                .to_bytes();
+        let sd =
+            unsafe { byte_blob_as_slice(&*condition.raw_condition.conditionValue.Anonymous.sd) };
+        assert_eq!(sd, expected);
+
+        let condition = UserConditionBuilder::remote()
+            .sddl("D:(A;;CC;;;AU)")
+            .unwrap()
+            .build();
+        assert_eq!(condition.field(), Some(ConditionField::RemoteUserId));
+
+        assert!(
+            UserConditionBuilder::local()
+                .sddl("D:(A;;CC;;;XX)")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_package_condition() {
+        let condition = PackageConditionBuilder::default()
+            .family_name("Microsoft.WindowsCalculator_8wekyb3d8bbwe")
+            .unwrap()
+            .build();
+
+        assert_field_key_eq(
+            &condition.raw_condition.fieldKey,
+            &FWPM_CONDITION_ALE_PACKAGE_ID,
+        );
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_EQUAL);
+        assert_eq!(condition.raw_condition.conditionValue.r#type, FWP_SID);
+
+        let sid =
+            unsafe { Sid::from_raw(condition.raw_condition.conditionValue.Anonymous.sid.cast()) };
+        assert_eq!(
+            sid.unwrap().to_string(),
+            "S-1-15-2-466767348-3739614953-2700836392-1801644223-4227750657-1087833535-2488631167"
+        );
+
+        // Copying the raw condition preserves the SID
+        let copy = unsafe { Condition::from_raw(condition.raw_condition()) }.unwrap();
+        assert_eq!(copy.field(), Some(ConditionField::PackageId));
+        assert_eq!(copy.value_type(), Some(DataType::Sid));
+
+        assert!(PackageConditionBuilder::default().family_name("").is_err());
+    }
+
+    #[test]
+    fn test_typed_u32_conditions() {
+        let condition = DirectionConditionBuilder::equal(Direction::Inbound).build();
+        assert_field_key_eq(&condition.raw_condition.fieldKey, &FWPM_CONDITION_DIRECTION);
+        assert_eq!(condition.raw_condition.conditionValue.r#type, FWP_UINT32);
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint32 },
+            FWP_DIRECTION_INBOUND as u32
+        );
+
+        let condition =
+            InterfaceTypeConditionBuilder::not_equal(InterfaceType::SoftwareLoopback).build();
+        assert_eq!(condition.field(), Some(ConditionField::InterfaceType));
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_NOT_EQUAL);
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint32 },
+            24
+        );
+
+        let condition = TunnelTypeConditionBuilder::equal(TunnelType::SixToFour).build();
+        assert_eq!(condition.field(), Some(ConditionField::TunnelType));
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint32 },
+            11
+        );
+
+        let condition = InterfaceIndexConditionBuilder::sub_interface()
+            .equal(7)
+            .build();
+        assert_eq!(condition.field(), Some(ConditionField::SubInterfaceIndex));
+        assert_eq!(condition.value_type(), Some(DataType::UInt32));
+
+        let condition = CompartmentIdConditionBuilder::equal(3).build();
+        assert_eq!(condition.field(), Some(ConditionField::CompartmentId));
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint32 },
+            3
+        );
+    }
+
+    #[test]
+    fn test_address_type_condition() {
+        let condition = AddressTypeConditionBuilder::destination()
+            .not_equal(AddressType::Broadcast)
+            .build();
+
+        assert_field_key_eq(
+            &condition.raw_condition.fieldKey,
+            &FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE,
+        );
+        assert_eq!(condition.raw_condition.matchType, FWP_MATCH_NOT_EQUAL);
+        assert_eq!(condition.raw_condition.conditionValue.r#type, FWP_UINT8);
+        assert_eq!(
+            unsafe { condition.raw_condition.conditionValue.Anonymous.uint8 },
+            4
+        );
+    }
+
+    #[test]
+    fn test_next_hop_and_arrival_conditions() {
+        let condition = IpAddressConditionBuilder::next_hop()
+            .address(Ipv4Addr::new(10, 0, 0, 1))
+            .build();
+        assert_field_key_eq(
+            &condition.raw_condition.fieldKey,
+            &FWPM_CONDITION_IP_NEXTHOP_ADDRESS,
+        );
+        assert_eq!(condition.value_type(), Some(DataType::UInt32));
+
+        let condition = InterfaceConditionBuilder::arrival().luid(1234).build();
+        assert_field_key_eq(
+            &condition.raw_condition.fieldKey,
+            &FWPM_CONDITION_IP_ARRIVAL_INTERFACE,
+        );
+        assert_eq!(condition.value_type(), Some(DataType::UInt64));
+
+        let condition = InterfaceConditionBuilder::next_hop()
+            .luid(NetLuid::new(6, 1))
+            .build();
+        assert_field_key_eq(
+            &condition.raw_condition.fieldKey,
+            &FWPM_CONDITION_IP_NEXTHOP_INTERFACE,
+        );
+        assert_eq!(
+            unsafe { *condition.raw_condition.conditionValue.Anonymous.uint64 },
+            0x0006_0000_0100_0000
+        );
+    }
+
+    #[test]
+    fn test_typed_value() {
+        let condition = IpAddressConditionBuilder::remote()
+            .address("fe80::1".parse::<Ipv6Addr>().unwrap())
+            .build();
+        assert_eq!(
+            condition.value(),
+            TypedValue::Address("fe80::1".parse().unwrap())
+        );
+
+        let condition = IpAddressConditionBuilder::local()
+            .subnet("10.0.0.0/8".parse::<IpNetwork>().unwrap())
+            .build();
+        assert_eq!(
+            condition.value(),
+            TypedValue::Network("10.0.0.0/8".parse().unwrap())
+        );
+
+        let condition = IpAddressConditionBuilder::remote()
+            .range(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 9))
+            .unwrap()
+            .build();
+        assert_eq!(condition.match_type(), Some(MatchType::Range));
+        assert_eq!(
+            condition.value(),
+            TypedValue::Range(
+                Box::new(TypedValue::Address(Ipv4Addr::new(10, 0, 0, 1).into())),
+                Box::new(TypedValue::Address(Ipv4Addr::new(10, 0, 0, 9).into())),
+            )
+        );
+
+        let condition = ProtocolConditionBuilder::tcp().build();
+        assert_eq!(condition.value(), TypedValue::Protocol(6));
+
+        let condition = FlagsConditionBuilder::any_set(ConditionFlags::IS_LOOPBACK).build();
+        assert_eq!(condition.match_type(), Some(MatchType::FlagsAnySet));
+        assert_eq!(
+            condition.value(),
+            TypedValue::Flags(ConditionFlags::IS_LOOPBACK)
+        );
+
+        let condition = DirectionConditionBuilder::equal(Direction::Outbound).build();
+        assert_eq!(
+            condition.value(),
+            TypedValue::Direction(Direction::Outbound)
+        );
+
+        let condition = InterfaceConditionBuilder::local()
+            .luid(NetLuid::new(6, 1))
+            .build();
+        assert_eq!(condition.value(), TypedValue::Interface(NetLuid::new(6, 1)));
+
+        let condition = UserConditionBuilder::local()
+            .sddl("D:(A;;CC;;;BU)")
+            .unwrap()
             .build();
+        assert_eq!(
+            condition.value(),
+            TypedValue::SecurityDescriptor(
+                SecurityDescriptor::from_sddl("D:(A;;CC;;;BU)").unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_typed_value_fallback() {
+        let app_id: Vec<u8> = "\\device\\harddiskvolume1\\app.exe\0"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let condition = ConditionBuilder::default()
+            .field(ConditionField::AppId)
+            .match_type(MatchType::Equal)
+            .value_byte_blob(app_id)
+            .build()
+            .unwrap();
+        assert_eq!(
+            condition.value(),
+            TypedValue::AppId("\\device\\harddiskvolume1\\app.exe".to_owned())
+        );
+
+        // Values that do not fit the field are decoded by their data type
+        let condition = ConditionBuilder::default()
+            .field(ConditionField::Direction)
+            .match_type(MatchType::Equal)
+            .value_u32(7)
+            .build()
+            .unwrap();
+        assert_eq!(condition.value(), TypedValue::UInt32(7));
+
+        let condition = ConditionBuilder::default()
+            .field(ConditionField::RemoteAddress)
+            .match_type(MatchType::Equal)
+            .value_v4_addr_mask(0x0a00_0000, 0xff00_ff00)
+            .build()
+            .unwrap();
+        assert_eq!(
+            condition.value(),
+            TypedValue::V4AddrMask(Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(255, 0, 255, 0))
+        );
+
+        // Unknown fields are decoded by their data type
+        let raw = FWPM_FILTER_CONDITION0 {
+            fieldKey: GUID::from_u128(0x1234),
+            ..*CompartmentIdConditionBuilder::equal(3)
+                .build()
+                .raw_condition()
+        };
+        let condition = unsafe { Condition::from_raw(&raw) }.unwrap();
+        assert_eq!(condition.field(), None);
+        assert_eq!(condition.value(), TypedValue::UInt32(3));
+        assert!(format!("{condition:?}").contains("field_key"));
     }
 }
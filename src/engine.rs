@@ -1,19 +1,32 @@
 //! Filter engine management for the Windows Filtering Platform.
 
-use std::io;
+use std::ffi::OsStr;
 use std::mem;
+#[cfg(target_os = "windows")]
 use std::os::windows::io::AsRawHandle;
+#[cfg(target_os = "windows")]
 use std::os::windows::io::RawHandle;
 use std::ptr;
+use std::time::Duration;
 
-use windows_sys::Win32::Foundation::HANDLE;
-use windows_sys::Win32::Foundation::STATUS_SUCCESS;
-use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWPM_SESSION_FLAG_DYNAMIC;
-use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FwpmEngineClose0;
 use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
-    FWPM_SESSION0, FwpmEngineOpen0,
+    FWPM_SESSION_FLAG_DYNAMIC, FWPM_SESSION_FLAG_RESERVED, FWPM_SESSION0,
 };
-use windows_sys::Win32::System::Rpc::RPC_C_AUTHN_DEFAULT;
+use windows_sys::core::GUID;
+
+#[cfg(target_os = "windows")]
+use crate::backend::FwpmBackend;
+use crate::backend::{Backend, MemoryBfe};
+use crate::error::{ObjectType, Result, WfpError};
+use crate::option::{EngineOption, EngineOptionKind};
+use crate::sid::Sid;
+use crate::util::{
+    GuidDisplay, generate_guid, guid_eq, guid_to_u128, string_to_null_terminated_utf16,
+    wide_to_string,
+};
+
+/// Wait indefinitely. This is the value of `INFINITE`.
+const INFINITE: u32 = u32::MAX;
 
 /// Builder for creating a Windows Filtering Platform engine session.
 ///
@@ -25,18 +38,27 @@ use windows_sys::Win32::System::Rpc::RPC_C_AUTHN_DEFAULT;
 /// ```no_run
 /// use wfp::FilterEngineBuilder;
 /// use std::io;
+/// use std::time::Duration;
 ///
+/// # #[cfg(target_os = "windows")]
 /// fn main() -> io::Result<()> {
 ///     let engine = FilterEngineBuilder::default()
+///         .name("My session")
+///         .txn_wait_timeout(Duration::from_secs(5))
 ///         .dynamic()
 ///         .open()?;
 ///     Ok(())
 /// }
+/// # #[cfg(not(target_os = "windows"))]
+/// # fn main() {}
 /// ```
 ///
 /// [`FWPM_SESSION0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
 pub struct FilterEngineBuilder {
     session: FWPM_SESSION0,
+
+    display_data_name_buffer: Option<Vec<u16>>,
+    display_data_desc_buffer: Option<Vec<u16>>,
 }
 
 // SAFETY: Crossing thread-boundaries is fine
@@ -49,6 +71,8 @@ impl Default for FilterEngineBuilder {
             // All fields have valid zero representations, and the Windows API expects
             // zero-initialized sessions to use default values.
             session: unsafe { mem::zeroed() },
+            display_data_name_buffer: None,
+            display_data_desc_buffer: None,
         }
     }
 }
@@ -65,27 +89,103 @@ impl FilterEngineBuilder {
     /// be established.
     ///
     /// [`FwpmEngineOpen0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmengineopen0
-    pub fn open(self) -> io::Result<FilterEngine> {
-        let mut handle: HANDLE = ptr::null_mut();
-
-        // SAFETY:
-        // - All parameters are valid: null pointers are acceptable for serverName and authInfo
-        // - RPC_C_AUTHN_DEFAULT is a valid authentication service constant
-        // - self.session is a properly initialized FWPM_SESSION0 structure
-        // - handle is a valid mutable pointer to receive the engine handle
-        let result = unsafe {
-            FwpmEngineOpen0(
-                ptr::null_mut(),
-                RPC_C_AUTHN_DEFAULT as u32,
-                ptr::null_mut(),
-                &self.session,
-                &mut handle,
-            )
-        };
-        if result != STATUS_SUCCESS as u32 {
-            return Err(io::Error::last_os_error());
+    #[cfg(target_os = "windows")]
+    pub fn open(self) -> Result<FilterEngine> {
+        Ok(FilterEngine::with_backend(FwpmBackend::open(
+            &self.raw_session(),
+        )?))
+    }
+
+    /// Opens a session on an in-memory stand-in for the Base Filtering Engine.
+    ///
+    /// This is available on every target, and does not require any privileges. See
+    /// [`MemoryBfe`] for details.
+    pub fn open_in_memory(self, bfe: &MemoryBfe) -> Result<FilterEngine> {
+        // SAFETY: The pointers in the session refer to buffers owned by `self`
+        let backend = unsafe { bfe.open_session(&self.raw_session()) };
+        Ok(FilterEngine::with_backend(backend))
+    }
+
+    /// Returns the session to open.
+    ///
+    /// The session key is generated here unless it has been set, so that the session can be
+    /// identified later. The pointers in the returned session refer to buffers owned by `self`.
+    fn raw_session(&self) -> FWPM_SESSION0 {
+        let mut session = self.session;
+        if guid_to_u128(&session.sessionKey) == 0 {
+            session.sessionKey = generate_guid();
         }
-        Ok(FilterEngine { handle })
+        session.displayData.name = wide_ptr(&self.display_data_name_buffer);
+        session.displayData.description = wide_ptr(&self.display_data_desc_buffer);
+        session
+    }
+
+    /// Sets the GUID that uniquely identifies this session.
+    ///
+    /// If not set, a random GUID is used.
+    ///
+    /// This sets the `sessionKey` field in the underlying [`FWPM_SESSION0`] structure.
+    ///
+    /// [`FWPM_SESSION0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
+    pub fn key(mut self, key: GUID) -> Self {
+        self.session.sessionKey = key;
+        self
+    }
+
+    /// Sets the display name of the session.
+    ///
+    /// This sets `displayData.name` in the underlying [`FWPM_SESSION0`] structure.
+    ///
+    /// [`FWPM_SESSION0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
+    pub fn name(mut self, name: impl AsRef<OsStr>) -> Self {
+        self.display_data_name_buffer = Some(string_to_null_terminated_utf16(name));
+        self
+    }
+
+    /// Sets the description of the session.
+    ///
+    /// This sets `displayData.description` in the underlying [`FWPM_SESSION0`] structure.
+    ///
+    /// [`FWPM_SESSION0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
+    pub fn description(mut self, description: impl AsRef<OsStr>) -> Self {
+        self.display_data_desc_buffer = Some(string_to_null_terminated_utf16(description));
+        self
+    }
+
+    /// Sets how long to wait to begin a transaction while another session holds one.
+    ///
+    /// If the timeout expires, beginning the transaction fails with
+    /// [`WfpError::Timeout`]. A timeout of zero means that the engine uses its default
+    /// timeout, and timeouts that are too large to represent in milliseconds wait
+    /// indefinitely. Use [`FilterEngineBuilder::txn_wait_infinite`] to wait indefinitely.
+    ///
+    /// This sets `txnWaitTimeoutInMSec` in the underlying [`FWPM_SESSION0`] structure.
+    ///
+    /// [`FWPM_SESSION0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
+    pub fn txn_wait_timeout(mut self, timeout: Duration) -> Self {
+        self.session.txnWaitTimeoutInMSec = u32::try_from(timeout.as_millis()).unwrap_or(INFINITE);
+        self
+    }
+
+    /// Waits indefinitely to begin a transaction while another session holds one.
+    ///
+    /// This sets `txnWaitTimeoutInMSec` in the underlying [`FWPM_SESSION0`] structure to
+    /// `INFINITE`.
+    ///
+    /// [`FWPM_SESSION0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
+    pub fn txn_wait_infinite(mut self) -> Self {
+        self.session.txnWaitTimeoutInMSec = INFINITE;
+        self
+    }
+
+    /// Sets the reserved session flag.
+    ///
+    /// This sets the [`FWPM_SESSION_FLAG_RESERVED`] flag.
+    ///
+    /// [`FWPM_SESSION_FLAG_RESERVED`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
+    pub fn reserved(mut self) -> Self {
+        self.session.flags |= FWPM_SESSION_FLAG_RESERVED;
+        self
     }
 
     /// Configures the session to use dynamic filters.
@@ -106,33 +206,249 @@ impl FilterEngineBuilder {
 /// Represents an active connection to the Windows Filtering Platform engine.
 ///
 /// This struct manages the lifetime of a WFP engine session and provides
-/// the context needed for filter operations. The engine automatically
-/// closes the session when dropped using [`FwpmEngineClose0`].
+/// the context needed for filter operations. The session is closed when the
+/// engine is dropped, which for the Base Filtering Engine uses [`FwpmEngineClose0`].
+///
+/// All operations are carried out by a [`Backend`]. Use [`FilterEngineBuilder::open`]
+/// to connect to the Base Filtering Engine, or [`FilterEngineBuilder::open_in_memory`]
+/// to use an in-memory stand-in.
 ///
 /// `FilterEngine` is `Send` and can be moved between threads, but is not `Sync`.
 /// This prevents multiple concurrent transactions on the same engine at compile-time, which
 /// otherwise result in runtime errors.
 ///
 /// [`FwpmEngineClose0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmengineclose0
+#[cfg_attr(
+    target_os = "windows",
+    doc = "[`FilterEngineBuilder::open`]: FilterEngineBuilder::open"
+)]
+#[cfg_attr(
+    not(target_os = "windows"),
+    doc = "[`FilterEngineBuilder::open`]: FilterEngineBuilder"
+)]
 pub struct FilterEngine {
-    handle: HANDLE,
+    backend: Box<dyn Backend>,
 }
 
-// SAFETY: Crossing thread-boundaries is fine
-unsafe impl Send for FilterEngine {}
+impl FilterEngine {
+    /// Creates an engine that forwards all operations to `backend`.
+    pub fn with_backend(backend: impl Backend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
 
-impl AsRawHandle for FilterEngine {
-    fn as_raw_handle(&self) -> RawHandle {
-        self.handle
+    /// Returns the backend that carries out all operations.
+    pub(crate) fn backend(&self) -> &dyn Backend {
+        &*self.backend
+    }
+
+    /// Returns the key that uniquely identifies this session.
+    ///
+    /// This corresponds to the `sessionKey` field in the underlying [`FWPM_SESSION0`]
+    /// structure.
+    ///
+    /// [`FWPM_SESSION0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
+    pub fn session_key(&self) -> GUID {
+        self.backend.session_key()
+    }
+
+    /// Returns the current value of an engine option.
+    ///
+    /// This uses [`FwpmEngineGetOption0`].
+    ///
+    /// [`FwpmEngineGetOption0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmenginegetoption0
+    pub fn option(&self, kind: EngineOptionKind) -> Result<EngineOption> {
+        let value = self.backend.engine_get_option(kind.as_raw())?;
+        EngineOption::from_value(kind, &value)
+    }
+
+    /// Sets an engine option.
+    ///
+    /// Engine options apply to all sessions, and some of them require administrator
+    /// privileges to change. This uses [`FwpmEngineSetOption0`].
+    ///
+    /// [`FwpmEngineSetOption0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmenginesetoption0
+    pub fn set_option(&self, option: EngineOption) -> Result<()> {
+        let value = option.to_value();
+        // SAFETY: The value contains no pointers
+        unsafe {
+            self.backend
+                .engine_set_option(option.kind().as_raw(), &value)
+        }
+    }
+
+    /// Returns information about this session, as seen by the engine.
+    pub fn session(&self) -> Result<SessionInfo> {
+        let key = self.session_key();
+        self.sessions()?
+            .into_iter()
+            .find(|session| guid_eq(&session.key, &key))
+            .ok_or(WfpError::NotFound(ObjectType::Unspecified))
+    }
+
+    /// Returns all sessions that are open on the engine, including this one.
+    ///
+    /// This uses [`FwpmSessionEnum0`]. When beginning a transaction times out, this can be
+    /// used to find out which processes have sessions open.
+    ///
+    /// [`FwpmSessionEnum0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmsessionenum0
+    pub fn sessions(&self) -> Result<Vec<SessionInfo>> {
+        const NUM_ENTRIES: u32 = 50;
+
+        let backend = self.backend();
+        let enum_handle = backend.session_create_enum_handle()?;
+
+        let mut sessions = vec![];
+        let result = loop {
+            let batch = match backend.session_enum(enum_handle, NUM_ENTRIES) {
+                Ok(batch) => batch,
+                Err(error) => break Err(error),
+            };
+            sessions.extend(
+                (0..batch.len())
+                    .filter_map(|i| batch.get(i))
+                    .map(|session| {
+                        // SAFETY: The session was returned by the backend, so its pointers are valid
+                        unsafe { SessionInfo::from_raw(session) }
+                    }),
+            );
+            if batch.len() < NUM_ENTRIES as usize {
+                break Ok(sessions);
+            }
+        };
+
+        if let Err(error) = backend.session_destroy_enum_handle(enum_handle) {
+            log::error!("Failed to destroy session enumeration handle: {error}");
+        }
+        result
     }
 }
 
-impl Drop for FilterEngine {
-    fn drop(&mut self) {
-        // SAFETY:
-        // - self.handle is a valid engine handle obtained from FwpmEngineOpen0
-        // - We are the sole owner of this handle (FilterEngine is not Clone)
-        // - This is called exactly once during drop, preventing double-free
-        unsafe { FwpmEngineClose0(self.handle) };
+/// Information about a session opened on the engine.
+///
+/// This is an owned copy of an [`FWPM_SESSION0`] structure.
+///
+/// [`FWPM_SESSION0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_session0
+#[derive(Clone)]
+pub struct SessionInfo {
+    key: GUID,
+    name: Option<String>,
+    description: Option<String>,
+    flags: u32,
+    txn_wait_timeout: u32,
+    process_id: u32,
+    sid: Option<Sid>,
+    username: Option<String>,
+    kernel_mode: bool,
+}
+
+impl SessionInfo {
+    /// Copies a raw session.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `session` must be valid.
+    unsafe fn from_raw(session: &FWPM_SESSION0) -> Self {
+        // SAFETY: The caller guarantees that the pointers are valid
+        unsafe {
+            Self {
+                key: session.sessionKey,
+                name: wide_to_string(session.displayData.name),
+                description: wide_to_string(session.displayData.description),
+                flags: session.flags,
+                txn_wait_timeout: session.txnWaitTimeoutInMSec,
+                process_id: session.processId,
+                sid: Sid::from_raw(session.sid.cast()),
+                username: wide_to_string(session.username),
+                kernel_mode: session.kernelMode != 0,
+            }
+        }
+    }
+
+    /// Return the key that uniquely identifies the session.
+    pub fn key(&self) -> GUID {
+        self.key
+    }
+
+    /// Return the display name of the session, if set.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Return the description of the session, if set.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return whether the session is dynamic.
+    ///
+    /// Objects added by a dynamic session are deleted when the session is closed.
+    pub fn is_dynamic(&self) -> bool {
+        self.flags & FWPM_SESSION_FLAG_DYNAMIC != 0
+    }
+
+    /// Return whether the reserved session flag is set.
+    pub fn is_reserved(&self) -> bool {
+        self.flags & FWPM_SESSION_FLAG_RESERVED != 0
+    }
+
+    /// Return how long the session waits to begin a transaction.
+    ///
+    /// `None` means that the session waits indefinitely. Zero means that the engine's default
+    /// timeout is used.
+    pub fn txn_wait_timeout(&self) -> Option<Duration> {
+        (self.txn_wait_timeout != INFINITE)
+            .then(|| Duration::from_millis(u64::from(self.txn_wait_timeout)))
+    }
+
+    /// Return the ID of the process that opened the session.
+    pub fn process_id(&self) -> u32 {
+        self.process_id
+    }
+
+    /// Return the SID of the user that opened the session, if known.
+    pub fn sid(&self) -> Option<&Sid> {
+        self.sid.as_ref()
+    }
+
+    /// Return the name of the user that opened the session, if known.
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    /// Return whether the session was opened by a kernel-mode client.
+    pub fn is_kernel_mode(&self) -> bool {
+        self.kernel_mode
+    }
+}
+
+impl std::fmt::Debug for SessionInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionInfo")
+            .field("key", &GuidDisplay(&self.key))
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("flags", &self.flags)
+            .field("txn_wait_timeout", &self.txn_wait_timeout())
+            .field("process_id", &self.process_id)
+            .field("sid", &self.sid)
+            .field("username", &self.username)
+            .field("kernel_mode", &self.kernel_mode)
+            .finish()
+    }
+}
+
+fn wide_ptr(s: &Option<Vec<u16>>) -> *mut u16 {
+    s.as_ref().map_or(ptr::null_mut(), |s| s.as_ptr() as *mut _)
+}
+
+/// Returns the raw engine handle.
+///
+/// This is null if the engine does not use the Base Filtering Engine.
+#[cfg(target_os = "windows")]
+impl AsRawHandle for FilterEngine {
+    fn as_raw_handle(&self) -> RawHandle {
+        self.backend.raw_handle()
     }
 }
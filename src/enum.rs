@@ -1,15 +1,34 @@
 //! Enumeration over WFP objects.
 
 use crate::Transaction;
+use crate::action::{ActionMask, ActionType};
+use crate::backend::{Backend, EnumBatch};
+use crate::callout::CalloutInfo;
+use crate::condition::Condition;
+use crate::engine::FilterEngine;
+use crate::error::Result;
+use crate::filter::{
+    FilterFlags, FilterId, FilterInfo, FilterLifetime, FilterWeight, callout_from_raw,
+    conditions_from_raw, effective_weight_from_raw, weight_from_raw,
+};
+use crate::layer::{Layer, LayerInfo};
+use crate::provider::ProviderInfo;
+use crate::provider_context::ProviderContextInfo;
+use crate::sublayer::SubLayerInfo;
+use crate::util::{byte_blob_as_slice, display_string, flags_type};
 
 use std::io;
-use std::os::windows::io::AsRawHandle;
+use std::marker::PhantomData;
 use std::ptr;
-use windows_sys::Win32::Foundation::{ERROR_NO_MORE_ITEMS, ERROR_SUCCESS, HANDLE};
+use windows_sys::Win32::Foundation::HANDLE;
 use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
-    FWPM_FILTER0, FwpmFilterCreateEnumHandle0, FwpmFilterDestroyEnumHandle0, FwpmFilterEnum0,
-    FwpmFreeMemory0,
+    FWP_FILTER_ENUM_FLAG_BEST_TERMINATING_MATCH, FWP_FILTER_ENUM_FLAG_BOOTTIME_ONLY,
+    FWP_FILTER_ENUM_FLAG_INCLUDE_BOOTTIME, FWP_FILTER_ENUM_FLAG_INCLUDE_DISABLED,
+    FWP_FILTER_ENUM_FLAG_SORTED, FWP_FILTER_ENUM_FULLY_CONTAINED, FWP_FILTER_ENUM_OVERLAPPING,
+    FWPM_CALLOUT0, FWPM_FILTER_CONDITION0, FWPM_FILTER_ENUM_TEMPLATE0, FWPM_FILTER0, FWPM_LAYER0,
+    FWPM_PROVIDER_CONTEXT0, FWPM_PROVIDER0, FWPM_SUBLAYER0,
 };
+use windows_sys::core::GUID;
 
 /// An iterator over filters.
 ///
@@ -23,9 +42,10 @@ use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
 /// use wfp::{FilterEngineBuilder, FilterEnumerator, Transaction};
 /// use std::io;
 ///
+/// # #[cfg(target_os = "windows")]
 /// fn main() -> io::Result<()> {
-///     let mut engine = FilterEngineBuilder::default().dynamic().open()?;
-///     let t = Transaction::new(&mut engine)?;
+///     let engine = FilterEngineBuilder::default().dynamic().open()?;
+///     let t = Transaction::read_only(&engine)?;
 ///
 ///     let mut filter_enum = FilterEnumerator::new(&t)?;
 ///
@@ -37,14 +57,11 @@ use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
 ///
 ///     Ok(())
 /// }
+/// # #[cfg(not(target_os = "windows"))]
+/// # fn main() {}
 /// ```
 pub struct FilterEnumerator<'a, 'b: 'a> {
-    transaction: &'a Transaction<'b>,
-    enum_handle: HANDLE,
-    exhausted: bool,
-    current_entries: *mut *mut FWPM_FILTER0,
-    current_num_entries: u32,
-    current_index: u32,
+    inner: RawEnumerator<'a, 'b, FWPM_FILTER0>,
 }
 
 impl<'a, 'b> FilterEnumerator<'a, 'b> {
@@ -55,38 +72,38 @@ impl<'a, 'b> FilterEnumerator<'a, 'b> {
     ///
     /// # Arguments
     ///
-    /// * `transaction` - A transaction
+    /// * `transaction` - A transaction. This may be a [read-only](Transaction::read_only)
+    ///   transaction.
     ///
     /// # Returns
     ///
-    /// Returns a new `FilterEnumerator` on success, or an `io::Error` if the
+    /// Returns a new `FilterEnumerator` on success, or a [`WfpError`](crate::WfpError) if the
     /// enumeration handle could not be created.
-    pub fn new(transaction: &'a Transaction<'b>) -> io::Result<Self> {
-        let mut enum_handle = HANDLE::default();
-
-        // SAFETY:
-        // - engine.as_raw_handle() returns a valid engine handle
-        // - enum_template is null (enumerate all filters)
-        // - enum_handle is a valid pointer to receive the handle
-        let status = unsafe {
-            FwpmFilterCreateEnumHandle0(
-                transaction.engine.as_raw_handle(),
-                ptr::null_mut(),
-                &mut enum_handle,
-            )
-        };
-
-        if status != ERROR_SUCCESS {
-            return Err(io::Error::from_raw_os_error(status as i32));
-        }
+    pub fn new<Mode>(transaction: &'a Transaction<'b, Mode>) -> Result<Self> {
+        Ok(Self {
+            inner: RawEnumerator::new(transaction)?,
+        })
+    }
 
+    /// Creates a filter enumerator that only returns filters that match `template`.
+    ///
+    /// The filters are selected by the engine, rather than by the caller after enumerating
+    /// every filter. See [`FilterEnumTemplate`].
+    pub fn with_template<Mode>(
+        transaction: &'a Transaction<'b, Mode>,
+        template: &FilterEnumTemplate,
+    ) -> Result<Self> {
+        let enum_handle = template.with_raw(|raw| {
+            // SAFETY: The pointers in the template are valid for the duration of the call
+            unsafe {
+                transaction
+                    .engine
+                    .backend()
+                    .filter_create_enum_handle(Some(raw))
+            }
+        })?;
         Ok(Self {
-            transaction,
-            enum_handle,
-            exhausted: false,
-            current_entries: ptr::null_mut(),
-            current_num_entries: 0,
-            current_index: 0,
+            inner: RawEnumerator::from_handle(transaction, enum_handle),
         })
     }
 }
@@ -99,114 +116,581 @@ impl<'a, 'b> FilterEnumerator<'a, 'b> {
     ///
     /// If an error occurs, an error is returned, and future calls to `next` return `None`.
     #[allow(clippy::should_implement_trait)]
-    pub fn next(&mut self) -> Option<io::Result<FilterEnumItem<'a, 'b, '_>>> {
-        const NUM_ENTRIES: u32 = 50;
+    pub fn next(&mut self) -> Option<Result<FilterEnumItem<'a, 'b, '_>>> {
+        // Since `FilterEnumItem` borrows `self`, and `next()` borrows self mutably, the
+        // entries will not be freed until the `FilterEnumItem` has been dropped.
+        let filter = self.inner.next()?;
+        Some(filter.map(|filter| FilterEnumItem {
+            filter,
+            _enumerator: PhantomData,
+        }))
+    }
+}
 
-        if self.exhausted {
-            return None;
-        }
+impl<'a, 'b> IntoIterator for FilterEnumerator<'a, 'b> {
+    type Item = io::Result<FilterInfo>;
+    type IntoIter = FilterInfoIter<'a, 'b>;
+
+    /// Returns an iterator over owned copies of the filters.
+    ///
+    /// Errors are converted into [`io::Error`]s, which wrap the original
+    /// [`WfpError`](crate::WfpError).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use wfp::{FilterEngineBuilder, FilterEnumerator, FilterInfo, Transaction};
+    ///
+    /// # #[cfg(target_os = "windows")]
+    /// fn main() -> wfp::Result<()> {
+    ///     let engine = FilterEngineBuilder::default().dynamic().open()?;
+    ///     let t = Transaction::read_only(&engine)?;
+    ///
+    ///     let filters = FilterEnumerator::new(&t)?
+    ///         .into_iter()
+    ///         .collect::<std::io::Result<Vec<FilterInfo>>>()?;
+    ///     for filter in filters {
+    ///         println!("{:?}", filter.name());
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// # #[cfg(not(target_os = "windows"))]
+    /// # fn main() {}
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        FilterInfoIter { enumerator: self }
+    }
+}
+
+/// An iterator over owned copies of filters.
+///
+/// This is returned by [`FilterEnumerator::into_iter`]. If an error occurs while enumerating,
+/// it is returned, and the iterator ends.
+pub struct FilterInfoIter<'a, 'b> {
+    enumerator: FilterEnumerator<'a, 'b>,
+}
+
+impl Iterator for FilterInfoIter<'_, '_> {
+    type Item = io::Result<FilterInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.enumerator.next()?;
+        Some(
+            item.and_then(|filter| filter.to_info())
+                .map_err(io::Error::from),
+        )
+    }
+}
+
+/// A raw WFP object that can be enumerated.
+trait EnumObject: Sized + 'static {
+    /// The name of the object type, used in log messages.
+    const NAME: &'static str;
+
+    /// Creates a handle for enumerating all objects of this type.
+    fn create_enum_handle(backend: &dyn Backend) -> Result<HANDLE>;
+
+    /// Returns up to `num_entries` objects from an enumeration.
+    fn enum_batch(
+        backend: &dyn Backend,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<Self>>;
+
+    /// Destroys an enumeration handle.
+    fn destroy_enum_handle(backend: &dyn Backend, enum_handle: HANDLE) -> Result<()>;
+}
+
+impl EnumObject for FWPM_FILTER0 {
+    const NAME: &'static str = "filter";
+
+    fn create_enum_handle(backend: &dyn Backend) -> Result<HANDLE> {
+        // SAFETY: There is no template
+        unsafe { backend.filter_create_enum_handle(None) }
+    }
+
+    fn enum_batch(
+        backend: &dyn Backend,
+        enum_handle: HANDLE,
+        num_entries: u32,
+    ) -> Result<EnumBatch<Self>> {
+        backend.filter_enum(enum_handle, num_entries)
+    }
 
-        // If we have filters in the current batch, return the next one
-        if self.current_index < self.current_num_entries {
-            // SAFETY: The entries are valid and `current_index` is less than the total number of entries.
-            //         Since `FilterEnumItem` borrows `self`, and `next()` borrows self mutably, the
-            //         pointer will not be freed until the `FilterEnumItem` has been dropped.
-            let idx = usize::try_from(self.current_index).unwrap();
-            let filter = unsafe { &**self.current_entries.add(idx) };
-            self.current_index += 1;
-
-            return Some(Ok(FilterEnumItem {
-                filter,
-                _enumerator: self,
-            }));
+    fn destroy_enum_handle(backend: &dyn Backend, enum_handle: HANDLE) -> Result<()> {
+        backend.filter_destroy_enum_handle(enum_handle)
+    }
+}
+
+/// Implements [`EnumObject`] for objects that are always enumerated without a template.
+macro_rules! impl_enum_object {
+    ($raw:ty, $name:literal, $create:ident, $enum:ident, $destroy:ident) => {
+        impl EnumObject for $raw {
+            const NAME: &'static str = $name;
+
+            fn create_enum_handle(backend: &dyn Backend) -> Result<HANDLE> {
+                backend.$create()
+            }
+
+            fn enum_batch(
+                backend: &dyn Backend,
+                enum_handle: HANDLE,
+                num_entries: u32,
+            ) -> Result<EnumBatch<Self>> {
+                backend.$enum(enum_handle, num_entries)
+            }
+
+            fn destroy_enum_handle(backend: &dyn Backend, enum_handle: HANDLE) -> Result<()> {
+                backend.$destroy(enum_handle)
+            }
         }
+    };
+}
+
+impl_enum_object!(
+    FWPM_PROVIDER0,
+    "provider",
+    provider_create_enum_handle,
+    provider_enum,
+    provider_destroy_enum_handle
+);
+impl_enum_object!(
+    FWPM_SUBLAYER0,
+    "sublayer",
+    sublayer_create_enum_handle,
+    sublayer_enum,
+    sublayer_destroy_enum_handle
+);
+impl_enum_object!(
+    FWPM_CALLOUT0,
+    "callout",
+    callout_create_enum_handle,
+    callout_enum,
+    callout_destroy_enum_handle
+);
+impl_enum_object!(
+    FWPM_LAYER0,
+    "layer",
+    layer_create_enum_handle,
+    layer_enum,
+    layer_destroy_enum_handle
+);
+impl_enum_object!(
+    FWPM_PROVIDER_CONTEXT0,
+    "provider context",
+    provider_context_create_enum_handle,
+    provider_context_enum,
+    provider_context_destroy_enum_handle
+);
+
+/// Fetches objects from an enumeration handle in batches, and destroys the handle when
+/// dropped.
+struct RawEnumerator<'a, 'b: 'a, T: EnumObject> {
+    engine: &'a FilterEngine,
+    // The transaction must remain active while enumerating
+    _transaction: PhantomData<&'a Transaction<'b>>,
+    enum_handle: HANDLE,
+    exhausted: bool,
+    current_entries: Option<EnumBatch<T>>,
+    current_index: usize,
+}
 
-        let prev_num_entries = self.current_num_entries;
+impl<'a, 'b, T: EnumObject> RawEnumerator<'a, 'b, T> {
+    fn new<Mode>(transaction: &'a Transaction<'b, Mode>) -> Result<Self> {
+        let enum_handle = T::create_enum_handle(transaction.engine.backend())?;
+        Ok(Self::from_handle(transaction, enum_handle))
+    }
+
+    fn from_handle<Mode>(transaction: &'a Transaction<'b, Mode>, enum_handle: HANDLE) -> Self {
+        Self {
+            engine: transaction.engine,
+            _transaction: PhantomData,
+            enum_handle,
+            exhausted: false,
+            current_entries: None,
+            current_index: 0,
+        }
+    }
 
-        self.free_current_entries();
+    /// Returns the next object, which remains valid until `next` is called again.
+    ///
+    /// If an error occurs, an error is returned, and future calls to `next` return `None`.
+    fn next(&mut self) -> Option<Result<&T>> {
+        const NUM_ENTRIES: u32 = 50;
 
-        // If the previous entries were fewer than requested num, we are done
-        if prev_num_entries != 0 && prev_num_entries < NUM_ENTRIES {
-            self.exhausted = true;
+        if self.exhausted {
             return None;
         }
 
-        // SAFETY:
-        // - self.engine.as_raw_handle() returns a valid engine handle
-        // - self.enum_handle is a valid enumeration handle
-        // - entries and num_entries are valid pointers
-        let status = unsafe {
-            FwpmFilterEnum0(
-                self.transaction.engine.as_raw_handle(),
-                self.enum_handle,
-                NUM_ENTRIES,
-                &mut self.current_entries,
-                &mut self.current_num_entries,
-            )
-        };
-        self.current_index = 0;
+        let has_current = self
+            .current_entries
+            .as_ref()
+            .is_some_and(|entries| self.current_index < entries.len());
+
+        if !has_current {
+            // If the previous entries were fewer than requested num, we are done
+            let prev_num_entries = self.current_entries.take().map(|entries| entries.len());
+            if prev_num_entries.is_some_and(|n| n < NUM_ENTRIES as usize) {
+                self.exhausted = true;
+                return None;
+            }
 
-        match status {
-            ERROR_SUCCESS => {
-                if self.current_num_entries == 0 {
+            match T::enum_batch(self.engine.backend(), self.enum_handle, NUM_ENTRIES) {
+                Ok(entries) if entries.is_empty() => {
                     self.exhausted = true;
                     return None;
                 }
+                Ok(entries) => {
+                    self.current_entries = Some(entries);
+                    self.current_index = 0;
+                }
+                Err(error) => {
+                    self.exhausted = true;
+                    return Some(Err(error));
+                }
+            }
+        }
 
-                // SAFETY: Entries contain at least one filter
-                //         Since `FilterEnumItem` borrows `self`, and `next()` borrows self mutably, the
-                //         pointer will not be freed until the `FilterEnumItem` has been dropped.
-                let filter = unsafe { &**self.current_entries };
+        let index = self.current_index;
+        self.current_index += 1;
+        self.current_entries.as_ref()?.get(index).map(Ok)
+    }
+}
 
-                self.current_index = 1;
+impl<T: EnumObject> Drop for RawEnumerator<'_, '_, T> {
+    fn drop(&mut self) {
+        // Free any current entries before destroying the handle
+        self.current_entries = None;
 
-                Some(Ok(FilterEnumItem {
-                    filter,
-                    _enumerator: self,
-                }))
-            }
-            ERROR_NO_MORE_ITEMS => {
-                self.exhausted = true;
-                None
-            }
-            _ => {
-                self.exhausted = true;
-                Some(Err(io::Error::from_raw_os_error(status as i32)))
-            }
+        if let Err(error) = T::destroy_enum_handle(self.engine.backend(), self.enum_handle) {
+            log::error!("Failed to destroy {} enumeration handle: {error}", T::NAME);
         }
     }
+}
 
-    /// Frees the current entries if they exist.
-    fn free_current_entries(&mut self) {
-        if !self.current_entries.is_null() {
-            // SAFETY: current_entries was allocated by FwpmFilterEnum0
-            unsafe { FwpmFreeMemory0((&mut self.current_entries) as *mut _ as *mut _) };
-            self.current_entries = ptr::null_mut();
-            self.current_num_entries = 0;
-            self.current_index = 0;
+/// Defines an enumerator that returns owned copies of objects.
+macro_rules! info_enumerator {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident($raw:ty) -> $info:ty;
+    ) => {
+        $(#[$meta])*
+        pub struct $name<'a, 'b: 'a> {
+            inner: RawEnumerator<'a, 'b, $raw>,
         }
+
+        impl<'a, 'b> $name<'a, 'b> {
+            /// Creates an enumerator over all objects of this type.
+            ///
+            /// `transaction` may be a [read-only](Transaction::read_only) transaction.
+            pub fn new<Mode>(transaction: &'a Transaction<'b, Mode>) -> Result<Self> {
+                Ok(Self {
+                    inner: RawEnumerator::new(transaction)?,
+                })
+            }
+        }
+
+        impl Iterator for $name<'_, '_> {
+            type Item = Result<$info>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let item = self.inner.next()?;
+                // SAFETY: The object was returned by the backend, so its pointers are valid
+                Some(item.and_then(|raw| unsafe { <$info>::from_raw(raw) }))
+            }
+        }
+    };
+}
+
+info_enumerator! {
+    /// An iterator over providers.
+    ///
+    /// This struct wraps the [`FwpmProviderEnum0`] API. If an error occurs while enumerating,
+    /// it is returned, and the iterator ends.
+    ///
+    /// [`FwpmProviderEnum0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmproviderenum0
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use wfp::{FilterEngineBuilder, ProviderEnumerator, Transaction};
+    ///
+    /// # #[cfg(target_os = "windows")]
+    /// fn main() -> wfp::Result<()> {
+    ///     let engine = FilterEngineBuilder::default().dynamic().open()?;
+    ///     let t = Transaction::read_only(&engine)?;
+    ///
+    ///     for provider in ProviderEnumerator::new(&t)? {
+    ///         let provider = provider?;
+    ///         println!("{:?}: {:?}", provider.name(), provider.service_name());
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// # #[cfg(not(target_os = "windows"))]
+    /// # fn main() {}
+    /// ```
+    pub struct ProviderEnumerator(FWPM_PROVIDER0) -> ProviderInfo;
+}
+
+info_enumerator! {
+    /// An iterator over sublayers.
+    ///
+    /// This struct wraps the [`FwpmSubLayerEnum0`] API. If an error occurs while enumerating,
+    /// it is returned, and the iterator ends.
+    ///
+    /// [`FwpmSubLayerEnum0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmsublayerenum0
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use wfp::{FilterEngineBuilder, SubLayerEnumerator, SubLayerInfo, Transaction};
+    /// use std::cmp::Reverse;
+    ///
+    /// # #[cfg(target_os = "windows")]
+    /// fn main() -> wfp::Result<()> {
+    ///     let engine = FilterEngineBuilder::default().dynamic().open()?;
+    ///     let t = Transaction::read_only(&engine)?;
+    ///
+    ///     // List sublayers in the order in which they are evaluated
+    ///     let mut sublayers =
+    ///         SubLayerEnumerator::new(&t)?.collect::<wfp::Result<Vec<SubLayerInfo>>>()?;
+    ///     sublayers.sort_by_key(|sublayer| Reverse(sublayer.weight()));
+    ///     for sublayer in sublayers {
+    ///         println!("{:#06x} {:?}", sublayer.weight(), sublayer.name());
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// # #[cfg(not(target_os = "windows"))]
+    /// # fn main() {}
+    /// ```
+    pub struct SubLayerEnumerator(FWPM_SUBLAYER0) -> SubLayerInfo;
+}
+
+info_enumerator! {
+    /// An iterator over callouts.
+    ///
+    /// This struct wraps the [`FwpmCalloutEnum0`] API. If an error occurs while enumerating,
+    /// it is returned, and the iterator ends.
+    ///
+    /// [`FwpmCalloutEnum0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmcalloutenum0
+    pub struct CalloutEnumerator(FWPM_CALLOUT0) -> CalloutInfo;
+}
+
+info_enumerator! {
+    /// An iterator over layers.
+    ///
+    /// This struct wraps the [`FwpmLayerEnum0`] API. If an error occurs while enumerating,
+    /// it is returned, and the iterator ends.
+    ///
+    /// [`FwpmLayerEnum0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmlayerenum0
+    pub struct LayerEnumerator(FWPM_LAYER0) -> LayerInfo;
+}
+
+info_enumerator! {
+    /// An iterator over provider contexts.
+    ///
+    /// This struct wraps the [`FwpmProviderContextEnum0`] API. If an error occurs while
+    /// enumerating, it is returned, and the iterator ends.
+    ///
+    /// [`FwpmProviderContextEnum0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmprovidercontextenum0
+    pub struct ProviderContextEnumerator(FWPM_PROVIDER_CONTEXT0) -> ProviderContextInfo;
+}
+
+/// How the conditions of a [`FilterEnumTemplate`] are compared against the conditions of
+/// filters.
+///
+/// These correspond to the [`FWP_FILTER_ENUM_TYPE`] enumeration values.
+///
+/// [`FWP_FILTER_ENUM_TYPE`]: https://learn.microsoft.com/en-us/windows/win32/api/fwptypes/ne-fwptypes-fwp_filter_enum_type
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum FilterEnumType {
+    /// Return filters whose conditions fully contain the template conditions, i.e. filters
+    /// that match all traffic that the template matches.
+    FullyContained = FWP_FILTER_ENUM_FULLY_CONTAINED,
+    /// Return filters whose conditions overlap with the template conditions, i.e. filters
+    /// that match some traffic that the template matches. A template without conditions
+    /// overlaps with every filter.
+    #[default]
+    Overlapping = FWP_FILTER_ENUM_OVERLAPPING,
+}
+
+flags_type! {
+    /// Flags of a [`FilterEnumTemplate`].
+    ///
+    /// These correspond to the `FWP_FILTER_ENUM_FLAG_*` values of the `flags` field in the
+    /// underlying [`FWPM_FILTER_ENUM_TEMPLATE0`] structure.
+    ///
+    /// [`FWPM_FILTER_ENUM_TEMPLATE0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_filter_enum_template0
+    pub struct FilterEnumFlags {
+        /// Only return the terminating filter with the highest weight
+        /// (`FWP_FILTER_ENUM_FLAG_BEST_TERMINATING_MATCH`).
+        const BEST_TERMINATING_MATCH = FWP_FILTER_ENUM_FLAG_BEST_TERMINATING_MATCH;
+        /// Return the filters in order of decreasing weight (`FWP_FILTER_ENUM_FLAG_SORTED`).
+        const SORTED = FWP_FILTER_ENUM_FLAG_SORTED;
+        /// Only return boot-time filters (`FWP_FILTER_ENUM_FLAG_BOOTTIME_ONLY`).
+        const BOOTTIME_ONLY = FWP_FILTER_ENUM_FLAG_BOOTTIME_ONLY;
+        /// Also return boot-time filters (`FWP_FILTER_ENUM_FLAG_INCLUDE_BOOTTIME`).
+        const INCLUDE_BOOTTIME = FWP_FILTER_ENUM_FLAG_INCLUDE_BOOTTIME;
+        /// Also return disabled filters (`FWP_FILTER_ENUM_FLAG_INCLUDE_DISABLED`).
+        const INCLUDE_DISABLED = FWP_FILTER_ENUM_FLAG_INCLUDE_DISABLED;
     }
 }
 
-impl<'a, 'b> Drop for FilterEnumerator<'a, 'b> {
-    fn drop(&mut self) {
-        // Free any current entries before destroying the handle
-        self.free_current_entries();
+/// Selects the filters that a [`FilterEnumerator`] returns.
+///
+/// The BFE requires a layer, so only filters in a single layer can be selected at a time.
+/// All other criteria are optional.
+///
+/// This corresponds to the underlying [`FWPM_FILTER_ENUM_TEMPLATE0`] structure.
+///
+/// # Example
+///
+/// ```no_run
+/// use wfp::{
+///     ActionMask, FilterEngineBuilder, FilterEnumTemplate, FilterEnumerator, GUID, Layer,
+///     Transaction,
+/// };
+///
+/// # #[cfg(target_os = "windows")]
+/// fn main() -> wfp::Result<()> {
+///     let engine = FilterEngineBuilder::default().dynamic().open()?;
+///     let t = Transaction::read_only(&engine)?;
+///
+///     // List the terminating filters that our provider added at the connect layer
+///     let template = FilterEnumTemplate::new(Layer::ConnectV4)
+///         .provider(GUID::from_u128(0x12345678_1234_5678_9abc_def012345678))
+///         .action_mask(ActionMask::TERMINATING);
+///     for filter in FilterEnumerator::with_template(&t, &template)? {
+///         println!("{:?}", filter?.name());
+///     }
+///
+///     Ok(())
+/// }
+/// # #[cfg(not(target_os = "windows"))]
+/// # fn main() {}
+/// ```
+///
+/// [`FWPM_FILTER_ENUM_TEMPLATE0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_filter_enum_template0
+#[derive(Clone)]
+pub struct FilterEnumTemplate {
+    layer: Layer,
+    provider: Option<GUID>,
+    enum_type: FilterEnumType,
+    flags: FilterEnumFlags,
+    conditions: Vec<Condition>,
+    action_mask: Option<ActionMask>,
+    callout: Option<GUID>,
+}
 
-        // SAFETY:
-        // - self.engine.as_raw_handle() returns a valid engine handle
-        // - self.enum_handle is a valid enumeration handle created by FwpmFilterCreateEnumHandle0
-        // - This is called exactly once during drop
-        unsafe {
-            FwpmFilterDestroyEnumHandle0(self.transaction.engine.as_raw_handle(), self.enum_handle);
+impl FilterEnumTemplate {
+    /// Creates a template that selects all filters in `layer`.
+    pub fn new(layer: Layer) -> Self {
+        Self {
+            layer,
+            provider: None,
+            enum_type: FilterEnumType::default(),
+            flags: FilterEnumFlags::empty(),
+            conditions: vec![],
+            action_mask: None,
+            callout: None,
         }
     }
+
+    /// Only select filters that belong to `provider`.
+    ///
+    /// This sets the `providerKey` field in the underlying `FWPM_FILTER_ENUM_TEMPLATE0`
+    /// structure.
+    pub fn provider(mut self, provider: GUID) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Sets how the [conditions](Self::condition) are compared against the conditions of
+    /// filters. The default is [`FilterEnumType::Overlapping`].
+    ///
+    /// This sets the `enumType` field in the underlying `FWPM_FILTER_ENUM_TEMPLATE0` structure.
+    pub fn enum_type(mut self, enum_type: FilterEnumType) -> Self {
+        self.enum_type = enum_type;
+        self
+    }
+
+    /// Sets the enumeration flags.
+    ///
+    /// This sets the `flags` field in the underlying `FWPM_FILTER_ENUM_TEMPLATE0` structure.
+    pub fn flags(mut self, flags: FilterEnumFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Only select filters whose conditions are related to `condition`, as specified by the
+    /// [enumeration type](Self::enum_type).
+    ///
+    /// Conditions are combined the same way as in
+    /// [`FilterBuilder::condition`](crate::FilterBuilder::condition).
+    ///
+    /// This adds to the `filterCondition` field in the underlying `FWPM_FILTER_ENUM_TEMPLATE0`
+    /// structure.
+    pub fn condition(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Only select filters whose action type has at least one bit in common with `mask`.
+    /// By default, filters with any action are selected.
+    ///
+    /// This sets the `actionMask` field in the underlying `FWPM_FILTER_ENUM_TEMPLATE0`
+    /// structure.
+    pub fn action_mask(mut self, mask: ActionMask) -> Self {
+        self.action_mask = Some(mask);
+        self
+    }
+
+    /// Only select filters that invoke `callout`.
+    ///
+    /// This sets the `calloutKey` field in the underlying `FWPM_FILTER_ENUM_TEMPLATE0`
+    /// structure.
+    pub fn callout(mut self, callout: GUID) -> Self {
+        self.callout = Some(callout);
+        self
+    }
+
+    /// Calls `f` with a raw template that points into `self`.
+    fn with_raw<T>(&self, f: impl FnOnce(&FWPM_FILTER_ENUM_TEMPLATE0) -> T) -> T {
+        let mut provider = self.provider;
+        let mut callout = self.callout;
+        let mut conditions: Vec<FWPM_FILTER_CONDITION0> = self
+            .conditions
+            .iter()
+            .map(|condition| *condition.raw_condition())
+            .collect();
+
+        let raw = FWPM_FILTER_ENUM_TEMPLATE0 {
+            providerKey: provider.as_mut().map_or(ptr::null_mut(), ptr::from_mut),
+            layerKey: *self.layer.guid(),
+            enumType: self.enum_type as i32,
+            flags: self.flags.bits(),
+            providerContextTemplate: ptr::null_mut(),
+            numFilterConditions: u32::try_from(conditions.len()).unwrap(),
+            filterCondition: if conditions.is_empty() {
+                ptr::null_mut()
+            } else {
+                conditions.as_mut_ptr()
+            },
+            actionMask: self.action_mask.map_or(u32::MAX, ActionMask::bits),
+            calloutKey: callout.as_mut().map_or(ptr::null_mut(), ptr::from_mut),
+        };
+        f(&raw)
+    }
 }
 
 /// A WFP filter
 pub struct FilterEnumItem<'a, 'b, 'c> {
     filter: &'c FWPM_FILTER0,
-    _enumerator: &'c FilterEnumerator<'a, 'b>,
+    _enumerator: PhantomData<&'c FilterEnumerator<'a, 'b>>,
 }
 
 impl<'a, 'b, 'c> FilterEnumItem<'a, 'b, 'c> {
@@ -215,8 +699,8 @@ impl<'a, 'b, 'c> FilterEnumItem<'a, 'b, 'c> {
     /// This corresponds to the `filterId` field in the underlying `FWPM_FILTER0` structure.
     ///
     /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
-    pub fn id(&self) -> u64 {
-        self.filter.filterId
+    pub fn id(&self) -> FilterId {
+        FilterId::from(self.filter.filterId)
     }
 
     /// Return the filter GUID.
@@ -242,21 +726,136 @@ impl<'a, 'b, 'c> FilterEnumItem<'a, 'b, 'c> {
         }
     }
 
+    /// Return the layer that the filter belongs to, or `None` if it is not a built-in layer.
+    ///
+    /// This corresponds to the `layerKey` field in the underlying `FWPM_FILTER0` structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn layer(&self) -> Option<Layer> {
+        Layer::from_guid(&self.filter.layerKey)
+    }
+
+    /// Return the GUID of the layer that the filter belongs to.
+    ///
+    /// Unlike [`FilterEnumItem::layer`], this is also available for layers that are not
+    /// built-in.
+    ///
+    /// This corresponds to the `layerKey` field in the underlying `FWPM_FILTER0` structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn layer_key(&self) -> GUID {
+        self.filter.layerKey
+    }
+
+    /// Return the GUID of the sublayer that the filter belongs to.
+    ///
+    /// This corresponds to the `subLayerKey` field in the underlying `FWPM_FILTER0` structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn sublayer(&self) -> GUID {
+        self.filter.subLayerKey
+    }
+
+    /// Return the action of the filter, or `None` if it is a callout action.
+    ///
+    /// This corresponds to `action.type` in the underlying `FWPM_FILTER0` structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn action(&self) -> Option<ActionType> {
+        ActionType::from_raw(self.filter.action.r#type)
+    }
+
+    /// Return the callout that the filter invokes, if it has a callout action.
+    ///
+    /// This corresponds to `action.calloutKey` in the underlying `FWPM_FILTER0` structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn callout(&self) -> Option<GUID> {
+        callout_from_raw(&self.filter.action)
+    }
+
+    /// Return the weight that was requested when the filter was added, or `None` if it is
+    /// not a valid [`FilterWeight`].
+    ///
+    /// This corresponds to the `weight` field in the underlying `FWPM_FILTER0` structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn weight(&self) -> Option<FilterWeight> {
+        // SAFETY: The union field matching the type of the weight is valid
+        unsafe { weight_from_raw(&self.filter.weight) }
+    }
+
+    /// Return the weight that BFE assigned to the filter, or `None` if it is not set.
+    ///
+    /// This is the weight that determines the order in which filters are evaluated.
+    ///
+    /// This corresponds to the `effectiveWeight` field in the underlying `FWPM_FILTER0`
+    /// structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn effective_weight(&self) -> Option<u64> {
+        // SAFETY: The union field matching the type of the weight is valid
+        unsafe { effective_weight_from_raw(&self.filter.effectiveWeight) }
+    }
+
+    /// Return the filter flags.
+    ///
+    /// This corresponds to the `flags` field in the underlying `FWPM_FILTER0` structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn flags(&self) -> FilterFlags {
+        FilterFlags::from_bits_retain(self.filter.flags)
+    }
+
+    /// Return the lifetime of the filter, which is derived from its [flags](Self::flags).
+    pub fn lifetime(&self) -> FilterLifetime {
+        FilterLifetime::from_flags(self.flags())
+    }
+
+    /// Return the provider data of the filter. This is empty if none is set.
+    ///
+    /// This corresponds to the `providerData` field in the underlying `FWPM_FILTER0` structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
+    pub fn provider_data(&self) -> &[u8] {
+        // SAFETY: `providerData` points to `size` bytes that live as long as the filter
+        unsafe { byte_blob_as_slice(&self.filter.providerData) }
+    }
+
+    /// Return the filter conditions.
+    ///
+    /// The conditions are copied, so they remain valid after the enumerator has moved on,
+    /// and can be added to another filter. Use [`Condition::value`] to decode them.
+    ///
+    /// This corresponds to the `filterCondition` field in the underlying `FWPM_FILTER0`
+    /// structure.
+    ///
+    /// Returns [`ConditionError::UnsupportedValueType`] if a condition has a value type that is
+    /// not supported.
+    ///
+    /// [`ConditionError::UnsupportedValueType`]: crate::ConditionError::UnsupportedValueType
+    pub fn conditions(&self) -> Result<Vec<Condition>> {
+        // SAFETY: The pointers in the filter are valid as long as `self`
+        unsafe { conditions_from_raw(self.filter) }
+    }
+
+    /// Return an owned copy of the filter.
+    ///
+    /// This fails if the name or description is not valid UTF-16, or if a condition has a
+    /// value type that is not supported.
+    pub fn to_info(&self) -> Result<FilterInfo> {
+        // SAFETY: The pointers in the filter are valid as long as `self`
+        unsafe { FilterInfo::from_raw(self.filter) }
+    }
+
     /// Return the filter name, if set.
     ///
     /// This corresponds to `displayName.name` in the underlying `FWPM_FILTER0` structure.
     ///
     /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
-    pub fn name(&self) -> io::Result<Option<String>> {
-        if !self.filter.displayData.name.is_null() {
-            let len = unsafe { wcslen(self.filter.displayData.name) };
-            let slice = unsafe { std::slice::from_raw_parts(self.filter.displayData.name, len) };
-            String::from_utf16(slice)
-                .map_err(|_err| io::Error::other("invalid filter name"))
-                .map(Some)
-        } else {
-            Ok(None)
-        }
+    pub fn name(&self) -> Result<Option<String>> {
+        // SAFETY: The name is null or null-terminated
+        unsafe { display_string(self.filter.displayData.name, "invalid filter name") }
     }
 
     /// Return the filter description, if set.
@@ -264,29 +863,13 @@ impl<'a, 'b, 'c> FilterEnumItem<'a, 'b, 'c> {
     /// This corresponds to `displayName.description` in the underlying `FWPM_FILTER0` structure.
     ///
     /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/
-    pub fn description(&self) -> io::Result<Option<String>> {
-        if !self.filter.displayData.description.is_null() {
-            let len = unsafe { wcslen(self.filter.displayData.description) };
-            let slice =
-                unsafe { std::slice::from_raw_parts(self.filter.displayData.description, len) };
-            String::from_utf16(slice)
-                .map_err(|_err| io::Error::other("invalid filter description"))
-                .map(Some)
-        } else {
-            Ok(None)
+    pub fn description(&self) -> Result<Option<String>> {
+        // SAFETY: The description is null or null-terminated
+        unsafe {
+            display_string(
+                self.filter.displayData.description,
+                "invalid filter description",
+            )
         }
     }
 }
-
-/// Retrieve the length of `s`, a null-terminated UTF-16 string.
-///
-/// # Safety
-///
-/// `s` must be null-terminated.
-unsafe fn wcslen(s: *const u16) -> usize {
-    let mut current = s;
-    while unsafe { std::ptr::read_unaligned(current) } != 0 {
-        current = unsafe { current.add(1) };
-    }
-    usize::try_from(unsafe { current.offset_from(s) }).unwrap()
-}
@@ -0,0 +1,635 @@
+//! Errors returned by WFP operations.
+
+use std::fmt;
+use std::io;
+
+use windows_sys::Win32::Foundation::{
+    ERROR_ACCESS_DENIED, ERROR_NOT_SUPPORTED, FWP_E_ALREADY_EXISTS, FWP_E_BUILTIN_OBJECT,
+    FWP_E_CALLOUT_NOT_FOUND, FWP_E_CONDITION_NOT_FOUND, FWP_E_DUPLICATE_CONDITION,
+    FWP_E_DYNAMIC_SESSION_IN_PROGRESS, FWP_E_FILTER_NOT_FOUND, FWP_E_IN_USE,
+    FWP_E_INCOMPATIBLE_LAYER, FWP_E_INCOMPATIBLE_TXN, FWP_E_INVALID_ENUMERATOR,
+    FWP_E_INVALID_FLAGS, FWP_E_INVALID_NET_MASK, FWP_E_INVALID_PARAMETER, FWP_E_INVALID_RANGE,
+    FWP_E_INVALID_WEIGHT, FWP_E_LAYER_NOT_FOUND, FWP_E_LIFETIME_MISMATCH,
+    FWP_E_MATCH_TYPE_MISMATCH, FWP_E_NEVER_MATCH, FWP_E_NO_TXN_IN_PROGRESS, FWP_E_NOT_FOUND,
+    FWP_E_PROVIDER_CONTEXT_NOT_FOUND, FWP_E_PROVIDER_NOT_FOUND, FWP_E_SESSION_ABORTED,
+    FWP_E_SUBLAYER_NOT_FOUND, FWP_E_TIMEOUT, FWP_E_TXN_ABORTED, FWP_E_TXN_IN_PROGRESS,
+    FWP_E_TYPE_MISMATCH, FWP_E_WRONG_SESSION,
+};
+use windows_sys::Win32::System::Rpc::EPT_S_NOT_REGISTERED;
+
+use crate::condition::ConditionField;
+use crate::layer::Layer;
+
+/// A specialized [`Result`](std::result::Result) type for WFP operations.
+pub type Result<T, E = WfpError> = std::result::Result<T, E>;
+
+/// An error returned by a WFP operation.
+///
+/// Status codes returned by the WFP API are mapped to named variants where possible.
+/// Codes that have no variant of their own are returned as [`WfpError::Other`]. The original
+/// status code can be retrieved using [`WfpError::code`].
+///
+/// `WfpError` can be converted into an [`io::Error`], so `?` may be used in functions that
+/// return [`io::Result`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WfpError {
+    /// An object with the same key already exists (`FWP_E_ALREADY_EXISTS`).
+    AlreadyExists,
+    /// The object does not exist (`FWP_E_*_NOT_FOUND`).
+    NotFound(ObjectType),
+    /// The object is referenced by other objects and cannot be deleted (`FWP_E_IN_USE`).
+    InUse,
+    /// The session already has an explicit transaction in progress (`FWP_E_TXN_IN_PROGRESS`).
+    TxnInProgress,
+    /// The session does not have an explicit transaction in progress
+    /// (`FWP_E_NO_TXN_IN_PROGRESS`).
+    NoTxnInProgress,
+    /// The transaction was aborted (`FWP_E_TXN_ABORTED`).
+    TxnAborted,
+    /// The operation is not allowed in the current transaction, e.g. a write in a read-only
+    /// transaction (`FWP_E_INCOMPATIBLE_TXN`).
+    IncompatibleTxn,
+    /// The session was aborted (`FWP_E_SESSION_ABORTED`).
+    SessionAborted,
+    /// The object belongs to a different session (`FWP_E_WRONG_SESSION`).
+    WrongSession,
+    /// Timed out waiting for another session's transaction to complete (`FWP_E_TIMEOUT`).
+    Timeout,
+    /// Persistent objects cannot be added in a dynamic session
+    /// (`FWP_E_DYNAMIC_SESSION_IN_PROGRESS`).
+    DynamicSessionInProgress,
+    /// A persistent object refers to an object that is not persistent
+    /// (`FWP_E_LIFETIME_MISMATCH`).
+    LifetimeMismatch,
+    /// Built-in objects cannot be modified or deleted (`FWP_E_BUILTIN_OBJECT`).
+    BuiltinObject,
+    /// A filter condition is invalid.
+    InvalidCondition(ConditionError),
+    /// A filter condition is not supported at the layer of the filter.
+    ///
+    /// This is detected by [`FilterBuilder::add`](crate::FilterBuilder::add) before the filter
+    /// is added, using the metadata returned by [`Layer::condition_fields`].
+    IncompatibleCondition {
+        /// The layer of the filter.
+        layer: Layer,
+        /// The field that the condition matches, if it is a known field.
+        field: Option<ConditionField>,
+        /// Why the condition is not supported.
+        reason: ConditionError,
+    },
+    /// The filter weight is invalid (`FWP_E_INVALID_WEIGHT`).
+    InvalidWeight,
+    /// The flags are invalid or incompatible with each other (`FWP_E_INVALID_FLAGS`).
+    InvalidFlags,
+    /// The enumeration handle is invalid (`FWP_E_INVALID_ENUMERATOR`).
+    InvalidEnumerator,
+    /// A parameter is invalid (`FWP_E_INVALID_PARAMETER`).
+    InvalidParameter,
+    /// The operation is not supported by the layer (`FWP_E_INCOMPATIBLE_LAYER`).
+    IncompatibleLayer,
+    /// The caller does not have the required access rights (`ERROR_ACCESS_DENIED`).
+    AccessDenied,
+    /// The Base Filtering Engine service is not running (`EPT_S_NOT_REGISTERED`).
+    BfeNotRunning,
+    /// A status code that has no variant of its own.
+    Other(i32),
+    /// An error that did not originate from a status code.
+    Io(io::Error),
+}
+
+/// The type of object that was not found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ObjectType {
+    /// A callout (`FWP_E_CALLOUT_NOT_FOUND`).
+    Callout,
+    /// A filter (`FWP_E_FILTER_NOT_FOUND`).
+    Filter,
+    /// A layer (`FWP_E_LAYER_NOT_FOUND`).
+    Layer,
+    /// A provider (`FWP_E_PROVIDER_NOT_FOUND`).
+    Provider,
+    /// A provider context (`FWP_E_PROVIDER_CONTEXT_NOT_FOUND`).
+    ProviderContext,
+    /// A sublayer (`FWP_E_SUBLAYER_NOT_FOUND`).
+    SubLayer,
+    /// An object of unspecified type (`FWP_E_NOT_FOUND`).
+    Unspecified,
+}
+
+/// The reason that a filter condition is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConditionError {
+    /// The condition field does not exist in the layer (`FWP_E_CONDITION_NOT_FOUND`).
+    FieldNotFound,
+    /// The same condition was specified more than once (`FWP_E_DUPLICATE_CONDITION`).
+    Duplicate,
+    /// The network mask is invalid (`FWP_E_INVALID_NET_MASK`).
+    InvalidNetMask,
+    /// The range is invalid (`FWP_E_INVALID_RANGE`).
+    InvalidRange,
+    /// The match type is not supported for the value (`FWP_E_MATCH_TYPE_MISMATCH`).
+    MatchTypeMismatch,
+    /// The value type does not match the type of the field (`FWP_E_TYPE_MISMATCH`).
+    TypeMismatch,
+    /// The conditions can never match (`FWP_E_NEVER_MATCH`).
+    NeverMatch,
+    /// The IP version of an address does not match the IP version of the layer.
+    ///
+    /// WFP reports this as a type mismatch (`FWP_E_TYPE_MISMATCH`).
+    AddressFamilyMismatch,
+    /// The condition value has a type that this library cannot decode, e.g. in a filter
+    /// returned by an enumerator.
+    ///
+    /// The status code of this error is `ERROR_NOT_SUPPORTED`.
+    UnsupportedValueType,
+}
+
+impl WfpError {
+    /// Creates an error from a status code returned by the WFP API.
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            FWP_E_ALREADY_EXISTS => Self::AlreadyExists,
+            FWP_E_CALLOUT_NOT_FOUND => Self::NotFound(ObjectType::Callout),
+            FWP_E_FILTER_NOT_FOUND => Self::NotFound(ObjectType::Filter),
+            FWP_E_PROVIDER_NOT_FOUND => Self::NotFound(ObjectType::Provider),
+            FWP_E_PROVIDER_CONTEXT_NOT_FOUND => Self::NotFound(ObjectType::ProviderContext),
+            FWP_E_SUBLAYER_NOT_FOUND => Self::NotFound(ObjectType::SubLayer),
+            FWP_E_NOT_FOUND => Self::NotFound(ObjectType::Unspecified),
+            FWP_E_LAYER_NOT_FOUND => Self::NotFound(ObjectType::Layer),
+            FWP_E_IN_USE => Self::InUse,
+            FWP_E_TXN_IN_PROGRESS => Self::TxnInProgress,
+            FWP_E_NO_TXN_IN_PROGRESS => Self::NoTxnInProgress,
+            FWP_E_TXN_ABORTED => Self::TxnAborted,
+            FWP_E_INCOMPATIBLE_TXN => Self::IncompatibleTxn,
+            FWP_E_SESSION_ABORTED => Self::SessionAborted,
+            FWP_E_WRONG_SESSION => Self::WrongSession,
+            FWP_E_TIMEOUT => Self::Timeout,
+            FWP_E_DYNAMIC_SESSION_IN_PROGRESS => Self::DynamicSessionInProgress,
+            FWP_E_LIFETIME_MISMATCH => Self::LifetimeMismatch,
+            FWP_E_BUILTIN_OBJECT => Self::BuiltinObject,
+            FWP_E_CONDITION_NOT_FOUND => Self::InvalidCondition(ConditionError::FieldNotFound),
+            FWP_E_DUPLICATE_CONDITION => Self::InvalidCondition(ConditionError::Duplicate),
+            FWP_E_INVALID_NET_MASK => Self::InvalidCondition(ConditionError::InvalidNetMask),
+            FWP_E_INVALID_RANGE => Self::InvalidCondition(ConditionError::InvalidRange),
+            FWP_E_MATCH_TYPE_MISMATCH => Self::InvalidCondition(ConditionError::MatchTypeMismatch),
+            FWP_E_TYPE_MISMATCH => Self::InvalidCondition(ConditionError::TypeMismatch),
+            FWP_E_NEVER_MATCH => Self::InvalidCondition(ConditionError::NeverMatch),
+            FWP_E_INVALID_WEIGHT => Self::InvalidWeight,
+            FWP_E_INVALID_FLAGS => Self::InvalidFlags,
+            FWP_E_INVALID_ENUMERATOR => Self::InvalidEnumerator,
+            FWP_E_INVALID_PARAMETER => Self::InvalidParameter,
+            FWP_E_INCOMPATIBLE_LAYER => Self::IncompatibleLayer,
+            code if code == ERROR_ACCESS_DENIED as i32 => Self::AccessDenied,
+            EPT_S_NOT_REGISTERED => Self::BfeNotRunning,
+            code => Self::Other(code),
+        }
+    }
+
+    /// Returns the status code that corresponds to this error.
+    ///
+    /// This returns `None` for [`WfpError::Io`] errors that do not contain an OS error code.
+    pub fn code(&self) -> Option<i32> {
+        let code = match self {
+            Self::AlreadyExists => FWP_E_ALREADY_EXISTS,
+            Self::NotFound(object) => object.code(),
+            Self::InUse => FWP_E_IN_USE,
+            Self::TxnInProgress => FWP_E_TXN_IN_PROGRESS,
+            Self::NoTxnInProgress => FWP_E_NO_TXN_IN_PROGRESS,
+            Self::TxnAborted => FWP_E_TXN_ABORTED,
+            Self::IncompatibleTxn => FWP_E_INCOMPATIBLE_TXN,
+            Self::SessionAborted => FWP_E_SESSION_ABORTED,
+            Self::WrongSession => FWP_E_WRONG_SESSION,
+            Self::Timeout => FWP_E_TIMEOUT,
+            Self::DynamicSessionInProgress => FWP_E_DYNAMIC_SESSION_IN_PROGRESS,
+            Self::LifetimeMismatch => FWP_E_LIFETIME_MISMATCH,
+            Self::BuiltinObject => FWP_E_BUILTIN_OBJECT,
+            Self::InvalidCondition(reason) | Self::IncompatibleCondition { reason, .. } => {
+                reason.code()
+            }
+            Self::InvalidWeight => FWP_E_INVALID_WEIGHT,
+            Self::InvalidFlags => FWP_E_INVALID_FLAGS,
+            Self::InvalidEnumerator => FWP_E_INVALID_ENUMERATOR,
+            Self::InvalidParameter => FWP_E_INVALID_PARAMETER,
+            Self::IncompatibleLayer => FWP_E_INCOMPATIBLE_LAYER,
+            Self::AccessDenied => ERROR_ACCESS_DENIED as i32,
+            Self::BfeNotRunning => EPT_S_NOT_REGISTERED,
+            Self::Other(code) => *code,
+            Self::Io(err) => return err.raw_os_error(),
+        };
+        Some(code)
+    }
+
+    /// Returns whether an object with the same key already exists.
+    pub fn is_already_exists(&self) -> bool {
+        matches!(self, Self::AlreadyExists)
+    }
+
+    /// Returns whether an object or layer does not exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound(_))
+    }
+
+    /// Returns whether the layer does not exist.
+    pub fn is_layer_not_found(&self) -> bool {
+        matches!(self, Self::NotFound(ObjectType::Layer))
+    }
+
+    /// Returns whether the object is still in use.
+    pub fn is_in_use(&self) -> bool {
+        matches!(self, Self::InUse)
+    }
+
+    /// Returns whether a transaction is already in progress.
+    pub fn is_txn_in_progress(&self) -> bool {
+        matches!(self, Self::TxnInProgress)
+    }
+
+    /// Returns whether no transaction is in progress.
+    pub fn is_no_txn_in_progress(&self) -> bool {
+        matches!(self, Self::NoTxnInProgress)
+    }
+
+    /// Returns whether the transaction was aborted.
+    pub fn is_txn_aborted(&self) -> bool {
+        matches!(self, Self::TxnAborted)
+    }
+
+    /// Returns whether the operation is not allowed in the current transaction.
+    pub fn is_incompatible_txn(&self) -> bool {
+        matches!(self, Self::IncompatibleTxn)
+    }
+
+    /// Returns whether the session was aborted.
+    pub fn is_session_aborted(&self) -> bool {
+        matches!(self, Self::SessionAborted)
+    }
+
+    /// Returns whether the object belongs to a different session.
+    pub fn is_wrong_session(&self) -> bool {
+        matches!(self, Self::WrongSession)
+    }
+
+    /// Returns whether the operation timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Timeout)
+    }
+
+    /// Returns whether a persistent object was added in a dynamic session.
+    pub fn is_dynamic_session_in_progress(&self) -> bool {
+        matches!(self, Self::DynamicSessionInProgress)
+    }
+
+    /// Returns whether a persistent object refers to an object that is not persistent.
+    pub fn is_lifetime_mismatch(&self) -> bool {
+        matches!(self, Self::LifetimeMismatch)
+    }
+
+    /// Returns whether a built-in object cannot be modified or deleted.
+    pub fn is_builtin_object(&self) -> bool {
+        matches!(self, Self::BuiltinObject)
+    }
+
+    /// Returns whether a filter condition is invalid.
+    ///
+    /// This includes conditions that are not supported at the layer of the filter, see
+    /// [`is_incompatible_condition`](Self::is_incompatible_condition).
+    pub fn is_invalid_condition(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidCondition(_) | Self::IncompatibleCondition { .. }
+        )
+    }
+
+    /// Returns whether a filter condition is not supported at the layer of the filter.
+    pub fn is_incompatible_condition(&self) -> bool {
+        matches!(self, Self::IncompatibleCondition { .. })
+    }
+
+    /// Returns whether the filter weight is invalid.
+    pub fn is_invalid_weight(&self) -> bool {
+        matches!(self, Self::InvalidWeight)
+    }
+
+    /// Returns whether the flags are invalid.
+    pub fn is_invalid_flags(&self) -> bool {
+        matches!(self, Self::InvalidFlags)
+    }
+
+    /// Returns whether the enumeration handle is invalid.
+    pub fn is_invalid_enumerator(&self) -> bool {
+        matches!(self, Self::InvalidEnumerator)
+    }
+
+    /// Returns whether a parameter is invalid.
+    pub fn is_invalid_parameter(&self) -> bool {
+        matches!(self, Self::InvalidParameter)
+    }
+
+    /// Returns whether the operation is not supported by the layer.
+    pub fn is_incompatible_layer(&self) -> bool {
+        matches!(self, Self::IncompatibleLayer)
+    }
+
+    /// Returns whether the caller does not have the required access rights.
+    pub fn is_access_denied(&self) -> bool {
+        matches!(self, Self::AccessDenied)
+    }
+
+    /// Returns whether the Base Filtering Engine service is not running.
+    pub fn is_bfe_not_running(&self) -> bool {
+        matches!(self, Self::BfeNotRunning)
+    }
+
+    /// Returns whether the error is a status code that has no variant of its own.
+    pub fn is_other(&self) -> bool {
+        matches!(self, Self::Other(_))
+    }
+
+    /// Returns whether the error did not originate from a status code.
+    pub fn is_io(&self) -> bool {
+        matches!(self, Self::Io(_))
+    }
+}
+
+impl ObjectType {
+    fn code(self) -> i32 {
+        match self {
+            Self::Callout => FWP_E_CALLOUT_NOT_FOUND,
+            Self::Filter => FWP_E_FILTER_NOT_FOUND,
+            Self::Layer => FWP_E_LAYER_NOT_FOUND,
+            Self::Provider => FWP_E_PROVIDER_NOT_FOUND,
+            Self::ProviderContext => FWP_E_PROVIDER_CONTEXT_NOT_FOUND,
+            Self::SubLayer => FWP_E_SUBLAYER_NOT_FOUND,
+            Self::Unspecified => FWP_E_NOT_FOUND,
+        }
+    }
+}
+
+impl ConditionError {
+    fn code(self) -> i32 {
+        match self {
+            Self::FieldNotFound => FWP_E_CONDITION_NOT_FOUND,
+            Self::Duplicate => FWP_E_DUPLICATE_CONDITION,
+            Self::InvalidNetMask => FWP_E_INVALID_NET_MASK,
+            Self::InvalidRange => FWP_E_INVALID_RANGE,
+            Self::MatchTypeMismatch => FWP_E_MATCH_TYPE_MISMATCH,
+            Self::TypeMismatch => FWP_E_TYPE_MISMATCH,
+            Self::NeverMatch => FWP_E_NEVER_MATCH,
+            // WFP reports this as a type mismatch
+            Self::AddressFamilyMismatch => FWP_E_TYPE_MISMATCH,
+            Self::UnsupportedValueType => ERROR_NOT_SUPPORTED as i32,
+        }
+    }
+}
+
+impl fmt::Display for WfpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyExists => f.write_str("an object with the same key already exists"),
+            Self::NotFound(object) => write!(f, "{object} not found"),
+            Self::InUse => f.write_str("the object is in use"),
+            Self::TxnInProgress => f.write_str("a transaction is already in progress"),
+            Self::NoTxnInProgress => f.write_str("no transaction is in progress"),
+            Self::TxnAborted => f.write_str("the transaction was aborted"),
+            Self::IncompatibleTxn => {
+                f.write_str("the operation is not allowed in the current transaction")
+            }
+            Self::SessionAborted => f.write_str("the session was aborted"),
+            Self::WrongSession => f.write_str("the object belongs to a different session"),
+            Self::Timeout => f.write_str("timed out waiting for another transaction"),
+            Self::DynamicSessionInProgress => {
+                f.write_str("persistent objects cannot be added in a dynamic session")
+            }
+            Self::LifetimeMismatch => {
+                f.write_str("a persistent object cannot refer to a non-persistent object")
+            }
+            Self::BuiltinObject => f.write_str("built-in objects cannot be modified"),
+            Self::InvalidCondition(reason) => write!(f, "invalid filter condition: {reason}"),
+            Self::IncompatibleCondition {
+                layer,
+                field: Some(field),
+                reason,
+            } => write!(
+                f,
+                "condition on {field:?} is not supported at layer {layer:?}: {reason}"
+            ),
+            Self::IncompatibleCondition {
+                layer,
+                field: None,
+                reason,
+            } => write!(f, "condition is not supported at layer {layer:?}: {reason}"),
+            Self::InvalidWeight => f.write_str("invalid weight"),
+            Self::InvalidFlags => f.write_str("invalid flags"),
+            Self::InvalidEnumerator => f.write_str("invalid enumeration handle"),
+            Self::InvalidParameter => f.write_str("invalid parameter"),
+            Self::IncompatibleLayer => f.write_str("the operation is not supported by the layer"),
+            Self::AccessDenied => f.write_str("access denied"),
+            Self::BfeNotRunning => f.write_str("the Base Filtering Engine is not running"),
+            Self::Other(code) => io::Error::from_raw_os_error(*code).fmt(f),
+            Self::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Callout => "callout",
+            Self::Filter => "filter",
+            Self::Layer => "layer",
+            Self::Provider => "provider",
+            Self::ProviderContext => "provider context",
+            Self::SubLayer => "sublayer",
+            Self::Unspecified => "object",
+        })
+    }
+}
+
+impl fmt::Display for ConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::FieldNotFound => "the field does not exist in the layer",
+            Self::Duplicate => "duplicate condition",
+            Self::InvalidNetMask => "invalid network mask",
+            Self::InvalidRange => "invalid range",
+            Self::MatchTypeMismatch => "the match type is not supported for the value",
+            Self::TypeMismatch => "the value type does not match the field",
+            Self::NeverMatch => "the conditions can never match",
+            Self::AddressFamilyMismatch => "the address family does not match the layer",
+            Self::UnsupportedValueType => "the value type is not supported",
+        })
+    }
+}
+
+impl std::error::Error for WfpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WfpError {
+    fn from(err: io::Error) -> Self {
+        match err.raw_os_error() {
+            Some(code) => Self::from_code(code),
+            None => Self::Io(err),
+        }
+    }
+}
+
+impl From<WfpError> for io::Error {
+    fn from(err: WfpError) -> Self {
+        let kind = match err {
+            WfpError::Io(err) => return err,
+            WfpError::Other(code) => return io::Error::from_raw_os_error(code),
+            WfpError::AlreadyExists => io::ErrorKind::AlreadyExists,
+            WfpError::NotFound(_) => io::ErrorKind::NotFound,
+            WfpError::InUse => io::ErrorKind::ResourceBusy,
+            WfpError::Timeout => io::ErrorKind::TimedOut,
+            WfpError::AccessDenied => io::ErrorKind::PermissionDenied,
+            WfpError::InvalidCondition(_)
+            | WfpError::IncompatibleCondition { .. }
+            | WfpError::InvalidWeight
+            | WfpError::InvalidFlags
+            | WfpError::InvalidParameter => io::ErrorKind::InvalidInput,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+/// An error returned when a string cannot be parsed, e.g. by [`Sid::from_str`].
+///
+/// It can be converted into a [`WfpError`] or an [`io::Error`] of kind
+/// [`InvalidInput`](io::ErrorKind::InvalidInput).
+///
+/// [`Sid::from_str`]: crate::Sid#impl-FromStr-for-Sid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    what: &'static str,
+    reason: String,
+}
+
+impl ParseError {
+    /// Creates an error for an invalid `what`, e.g. "SID", with a description of the problem.
+    pub(crate) fn new(what: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            what,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {}", self.what, self.reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(err: ParseError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+impl From<ParseError> for WfpError {
+    fn from(err: ParseError) -> Self {
+        Self::Io(err.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_code_roundtrip() {
+        for code in [
+            FWP_E_ALREADY_EXISTS,
+            FWP_E_FILTER_NOT_FOUND,
+            FWP_E_SUBLAYER_NOT_FOUND,
+            FWP_E_NOT_FOUND,
+            FWP_E_LAYER_NOT_FOUND,
+            FWP_E_TXN_IN_PROGRESS,
+            FWP_E_TIMEOUT,
+            FWP_E_TYPE_MISMATCH,
+            ERROR_ACCESS_DENIED as i32,
+            EPT_S_NOT_REGISTERED,
+            // Not a WFP status code
+            87,
+        ] {
+            assert_eq!(WfpError::from_code(code).code(), Some(code));
+        }
+    }
+
+    #[test]
+    fn test_named_variants() {
+        assert!(WfpError::from_code(FWP_E_ALREADY_EXISTS).is_already_exists());
+        assert!(WfpError::from_code(FWP_E_PROVIDER_NOT_FOUND).is_not_found());
+        assert!(WfpError::from_code(FWP_E_LAYER_NOT_FOUND).is_not_found());
+        assert!(!WfpError::from_code(FWP_E_PROVIDER_NOT_FOUND).is_layer_not_found());
+        assert!(WfpError::from_code(FWP_E_MATCH_TYPE_MISMATCH).is_invalid_condition());
+        assert!(WfpError::from_code(EPT_S_NOT_REGISTERED).is_bfe_not_running());
+        assert!(WfpError::from_code(FWP_E_SESSION_ABORTED).is_session_aborted());
+        assert!(WfpError::from_code(FWP_E_WRONG_SESSION).is_wrong_session());
+        assert!(
+            WfpError::from_code(FWP_E_DYNAMIC_SESSION_IN_PROGRESS).is_dynamic_session_in_progress()
+        );
+        assert!(WfpError::from_code(FWP_E_LIFETIME_MISMATCH).is_lifetime_mismatch());
+        assert!(WfpError::from_code(FWP_E_BUILTIN_OBJECT).is_builtin_object());
+        assert!(WfpError::from_code(FWP_E_INVALID_WEIGHT).is_invalid_weight());
+        assert!(WfpError::from_code(FWP_E_INVALID_FLAGS).is_invalid_flags());
+        assert!(WfpError::from_code(FWP_E_INVALID_ENUMERATOR).is_invalid_enumerator());
+        assert!(WfpError::from_code(FWP_E_INVALID_PARAMETER).is_invalid_parameter());
+        assert!(WfpError::from_code(FWP_E_INCOMPATIBLE_LAYER).is_incompatible_layer());
+        assert!(!WfpError::from_code(FWP_E_TYPE_MISMATCH).is_incompatible_condition());
+        let err = WfpError::IncompatibleCondition {
+            layer: Layer::ConnectV4,
+            field: None,
+            reason: ConditionError::FieldNotFound,
+        };
+        assert!(err.is_incompatible_condition());
+        assert!(err.is_invalid_condition());
+        assert!(matches!(WfpError::from_code(87), WfpError::Other(87)));
+        assert!(WfpError::from_code(87).is_other());
+
+        let err = WfpError::InvalidCondition(ConditionError::UnsupportedValueType);
+        assert!(err.is_invalid_condition());
+        assert_eq!(err.code(), Some(ERROR_NOT_SUPPORTED as i32));
+        assert!(!WfpError::from_code(87).is_io());
+    }
+
+    #[test]
+    fn test_io_error_conversion() {
+        let err = io::Error::from(WfpError::AlreadyExists);
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        let inner = err.into_inner().unwrap().downcast::<WfpError>().unwrap();
+        assert!(inner.is_already_exists());
+
+        let err = WfpError::from(io::Error::from_raw_os_error(FWP_E_IN_USE));
+        assert!(err.is_in_use());
+
+        let err = WfpError::from(io::Error::other("custom"));
+        assert!(err.is_io());
+        assert_eq!(err.code(), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            WfpError::NotFound(ObjectType::SubLayer).to_string(),
+            "sublayer not found"
+        );
+        assert_eq!(
+            WfpError::InvalidCondition(ConditionError::InvalidRange).to_string(),
+            "invalid filter condition: invalid range"
+        );
+    }
+}
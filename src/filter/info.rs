@@ -0,0 +1,248 @@
+//! Owned copies of filters that have been added to the engine.
+
+use std::fmt;
+
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWP_ACTION_FLAG_CALLOUT, FWP_EMPTY, FWP_UINT8, FWP_UINT64, FWP_VALUE0, FWPM_ACTION0,
+    FWPM_FILTER0,
+};
+use windows_sys::core::GUID;
+
+use super::{FilterFlags, FilterId, FilterLifetime, FilterWeight, WeightRange};
+use crate::action::ActionType;
+use crate::condition::Condition;
+use crate::error::{ConditionError, Result, WfpError};
+use crate::layer::Layer;
+use crate::util::{GuidDisplay, byte_blob_as_slice, display_string};
+
+/// An owned copy of a filter.
+///
+/// Unlike [`FilterEnumItem`](crate::FilterEnumItem), this does not borrow the enumerator, so
+/// it can be collected, stored and sent to other threads. It is returned when iterating over
+/// a [`FilterEnumerator`](crate::FilterEnumerator) using [`IntoIterator`], and by
+/// [`get_filter_by_id`](crate::get_filter_by_id) and
+/// [`get_filter_by_key`](crate::get_filter_by_key).
+///
+/// This corresponds to the underlying [`FWPM_FILTER0`] structure.
+///
+/// [`FWPM_FILTER0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_filter0
+#[derive(Clone)]
+pub struct FilterInfo {
+    id: FilterId,
+    key: GUID,
+    name: Option<String>,
+    description: Option<String>,
+    provider: Option<GUID>,
+    layer_key: GUID,
+    sublayer: GUID,
+    action: Option<ActionType>,
+    callout: Option<GUID>,
+    weight: Option<FilterWeight>,
+    effective_weight: Option<u64>,
+    flags: FilterFlags,
+    provider_data: Vec<u8>,
+    conditions: Vec<Condition>,
+}
+
+impl FilterInfo {
+    /// Deep-copies a raw filter.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `filter` must be valid.
+    pub(crate) unsafe fn from_raw(filter: &FWPM_FILTER0) -> Result<Self> {
+        // SAFETY: The caller guarantees that the pointers are valid
+        unsafe {
+            Ok(Self {
+                id: FilterId::from(filter.filterId),
+                key: filter.filterKey,
+                name: display_string(filter.displayData.name, "invalid filter name")?,
+                description: display_string(
+                    filter.displayData.description,
+                    "invalid filter description",
+                )?,
+                provider: filter.providerKey.as_ref().copied(),
+                layer_key: filter.layerKey,
+                sublayer: filter.subLayerKey,
+                action: ActionType::from_raw(filter.action.r#type),
+                callout: callout_from_raw(&filter.action),
+                weight: weight_from_raw(&filter.weight),
+                effective_weight: effective_weight_from_raw(&filter.effectiveWeight),
+                flags: FilterFlags::from_bits_retain(filter.flags),
+                provider_data: byte_blob_as_slice(&filter.providerData).to_vec(),
+                conditions: conditions_from_raw(filter)?,
+            })
+        }
+    }
+
+    /// Return the filter ID.
+    pub fn id(&self) -> FilterId {
+        self.id
+    }
+
+    /// Return the filter GUID.
+    pub fn guid(&self) -> GUID {
+        self.key
+    }
+
+    /// Return the filter name, if set.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Return the filter description, if set.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return the filter provider, if set.
+    pub fn provider(&self) -> Option<GUID> {
+        self.provider
+    }
+
+    /// Return the layer that the filter belongs to, or `None` if it is not a built-in layer.
+    pub fn layer(&self) -> Option<Layer> {
+        Layer::from_guid(&self.layer_key)
+    }
+
+    /// Return the GUID of the layer that the filter belongs to.
+    pub fn layer_key(&self) -> GUID {
+        self.layer_key
+    }
+
+    /// Return the GUID of the sublayer that the filter belongs to.
+    pub fn sublayer(&self) -> GUID {
+        self.sublayer
+    }
+
+    /// Return the action of the filter, or `None` if it is a callout action.
+    pub fn action(&self) -> Option<ActionType> {
+        self.action
+    }
+
+    /// Return the callout that the filter invokes, if it has a callout action.
+    pub fn callout(&self) -> Option<GUID> {
+        self.callout
+    }
+
+    /// Return the weight that was requested when the filter was added, or `None` if it is
+    /// not a valid [`FilterWeight`].
+    pub fn weight(&self) -> Option<FilterWeight> {
+        self.weight
+    }
+
+    /// Return the weight that BFE assigned to the filter, or `None` if it is not set.
+    pub fn effective_weight(&self) -> Option<u64> {
+        self.effective_weight
+    }
+
+    /// Return the filter flags.
+    pub fn flags(&self) -> FilterFlags {
+        self.flags
+    }
+
+    /// Return the lifetime of the filter, which is derived from its [flags](Self::flags).
+    pub fn lifetime(&self) -> FilterLifetime {
+        FilterLifetime::from_flags(self.flags)
+    }
+
+    /// Return the provider data of the filter. This is empty if none is set.
+    pub fn provider_data(&self) -> &[u8] {
+        &self.provider_data
+    }
+
+    /// Return the filter conditions. Use [`Condition::value`] to decode them.
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+}
+
+impl fmt::Debug for FilterInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterInfo")
+            .field("id", &self.id)
+            .field("key", &GuidDisplay(&self.key))
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("provider", &self.provider.as_ref().map(GuidDisplay))
+            .field("layer_key", &GuidDisplay(&self.layer_key))
+            .field("sublayer", &GuidDisplay(&self.sublayer))
+            .field("action", &self.action)
+            .field("callout", &self.callout.as_ref().map(GuidDisplay))
+            .field("weight", &self.weight)
+            .field("effective_weight", &self.effective_weight)
+            .field("flags", &self.flags)
+            .field("provider_data", &self.provider_data)
+            .field("conditions", &self.conditions)
+            .finish()
+    }
+}
+
+/// Returns the callout of a raw action, if it is a callout action.
+pub(crate) fn callout_from_raw(action: &FWPM_ACTION0) -> Option<GUID> {
+    if action.r#type & FWP_ACTION_FLAG_CALLOUT == 0 {
+        return None;
+    }
+    // SAFETY: `calloutKey` is the active union field for callout actions
+    Some(unsafe { action.Anonymous.calloutKey })
+}
+
+/// Decodes the requested weight of a raw filter.
+///
+/// # Safety
+///
+/// The union field matching the type of `weight` must be valid.
+pub(crate) unsafe fn weight_from_raw(weight: &FWP_VALUE0) -> Option<FilterWeight> {
+    // SAFETY: The caller guarantees that the union field matching `type` is valid
+    unsafe {
+        match weight.r#type {
+            FWP_EMPTY => Some(FilterWeight::Auto),
+            FWP_UINT8 => WeightRange::try_from(weight.Anonymous.uint8)
+                .ok()
+                .map(FilterWeight::Range),
+            FWP_UINT64 if !weight.Anonymous.uint64.is_null() => {
+                Some(FilterWeight::Exact(*weight.Anonymous.uint64))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Decodes the effective weight of a raw filter.
+///
+/// # Safety
+///
+/// The union field matching the type of `weight` must be valid.
+pub(crate) unsafe fn effective_weight_from_raw(weight: &FWP_VALUE0) -> Option<u64> {
+    // SAFETY: The caller guarantees that the union field matching `type` is valid
+    unsafe {
+        (weight.r#type == FWP_UINT64 && !weight.Anonymous.uint64.is_null())
+            .then(|| *weight.Anonymous.uint64)
+    }
+}
+
+/// Deep-copies the conditions of a raw filter.
+///
+/// Returns [`ConditionError::UnsupportedValueType`] if a condition has a value type that is
+/// not supported.
+///
+/// # Safety
+///
+/// All pointers in `filter` must be valid.
+pub(crate) unsafe fn conditions_from_raw(filter: &FWPM_FILTER0) -> Result<Vec<Condition>> {
+    let num_conditions = usize::try_from(filter.numFilterConditions).unwrap();
+    if num_conditions == 0 || filter.filterCondition.is_null() {
+        return Ok(vec![]);
+    }
+    // SAFETY: `filterCondition` points to `numFilterConditions` conditions
+    let raw_conditions =
+        unsafe { std::slice::from_raw_parts(filter.filterCondition, num_conditions) };
+    raw_conditions
+        .iter()
+        // SAFETY: The caller guarantees that the pointers in the conditions are valid
+        .map(|raw| unsafe { Condition::from_raw(raw) })
+        .collect::<Option<_>>()
+        .ok_or(WfpError::InvalidCondition(
+            ConditionError::UnsupportedValueType,
+        ))
+}
@@ -1,29 +1,33 @@
 //! Filter creation and management for the Windows Filtering Platform.
 
+mod info;
 mod weight;
 
+pub use info::FilterInfo;
+pub(crate) use info::{
+    callout_from_raw, conditions_from_raw, effective_weight_from_raw, weight_from_raw,
+};
 pub use weight::*;
 
 use std::ffi::OsStr;
-use std::io;
-use std::os::windows::io::AsRawHandle;
-use std::ptr;
 use std::sync::Arc;
 
-use windows_sys::Win32::Foundation::ERROR_SUCCESS;
-use windows_sys::Win32::Foundation::STATUS_SUCCESS;
 use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
     FWP_EMPTY, FWP_UINT8, FWP_UINT64, FWPM_FILTER_CONDITION0, FWPM_FILTER_FLAG_BOOTTIME,
-    FWPM_FILTER_FLAG_PERSISTENT, FWPM_FILTER0, FwpmFilterAdd0, FwpmFilterDeleteById0,
-    FwpmFilterDeleteByKey0,
+    FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT, FWPM_FILTER_FLAG_DISABLED, FWPM_FILTER_FLAG_GAMEOS_ONLY,
+    FWPM_FILTER_FLAG_HAS_PROVIDER_CONTEXT, FWPM_FILTER_FLAG_HAS_SECURITY_REALM_PROVIDER_CONTEXT,
+    FWPM_FILTER_FLAG_INDEXED, FWPM_FILTER_FLAG_IPSEC_NO_ACQUIRE_INITIATE,
+    FWPM_FILTER_FLAG_PERMIT_IF_CALLOUT_UNREGISTERED, FWPM_FILTER_FLAG_PERSISTENT,
+    FWPM_FILTER_FLAG_SILENT_MODE, FWPM_FILTER_FLAG_SYSTEMOS_ONLY, FWPM_FILTER0,
 };
 use windows_sys::core::GUID;
 
 use crate::action::ActionType;
 use crate::condition::Condition;
+use crate::error::Result;
 use crate::layer::Layer;
 use crate::transaction::Transaction;
-use crate::util::string_to_null_terminated_utf16;
+use crate::util::{GuidDisplay, flags_type, string_to_null_terminated_utf16};
 
 /// Builder for creating Windows Filtering Platform filters.
 ///
@@ -300,12 +304,26 @@ impl FilterBuilder<FilterBuilderHasName, FilterBuilderHasAction> {
     ///
     /// It calls [`FwpmFilterAdd0`] to add the filter to the engine.
     ///
+    /// If the layer is a built-in [`Layer`], the conditions are first checked against the
+    /// fields available at that layer. A condition on a field that the layer does not support,
+    /// or with a value of the wrong type, fails with
+    /// [`WfpError::IncompatibleCondition`](crate::WfpError::IncompatibleCondition)
+    /// without calling into the engine.
+    ///
     /// # Returns
     ///
-    /// Returns `Ok(())` on success, or an error if the filter could not be added.
+    /// Returns the runtime ID and key of the new filter on success, or an error if the filter
+    /// could not be added. If no key was set using [`FilterBuilder::guid`], the key is the one
+    /// generated by the engine.
     ///
     /// [`FwpmFilterAdd0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmfilteradd0
-    pub fn add<'a>(&self, transaction: &Transaction<'a>) -> io::Result<()> {
+    pub fn add<'a>(&self, transaction: &Transaction<'a>) -> Result<AddedFilter> {
+        if let Some(layer) = Layer::from_guid(&self.filter.layerKey) {
+            for condition in &self.conditions {
+                layer.check_condition(condition)?;
+            }
+        }
+
         // Convert conditions to FWPM_FILTER_CONDITION0 array
         let fwpm_conditions: Vec<FWPM_FILTER_CONDITION0> = self
             .conditions
@@ -332,20 +350,12 @@ impl FilterBuilder<FilterBuilderHasName, FilterBuilderHasAction> {
         // SAFETY:
         // - &filter is a valid pointer to a properly initialized FWPM_FILTER0 structure
         // - All pointers and data have the same lifetime as `self` (at least)
-        // - NULL security descriptor and filter ID pointers are acceptable
-        let status = unsafe {
-            FwpmFilterAdd0(
-                transaction.engine.as_raw_handle(),
-                &filter,
-                ptr::null_mut(),
-                ptr::null_mut(),
-            )
-        };
-        if status != ERROR_SUCCESS {
-            return Err(io::Error::from_raw_os_error(status as i32));
-        }
+        let (id, key) = unsafe { transaction.engine.backend().filter_add(&filter) }?;
 
-        Ok(())
+        Ok(AddedFilter {
+            id: FilterId(id),
+            key,
+        })
     }
 }
 
@@ -371,18 +381,134 @@ pub enum FilterLifetime {
     Persistent,
 }
 
+impl FilterLifetime {
+    /// Returns the lifetime that is set in `flags`.
+    pub(crate) fn from_flags(flags: FilterFlags) -> Self {
+        if flags.contains(FilterFlags::PERSISTENT) {
+            FilterLifetime::Persistent
+        } else if flags.contains(FilterFlags::BOOTTIME) {
+            FilterLifetime::Boottime
+        } else {
+            FilterLifetime::Default
+        }
+    }
+}
+
+flags_type! {
+    /// Flags of a filter.
+    ///
+    /// These correspond to the `FWPM_FILTER_FLAG_*` values of the `flags` field in the
+    /// underlying [`FWPM_FILTER0`] structure.
+    ///
+    /// [`FWPM_FILTER0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_filter0
+    pub struct FilterFlags {
+        /// The filter persists across reboots (`FWPM_FILTER_FLAG_PERSISTENT`).
+        const PERSISTENT = FWPM_FILTER_FLAG_PERSISTENT;
+        /// The filter is only active at boot time (`FWPM_FILTER_FLAG_BOOTTIME`).
+        const BOOTTIME = FWPM_FILTER_FLAG_BOOTTIME;
+        /// The filter references a provider context (`FWPM_FILTER_FLAG_HAS_PROVIDER_CONTEXT`).
+        const HAS_PROVIDER_CONTEXT = FWPM_FILTER_FLAG_HAS_PROVIDER_CONTEXT;
+        /// The filter clears the action right, so that lower-weight filters cannot override
+        /// its action (`FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT`).
+        const CLEAR_ACTION_RIGHT = FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT;
+        /// The filter permits traffic if its callout is not registered
+        /// (`FWPM_FILTER_FLAG_PERMIT_IF_CALLOUT_UNREGISTERED`).
+        const PERMIT_IF_CALLOUT_UNREGISTERED = FWPM_FILTER_FLAG_PERMIT_IF_CALLOUT_UNREGISTERED;
+        /// The filter is disabled (`FWPM_FILTER_FLAG_DISABLED`).
+        const DISABLED = FWPM_FILTER_FLAG_DISABLED;
+        /// The filter is indexed for faster lookup (`FWPM_FILTER_FLAG_INDEXED`).
+        const INDEXED = FWPM_FILTER_FLAG_INDEXED;
+        /// The filter references a security realm provider context
+        /// (`FWPM_FILTER_FLAG_HAS_SECURITY_REALM_PROVIDER_CONTEXT`).
+        const HAS_SECURITY_REALM_PROVIDER_CONTEXT =
+            FWPM_FILTER_FLAG_HAS_SECURITY_REALM_PROVIDER_CONTEXT;
+        /// The filter only applies to the system OS (`FWPM_FILTER_FLAG_SYSTEMOS_ONLY`).
+        const SYSTEMOS_ONLY = FWPM_FILTER_FLAG_SYSTEMOS_ONLY;
+        /// The filter only applies to the game OS (`FWPM_FILTER_FLAG_GAMEOS_ONLY`).
+        const GAMEOS_ONLY = FWPM_FILTER_FLAG_GAMEOS_ONLY;
+        /// Matches are not logged as network events (`FWPM_FILTER_FLAG_SILENT_MODE`).
+        const SILENT_MODE = FWPM_FILTER_FLAG_SILENT_MODE;
+        /// IPsec does not initiate an acquire for matching traffic
+        /// (`FWPM_FILTER_FLAG_IPSEC_NO_ACQUIRE_INITIATE`).
+        const IPSEC_NO_ACQUIRE_INITIATE = FWPM_FILTER_FLAG_IPSEC_NO_ACQUIRE_INITIATE;
+    }
+}
+
+/// The runtime ID of a filter.
+///
+/// This corresponds to the `filterId` field in the underlying [`FWPM_FILTER0`] structure.
+/// Unlike the filter key, the ID is assigned by the engine when the filter is added, and is
+/// only meaningful on the machine that the filter was added to.
+///
+/// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_filter0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FilterId(u64);
+
+impl FilterId {
+    /// Returns the raw ID.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for FilterId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<FilterId> for u64 {
+    fn from(id: FilterId) -> Self {
+        id.0
+    }
+}
+
+impl std::fmt::Display for FilterId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A filter that was added using [`FilterBuilder::add`].
+#[derive(Clone, Copy)]
+pub struct AddedFilter {
+    id: FilterId,
+    key: GUID,
+}
+
+impl std::fmt::Debug for AddedFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AddedFilter")
+            .field("id", &self.id)
+            .field("key", &GuidDisplay(&self.key))
+            .finish()
+    }
+}
+
+impl AddedFilter {
+    /// Return the runtime ID of the filter.
+    ///
+    /// This can be passed to [`delete_filter`].
+    pub fn id(&self) -> FilterId {
+        self.id
+    }
+
+    /// Return the filter key.
+    ///
+    /// This is the key set using [`FilterBuilder::guid`], or the key generated by the engine.
+    /// It can be passed to [`delete_filter_by_guid`].
+    pub fn key(&self) -> GUID {
+        self.key
+    }
+}
+
 /// Delete a filter by its ID.
 ///
 /// The ID corresponds to the `filterId` field in the underlying [`FWPM_FILTER0`] structure.
 ///
 /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_filter0
-pub fn delete_filter<'a>(transaction: &Transaction<'a>, id: u64) -> io::Result<()> {
-    // SAFETY: The handle and ID are valid
-    let status = unsafe { FwpmFilterDeleteById0(transaction.engine.as_raw_handle(), id) };
-    if status != STATUS_SUCCESS as u32 {
-        return Err(io::Error::from_raw_os_error(status as i32));
-    }
-    Ok(())
+pub fn delete_filter<'a>(transaction: &Transaction<'a>, id: FilterId) -> Result<()> {
+    transaction.engine.backend().filter_delete_by_id(id.0)
 }
 
 /// Delete a filter by its GUID.
@@ -390,11 +516,61 @@ pub fn delete_filter<'a>(transaction: &Transaction<'a>, id: u64) -> io::Result<(
 /// The GUID corresponds to the `filterKey` field in the underlying [`FWPM_FILTER0`] structure.
 ///
 /// [`FWPM_FILTER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_filter0
-pub fn delete_filter_by_guid<'a>(transaction: &Transaction<'a>, guid: &GUID) -> io::Result<()> {
-    // SAFETY: The handle and GUID are valid
-    let status = unsafe { FwpmFilterDeleteByKey0(transaction.engine.as_raw_handle(), guid) };
-    if status != STATUS_SUCCESS as u32 {
-        return Err(io::Error::from_raw_os_error(status as i32));
-    }
-    Ok(())
+pub fn delete_filter_by_guid<'a>(transaction: &Transaction<'a>, guid: &GUID) -> Result<()> {
+    transaction.engine.backend().filter_delete_by_key(guid)
+}
+
+/// Return an owned copy of the filter with the given ID.
+///
+/// This calls [`FwpmFilterGetById0`]. It returns
+/// [`WfpError::NotFound`](crate::WfpError::NotFound) if there is no such filter.
+/// `transaction` may be a [read-only](Transaction::read_only) transaction.
+///
+/// [`FwpmFilterGetById0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmfiltergetbyid0
+pub fn get_filter_by_id<Mode>(
+    transaction: &Transaction<'_, Mode>,
+    id: FilterId,
+) -> Result<FilterInfo> {
+    let filter = transaction.engine.backend().filter_get_by_id(id.0)?;
+    // SAFETY: The filter was returned by the backend, so its pointers are valid
+    unsafe { FilterInfo::from_raw(filter.get()) }
+}
+
+/// Return an owned copy of the filter with the given GUID.
+///
+/// This calls [`FwpmFilterGetByKey0`]. It returns
+/// [`WfpError::NotFound`](crate::WfpError::NotFound) if there is no such filter.
+/// `transaction` may be a [read-only](Transaction::read_only) transaction.
+///
+/// # Example
+///
+/// ```no_run
+/// use wfp::{FilterEngineBuilder, GUID, Transaction, get_filter_by_key};
+///
+/// # #[cfg(target_os = "windows")]
+/// fn main() -> wfp::Result<()> {
+///     let engine = FilterEngineBuilder::default().dynamic().open()?;
+///     let t = Transaction::read_only(&engine)?;
+///
+///     let guid = GUID::from_u128(0x12345678_1234_5678_9abc_def012345678);
+///     match get_filter_by_key(&t, &guid) {
+///         Ok(filter) => println!("Found {:?}", filter.name()),
+///         Err(error) if error.is_not_found() => println!("No such filter"),
+///         Err(error) => return Err(error),
+///     }
+///
+///     Ok(())
+/// }
+/// # #[cfg(not(target_os = "windows"))]
+/// # fn main() {}
+/// ```
+///
+/// [`FwpmFilterGetByKey0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmfiltergetbykey0
+pub fn get_filter_by_key<Mode>(
+    transaction: &Transaction<'_, Mode>,
+    guid: &GUID,
+) -> Result<FilterInfo> {
+    let filter = transaction.engine.backend().filter_get_by_key(guid)?;
+    // SAFETY: The filter was returned by the backend, so its pointers are valid
+    unsafe { FilterInfo::from_raw(filter.get()) }
 }
@@ -1,7 +1,46 @@
 //! Layers
 
+use std::fmt;
+
 use windows_sys::{Win32::NetworkManagement::WindowsFilteringPlatform::*, core::GUID};
 
+use crate::condition::{Condition, ConditionField, DataType};
+use crate::error::{ConditionError, Result, WfpError};
+use crate::transaction::Transaction;
+use crate::util::{GuidDisplay, display_string, flags_type, guid_eq};
+
+/// The IP version of the traffic at a layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IpFamily {
+    /// IPv4 traffic.
+    V4,
+    /// IPv6 traffic.
+    V6,
+}
+
+/// The direction of traffic.
+///
+/// These correspond to the [`FWP_DIRECTION`] enumeration values.
+///
+/// [`FWP_DIRECTION`]: https://learn.microsoft.com/en-us/windows/win32/api/fwptypes/ne-fwptypes-fwp_direction
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Outbound traffic.
+    Outbound = FWP_DIRECTION_OUTBOUND,
+    /// Inbound traffic.
+    Inbound = FWP_DIRECTION_INBOUND,
+}
+
+impl Direction {
+    /// Returns the direction that corresponds to a raw `FWP_DIRECTION` value, if any.
+    pub fn from_raw(raw: FWP_DIRECTION) -> Option<Self> {
+        [Self::Outbound, Self::Inbound]
+            .into_iter()
+            .find(|direction| *direction as i32 == raw)
+    }
+}
+
 /// Specifies the network layer at which a filter operates.
 ///
 /// Different layers provide different types of network information and
@@ -10,9 +49,277 @@ use windows_sys::{Win32::NetworkManagement::WindowsFilteringPlatform::*, core::G
 ///
 /// For more information about filtering layers, see the [WFP Layer Reference].
 ///
+/// Discard layers, e.g. [`Layer::ConnectV4Discard`], are where traffic that was blocked at the
+/// corresponding layer is classified again. They are mostly useful for auditing.
+///
 /// [WFP Layer Reference]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum Layer {
+    /// Incoming IPv4 packets before any IP header processing has occurred.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_IPPACKET_V4`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_IPPACKET_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundIpPacketV4,
+    /// Traffic that was discarded at the [`Layer::InboundIpPacketV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_IPPACKET_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_IPPACKET_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundIpPacketV4Discard,
+    /// Incoming IPv6 packets before any IP header processing has occurred.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_IPPACKET_V6`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_IPPACKET_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundIpPacketV6,
+    /// Traffic that was discarded at the [`Layer::InboundIpPacketV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_IPPACKET_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_IPPACKET_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundIpPacketV6Discard,
+    /// Outbound IPv4 packets just before fragmentation.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_IPPACKET_V4`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_IPPACKET_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundIpPacketV4,
+    /// Traffic that was discarded at the [`Layer::OutboundIpPacketV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_IPPACKET_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_IPPACKET_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundIpPacketV4Discard,
+    /// Outbound IPv6 packets just before fragmentation.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_IPPACKET_V6`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_IPPACKET_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundIpPacketV6,
+    /// Traffic that was discarded at the [`Layer::OutboundIpPacketV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_IPPACKET_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_IPPACKET_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundIpPacketV6Discard,
+    /// IPv4 packets that are being forwarded.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IPFORWARD_V4`].
+    ///
+    /// [`FWPM_LAYER_IPFORWARD_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IpForwardV4,
+    /// Traffic that was discarded at the [`Layer::IpForwardV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IPFORWARD_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_IPFORWARD_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IpForwardV4Discard,
+    /// IPv6 packets that are being forwarded.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IPFORWARD_V6`].
+    ///
+    /// [`FWPM_LAYER_IPFORWARD_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IpForwardV6,
+    /// Traffic that was discarded at the [`Layer::IpForwardV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IPFORWARD_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_IPFORWARD_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IpForwardV6Discard,
+    /// Incoming IPv4 packets before transport layer processing.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_TRANSPORT_V4`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_TRANSPORT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundTransportV4,
+    /// Traffic that was discarded at the [`Layer::InboundTransportV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_TRANSPORT_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_TRANSPORT_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundTransportV4Discard,
+    /// Incoming IPv6 packets before transport layer processing.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_TRANSPORT_V6`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_TRANSPORT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundTransportV6,
+    /// Traffic that was discarded at the [`Layer::InboundTransportV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_TRANSPORT_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_TRANSPORT_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundTransportV6Discard,
+    /// Outbound IPv4 packets before any network layer processing.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_TRANSPORT_V4`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_TRANSPORT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundTransportV4,
+    /// Traffic that was discarded at the [`Layer::OutboundTransportV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_TRANSPORT_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_TRANSPORT_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundTransportV4Discard,
+    /// Outbound IPv6 packets before any network layer processing.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_TRANSPORT_V6`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_TRANSPORT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundTransportV6,
+    /// Traffic that was discarded at the [`Layer::OutboundTransportV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_TRANSPORT_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_TRANSPORT_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundTransportV6Discard,
+    /// TCP data streams over IPv4.
+    ///
+    /// Corresponds to [`FWPM_LAYER_STREAM_V4`].
+    ///
+    /// [`FWPM_LAYER_STREAM_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    StreamV4,
+    /// Traffic that was discarded at the [`Layer::StreamV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_STREAM_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_STREAM_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    StreamV4Discard,
+    /// TCP data streams over IPv6.
+    ///
+    /// Corresponds to [`FWPM_LAYER_STREAM_V6`].
+    ///
+    /// [`FWPM_LAYER_STREAM_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    StreamV6,
+    /// Traffic that was discarded at the [`Layer::StreamV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_STREAM_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_STREAM_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    StreamV6Discard,
+    /// Datagrams over IPv4, such as UDP, at the transport layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_DATAGRAM_DATA_V4`].
+    ///
+    /// [`FWPM_LAYER_DATAGRAM_DATA_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    DatagramDataV4,
+    /// Traffic that was discarded at the [`Layer::DatagramDataV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_DATAGRAM_DATA_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_DATAGRAM_DATA_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    DatagramDataV4Discard,
+    /// Datagrams over IPv6, such as UDP, at the transport layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_DATAGRAM_DATA_V6`].
+    ///
+    /// [`FWPM_LAYER_DATAGRAM_DATA_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    DatagramDataV6,
+    /// Traffic that was discarded at the [`Layer::DatagramDataV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_DATAGRAM_DATA_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_DATAGRAM_DATA_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    DatagramDataV6Discard,
+    /// Incoming ICMP error messages over IPv4.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_ICMP_ERROR_V4`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_ICMP_ERROR_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundIcmpErrorV4,
+    /// Traffic that was discarded at the [`Layer::InboundIcmpErrorV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_ICMP_ERROR_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_ICMP_ERROR_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundIcmpErrorV4Discard,
+    /// Incoming ICMP error messages over IPv6.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_ICMP_ERROR_V6`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_ICMP_ERROR_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundIcmpErrorV6,
+    /// Traffic that was discarded at the [`Layer::InboundIcmpErrorV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_ICMP_ERROR_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_ICMP_ERROR_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundIcmpErrorV6Discard,
+    /// Outbound ICMP error messages over IPv4.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundIcmpErrorV4,
+    /// Traffic that was discarded at the [`Layer::OutboundIcmpErrorV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundIcmpErrorV4Discard,
+    /// Outbound ICMP error messages over IPv6.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundIcmpErrorV6,
+    /// Traffic that was discarded at the [`Layer::OutboundIcmpErrorV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundIcmpErrorV6Discard,
+    /// Used for authorizing the assignment of local IPv4 ports and sockets, e.g. when binding.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4`].
+    ///
+    /// [`FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ResourceAssignmentV4,
+    /// Traffic that was discarded at the [`Layer::ResourceAssignmentV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ResourceAssignmentV4Discard,
+    /// Used for authorizing the assignment of local IPv6 ports and sockets, e.g. when binding.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6`].
+    ///
+    /// [`FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ResourceAssignmentV6,
+    /// Traffic that was discarded at the [`Layer::ResourceAssignmentV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ResourceAssignmentV6Discard,
+    /// Used for authorizing TCP listen requests on IPv4.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_AUTH_LISTEN_V4`].
+    ///
+    /// [`FWPM_LAYER_ALE_AUTH_LISTEN_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ListenV4,
+    /// Traffic that was discarded at the [`Layer::ListenV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_AUTH_LISTEN_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_AUTH_LISTEN_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ListenV4Discard,
+    /// Used for authorizing TCP listen requests on IPv6.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_AUTH_LISTEN_V6`].
+    ///
+    /// [`FWPM_LAYER_ALE_AUTH_LISTEN_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ListenV6,
+    /// Traffic that was discarded at the [`Layer::ListenV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_AUTH_LISTEN_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_AUTH_LISTEN_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ListenV6Discard,
     /// Used for authorizing accept requests for incoming TCP IPv4 connections, as well as incoming
     /// non-TCP traffic based on the first packed received.
     ///
@@ -20,6 +327,12 @@ pub enum Layer {
     ///
     /// [`FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
     AcceptV4,
+    /// Traffic that was discarded at the [`Layer::AcceptV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    AcceptV4Discard,
     /// Used for authorizing accept requests for incoming TCP IPv6 connections, as well as incoming
     /// non-TCP traffic based on the first packed received.
     ///
@@ -27,6 +340,12 @@ pub enum Layer {
     ///
     /// [`FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
     AcceptV6,
+    /// Traffic that was discarded at the [`Layer::AcceptV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    AcceptV6Discard,
     /// Used for authorizing accept requests for outgoing TCP IPv4 connections, as well as outgoing
     /// non-TCP traffic based on the first packed received.
     ///
@@ -34,6 +353,12 @@ pub enum Layer {
     ///
     /// [`FWPM_LAYER_ALE_AUTH_CONNECT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
     ConnectV4,
+    /// Traffic that was discarded at the [`Layer::ConnectV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_AUTH_CONNECT_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_AUTH_CONNECT_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ConnectV4Discard,
     /// Used for authorizing accept requests for outgoing TCP IPv6 connections, as well as outgoing
     /// non-TCP traffic based on the first packed received.
     ///
@@ -41,88 +366,1546 @@ pub enum Layer {
     ///
     /// [`FWPM_LAYER_ALE_AUTH_CONNECT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
     ConnectV6,
+    /// Traffic that was discarded at the [`Layer::ConnectV6`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_AUTH_CONNECT_V6_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_AUTH_CONNECT_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ConnectV6Discard,
     /// Filters at this layer can inspect an IPv4 connection that has been authorized.
     ///
     /// Corresponds to [`FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4`].
     ///
     /// [`FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
     FlowEstablishedV4,
+    /// Traffic that was discarded at the [`Layer::FlowEstablishedV4`] layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4_DISCARD`].
+    ///
+    /// [`FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    FlowEstablishedV4Discard,
     /// Filters at this layer can inspect an IPv6 connection that has been authorized.
     ///
     /// Corresponds to [`FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6`].
     ///
     /// [`FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
     FlowEstablishedV6,
-    /// Incoming IPv4 packets before any IP header processing has occurred.
+    /// Traffic that was discarded at the [`Layer::FlowEstablishedV6`] layer.
     ///
-    /// Corresponds to [`FWPM_LAYER_INBOUND_IPPACKET_V4`].
+    /// Corresponds to [`FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6_DISCARD`].
     ///
-    /// [`FWPM_LAYER_INBOUND_IPPACKET_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-    InboundIpPacketV4,
-    /// Incoming IPv6 packets before any IP header processing has occurred.
+    /// [`FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6_DISCARD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    FlowEstablishedV6Discard,
+    /// Notified when an IPv4 port or socket is released.
     ///
-    /// Corresponds to [`FWPM_LAYER_INBOUND_IPPACKET_V6`].
+    /// Corresponds to [`FWPM_LAYER_ALE_RESOURCE_RELEASE_V4`].
     ///
-    /// [`FWPM_LAYER_INBOUND_IPPACKET_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-    InboundIpPacketV6,
-    /// Outbound IPv4 packets just before fragmentation.
+    /// [`FWPM_LAYER_ALE_RESOURCE_RELEASE_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ResourceReleaseV4,
+    /// Notified when an IPv6 port or socket is released.
     ///
-    /// Corresponds to [`FWPM_LAYER_OUTBOUND_IPPACKET_V4`].
+    /// Corresponds to [`FWPM_LAYER_ALE_RESOURCE_RELEASE_V6`].
     ///
-    /// [`FWPM_LAYER_OUTBOUND_IPPACKET_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-    OutboundIpPacketV4,
-    /// Outbound IPv6 packets just before fragmentation.
+    /// [`FWPM_LAYER_ALE_RESOURCE_RELEASE_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ResourceReleaseV6,
+    /// Notified when an IPv4 endpoint is closed.
     ///
-    /// Corresponds to [`FWPM_LAYER_OUTBOUND_IPPACKET_V6`].
+    /// Corresponds to [`FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V4`].
     ///
-    /// [`FWPM_LAYER_OUTBOUND_IPPACKET_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-    OutboundIpPacketV6,
-    /// Incoming IPv4 packets before transport layer processing.
+    /// [`FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    EndpointClosureV4,
+    /// Notified when an IPv6 endpoint is closed.
     ///
-    /// Corresponds to [`FWPM_LAYER_INBOUND_TRANSPORT_V4`].
+    /// Corresponds to [`FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V6`].
     ///
-    /// [`FWPM_LAYER_INBOUND_TRANSPORT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-    InboundTransportV4,
-    /// Incoming IPv6 packets before transport layer processing.
+    /// [`FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    EndpointClosureV6,
+    /// Used for redirecting outbound IPv4 connections.
     ///
-    /// Corresponds to [`FWPM_LAYER_INBOUND_TRANSPORT_V6`].
+    /// Corresponds to [`FWPM_LAYER_ALE_CONNECT_REDIRECT_V4`].
     ///
-    /// [`FWPM_LAYER_INBOUND_TRANSPORT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-    InboundTransportV6,
-    /// Outbound IPv4 packets before any network layer processing.
+    /// [`FWPM_LAYER_ALE_CONNECT_REDIRECT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ConnectRedirectV4,
+    /// Used for redirecting outbound IPv6 connections.
     ///
-    /// Corresponds to [`FWPM_LAYER_OUTBOUND_TRANSPORT_V4`].
+    /// Corresponds to [`FWPM_LAYER_ALE_CONNECT_REDIRECT_V6`].
     ///
-    /// [`FWPM_LAYER_OUTBOUND_TRANSPORT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-    OutboundTransportV4,
-    /// Outbound IPv6 packets before any network layer processing.
+    /// [`FWPM_LAYER_ALE_CONNECT_REDIRECT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    ConnectRedirectV6,
+    /// Used for redirecting local IPv4 bind requests.
     ///
-    /// Corresponds to [`FWPM_LAYER_OUTBOUND_TRANSPORT_V6`].
+    /// Corresponds to [`FWPM_LAYER_ALE_BIND_REDIRECT_V4`].
     ///
-    /// [`FWPM_LAYER_OUTBOUND_TRANSPORT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
-    OutboundTransportV6,
+    /// [`FWPM_LAYER_ALE_BIND_REDIRECT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    BindRedirectV4,
+    /// Used for redirecting local IPv6 bind requests.
+    ///
+    /// Corresponds to [`FWPM_LAYER_ALE_BIND_REDIRECT_V6`].
+    ///
+    /// [`FWPM_LAYER_ALE_BIND_REDIRECT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    BindRedirectV6,
+    /// TCP packets over IPv4 at the stream layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_STREAM_PACKET_V4`].
+    ///
+    /// [`FWPM_LAYER_STREAM_PACKET_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    StreamPacketV4,
+    /// TCP packets over IPv6 at the stream layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_STREAM_PACKET_V6`].
+    ///
+    /// [`FWPM_LAYER_STREAM_PACKET_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    StreamPacketV6,
+    /// Name resolution cache entries for IPv4 addresses.
+    ///
+    /// Corresponds to [`FWPM_LAYER_NAME_RESOLUTION_CACHE_V4`].
+    ///
+    /// [`FWPM_LAYER_NAME_RESOLUTION_CACHE_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    NameResolutionCacheV4,
+    /// Name resolution cache entries for IPv6 addresses.
+    ///
+    /// Corresponds to [`FWPM_LAYER_NAME_RESOLUTION_CACHE_V6`].
+    ///
+    /// [`FWPM_LAYER_NAME_RESOLUTION_CACHE_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    NameResolutionCacheV6,
+    /// Used for applying network connection policies to outbound IPv4 connections.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V4`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundNetworkConnectionPolicyV4,
+    /// Used for applying network connection policies to outbound IPv6 connections.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V6`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundNetworkConnectionPolicyV6,
+    /// Used for IKE and AuthIP policy for IPv4. Only IPsec policy providers use this layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IKEEXT_V4`].
+    ///
+    /// [`FWPM_LAYER_IKEEXT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IkeExtV4,
+    /// Used for IKE and AuthIP policy for IPv6. Only IPsec policy providers use this layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IKEEXT_V6`].
+    ///
+    /// [`FWPM_LAYER_IKEEXT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IkeExtV6,
+    /// Used for selecting the IPsec keying module for IPv4. Only IPsec policy providers use this
+    /// layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IPSEC_KM_DEMUX_V4`].
+    ///
+    /// [`FWPM_LAYER_IPSEC_KM_DEMUX_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IpsecKmDemuxV4,
+    /// Used for selecting the IPsec keying module for IPv6. Only IPsec policy providers use this
+    /// layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IPSEC_KM_DEMUX_V6`].
+    ///
+    /// [`FWPM_LAYER_IPSEC_KM_DEMUX_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IpsecKmDemuxV6,
+    /// Used for IPsec policy for IPv4. Only IPsec policy providers use this layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IPSEC_V4`].
+    ///
+    /// [`FWPM_LAYER_IPSEC_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IpsecV4,
+    /// Used for IPsec policy for IPv6. Only IPsec policy providers use this layer.
+    ///
+    /// Corresponds to [`FWPM_LAYER_IPSEC_V6`].
+    ///
+    /// [`FWPM_LAYER_IPSEC_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IpsecV6,
+    /// Incoming Ethernet frames (802.3), before any MAC layer processing.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_MAC_FRAME_ETHERNET`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_MAC_FRAME_ETHERNET`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundMacFrameEthernet,
+    /// Outbound Ethernet frames (802.3), after MAC layer processing.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_MAC_FRAME_ETHERNET`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_MAC_FRAME_ETHERNET`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundMacFrameEthernet,
+    /// Incoming frames in the native format of the interface, before any MAC layer processing.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundMacFrameNative,
+    /// Outbound frames in the native format of the interface, after MAC layer processing.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundMacFrameNative,
+    /// Fast path for incoming frames in the native format. Reserved for system use.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE_FAST`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE_FAST`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundMacFrameNativeFast,
+    /// Fast path for outbound frames in the native format. Reserved for system use.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE_FAST`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE_FAST`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundMacFrameNativeFast,
+    /// Fast path for incoming packets at the transport layer. Reserved for system use.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_TRANSPORT_FAST`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_TRANSPORT_FAST`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundTransportFast,
+    /// Fast path for outbound packets at the transport layer. Reserved for system use.
+    ///
+    /// Corresponds to [`FWPM_LAYER_OUTBOUND_TRANSPORT_FAST`].
+    ///
+    /// [`FWPM_LAYER_OUTBOUND_TRANSPORT_FAST`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    OutboundTransportFast,
+    /// Reserved for system use.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INBOUND_RESERVED2`].
+    ///
+    /// [`FWPM_LAYER_INBOUND_RESERVED2`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    InboundReserved2,
+    /// Ethernet frames entering a Hyper-V virtual switch.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INGRESS_VSWITCH_ETHERNET`].
+    ///
+    /// [`FWPM_LAYER_INGRESS_VSWITCH_ETHERNET`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IngressVSwitchEthernet,
+    /// Ethernet frames leaving a Hyper-V virtual switch.
+    ///
+    /// Corresponds to [`FWPM_LAYER_EGRESS_VSWITCH_ETHERNET`].
+    ///
+    /// [`FWPM_LAYER_EGRESS_VSWITCH_ETHERNET`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    EgressVSwitchEthernet,
+    /// IPv4 traffic entering a Hyper-V virtual switch.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V4`].
+    ///
+    /// [`FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IngressVSwitchTransportV4,
+    /// IPv6 traffic entering a Hyper-V virtual switch.
+    ///
+    /// Corresponds to [`FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V6`].
+    ///
+    /// [`FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    IngressVSwitchTransportV6,
+    /// IPv4 traffic leaving a Hyper-V virtual switch.
+    ///
+    /// Corresponds to [`FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V4`].
+    ///
+    /// [`FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V4`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    EgressVSwitchTransportV4,
+    /// IPv6 traffic leaving a Hyper-V virtual switch.
+    ///
+    /// Corresponds to [`FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V6`].
+    ///
+    /// [`FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V6`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    EgressVSwitchTransportV6,
+    /// Used for authorizing keying module negotiations.
+    ///
+    /// Corresponds to [`FWPM_LAYER_KM_AUTHORIZATION`].
+    ///
+    /// [`FWPM_LAYER_KM_AUTHORIZATION`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    KmAuthorization,
+    /// Used for filtering RPC interfaces in user mode.
+    ///
+    /// Corresponds to [`FWPM_LAYER_RPC_UM`].
+    ///
+    /// [`FWPM_LAYER_RPC_UM`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    RpcUm,
+    /// Used for filtering requests to the RPC endpoint mapper.
+    ///
+    /// Corresponds to [`FWPM_LAYER_RPC_EPMAP`].
+    ///
+    /// [`FWPM_LAYER_RPC_EPMAP`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    RpcEpMap,
+    /// Used for filtering the registration of RPC endpoints.
+    ///
+    /// Corresponds to [`FWPM_LAYER_RPC_EP_ADD`].
+    ///
+    /// [`FWPM_LAYER_RPC_EP_ADD`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    RpcEpAdd,
+    /// Used for filtering connections made through an RPC proxy.
+    ///
+    /// Corresponds to [`FWPM_LAYER_RPC_PROXY_CONN`].
+    ///
+    /// [`FWPM_LAYER_RPC_PROXY_CONN`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    RpcProxyConn,
+    /// Used for filtering RPC proxy interfaces.
+    ///
+    /// Corresponds to [`FWPM_LAYER_RPC_PROXY_IF`].
+    ///
+    /// [`FWPM_LAYER_RPC_PROXY_IF`]: https://docs.microsoft.com/en-us/windows/win32/fwp/management-filtering-layer-identifiers-
+    RpcProxyIf,
 }
 
 impl Layer {
+    /// Every built-in layer.
+    pub(crate) const ALL: &[Layer] = &[
+        Self::InboundIpPacketV4,
+        Self::InboundIpPacketV4Discard,
+        Self::InboundIpPacketV6,
+        Self::InboundIpPacketV6Discard,
+        Self::OutboundIpPacketV4,
+        Self::OutboundIpPacketV4Discard,
+        Self::OutboundIpPacketV6,
+        Self::OutboundIpPacketV6Discard,
+        Self::IpForwardV4,
+        Self::IpForwardV4Discard,
+        Self::IpForwardV6,
+        Self::IpForwardV6Discard,
+        Self::InboundTransportV4,
+        Self::InboundTransportV4Discard,
+        Self::InboundTransportV6,
+        Self::InboundTransportV6Discard,
+        Self::OutboundTransportV4,
+        Self::OutboundTransportV4Discard,
+        Self::OutboundTransportV6,
+        Self::OutboundTransportV6Discard,
+        Self::StreamV4,
+        Self::StreamV4Discard,
+        Self::StreamV6,
+        Self::StreamV6Discard,
+        Self::DatagramDataV4,
+        Self::DatagramDataV4Discard,
+        Self::DatagramDataV6,
+        Self::DatagramDataV6Discard,
+        Self::InboundIcmpErrorV4,
+        Self::InboundIcmpErrorV4Discard,
+        Self::InboundIcmpErrorV6,
+        Self::InboundIcmpErrorV6Discard,
+        Self::OutboundIcmpErrorV4,
+        Self::OutboundIcmpErrorV4Discard,
+        Self::OutboundIcmpErrorV6,
+        Self::OutboundIcmpErrorV6Discard,
+        Self::ResourceAssignmentV4,
+        Self::ResourceAssignmentV4Discard,
+        Self::ResourceAssignmentV6,
+        Self::ResourceAssignmentV6Discard,
+        Self::ListenV4,
+        Self::ListenV4Discard,
+        Self::ListenV6,
+        Self::ListenV6Discard,
+        Self::AcceptV4,
+        Self::AcceptV4Discard,
+        Self::AcceptV6,
+        Self::AcceptV6Discard,
+        Self::ConnectV4,
+        Self::ConnectV4Discard,
+        Self::ConnectV6,
+        Self::ConnectV6Discard,
+        Self::FlowEstablishedV4,
+        Self::FlowEstablishedV4Discard,
+        Self::FlowEstablishedV6,
+        Self::FlowEstablishedV6Discard,
+        Self::ResourceReleaseV4,
+        Self::ResourceReleaseV6,
+        Self::EndpointClosureV4,
+        Self::EndpointClosureV6,
+        Self::ConnectRedirectV4,
+        Self::ConnectRedirectV6,
+        Self::BindRedirectV4,
+        Self::BindRedirectV6,
+        Self::StreamPacketV4,
+        Self::StreamPacketV6,
+        Self::NameResolutionCacheV4,
+        Self::NameResolutionCacheV6,
+        Self::OutboundNetworkConnectionPolicyV4,
+        Self::OutboundNetworkConnectionPolicyV6,
+        Self::IkeExtV4,
+        Self::IkeExtV6,
+        Self::IpsecKmDemuxV4,
+        Self::IpsecKmDemuxV6,
+        Self::IpsecV4,
+        Self::IpsecV6,
+        Self::InboundMacFrameEthernet,
+        Self::OutboundMacFrameEthernet,
+        Self::InboundMacFrameNative,
+        Self::OutboundMacFrameNative,
+        Self::InboundMacFrameNativeFast,
+        Self::OutboundMacFrameNativeFast,
+        Self::InboundTransportFast,
+        Self::OutboundTransportFast,
+        Self::InboundReserved2,
+        Self::IngressVSwitchEthernet,
+        Self::EgressVSwitchEthernet,
+        Self::IngressVSwitchTransportV4,
+        Self::IngressVSwitchTransportV6,
+        Self::EgressVSwitchTransportV4,
+        Self::EgressVSwitchTransportV6,
+        Self::KmAuthorization,
+        Self::RpcUm,
+        Self::RpcEpMap,
+        Self::RpcEpAdd,
+        Self::RpcProxyConn,
+        Self::RpcProxyIf,
+    ];
+
     /// Returns the Windows GUID identifier for this layer.
     ///
     /// This is used internally when communicating with the Windows Filtering Platform API.
     pub fn guid(&self) -> &GUID {
         match self {
-            Self::AcceptV4 => &FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4,
-            Self::AcceptV6 => &FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6,
-            Self::ConnectV4 => &FWPM_LAYER_ALE_AUTH_CONNECT_V4,
-            Self::ConnectV6 => &FWPM_LAYER_ALE_AUTH_CONNECT_V6,
-            Self::FlowEstablishedV4 => &FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4,
-            Self::FlowEstablishedV6 => &FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6,
             Self::InboundIpPacketV4 => &FWPM_LAYER_INBOUND_IPPACKET_V4,
+            Self::InboundIpPacketV4Discard => &FWPM_LAYER_INBOUND_IPPACKET_V4_DISCARD,
             Self::InboundIpPacketV6 => &FWPM_LAYER_INBOUND_IPPACKET_V6,
+            Self::InboundIpPacketV6Discard => &FWPM_LAYER_INBOUND_IPPACKET_V6_DISCARD,
             Self::OutboundIpPacketV4 => &FWPM_LAYER_OUTBOUND_IPPACKET_V4,
+            Self::OutboundIpPacketV4Discard => &FWPM_LAYER_OUTBOUND_IPPACKET_V4_DISCARD,
             Self::OutboundIpPacketV6 => &FWPM_LAYER_OUTBOUND_IPPACKET_V6,
+            Self::OutboundIpPacketV6Discard => &FWPM_LAYER_OUTBOUND_IPPACKET_V6_DISCARD,
+            Self::IpForwardV4 => &FWPM_LAYER_IPFORWARD_V4,
+            Self::IpForwardV4Discard => &FWPM_LAYER_IPFORWARD_V4_DISCARD,
+            Self::IpForwardV6 => &FWPM_LAYER_IPFORWARD_V6,
+            Self::IpForwardV6Discard => &FWPM_LAYER_IPFORWARD_V6_DISCARD,
             Self::InboundTransportV4 => &FWPM_LAYER_INBOUND_TRANSPORT_V4,
+            Self::InboundTransportV4Discard => &FWPM_LAYER_INBOUND_TRANSPORT_V4_DISCARD,
             Self::InboundTransportV6 => &FWPM_LAYER_INBOUND_TRANSPORT_V6,
+            Self::InboundTransportV6Discard => &FWPM_LAYER_INBOUND_TRANSPORT_V6_DISCARD,
             Self::OutboundTransportV4 => &FWPM_LAYER_OUTBOUND_TRANSPORT_V4,
+            Self::OutboundTransportV4Discard => &FWPM_LAYER_OUTBOUND_TRANSPORT_V4_DISCARD,
             Self::OutboundTransportV6 => &FWPM_LAYER_OUTBOUND_TRANSPORT_V6,
+            Self::OutboundTransportV6Discard => &FWPM_LAYER_OUTBOUND_TRANSPORT_V6_DISCARD,
+            Self::StreamV4 => &FWPM_LAYER_STREAM_V4,
+            Self::StreamV4Discard => &FWPM_LAYER_STREAM_V4_DISCARD,
+            Self::StreamV6 => &FWPM_LAYER_STREAM_V6,
+            Self::StreamV6Discard => &FWPM_LAYER_STREAM_V6_DISCARD,
+            Self::DatagramDataV4 => &FWPM_LAYER_DATAGRAM_DATA_V4,
+            Self::DatagramDataV4Discard => &FWPM_LAYER_DATAGRAM_DATA_V4_DISCARD,
+            Self::DatagramDataV6 => &FWPM_LAYER_DATAGRAM_DATA_V6,
+            Self::DatagramDataV6Discard => &FWPM_LAYER_DATAGRAM_DATA_V6_DISCARD,
+            Self::InboundIcmpErrorV4 => &FWPM_LAYER_INBOUND_ICMP_ERROR_V4,
+            Self::InboundIcmpErrorV4Discard => &FWPM_LAYER_INBOUND_ICMP_ERROR_V4_DISCARD,
+            Self::InboundIcmpErrorV6 => &FWPM_LAYER_INBOUND_ICMP_ERROR_V6,
+            Self::InboundIcmpErrorV6Discard => &FWPM_LAYER_INBOUND_ICMP_ERROR_V6_DISCARD,
+            Self::OutboundIcmpErrorV4 => &FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4,
+            Self::OutboundIcmpErrorV4Discard => &FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4_DISCARD,
+            Self::OutboundIcmpErrorV6 => &FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6,
+            Self::OutboundIcmpErrorV6Discard => &FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6_DISCARD,
+            Self::ResourceAssignmentV4 => &FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4,
+            Self::ResourceAssignmentV4Discard => &FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4_DISCARD,
+            Self::ResourceAssignmentV6 => &FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6,
+            Self::ResourceAssignmentV6Discard => &FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6_DISCARD,
+            Self::ListenV4 => &FWPM_LAYER_ALE_AUTH_LISTEN_V4,
+            Self::ListenV4Discard => &FWPM_LAYER_ALE_AUTH_LISTEN_V4_DISCARD,
+            Self::ListenV6 => &FWPM_LAYER_ALE_AUTH_LISTEN_V6,
+            Self::ListenV6Discard => &FWPM_LAYER_ALE_AUTH_LISTEN_V6_DISCARD,
+            Self::AcceptV4 => &FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4,
+            Self::AcceptV4Discard => &FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4_DISCARD,
+            Self::AcceptV6 => &FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6,
+            Self::AcceptV6Discard => &FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6_DISCARD,
+            Self::ConnectV4 => &FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+            Self::ConnectV4Discard => &FWPM_LAYER_ALE_AUTH_CONNECT_V4_DISCARD,
+            Self::ConnectV6 => &FWPM_LAYER_ALE_AUTH_CONNECT_V6,
+            Self::ConnectV6Discard => &FWPM_LAYER_ALE_AUTH_CONNECT_V6_DISCARD,
+            Self::FlowEstablishedV4 => &FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4,
+            Self::FlowEstablishedV4Discard => &FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4_DISCARD,
+            Self::FlowEstablishedV6 => &FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6,
+            Self::FlowEstablishedV6Discard => &FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6_DISCARD,
+            Self::ResourceReleaseV4 => &FWPM_LAYER_ALE_RESOURCE_RELEASE_V4,
+            Self::ResourceReleaseV6 => &FWPM_LAYER_ALE_RESOURCE_RELEASE_V6,
+            Self::EndpointClosureV4 => &FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V4,
+            Self::EndpointClosureV6 => &FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V6,
+            Self::ConnectRedirectV4 => &FWPM_LAYER_ALE_CONNECT_REDIRECT_V4,
+            Self::ConnectRedirectV6 => &FWPM_LAYER_ALE_CONNECT_REDIRECT_V6,
+            Self::BindRedirectV4 => &FWPM_LAYER_ALE_BIND_REDIRECT_V4,
+            Self::BindRedirectV6 => &FWPM_LAYER_ALE_BIND_REDIRECT_V6,
+            Self::StreamPacketV4 => &FWPM_LAYER_STREAM_PACKET_V4,
+            Self::StreamPacketV6 => &FWPM_LAYER_STREAM_PACKET_V6,
+            Self::NameResolutionCacheV4 => &FWPM_LAYER_NAME_RESOLUTION_CACHE_V4,
+            Self::NameResolutionCacheV6 => &FWPM_LAYER_NAME_RESOLUTION_CACHE_V6,
+            Self::OutboundNetworkConnectionPolicyV4 => {
+                &FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V4
+            }
+            Self::OutboundNetworkConnectionPolicyV6 => {
+                &FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V6
+            }
+            Self::IkeExtV4 => &FWPM_LAYER_IKEEXT_V4,
+            Self::IkeExtV6 => &FWPM_LAYER_IKEEXT_V6,
+            Self::IpsecKmDemuxV4 => &FWPM_LAYER_IPSEC_KM_DEMUX_V4,
+            Self::IpsecKmDemuxV6 => &FWPM_LAYER_IPSEC_KM_DEMUX_V6,
+            Self::IpsecV4 => &FWPM_LAYER_IPSEC_V4,
+            Self::IpsecV6 => &FWPM_LAYER_IPSEC_V6,
+            Self::InboundMacFrameEthernet => &FWPM_LAYER_INBOUND_MAC_FRAME_ETHERNET,
+            Self::OutboundMacFrameEthernet => &FWPM_LAYER_OUTBOUND_MAC_FRAME_ETHERNET,
+            Self::InboundMacFrameNative => &FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE,
+            Self::OutboundMacFrameNative => &FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE,
+            Self::InboundMacFrameNativeFast => &FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE_FAST,
+            Self::OutboundMacFrameNativeFast => &FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE_FAST,
+            Self::InboundTransportFast => &FWPM_LAYER_INBOUND_TRANSPORT_FAST,
+            Self::OutboundTransportFast => &FWPM_LAYER_OUTBOUND_TRANSPORT_FAST,
+            Self::InboundReserved2 => &FWPM_LAYER_INBOUND_RESERVED2,
+            Self::IngressVSwitchEthernet => &FWPM_LAYER_INGRESS_VSWITCH_ETHERNET,
+            Self::EgressVSwitchEthernet => &FWPM_LAYER_EGRESS_VSWITCH_ETHERNET,
+            Self::IngressVSwitchTransportV4 => &FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V4,
+            Self::IngressVSwitchTransportV6 => &FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V6,
+            Self::EgressVSwitchTransportV4 => &FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V4,
+            Self::EgressVSwitchTransportV6 => &FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V6,
+            Self::KmAuthorization => &FWPM_LAYER_KM_AUTHORIZATION,
+            Self::RpcUm => &FWPM_LAYER_RPC_UM,
+            Self::RpcEpMap => &FWPM_LAYER_RPC_EPMAP,
+            Self::RpcEpAdd => &FWPM_LAYER_RPC_EP_ADD,
+            Self::RpcProxyConn => &FWPM_LAYER_RPC_PROXY_CONN,
+            Self::RpcProxyIf => &FWPM_LAYER_RPC_PROXY_IF,
+        }
+    }
+
+    /// Returns the IP version of the traffic at this layer.
+    ///
+    /// This is `None` for layers that are not specific to an IP version, such as the MAC frame
+    /// and RPC layers.
+    pub fn ip_family(&self) -> Option<IpFamily> {
+        use Layer::*;
+        match self {
+            InboundIpPacketV4
+            | InboundIpPacketV4Discard
+            | OutboundIpPacketV4
+            | OutboundIpPacketV4Discard
+            | IpForwardV4
+            | IpForwardV4Discard
+            | InboundTransportV4
+            | InboundTransportV4Discard
+            | OutboundTransportV4
+            | OutboundTransportV4Discard
+            | StreamV4
+            | StreamV4Discard
+            | DatagramDataV4
+            | DatagramDataV4Discard
+            | InboundIcmpErrorV4
+            | InboundIcmpErrorV4Discard
+            | OutboundIcmpErrorV4
+            | OutboundIcmpErrorV4Discard
+            | ResourceAssignmentV4
+            | ResourceAssignmentV4Discard
+            | ListenV4
+            | ListenV4Discard
+            | AcceptV4
+            | AcceptV4Discard
+            | ConnectV4
+            | ConnectV4Discard
+            | FlowEstablishedV4
+            | FlowEstablishedV4Discard
+            | ResourceReleaseV4
+            | EndpointClosureV4
+            | ConnectRedirectV4
+            | BindRedirectV4
+            | StreamPacketV4
+            | NameResolutionCacheV4
+            | OutboundNetworkConnectionPolicyV4
+            | IkeExtV4
+            | IpsecKmDemuxV4
+            | IpsecV4
+            | IngressVSwitchTransportV4
+            | EgressVSwitchTransportV4 => Some(IpFamily::V4),
+            InboundIpPacketV6
+            | InboundIpPacketV6Discard
+            | OutboundIpPacketV6
+            | OutboundIpPacketV6Discard
+            | IpForwardV6
+            | IpForwardV6Discard
+            | InboundTransportV6
+            | InboundTransportV6Discard
+            | OutboundTransportV6
+            | OutboundTransportV6Discard
+            | StreamV6
+            | StreamV6Discard
+            | DatagramDataV6
+            | DatagramDataV6Discard
+            | InboundIcmpErrorV6
+            | InboundIcmpErrorV6Discard
+            | OutboundIcmpErrorV6
+            | OutboundIcmpErrorV6Discard
+            | ResourceAssignmentV6
+            | ResourceAssignmentV6Discard
+            | ListenV6
+            | ListenV6Discard
+            | AcceptV6
+            | AcceptV6Discard
+            | ConnectV6
+            | ConnectV6Discard
+            | FlowEstablishedV6
+            | FlowEstablishedV6Discard
+            | ResourceReleaseV6
+            | EndpointClosureV6
+            | ConnectRedirectV6
+            | BindRedirectV6
+            | StreamPacketV6
+            | NameResolutionCacheV6
+            | OutboundNetworkConnectionPolicyV6
+            | IkeExtV6
+            | IpsecKmDemuxV6
+            | IpsecV6
+            | IngressVSwitchTransportV6
+            | EgressVSwitchTransportV6 => Some(IpFamily::V6),
+            InboundMacFrameEthernet
+            | OutboundMacFrameEthernet
+            | InboundMacFrameNative
+            | OutboundMacFrameNative
+            | InboundMacFrameNativeFast
+            | OutboundMacFrameNativeFast
+            | InboundTransportFast
+            | OutboundTransportFast
+            | InboundReserved2
+            | IngressVSwitchEthernet
+            | EgressVSwitchEthernet
+            | KmAuthorization
+            | RpcUm
+            | RpcEpMap
+            | RpcEpAdd
+            | RpcProxyConn
+            | RpcProxyIf => None,
+        }
+    }
+
+    /// Returns the direction of the traffic at this layer.
+    ///
+    /// This is `None` for layers that see traffic in both directions, such as
+    /// [`Layer::FlowEstablishedV4`], and for layers that are not about traffic.
+    pub fn direction(&self) -> Option<Direction> {
+        use Layer::*;
+        match self {
+            InboundIpPacketV4
+            | InboundIpPacketV4Discard
+            | InboundIpPacketV6
+            | InboundIpPacketV6Discard
+            | InboundTransportV4
+            | InboundTransportV4Discard
+            | InboundTransportV6
+            | InboundTransportV6Discard
+            | InboundIcmpErrorV4
+            | InboundIcmpErrorV4Discard
+            | InboundIcmpErrorV6
+            | InboundIcmpErrorV6Discard
+            | ListenV4
+            | ListenV4Discard
+            | ListenV6
+            | ListenV6Discard
+            | AcceptV4
+            | AcceptV4Discard
+            | AcceptV6
+            | AcceptV6Discard
+            | InboundMacFrameEthernet
+            | InboundMacFrameNative
+            | InboundMacFrameNativeFast
+            | InboundTransportFast
+            | InboundReserved2
+            | IngressVSwitchEthernet
+            | IngressVSwitchTransportV4
+            | IngressVSwitchTransportV6 => Some(Direction::Inbound),
+            OutboundIpPacketV4
+            | OutboundIpPacketV4Discard
+            | OutboundIpPacketV6
+            | OutboundIpPacketV6Discard
+            | OutboundTransportV4
+            | OutboundTransportV4Discard
+            | OutboundTransportV6
+            | OutboundTransportV6Discard
+            | OutboundIcmpErrorV4
+            | OutboundIcmpErrorV4Discard
+            | OutboundIcmpErrorV6
+            | OutboundIcmpErrorV6Discard
+            | ConnectV4
+            | ConnectV4Discard
+            | ConnectV6
+            | ConnectV6Discard
+            | ConnectRedirectV4
+            | ConnectRedirectV6
+            | OutboundNetworkConnectionPolicyV4
+            | OutboundNetworkConnectionPolicyV6
+            | OutboundMacFrameEthernet
+            | OutboundMacFrameNative
+            | OutboundMacFrameNativeFast
+            | OutboundTransportFast
+            | EgressVSwitchEthernet
+            | EgressVSwitchTransportV4
+            | EgressVSwitchTransportV6 => Some(Direction::Outbound),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this is an Application Layer Enforcement (ALE) layer.
+    ///
+    /// ALE layers classify connections and sockets rather than individual packets, and are
+    /// the only layers where conditions such as [`ConditionField::AppId`] are available.
+    pub fn is_ale(&self) -> bool {
+        use Layer::*;
+        matches!(
+            self,
+            ResourceAssignmentV4
+                | ResourceAssignmentV4Discard
+                | ResourceAssignmentV6
+                | ResourceAssignmentV6Discard
+                | ListenV4
+                | ListenV4Discard
+                | ListenV6
+                | ListenV6Discard
+                | AcceptV4
+                | AcceptV4Discard
+                | AcceptV6
+                | AcceptV6Discard
+                | ConnectV4
+                | ConnectV4Discard
+                | ConnectV6
+                | ConnectV6Discard
+                | FlowEstablishedV4
+                | FlowEstablishedV4Discard
+                | FlowEstablishedV6
+                | FlowEstablishedV6Discard
+                | ResourceReleaseV4
+                | ResourceReleaseV6
+                | EndpointClosureV4
+                | EndpointClosureV6
+                | ConnectRedirectV4
+                | ConnectRedirectV6
+                | BindRedirectV4
+                | BindRedirectV6
+        )
+    }
+
+    /// Returns whether this is a discard layer.
+    pub fn is_discard(&self) -> bool {
+        use Layer::*;
+        matches!(
+            self,
+            InboundIpPacketV4Discard
+                | InboundIpPacketV6Discard
+                | OutboundIpPacketV4Discard
+                | OutboundIpPacketV6Discard
+                | IpForwardV4Discard
+                | IpForwardV6Discard
+                | InboundTransportV4Discard
+                | InboundTransportV6Discard
+                | OutboundTransportV4Discard
+                | OutboundTransportV6Discard
+                | StreamV4Discard
+                | StreamV6Discard
+                | DatagramDataV4Discard
+                | DatagramDataV6Discard
+                | InboundIcmpErrorV4Discard
+                | InboundIcmpErrorV6Discard
+                | OutboundIcmpErrorV4Discard
+                | OutboundIcmpErrorV6Discard
+                | ResourceAssignmentV4Discard
+                | ResourceAssignmentV6Discard
+                | ListenV4Discard
+                | ListenV6Discard
+                | AcceptV4Discard
+                | AcceptV6Discard
+                | ConnectV4Discard
+                | ConnectV6Discard
+                | FlowEstablishedV4Discard
+                | FlowEstablishedV6Discard
+        )
+    }
+
+    /// Returns the condition fields that are available at this layer.
+    ///
+    /// Only fields that can be expressed as a [`ConditionField`] are included. See
+    /// [Filtering Conditions Available at Each Filtering Layer] for the full list.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wfp::{ConditionField, Layer};
+    ///
+    /// assert!(Layer::ConnectV4.condition_fields().contains(&ConditionField::AppId));
+    /// assert!(!Layer::InboundIpPacketV4.condition_fields().contains(&ConditionField::AppId));
+    /// ```
+    ///
+    /// [Filtering Conditions Available at Each Filtering Layer]: https://learn.microsoft.com/en-us/windows/win32/fwp/filtering-conditions-available-at-each-filtering-layer
+    pub fn condition_fields(&self) -> &'static [ConditionField] {
+        use ConditionField::*;
+        use Layer::*;
+        match self {
+            InboundIpPacketV4
+            | InboundIpPacketV4Discard
+            | InboundIpPacketV6
+            | InboundIpPacketV6Discard => &[
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                LocalInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            OutboundIpPacketV4
+            | OutboundIpPacketV4Discard
+            | OutboundIpPacketV6
+            | OutboundIpPacketV6Discard => &[
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                DestinationAddressType,
+                LocalInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            IkeExtV4 | IkeExtV6 | IpsecKmDemuxV4 | IpsecKmDemuxV6 => {
+                &[LocalAddress, RemoteAddress, LocalInterface]
+            }
+            IpForwardV4 | IpForwardV4Discard | IpForwardV6 | IpForwardV6Discard => &[
+                DestinationAddressType,
+                ArrivalInterface,
+                NextHopAddress,
+                NextHopInterface,
+                CompartmentId,
+                Flags,
+            ],
+            InboundTransportV4
+            | InboundTransportV4Discard
+            | InboundTransportV6
+            | InboundTransportV6Discard => &[
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                LocalPort,
+                RemotePort,
+                IcmpType,
+                IcmpCode,
+                LocalInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            OutboundTransportV4
+            | OutboundTransportV4Discard
+            | OutboundTransportV6
+            | OutboundTransportV6Discard => &[
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                DestinationAddressType,
+                LocalPort,
+                RemotePort,
+                IcmpType,
+                IcmpCode,
+                LocalInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            DatagramDataV4 | DatagramDataV4Discard | DatagramDataV6 | DatagramDataV6Discard => &[
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                DestinationAddressType,
+                LocalPort,
+                RemotePort,
+                IcmpType,
+                IcmpCode,
+                Direction,
+                LocalInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            StreamV4 | StreamV4Discard | StreamV6 | StreamV6Discard => &[
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                LocalPort,
+                RemotePort,
+                Direction,
+                CompartmentId,
+            ],
+            StreamPacketV4 | StreamPacketV6 => &[
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                LocalPort,
+                RemotePort,
+                Direction,
+                LocalInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+            ],
+            InboundIcmpErrorV4
+            | InboundIcmpErrorV4Discard
+            | InboundIcmpErrorV6
+            | InboundIcmpErrorV6Discard
+            | OutboundIcmpErrorV4
+            | OutboundIcmpErrorV4Discard
+            | OutboundIcmpErrorV6
+            | OutboundIcmpErrorV6Discard => &[
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                IcmpType,
+                IcmpCode,
+                LocalInterface,
+                ArrivalInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            ResourceAssignmentV4
+            | ResourceAssignmentV4Discard
+            | ResourceAssignmentV6
+            | ResourceAssignmentV6Discard => &[
+                AppId,
+                UserId,
+                PackageId,
+                Protocol,
+                LocalAddress,
+                LocalAddressType,
+                LocalPort,
+                LocalInterface,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            ResourceReleaseV4 | ResourceReleaseV6 => &[
+                AppId,
+                PackageId,
+                Protocol,
+                LocalAddress,
+                LocalAddressType,
+                LocalPort,
+                LocalInterface,
+                CompartmentId,
+            ],
+            ListenV4 | ListenV4Discard | ListenV6 | ListenV6Discard => &[
+                AppId,
+                UserId,
+                PackageId,
+                LocalAddress,
+                LocalAddressType,
+                LocalPort,
+                LocalInterface,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            AcceptV4 | AcceptV4Discard | AcceptV6 | AcceptV6Discard => &[
+                AppId,
+                UserId,
+                RemoteUserId,
+                PackageId,
+                OriginalAppId,
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                LocalPort,
+                RemotePort,
+                IcmpType,
+                IcmpCode,
+                LocalInterface,
+                ArrivalInterface,
+                NextHopAddress,
+                NextHopInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            ConnectV4 | ConnectV4Discard | ConnectV6 | ConnectV6Discard => &[
+                AppId,
+                UserId,
+                RemoteUserId,
+                PackageId,
+                OriginalAppId,
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                DestinationAddressType,
+                LocalPort,
+                RemotePort,
+                IcmpType,
+                IcmpCode,
+                LocalInterface,
+                ArrivalInterface,
+                NextHopAddress,
+                NextHopInterface,
+                InterfaceIndex,
+                SubInterfaceIndex,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            FlowEstablishedV4
+            | FlowEstablishedV4Discard
+            | FlowEstablishedV6
+            | FlowEstablishedV6Discard => &[
+                AppId,
+                UserId,
+                RemoteUserId,
+                PackageId,
+                OriginalAppId,
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                DestinationAddressType,
+                LocalPort,
+                RemotePort,
+                IcmpType,
+                IcmpCode,
+                Direction,
+                LocalInterface,
+                InterfaceType,
+                TunnelType,
+                CompartmentId,
+                Flags,
+            ],
+            EndpointClosureV4 | EndpointClosureV6 => &[
+                AppId,
+                PackageId,
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                LocalPort,
+                RemotePort,
+                LocalInterface,
+                CompartmentId,
+            ],
+            ConnectRedirectV4 | ConnectRedirectV6 => &[
+                AppId,
+                UserId,
+                PackageId,
+                OriginalAppId,
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalAddressType,
+                DestinationAddressType,
+                LocalPort,
+                RemotePort,
+                CompartmentId,
+                Flags,
+            ],
+            OutboundNetworkConnectionPolicyV4 | OutboundNetworkConnectionPolicyV6 => &[
+                AppId,
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalPort,
+                RemotePort,
+            ],
+            BindRedirectV4 | BindRedirectV6 => &[
+                AppId,
+                UserId,
+                PackageId,
+                Protocol,
+                LocalAddress,
+                LocalAddressType,
+                LocalPort,
+                CompartmentId,
+                Flags,
+            ],
+            NameResolutionCacheV4 | NameResolutionCacheV6 => &[AppId, RemoteAddress],
+            IpsecV4 | IpsecV6 => &[
+                Protocol,
+                LocalAddress,
+                RemoteAddress,
+                LocalPort,
+                RemotePort,
+                LocalInterface,
+            ],
+            IngressVSwitchTransportV4
+            | IngressVSwitchTransportV6
+            | EgressVSwitchTransportV4
+            | EgressVSwitchTransportV6 => &[Protocol],
+            RpcUm | RpcEpMap => &[LocalPort],
+            // These layers only have fields that are not represented by ConditionField
+            InboundMacFrameEthernet
+            | OutboundMacFrameEthernet
+            | InboundMacFrameNative
+            | OutboundMacFrameNative
+            | InboundMacFrameNativeFast
+            | OutboundMacFrameNativeFast
+            | InboundTransportFast
+            | OutboundTransportFast
+            | InboundReserved2
+            | IngressVSwitchEthernet
+            | EgressVSwitchEthernet
+            | KmAuthorization
+            | RpcEpAdd
+            | RpcProxyConn
+            | RpcProxyIf => &[],
+        }
+    }
+
+    /// Returns the data type of `field` at this layer, or `None` if the field is not available
+    /// at this layer.
+    ///
+    /// The type of address fields depends on the IP version of the layer.
+    pub fn field_type(&self, field: ConditionField) -> Option<DataType> {
+        if !self.condition_fields().contains(&field) {
+            return None;
+        }
+        Some(match field {
+            ConditionField::RemoteAddress
+            | ConditionField::LocalAddress
+            | ConditionField::NextHopAddress => match self.ip_family() {
+                Some(IpFamily::V6) => DataType::ByteArray16,
+                _ => DataType::UInt32,
+            },
+            ConditionField::RemotePort
+            | ConditionField::LocalPort
+            | ConditionField::IcmpType
+            | ConditionField::IcmpCode => DataType::UInt16,
+            ConditionField::Protocol
+            | ConditionField::LocalAddressType
+            | ConditionField::DestinationAddressType => DataType::UInt8,
+            ConditionField::AppId | ConditionField::OriginalAppId => DataType::ByteBlob,
+            ConditionField::LocalInterface
+            | ConditionField::ArrivalInterface
+            | ConditionField::NextHopInterface => DataType::UInt64,
+            ConditionField::Flags
+            | ConditionField::Direction
+            | ConditionField::InterfaceType
+            | ConditionField::TunnelType
+            | ConditionField::InterfaceIndex
+            | ConditionField::SubInterfaceIndex
+            | ConditionField::CompartmentId => DataType::UInt32,
+            ConditionField::UserId | ConditionField::RemoteUserId => DataType::SecurityDescriptor,
+            ConditionField::PackageId => DataType::Sid,
+        })
+    }
+
+    /// Checks that `condition` can be used at this layer.
+    pub(crate) fn check_condition(&self, condition: &Condition) -> Result<()> {
+        let error = |reason| WfpError::IncompatibleCondition {
+            layer: *self,
+            field: condition.field(),
+            reason,
+        };
+
+        // Match by GUID, since some fields share their GUID with another field
+        let field_key = condition.field_key();
+        let field = self
+            .condition_fields()
+            .iter()
+            .copied()
+            .find(|field| guid_eq(field.guid(), &field_key))
+            .ok_or_else(|| error(ConditionError::FieldNotFound))?;
+        let field_type = self
+            .field_type(field)
+            .ok_or_else(|| error(ConditionError::FieldNotFound))?;
+
+        match condition.value_type() {
+            Some(value_type) if value_type.is_compatible_with(field_type) => Ok(()),
+            Some(value_type)
+                if matches!(
+                    field,
+                    ConditionField::LocalAddress
+                        | ConditionField::RemoteAddress
+                        | ConditionField::NextHopAddress
+                ) && address_family(value_type).is_some() =>
+            {
+                Err(error(ConditionError::AddressFamilyMismatch))
+            }
+            _ => Err(error(ConditionError::TypeMismatch)),
+        }
+    }
+
+    /// Returns the layer identified by `guid`, or `None` if it is not a built-in layer.
+    ///
+    /// This is the inverse of [`Layer::guid`], and is useful for mapping the layer keys of
+    /// enumerated objects back to a `Layer`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wfp::Layer;
+    ///
+    /// let guid = *Layer::ConnectV4.guid();
+    /// assert_eq!(Layer::from_guid(&guid), Some(Layer::ConnectV4));
+    /// ```
+    pub fn from_guid(guid: &GUID) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|layer| guid_eq(layer.guid(), guid))
+    }
+}
+
+flags_type! {
+    /// Flags of a layer.
+    ///
+    /// These correspond to the `FWPM_LAYER_FLAG_*` values of the `flags` field in the
+    /// underlying [`FWPM_LAYER0`] structure.
+    ///
+    /// [`FWPM_LAYER0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_layer0
+    pub struct LayerFlags {
+        /// The layer is classified in kernel mode (`FWPM_LAYER_FLAG_KERNEL`).
+        const KERNEL = FWPM_LAYER_FLAG_KERNEL;
+        /// The layer is built in, and cannot be deleted (`FWPM_LAYER_FLAG_BUILTIN`).
+        const BUILTIN = FWPM_LAYER_FLAG_BUILTIN;
+        /// The layer is optimized for classification rather than enumeration
+        /// (`FWPM_LAYER_FLAG_CLASSIFY_MOSTLY`).
+        const CLASSIFY_MOSTLY = FWPM_LAYER_FLAG_CLASSIFY_MOSTLY;
+        /// The layer is buffered (`FWPM_LAYER_FLAG_BUFFERED`).
+        const BUFFERED = FWPM_LAYER_FLAG_BUFFERED;
+    }
+}
+
+/// How the value of a layer field is interpreted.
+///
+/// These correspond to the [`FWPM_FIELD_TYPE`] enumeration values.
+///
+/// [`FWPM_FIELD_TYPE`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ne-fwpmtypes-fwpm_field_type
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    /// The value has no special meaning (`FWPM_FIELD_RAW_DATA`).
+    RawData = FWPM_FIELD_RAW_DATA,
+    /// The value is an IP address (`FWPM_FIELD_IP_ADDRESS`).
+    IpAddress = FWPM_FIELD_IP_ADDRESS,
+    /// The value is a set of bit flags (`FWPM_FIELD_FLAGS`).
+    Flags = FWPM_FIELD_FLAGS,
+}
+
+impl FieldType {
+    /// Returns the field type that corresponds to a raw `FWPM_FIELD_TYPE` value, if any.
+    pub fn from_raw(raw: FWPM_FIELD_TYPE) -> Option<Self> {
+        [Self::RawData, Self::IpAddress, Self::Flags]
+            .into_iter()
+            .find(|ty| *ty as i32 == raw)
+    }
+}
+
+/// A field that is available at a layer, as reported by the engine.
+///
+/// This corresponds to the underlying [`FWPM_FIELD0`] structure.
+///
+/// [`FWPM_FIELD0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_field0
+#[derive(Clone)]
+pub struct LayerField {
+    key: GUID,
+    field_type: Option<FieldType>,
+    data_type: Option<DataType>,
+}
+
+impl LayerField {
+    /// Return the GUID of the field.
+    pub fn guid(&self) -> GUID {
+        self.key
+    }
+
+    /// Return the field, or `None` if it is not a known [`ConditionField`].
+    pub fn field(&self) -> Option<ConditionField> {
+        ConditionField::from_guid(&self.key)
+    }
+
+    /// Return how the value of the field is interpreted, or `None` if it is not a known type.
+    pub fn field_type(&self) -> Option<FieldType> {
+        self.field_type
+    }
+
+    /// Return the data type of the field, or `None` if it is not a known type.
+    pub fn data_type(&self) -> Option<DataType> {
+        self.data_type
+    }
+}
+
+impl fmt::Debug for LayerField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LayerField")
+            .field("key", &GuidDisplay(&self.key))
+            .field("field_type", &self.field_type)
+            .field("data_type", &self.data_type)
+            .finish()
+    }
+}
+
+/// An owned copy of a layer.
+///
+/// This is returned by [`LayerEnumerator`](crate::LayerEnumerator) and [`get_layer_by_key`].
+/// Unlike [`Layer`], it describes the layer as reported by the engine, which includes the
+/// fields available at the layer and its default sublayer.
+///
+/// This corresponds to the underlying [`FWPM_LAYER0`] structure.
+///
+/// [`FWPM_LAYER0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_layer0
+#[derive(Clone)]
+pub struct LayerInfo {
+    key: GUID,
+    name: Option<String>,
+    description: Option<String>,
+    flags: LayerFlags,
+    fields: Vec<LayerField>,
+    default_sublayer: GUID,
+    id: u16,
+}
+
+impl LayerInfo {
+    /// Deep-copies a raw layer.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `layer` must be valid.
+    pub(crate) unsafe fn from_raw(layer: &FWPM_LAYER0) -> Result<Self> {
+        let num_fields = usize::try_from(layer.numFields).unwrap();
+        let raw_fields = if num_fields == 0 || layer.field.is_null() {
+            &[]
+        } else {
+            // SAFETY: `field` points to `numFields` fields
+            unsafe { std::slice::from_raw_parts(layer.field, num_fields) }
+        };
+        let fields = raw_fields
+            .iter()
+            // SAFETY: The caller guarantees that the field keys are valid
+            .filter_map(|field| unsafe { field.fieldKey.as_ref() }.map(|key| (key, field)))
+            .map(|(key, field)| LayerField {
+                key: *key,
+                field_type: FieldType::from_raw(field.r#type),
+                data_type: DataType::from_raw(field.dataType),
+            })
+            .collect();
+
+        // SAFETY: The caller guarantees that the pointers are valid
+        unsafe {
+            Ok(Self {
+                key: layer.layerKey,
+                name: display_string(layer.displayData.name, "invalid layer name")?,
+                description: display_string(
+                    layer.displayData.description,
+                    "invalid layer description",
+                )?,
+                flags: LayerFlags::from_bits_retain(layer.flags),
+                fields,
+                default_sublayer: layer.defaultSubLayerKey,
+                id: layer.layerId,
+            })
+        }
+    }
+
+    /// Return the layer GUID.
+    pub fn guid(&self) -> GUID {
+        self.key
+    }
+
+    /// Return the layer, or `None` if it is not a built-in layer.
+    pub fn layer(&self) -> Option<Layer> {
+        Layer::from_guid(&self.key)
+    }
+
+    /// Return the layer name, if set.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Return the layer description, if set.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return the layer flags.
+    pub fn flags(&self) -> LayerFlags {
+        self.flags
+    }
+
+    /// Return the fields that are available at the layer.
+    pub fn fields(&self) -> &[LayerField] {
+        &self.fields
+    }
+
+    /// Return the GUID of the sublayer that filters are added to if they do not specify one.
+    pub fn default_sublayer(&self) -> GUID {
+        self.default_sublayer
+    }
+
+    /// Return the runtime ID of the layer.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+}
+
+impl fmt::Debug for LayerInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LayerInfo")
+            .field("key", &GuidDisplay(&self.key))
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("flags", &self.flags)
+            .field("fields", &self.fields)
+            .field("default_sublayer", &GuidDisplay(&self.default_sublayer))
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// Return an owned copy of the layer with the given GUID.
+///
+/// This calls [`FwpmLayerGetByKey0`]. It returns [`WfpError::NotFound`] if there is no such
+/// layer. `transaction` may be a [read-only](Transaction::read_only) transaction.
+///
+/// # Example
+///
+/// ```no_run
+/// use wfp::{FilterEngineBuilder, Layer, Transaction, get_layer_by_key};
+///
+/// # #[cfg(target_os = "windows")]
+/// fn main() -> wfp::Result<()> {
+///     let engine = FilterEngineBuilder::default().dynamic().open()?;
+///     let t = Transaction::read_only(&engine)?;
+///
+///     let layer = get_layer_by_key(&t, Layer::ConnectV4.guid())?;
+///     for field in layer.fields() {
+///         println!("{:?}: {:?}", field.field(), field.data_type());
+///     }
+///
+///     Ok(())
+/// }
+/// # #[cfg(not(target_os = "windows"))]
+/// # fn main() {}
+/// ```
+///
+/// [`FwpmLayerGetByKey0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmlayergetbykey0
+pub fn get_layer_by_key<Mode>(
+    transaction: &Transaction<'_, Mode>,
+    guid: &GUID,
+) -> Result<LayerInfo> {
+    let layer = transaction.engine.backend().layer_get_by_key(guid)?;
+    // SAFETY: The layer was returned by the backend, so its pointers are valid
+    unsafe { LayerInfo::from_raw(layer.get()) }
+}
+
+/// Returns the IP version of an address value of type `value_type`, or `None` if it is not an
+/// address type.
+fn address_family(value_type: DataType) -> Option<IpFamily> {
+    match value_type {
+        DataType::UInt32 | DataType::V4AddrMask => Some(IpFamily::V4),
+        DataType::ByteArray16 | DataType::V6AddrMask => Some(IpFamily::V6),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_layer_metadata() {
+        assert_eq!(Layer::ConnectV6.ip_family(), Some(IpFamily::V6));
+        assert_eq!(Layer::ConnectV6.direction(), Some(Direction::Outbound));
+        assert!(Layer::ConnectV6.is_ale());
+        assert_eq!(Layer::FlowEstablishedV4.direction(), None);
+        assert!(!Layer::InboundIpPacketV4.is_ale());
+        assert_eq!(Layer::InboundMacFrameEthernet.ip_family(), None);
+        assert!(Layer::ConnectV4Discard.is_discard());
+
+        assert_eq!(
+            Layer::ConnectV4.field_type(ConditionField::RemoteAddress),
+            Some(DataType::UInt32)
+        );
+        assert_eq!(
+            Layer::ConnectV6.field_type(ConditionField::RemoteAddress),
+            Some(DataType::ByteArray16)
+        );
+        assert_eq!(
+            Layer::InboundIpPacketV4.field_type(ConditionField::AppId),
+            None
+        );
+        assert_eq!(
+            Layer::ConnectV6.field_type(ConditionField::NextHopAddress),
+            Some(DataType::ByteArray16)
+        );
+        assert_eq!(
+            Layer::DatagramDataV4.field_type(ConditionField::Direction),
+            Some(DataType::UInt32)
+        );
+        assert_eq!(
+            Layer::OutboundTransportV4.field_type(ConditionField::DestinationAddressType),
+            Some(DataType::UInt8)
+        );
+        assert_eq!(
+            Layer::InboundTransportV4.field_type(ConditionField::DestinationAddressType),
+            None
+        );
+
+        // Address fields are only available at layers with an IP version
+        for &layer in Layer::ALL {
+            if layer.ip_family().is_none() {
+                assert_eq!(layer.field_type(ConditionField::RemoteAddress), None);
+                assert_eq!(layer.field_type(ConditionField::LocalAddress), None);
+                assert_eq!(layer.field_type(ConditionField::NextHopAddress), None);
+            }
+            if layer.condition_fields().contains(&ConditionField::AppId) {
+                assert!(layer.is_ale() || layer.ip_family().is_some(), "{layer:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_address_family() {
+        use std::net::{Ipv4Addr, Ipv6Addr};
+
+        use crate::IpAddressConditionBuilder;
+
+        let v4 = IpAddressConditionBuilder::remote()
+            .address(Ipv4Addr::LOCALHOST)
+            .build();
+        let v6 = IpAddressConditionBuilder::remote()
+            .subnet_v6(Ipv6Addr::LOCALHOST, 64)
+            .unwrap()
+            .build();
+
+        assert!(Layer::ConnectV4.check_condition(&v4).is_ok());
+        assert!(Layer::ConnectV6.check_condition(&v6).is_ok());
+        assert!(matches!(
+            Layer::ConnectV4.check_condition(&v6),
+            Err(WfpError::IncompatibleCondition {
+                reason: ConditionError::AddressFamilyMismatch,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Layer::ConnectV6.check_condition(&v4),
+            Err(WfpError::IncompatibleCondition {
+                reason: ConditionError::AddressFamilyMismatch,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_layer_guid_round_trip() {
+        for &layer in Layer::ALL {
+            assert_eq!(Layer::from_guid(layer.guid()), Some(layer));
         }
+        assert_eq!(Layer::from_guid(&GUID::from_u128(0)), None);
     }
 }
@@ -16,6 +16,7 @@
 //! use wfp::{FilterEngineBuilder, FilterBuilder, PortConditionBuilder, ActionType, Layer, Transaction};
 //! use std::io;
 //!
+//! # #[cfg(target_os = "windows")]
 //! fn main() -> io::Result<()> {
 //!     // Open a dynamic filter engine session
 //!     let mut engine = FilterEngineBuilder::default().dynamic().open()?;
@@ -41,6 +42,8 @@
 //!     
 //!     Ok(())
 //! }
+//! # #[cfg(not(target_os = "windows"))]
+//! # fn main() {}
 //! ```
 //!
 //! ## Examples
@@ -48,31 +51,86 @@
 //! See the `examples/` directory for more usage examples.
 //!
 //! Run examples with: `cargo run --example <example>`
-
-#![cfg(target_os = "windows")]
+//!
+//! ## Platform support
+//!
+//! The policy model (layers, conditions, actions, weights and the filter, sublayer and
+//! provider builders) is available on every target, so code that builds rule sets can be
+//! compiled and unit-tested anywhere.
+//!
+//! A [`FilterEngine`] forwards every operation to a [`Backend`]. On Windows,
+//! [`FilterEngineBuilder::open`] connects to the Base Filtering Engine. On any target,
+//! [`FilterEngineBuilder::open_in_memory`] opens a session with a [`MemoryBfe`], an in-memory
+//! stand-in for the Base Filtering Engine that is mainly useful for tests:
+//!
+//! ```
+//! use wfp::{FilterEngineBuilder, MemoryBfe, Transaction};
+//!
+//! let bfe = MemoryBfe::new();
+//! let mut engine = FilterEngineBuilder::default().dynamic().open_in_memory(&bfe).unwrap();
+//! let transaction = Transaction::new(&mut engine).unwrap();
+//! transaction.commit().unwrap();
+//! ```
+//!
+//! Resolving application IDs and interface aliases requires Windows.
+//!
+#![cfg_attr(
+    target_os = "windows",
+    doc = "[`FilterEngineBuilder::open`]: FilterEngineBuilder::open"
+)]
+#![cfg_attr(
+    not(target_os = "windows"),
+    doc = "[`FilterEngineBuilder::open`]: FilterEngineBuilder"
+)]
 
 mod action;
+mod backend;
 mod blob;
+mod callout;
 mod condition;
 mod engine;
 mod r#enum;
+mod error;
 mod filter;
 mod layer;
+mod luid;
+mod network;
+mod option;
+mod port;
 mod provider;
+mod provider_context;
+mod range_set;
+mod security;
+mod sha256;
+mod sid;
 mod sublayer;
 mod transaction;
 mod util;
 
 // Re-export public API
-pub use action::ActionType;
+pub use action::{ActionMask, ActionType};
+pub use backend::{Backend, EnumBatch, FetchedObject, MemoryBfe};
+pub use callout::{CalloutFlags, CalloutInfo};
 pub use condition::*;
-pub use engine::{FilterEngine, FilterEngineBuilder};
-pub use r#enum::{FilterEnumItem, FilterEnumerator};
+pub use engine::{FilterEngine, FilterEngineBuilder, SessionInfo};
+pub use r#enum::{
+    CalloutEnumerator, FilterEnumFlags, FilterEnumItem, FilterEnumTemplate, FilterEnumType,
+    FilterEnumerator, FilterInfoIter, LayerEnumerator, ProviderContextEnumerator,
+    ProviderEnumerator, SubLayerEnumerator,
+};
+pub use error::{ConditionError, ObjectType, ParseError, Result, WfpError};
 pub use filter::*;
 pub use layer::*;
+pub use luid::NetLuid;
+pub use network::{IpNetwork, IpSet, Ipv4Network, Ipv6Network};
+pub use option::{EngineOption, EngineOptionKind, NetEventKeywords, PacketQueuing};
+pub use port::PortSet;
 pub use provider::*;
+pub use provider_context::{ProviderContextFlags, ProviderContextInfo, ProviderContextType};
+pub use security::SecurityDescriptor;
+pub use sid::Sid;
 pub use sublayer::*;
-pub use transaction::Transaction;
+pub use transaction::{ReadOnly, ReadWrite, Transaction};
 
 // Re-export publicly exposed types from external crates
 pub use windows_sys::core::GUID;
@@ -0,0 +1,202 @@
+//! Network interface LUIDs.
+
+#[cfg(target_os = "windows")]
+use std::ffi::OsStr;
+use std::fmt;
+
+#[cfg(target_os = "windows")]
+use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+#[cfg(target_os = "windows")]
+use windows_sys::Win32::NetworkManagement::IpHelper::{
+    ConvertInterfaceAliasToLuid, ConvertInterfaceGuidToLuid, ConvertInterfaceIndexToLuid,
+    ConvertInterfaceNameToLuidW,
+};
+#[cfg(target_os = "windows")]
+use windows_sys::Win32::NetworkManagement::Ndis::NET_LUID_LH;
+#[cfg(target_os = "windows")]
+use windows_sys::core::GUID;
+
+#[cfg(target_os = "windows")]
+use crate::error::{Result, WfpError};
+#[cfg(target_os = "windows")]
+use crate::util::string_to_null_terminated_utf16;
+
+/// The locally unique identifier (LUID) of a network interface.
+///
+/// This corresponds to the [`NET_LUID`] union. Its `Info` fields, the interface type (`IfType`)
+/// and the index of the interface among interfaces of that type (`NetLuidIndex`), can be
+/// split and joined without calling into Windows.
+///
+/// # Example
+///
+/// ```
+/// use wfp::NetLuid;
+///
+/// // The first software loopback interface
+/// let luid = NetLuid::new(24, 0);
+/// assert_eq!(luid.value(), 0x0018_0000_0000_0000);
+/// assert_eq!(luid.if_type(), 24);
+/// ```
+///
+/// [`NET_LUID`]: https://learn.microsoft.com/en-us/windows/win32/api/ifdef/ns-ifdef-net_luid_lh
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct NetLuid(u64);
+
+impl NetLuid {
+    /// The maximum `NetLuidIndex`, which is stored as a 24-bit integer.
+    pub const MAX_NET_LUID_INDEX: u32 = (1 << 24) - 1;
+
+    /// Offset of the `NetLuidIndex` field. The lowest 24 bits are reserved.
+    const NET_LUID_INDEX_SHIFT: u32 = 24;
+    /// Offset of the `IfType` field.
+    const IF_TYPE_SHIFT: u32 = 48;
+
+    /// Creates a LUID from an interface type and an index.
+    ///
+    /// `if_type` is an IANA interface type, such as
+    /// [`InterfaceType::Ethernet`](crate::InterfaceType::Ethernet).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `net_luid_index` is greater than [`NetLuid::MAX_NET_LUID_INDEX`].
+    pub const fn new(if_type: u16, net_luid_index: u32) -> Self {
+        assert!(net_luid_index <= Self::MAX_NET_LUID_INDEX);
+        Self(
+            ((if_type as u64) << Self::IF_TYPE_SHIFT)
+                | ((net_luid_index as u64) << Self::NET_LUID_INDEX_SHIFT),
+        )
+    }
+
+    /// Creates a LUID from its 64-bit value.
+    pub const fn from_value(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Returns the 64-bit value of the LUID.
+    pub const fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the IANA interface type (`IfType`).
+    pub const fn if_type(&self) -> u16 {
+        (self.0 >> Self::IF_TYPE_SHIFT) as u16
+    }
+
+    /// Returns the index of the interface among interfaces of the same type (`NetLuidIndex`).
+    pub const fn net_luid_index(&self) -> u32 {
+        ((self.0 >> Self::NET_LUID_INDEX_SHIFT) as u32) & Self::MAX_NET_LUID_INDEX
+    }
+
+    /// Returns the LUID of the interface with the given alias, e.g. `Ethernet`.
+    ///
+    /// Uses [`ConvertInterfaceAliasToLuid`].
+    ///
+    /// [`ConvertInterfaceAliasToLuid`]: https://learn.microsoft.com/en-us/windows/win32/api/netioapi/nf-netioapi-convertinterfacealiastoluid
+    #[cfg(target_os = "windows")]
+    pub fn from_alias(alias: impl AsRef<OsStr>) -> Result<Self> {
+        let wide_alias: Vec<u16> = string_to_null_terminated_utf16(alias);
+        // SAFETY: Passing a null-terminated UTF-16 string and a valid pointer to NET_LUID_LH.
+        convert(|luid| unsafe { ConvertInterfaceAliasToLuid(wide_alias.as_ptr(), luid) })
+    }
+
+    /// Returns the LUID of the interface with the given name, e.g. `ethernet_32768`.
+    ///
+    /// This is the name that is derived from the LUID itself, not the alias. Uses
+    /// [`ConvertInterfaceNameToLuidW`].
+    ///
+    /// [`ConvertInterfaceNameToLuidW`]: https://learn.microsoft.com/en-us/windows/win32/api/netioapi/nf-netioapi-convertinterfacenametoluidw
+    #[cfg(target_os = "windows")]
+    pub fn from_name(name: impl AsRef<OsStr>) -> Result<Self> {
+        let wide_name: Vec<u16> = string_to_null_terminated_utf16(name);
+        // SAFETY: Passing a null-terminated UTF-16 string and a valid pointer to NET_LUID_LH.
+        convert(|luid| unsafe { ConvertInterfaceNameToLuidW(wide_name.as_ptr(), luid) })
+    }
+
+    /// Returns the LUID of the interface with the given index.
+    ///
+    /// Uses [`ConvertInterfaceIndexToLuid`].
+    ///
+    /// [`ConvertInterfaceIndexToLuid`]: https://learn.microsoft.com/en-us/windows/win32/api/netioapi/nf-netioapi-convertinterfaceindextoluid
+    #[cfg(target_os = "windows")]
+    pub fn from_index(index: u32) -> Result<Self> {
+        // SAFETY: Passing a valid pointer to NET_LUID_LH.
+        convert(|luid| unsafe { ConvertInterfaceIndexToLuid(index, luid) })
+    }
+
+    /// Returns the LUID of the interface with the given GUID.
+    ///
+    /// Uses [`ConvertInterfaceGuidToLuid`].
+    ///
+    /// [`ConvertInterfaceGuidToLuid`]: https://learn.microsoft.com/en-us/windows/win32/api/netioapi/nf-netioapi-convertinterfaceguidtoluid
+    #[cfg(target_os = "windows")]
+    pub fn from_guid(guid: &GUID) -> Result<Self> {
+        // SAFETY: Passing a valid GUID and a valid pointer to NET_LUID_LH.
+        convert(|luid| unsafe { ConvertInterfaceGuidToLuid(guid, luid) })
+    }
+}
+
+/// Calls an IpHelper conversion function that writes a LUID to its argument.
+#[cfg(target_os = "windows")]
+fn convert(f: impl FnOnce(*mut NET_LUID_LH) -> u32) -> Result<NetLuid> {
+    let mut luid = NET_LUID_LH::default();
+    let status = f(&mut luid);
+    if status != ERROR_SUCCESS {
+        return Err(WfpError::from_code(status as i32));
+    }
+    // SAFETY: NET_LUID_LH is a union of `Value: u64` and `Info` (also 8 bytes), so reading
+    // `Value` is always valid regardless of how the OS populated the union.
+    Ok(NetLuid(unsafe { luid.Value }))
+}
+
+impl From<u64> for NetLuid {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<NetLuid> for u64 {
+    fn from(luid: NetLuid) -> Self {
+        luid.0
+    }
+}
+
+impl fmt::Debug for NetLuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NetLuid")
+            .field("if_type", &self.if_type())
+            .field("net_luid_index", &self.net_luid_index())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_and_join() {
+        let luid = NetLuid::new(6, 0x8001);
+        assert_eq!(luid.value(), 0x0006_0080_0100_0000);
+        assert_eq!(luid.if_type(), 6);
+        assert_eq!(luid.net_luid_index(), 0x8001);
+
+        // Reserved bits are ignored when splitting
+        let luid = NetLuid::from_value(0xffff_ffff_ffff_ffff);
+        assert_eq!(luid.if_type(), u16::MAX);
+        assert_eq!(luid.net_luid_index(), NetLuid::MAX_NET_LUID_INDEX);
+
+        let max = NetLuid::new(u16::MAX, NetLuid::MAX_NET_LUID_INDEX);
+        assert_eq!(max.value(), 0xffff_ffff_ff00_0000);
+        assert_eq!(u64::from(max), max.value());
+        assert_eq!(
+            format!("{max:?}"),
+            "NetLuid { if_type: 65535, net_luid_index: 16777215 }"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_range() {
+        NetLuid::new(6, NetLuid::MAX_NET_LUID_INDEX + 1);
+    }
+}
@@ -0,0 +1,958 @@
+//! IP networks in CIDR notation.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use crate::condition::{
+    Condition, IpAddressConditionBuilder, IpAddressConditionBuilderMissingValue,
+};
+use crate::error::{ConditionError, ParseError, Result as WfpResult, WfpError};
+use crate::layer::IpFamily;
+use crate::range_set::RangeSet;
+
+/// An IPv4 network, such as `10.0.0.0/8`.
+///
+/// The host bits of the address are always cleared, so `10.1.2.3/8` is stored as `10.0.0.0/8`.
+///
+/// # Example
+///
+/// ```
+/// use std::net::Ipv4Addr;
+/// use wfp::Ipv4Network;
+///
+/// let network: Ipv4Network = "10.1.2.3/8".parse().unwrap();
+/// assert_eq!(network.to_string(), "10.0.0.0/8");
+/// assert!(network.contains(Ipv4Addr::new(10, 255, 0, 1)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ipv4Network {
+    addr: Ipv4Addr,
+    prefix_len: u8,
+}
+
+impl Ipv4Network {
+    /// The prefix length of a network that contains a single address.
+    pub const MAX_PREFIX_LEN: u8 = 32;
+
+    /// Creates the network `addr/prefix_len`, clearing the host bits of `addr`.
+    ///
+    /// Fails if `prefix_len` is greater than 32.
+    pub fn new(addr: Ipv4Addr, prefix_len: u8) -> Result<Self, ParseError> {
+        if prefix_len > Self::MAX_PREFIX_LEN {
+            return Err(ParseError::new(
+                "IPv4 network",
+                format!("prefix length {prefix_len} exceeds 32"),
+            ));
+        }
+        let mask = Self::mask_bits(prefix_len);
+        Ok(Self {
+            addr: Ipv4Addr::from(u32::from(addr) & mask),
+            prefix_len,
+        })
+    }
+
+    /// Returns the first address of the network.
+    pub fn addr(&self) -> Ipv4Addr {
+        self.addr
+    }
+
+    /// Returns the last address of the network.
+    pub fn last(&self) -> Ipv4Addr {
+        Ipv4Addr::from(u32::from(self.addr) | !Self::mask_bits(self.prefix_len))
+    }
+
+    /// Returns the number of leading bits that are fixed.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns the network mask, e.g. `255.0.0.0` for a `/8` network.
+    pub fn mask(&self) -> Ipv4Addr {
+        Ipv4Addr::from(Self::mask_bits(self.prefix_len))
+    }
+
+    /// Returns whether `addr` belongs to the network.
+    pub fn contains(&self, addr: Ipv4Addr) -> bool {
+        u32::from(addr) & Self::mask_bits(self.prefix_len) == u32::from(self.addr)
+    }
+
+    /// Returns whether the networks have any address in common.
+    ///
+    /// Two networks overlap if and only if one of them contains the other.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.contains(other.addr) || other.contains(self.addr)
+    }
+
+    /// Converts a prefix length into a contiguous host-byte-order mask.
+    fn mask_bits(prefix_len: u8) -> u32 {
+        u32::MAX
+            .checked_shl(u32::from(Self::MAX_PREFIX_LEN - prefix_len))
+            .unwrap_or(0)
+    }
+}
+
+impl From<Ipv4Addr> for Ipv4Network {
+    /// Creates a network that only contains `addr`.
+    fn from(addr: Ipv4Addr) -> Self {
+        Self {
+            addr,
+            prefix_len: Self::MAX_PREFIX_LEN,
+        }
+    }
+}
+
+impl fmt::Display for Ipv4Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl FromStr for Ipv4Network {
+    type Err = ParseError;
+
+    /// Parses `addr/prefix_len`, or a single address.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = split_network("IPv4 network", s, Self::MAX_PREFIX_LEN)?;
+        let addr = addr
+            .parse()
+            .map_err(|_| ParseError::new("IPv4 network", format!("bad address: \"{s}\"")))?;
+        Self::new(addr, prefix_len)
+    }
+}
+
+/// An IPv6 network, such as `fe80::/10`.
+///
+/// The host bits of the address are always cleared, so `fe80::1/10` is stored as `fe80::/10`.
+///
+/// # Example
+///
+/// ```
+/// use wfp::Ipv6Network;
+///
+/// let network: Ipv6Network = "2001:db8::/32".parse().unwrap();
+/// assert!(network.overlaps(&"2001:db8:1::/48".parse().unwrap()));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ipv6Network {
+    addr: Ipv6Addr,
+    prefix_len: u8,
+}
+
+impl Ipv6Network {
+    /// The prefix length of a network that contains a single address.
+    pub const MAX_PREFIX_LEN: u8 = 128;
+
+    /// Creates the network `addr/prefix_len`, clearing the host bits of `addr`.
+    ///
+    /// Fails if `prefix_len` is greater than 128.
+    pub fn new(addr: Ipv6Addr, prefix_len: u8) -> Result<Self, ParseError> {
+        if prefix_len > Self::MAX_PREFIX_LEN {
+            return Err(ParseError::new(
+                "IPv6 network",
+                format!("prefix length {prefix_len} exceeds 128"),
+            ));
+        }
+        let mask = Self::mask_bits(prefix_len);
+        Ok(Self {
+            addr: Ipv6Addr::from(u128::from(addr) & mask),
+            prefix_len,
+        })
+    }
+
+    /// Returns the first address of the network.
+    pub fn addr(&self) -> Ipv6Addr {
+        self.addr
+    }
+
+    /// Returns the last address of the network.
+    pub fn last(&self) -> Ipv6Addr {
+        Ipv6Addr::from(u128::from(self.addr) | !Self::mask_bits(self.prefix_len))
+    }
+
+    /// Returns the number of leading bits that are fixed.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns whether `addr` belongs to the network.
+    pub fn contains(&self, addr: Ipv6Addr) -> bool {
+        u128::from(addr) & Self::mask_bits(self.prefix_len) == u128::from(self.addr)
+    }
+
+    /// Returns whether the networks have any address in common.
+    ///
+    /// Two networks overlap if and only if one of them contains the other.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.contains(other.addr) || other.contains(self.addr)
+    }
+
+    /// Converts a prefix length into a contiguous mask.
+    fn mask_bits(prefix_len: u8) -> u128 {
+        u128::MAX
+            .checked_shl(u32::from(Self::MAX_PREFIX_LEN - prefix_len))
+            .unwrap_or(0)
+    }
+}
+
+impl From<Ipv6Addr> for Ipv6Network {
+    /// Creates a network that only contains `addr`.
+    fn from(addr: Ipv6Addr) -> Self {
+        Self {
+            addr,
+            prefix_len: Self::MAX_PREFIX_LEN,
+        }
+    }
+}
+
+impl fmt::Display for Ipv6Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl FromStr for Ipv6Network {
+    type Err = ParseError;
+
+    /// Parses `addr/prefix_len`, or a single address.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = split_network("IPv6 network", s, Self::MAX_PREFIX_LEN)?;
+        let addr = addr
+            .parse()
+            .map_err(|_| ParseError::new("IPv6 network", format!("bad address: \"{s}\"")))?;
+        Self::new(addr, prefix_len)
+    }
+}
+
+/// An IPv4 or IPv6 network.
+///
+/// This is accepted by [`IpAddressConditionBuilder::subnet`](crate::IpAddressConditionBuilder::subnet).
+/// Networks are usually parsed from CIDR notation, and single addresses are parsed as networks
+/// that only contain that address.
+///
+/// # Example
+///
+/// ```
+/// use std::net::Ipv4Addr;
+/// use wfp::IpNetwork;
+///
+/// let network: IpNetwork = "192.168.0.0/16".parse().unwrap();
+/// assert!(network.contains(Ipv4Addr::new(192, 168, 1, 1).into()));
+/// assert_eq!(network.prefix_len(), 16);
+///
+/// // Invalid prefix lengths are errors
+/// assert!("192.168.0.0/33".parse::<IpNetwork>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IpNetwork {
+    /// An IPv4 network.
+    V4(Ipv4Network),
+    /// An IPv6 network.
+    V6(Ipv6Network),
+}
+
+impl IpNetwork {
+    /// Creates the network `addr/prefix_len`, clearing the host bits of `addr`.
+    ///
+    /// Fails if `prefix_len` is greater than the number of bits in `addr`.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, ParseError> {
+        match addr {
+            IpAddr::V4(addr) => Ipv4Network::new(addr, prefix_len).map(Self::V4),
+            IpAddr::V6(addr) => Ipv6Network::new(addr, prefix_len).map(Self::V6),
+        }
+    }
+
+    /// Returns the first address of the network.
+    pub fn addr(&self) -> IpAddr {
+        match self {
+            Self::V4(network) => network.addr().into(),
+            Self::V6(network) => network.addr().into(),
+        }
+    }
+
+    /// Returns the last address of the network.
+    pub fn last(&self) -> IpAddr {
+        match self {
+            Self::V4(network) => network.last().into(),
+            Self::V6(network) => network.last().into(),
+        }
+    }
+
+    /// Returns the number of leading bits that are fixed.
+    pub fn prefix_len(&self) -> u8 {
+        match self {
+            Self::V4(network) => network.prefix_len(),
+            Self::V6(network) => network.prefix_len(),
+        }
+    }
+
+    /// Returns whether `addr` belongs to the network. Addresses of the other family never do.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self, addr) {
+            (Self::V4(network), IpAddr::V4(addr)) => network.contains(addr),
+            (Self::V6(network), IpAddr::V6(addr)) => network.contains(addr),
+            _ => false,
+        }
+    }
+
+    /// Returns whether the networks have any address in common. Networks of different
+    /// families never do.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::V4(network), Self::V4(other)) => network.overlaps(other),
+            (Self::V6(network), Self::V6(other)) => network.overlaps(other),
+            _ => false,
+        }
+    }
+}
+
+impl From<Ipv4Network> for IpNetwork {
+    fn from(network: Ipv4Network) -> Self {
+        Self::V4(network)
+    }
+}
+
+impl From<Ipv6Network> for IpNetwork {
+    fn from(network: Ipv6Network) -> Self {
+        Self::V6(network)
+    }
+}
+
+impl From<IpAddr> for IpNetwork {
+    /// Creates a network that only contains `addr`.
+    fn from(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(addr) => Self::V4(addr.into()),
+            IpAddr::V6(addr) => Self::V6(addr.into()),
+        }
+    }
+}
+
+impl From<Ipv4Addr> for IpNetwork {
+    /// Creates a network that only contains `addr`.
+    fn from(addr: Ipv4Addr) -> Self {
+        Self::V4(addr.into())
+    }
+}
+
+impl From<Ipv6Addr> for IpNetwork {
+    /// Creates a network that only contains `addr`.
+    fn from(addr: Ipv6Addr) -> Self {
+        Self::V6(addr.into())
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V4(network) => network.fmt(f),
+            Self::V6(network) => network.fmt(f),
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = ParseError;
+
+    /// Parses `addr/prefix_len`, or a single address.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Only IPv6 addresses contain colons
+        if s.contains(':') {
+            s.parse().map(Self::V6)
+        } else {
+            s.parse().map(Self::V4)
+        }
+    }
+}
+
+/// A set of IPv4 and IPv6 addresses, for building address conditions.
+///
+/// Sets are built from networks and ranges, and combined using [`union`](Self::union),
+/// [`intersection`](Self::intersection), [`difference`](Self::difference) and
+/// [`complement`](Self::complement). [`conditions`](Self::conditions) then returns the fewest
+/// address conditions that together match exactly the addresses in the set.
+///
+/// # Example
+///
+/// ```
+/// use wfp::{IpAddressConditionBuilder, IpFamily, IpNetwork, IpSet};
+///
+/// # fn main() -> wfp::Result<()> {
+/// // Everything except private networks and the VPN server
+/// let excluded: IpSet = ["10.0.0.0/8", "192.168.0.0/16", "203.0.113.7"]
+///     .iter()
+///     .map(|network| network.parse::<IpNetwork>())
+///     .collect::<Result<_, _>>()?;
+/// let allowed = IpSet::all_v4().difference(&excluded);
+///
+/// assert!(!allowed.contains("10.1.2.3".parse().unwrap()));
+/// assert!(allowed.contains("203.0.113.8".parse().unwrap()));
+///
+/// let conditions = allowed.conditions(IpFamily::V4, IpAddressConditionBuilder::remote())?;
+/// assert_eq!(conditions.len(), 4);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IpSet {
+    v4: RangeSet<u32>,
+    v6: RangeSet<u128>,
+}
+
+impl IpSet {
+    /// Returns an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the set of all IPv4 and IPv6 addresses.
+    pub fn all() -> Self {
+        Self {
+            v4: RangeSet::full(),
+            v6: RangeSet::full(),
+        }
+    }
+
+    /// Returns the set of all IPv4 addresses.
+    pub fn all_v4() -> Self {
+        Self {
+            v4: RangeSet::full(),
+            v6: RangeSet::new(),
+        }
+    }
+
+    /// Returns the set of all IPv6 addresses.
+    pub fn all_v6() -> Self {
+        Self {
+            v4: RangeSet::new(),
+            v6: RangeSet::full(),
+        }
+    }
+
+    /// Adds the addresses in `network` to the set.
+    pub fn insert(&mut self, network: impl Into<IpNetwork>) {
+        match network.into() {
+            IpNetwork::V4(network) => self
+                .v4
+                .insert(u32::from(network.addr()), u32::from(network.last())),
+            IpNetwork::V6(network) => self
+                .v6
+                .insert(u128::from(network.addr()), u128::from(network.last())),
+        }
+    }
+
+    /// Adds the addresses from `start` to `end`, inclusive, to the set.
+    ///
+    /// Fails with [`ConditionError::AddressFamilyMismatch`] if `start` and `end` are of
+    /// different families, or with [`ConditionError::InvalidRange`] if `start > end`.
+    pub fn insert_range(
+        &mut self,
+        start: impl Into<IpAddr>,
+        end: impl Into<IpAddr>,
+    ) -> WfpResult<()> {
+        match (start.into(), end.into()) {
+            (IpAddr::V4(start), IpAddr::V4(end)) if start <= end => {
+                self.v4.insert(u32::from(start), u32::from(end))
+            }
+            (IpAddr::V6(start), IpAddr::V6(end)) if start <= end => {
+                self.v6.insert(u128::from(start), u128::from(end))
+            }
+            (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => {
+                return Err(WfpError::InvalidCondition(ConditionError::InvalidRange));
+            }
+            _ => {
+                return Err(WfpError::InvalidCondition(
+                    ConditionError::AddressFamilyMismatch,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the addresses in `network` from the set.
+    pub fn remove(&mut self, network: impl Into<IpNetwork>) {
+        match network.into() {
+            IpNetwork::V4(network) => self
+                .v4
+                .remove(u32::from(network.addr()), u32::from(network.last())),
+            IpNetwork::V6(network) => self
+                .v6
+                .remove(u128::from(network.addr()), u128::from(network.last())),
+        }
+    }
+
+    /// Returns whether `addr` is in the set.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match addr {
+            IpAddr::V4(addr) => self.v4.contains(u32::from(addr)),
+            IpAddr::V6(addr) => self.v6.contains(u128::from(addr)),
+        }
+    }
+
+    /// Returns whether the set contains no addresses.
+    pub fn is_empty(&self) -> bool {
+        self.v4.is_empty() && self.v6.is_empty()
+    }
+
+    /// Returns the addresses that are in `self`, `other` or both.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            v4: self.v4.union(&other.v4),
+            v6: self.v6.union(&other.v6),
+        }
+    }
+
+    /// Returns the addresses that are in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            v4: self.v4.intersection(&other.v4),
+            v6: self.v6.intersection(&other.v6),
+        }
+    }
+
+    /// Returns the addresses that are in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            v4: self.v4.difference(&other.v4),
+            v6: self.v6.difference(&other.v6),
+        }
+    }
+
+    /// Returns the IPv4 and IPv6 addresses that are not in `self`.
+    ///
+    /// To only complement the addresses of one family, use [`difference`](Self::difference)
+    /// with [`all_v4`](Self::all_v4) or [`all_v6`](Self::all_v6) instead.
+    pub fn complement(&self) -> Self {
+        Self {
+            v4: self.v4.complement(),
+            v6: self.v6.complement(),
+        }
+    }
+
+    /// Returns the contiguous ranges of addresses in the set, as `(first, last)` pairs in
+    /// ascending order. IPv4 ranges come first.
+    pub fn ranges(&self) -> Vec<(IpAddr, IpAddr)> {
+        let v4 = self.v4.ranges().iter().map(|&(start, end)| {
+            (
+                IpAddr::V4(Ipv4Addr::from(start)),
+                IpAddr::V4(Ipv4Addr::from(end)),
+            )
+        });
+        let v6 = self.v6.ranges().iter().map(|&(start, end)| {
+            (
+                IpAddr::V6(Ipv6Addr::from(start)),
+                IpAddr::V6(Ipv6Addr::from(end)),
+            )
+        });
+        v4.chain(v6).collect()
+    }
+
+    /// Returns the fewest networks that together contain exactly the addresses in the set,
+    /// in ascending order. IPv4 networks come first.
+    pub fn networks(&self) -> Vec<IpNetwork> {
+        let v4 = self.v4.ranges().iter().flat_map(|&(start, end)| {
+            cidr_blocks(
+                u128::from(start),
+                u128::from(end),
+                Ipv4Network::MAX_PREFIX_LEN,
+            )
+            .into_iter()
+            .map(|(addr, prefix_len)| {
+                // The block is within the range, so the address fits in 32 bits
+                let addr = Ipv4Addr::from(u32::try_from(addr).unwrap());
+                IpNetwork::V4(Ipv4Network { addr, prefix_len })
+            })
+        });
+        let v6 = self.v6.ranges().iter().flat_map(|&(start, end)| {
+            cidr_blocks(start, end, Ipv6Network::MAX_PREFIX_LEN)
+                .into_iter()
+                .map(|(addr, prefix_len)| {
+                    IpNetwork::V6(Ipv6Network {
+                        addr: Ipv6Addr::from(addr),
+                        prefix_len,
+                    })
+                })
+        });
+        v4.chain(v6).collect()
+    }
+
+    /// Returns the fewest conditions that together match exactly the addresses of `family`
+    /// in the set.
+    ///
+    /// Each contiguous range of addresses becomes a single condition: a subnet condition if
+    /// the range is a network, and a range condition otherwise. Conditions on the same field
+    /// are combined with logical OR, so all of the conditions should be added to the same
+    /// filter.
+    ///
+    /// If the set contains no addresses of `family`, no conditions are returned. A filter
+    /// without address conditions matches every address, so check for this before adding a
+    /// filter that is meant to match the set.
+    ///
+    /// IPv4-mapped addresses in the set are matched as IPv6 addresses, since the set already
+    /// separates IPv4 addresses from IPv6 addresses.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WfpError::InvalidParameter`] if
+    /// [`unmap_v4`](IpAddressConditionBuilder::unmap_v4) was called on `builder`.
+    pub fn conditions(
+        &self,
+        family: IpFamily,
+        builder: IpAddressConditionBuilder<IpAddressConditionBuilderMissingValue>,
+    ) -> WfpResult<Vec<Condition>> {
+        if builder.unmaps_v4() {
+            return Err(WfpError::InvalidParameter);
+        }
+        let ranges = self.ranges();
+        ranges
+            .into_iter()
+            .filter(|(start, _)| match family {
+                IpFamily::V4 => start.is_ipv4(),
+                IpFamily::V6 => start.is_ipv6(),
+            })
+            .map(|(start, end)| {
+                let builder = builder.clone();
+                let network = IpNetwork::new(start, common_prefix_len(start, end))
+                    .expect("the common prefix is not longer than the address");
+                if network.addr() == start && network.last() == end {
+                    Ok(builder.subnet(network).build())
+                } else {
+                    Ok(builder.range(start, end)?.build())
+                }
+            })
+            .collect()
+    }
+}
+
+impl<N: Into<IpNetwork>> FromIterator<N> for IpSet {
+    fn from_iter<I: IntoIterator<Item = N>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<N: Into<IpNetwork>> Extend<N> for IpSet {
+    fn extend<I: IntoIterator<Item = N>>(&mut self, iter: I) {
+        let mut v4 = vec![];
+        let mut v6 = vec![];
+        for network in iter {
+            match network.into() {
+                IpNetwork::V4(network) => {
+                    v4.push((u32::from(network.addr()), u32::from(network.last())))
+                }
+                IpNetwork::V6(network) => {
+                    v6.push((u128::from(network.addr()), u128::from(network.last())))
+                }
+            }
+        }
+        self.v4 = self.v4.union(&v4.into_iter().collect());
+        self.v6 = self.v6.union(&v6.into_iter().collect());
+    }
+}
+
+/// Returns the length of the longest prefix that `start` and `end` have in common. They must
+/// be of the same family.
+fn common_prefix_len(start: IpAddr, end: IpAddr) -> u8 {
+    let leading_zeros = match (start, end) {
+        (IpAddr::V4(start), IpAddr::V4(end)) => (u32::from(start) ^ u32::from(end)).leading_zeros(),
+        (IpAddr::V6(start), IpAddr::V6(end)) => {
+            (u128::from(start) ^ u128::from(end)).leading_zeros()
+        }
+        _ => 0,
+    };
+    u8::try_from(leading_zeros).unwrap()
+}
+
+/// Splits `start..=end` into the fewest aligned blocks, as `(first address, prefix length)`
+/// pairs. `bits` is the number of bits in an address.
+fn cidr_blocks(mut start: u128, end: u128, bits: u8) -> Vec<(u128, u8)> {
+    // Returns a mask of the `host_bits` lowest bits
+    let host_mask = |host_bits: u32| u128::MAX.checked_shr(128 - host_bits).unwrap_or(0);
+
+    let mut blocks = vec![];
+    loop {
+        // The largest block starting at `start` is limited by the alignment of `start`, and
+        // must not extend past `end`
+        let mut host_bits = start.trailing_zeros().min(u32::from(bits));
+        while start | host_mask(host_bits) > end {
+            host_bits -= 1;
+        }
+        let last = start | host_mask(host_bits);
+        blocks.push((start, bits - u8::try_from(host_bits).unwrap()));
+        if last >= end {
+            return blocks;
+        }
+        start = last + 1;
+    }
+}
+
+/// Splits `addr/prefix_len` into the address and prefix length. The prefix length defaults to
+/// `max_prefix_len` if it is omitted.
+fn split_network<'a>(
+    what: &'static str,
+    s: &'a str,
+    max_prefix_len: u8,
+) -> Result<(&'a str, u8), ParseError> {
+    let Some((addr, prefix_len)) = s.split_once('/') else {
+        return Ok((s, max_prefix_len));
+    };
+    // Reject signs and whitespace, which `u8::from_str` would partly accept
+    if prefix_len.is_empty() || !prefix_len.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::new(what, format!("bad prefix length: \"{s}\"")));
+    }
+    let prefix_len = prefix_len
+        .parse()
+        .map_err(|_| ParseError::new(what, format!("prefix length exceeds {max_prefix_len}")))?;
+    Ok((addr, prefix_len))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_network() {
+        let network: IpNetwork = "10.1.2.3/8".parse().unwrap();
+        assert_eq!(network.addr(), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)));
+        assert_eq!(network.last(), IpAddr::V4(Ipv4Addr::new(10, 255, 255, 255)));
+        assert_eq!(network.to_string(), "10.0.0.0/8");
+
+        let network: IpNetwork = "fe80::1/10".parse().unwrap();
+        assert_eq!(network.to_string(), "fe80::/10");
+        assert_eq!(network.prefix_len(), 10);
+
+        // Single addresses are host networks
+        let network: IpNetwork = "192.168.1.1".parse().unwrap();
+        assert_eq!(network.to_string(), "192.168.1.1/32");
+        assert_eq!("::1".parse::<IpNetwork>().unwrap().prefix_len(), 128);
+
+        assert_eq!("0.0.0.0/0".parse::<Ipv4Network>().unwrap().prefix_len(), 0);
+        assert_eq!(
+            "::/0".parse::<Ipv6Network>().unwrap().last(),
+            Ipv6Addr::from(u128::MAX)
+        );
+
+        for invalid in [
+            "",
+            "10.0.0.0/",
+            "10.0.0.0/33",
+            "10.0.0.0/256",
+            "10.0.0.0/+8",
+            "10.0.0.0/ 8",
+            "10.0.0/8",
+            "::/129",
+            "fe80::/x",
+            "10.0.0.0/8/8",
+        ] {
+            assert!(invalid.parse::<IpNetwork>().is_err(), "{invalid}");
+        }
+        assert!("::/64".parse::<Ipv4Network>().is_err());
+        assert!("10.0.0.0/8".parse::<Ipv6Network>().is_err());
+    }
+
+    #[test]
+    fn test_new_network() {
+        let network = Ipv4Network::new(Ipv4Addr::new(192, 168, 255, 1), 16).unwrap();
+        assert_eq!(network.addr(), Ipv4Addr::new(192, 168, 0, 0));
+        assert_eq!(network.mask(), Ipv4Addr::new(255, 255, 0, 0));
+        assert_eq!(
+            Ipv4Network::new(Ipv4Addr::BROADCAST, 0).unwrap().mask(),
+            Ipv4Addr::UNSPECIFIED
+        );
+        assert_eq!(
+            Ipv4Network::new(Ipv4Addr::BROADCAST, 32).unwrap().mask(),
+            Ipv4Addr::BROADCAST
+        );
+        assert!(Ipv4Network::new(Ipv4Addr::UNSPECIFIED, 33).is_err());
+        assert!(Ipv6Network::new(Ipv6Addr::UNSPECIFIED, 129).is_err());
+    }
+
+    #[test]
+    fn test_contains_and_overlaps() {
+        let network: IpNetwork = "10.0.0.0/8".parse().unwrap();
+        assert!(network.contains(Ipv4Addr::new(10, 0, 0, 0).into()));
+        assert!(network.contains(Ipv4Addr::new(10, 255, 255, 255).into()));
+        assert!(!network.contains(Ipv4Addr::new(11, 0, 0, 0).into()));
+        assert!(!network.contains(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped().into()));
+
+        let inner: IpNetwork = "10.20.0.0/16".parse().unwrap();
+        let other: IpNetwork = "172.16.0.0/12".parse().unwrap();
+        assert!(network.overlaps(&inner));
+        assert!(inner.overlaps(&network));
+        assert!(!network.overlaps(&other));
+        assert!(!network.overlaps(&"::/0".parse().unwrap()));
+
+        let v6: IpNetwork = "2001:db8::/32".parse().unwrap();
+        assert!(v6.contains("2001:db8:ffff::1".parse().unwrap()));
+        assert!(!v6.contains("2001:db9::".parse().unwrap()));
+        assert!(v6.overlaps(&"::/0".parse().unwrap()));
+    }
+
+    fn networks(set: &IpSet) -> Vec<String> {
+        set.networks().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_ip_set_algebra() {
+        let excluded: IpSet = ["10.0.0.0/8", "192.168.0.0/16", "203.0.113.7"]
+            .iter()
+            .map(|network| network.parse::<IpNetwork>().unwrap())
+            .collect();
+        let allowed = IpSet::all_v4().difference(&excluded);
+
+        assert_eq!(
+            allowed.ranges(),
+            [
+                ("0.0.0.0", "9.255.255.255"),
+                ("11.0.0.0", "192.167.255.255"),
+                ("192.169.0.0", "203.0.113.6"),
+                ("203.0.113.8", "255.255.255.255"),
+            ]
+            .map(|(start, end)| (start.parse().unwrap(), end.parse().unwrap()))
+        );
+        // Same as Python's `ipaddress.summarize_address_range`
+        let cover = networks(&allowed);
+        assert_eq!(cover.len(), 48);
+        assert_eq!(cover[..3], ["0.0.0.0/5", "8.0.0.0/7", "11.0.0.0/8"]);
+        assert_eq!(cover[27], "203.0.113.6/32");
+        assert_eq!(cover[47], "224.0.0.0/3");
+
+        assert!(allowed.intersection(&excluded).is_empty());
+        assert_eq!(allowed.union(&excluded), IpSet::all_v4());
+        assert_eq!(allowed.complement(), excluded.union(&IpSet::all_v6()));
+
+        let mut set = IpSet::all_v6();
+        set.remove("fe00::/7".parse::<IpNetwork>().unwrap());
+        assert_eq!(
+            networks(&set),
+            [
+                "::/1", "8000::/2", "c000::/3", "e000::/4", "f000::/5", "f800::/6", "fc00::/7"
+            ]
+        );
+        assert_eq!(networks(&IpSet::all()), ["0.0.0.0/0", "::/0"]);
+        assert!(networks(&IpSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_ip_set_ranges() {
+        let mut set = IpSet::new();
+        set.insert_range(Ipv4Addr::new(10, 0, 0, 5), Ipv4Addr::new(10, 0, 0, 20))
+            .unwrap();
+        assert_eq!(
+            networks(&set),
+            [
+                "10.0.0.5/32",
+                "10.0.0.6/31",
+                "10.0.0.8/29",
+                "10.0.0.16/30",
+                "10.0.0.20/32"
+            ]
+        );
+        assert!(set.contains(Ipv4Addr::new(10, 0, 0, 20).into()));
+        assert!(!set.contains(Ipv4Addr::new(10, 0, 0, 21).into()));
+
+        assert!(matches!(
+            set.insert_range(Ipv4Addr::LOCALHOST, Ipv6Addr::LOCALHOST),
+            Err(WfpError::InvalidCondition(
+                ConditionError::AddressFamilyMismatch
+            ))
+        ));
+        assert!(matches!(
+            set.insert_range(Ipv6Addr::LOCALHOST, Ipv6Addr::UNSPECIFIED),
+            Err(WfpError::InvalidCondition(ConditionError::InvalidRange))
+        ));
+    }
+
+    #[test]
+    fn test_ip_set_conditions() {
+        use crate::condition::DataType;
+
+        let mut set: IpSet = ["192.168.0.0/16", "10.0.0.1", "2001:db8::/32"]
+            .iter()
+            .map(|network| network.parse::<IpNetwork>().unwrap())
+            .collect();
+        set.insert_range(Ipv4Addr::new(172, 16, 0, 3), Ipv4Addr::new(172, 16, 0, 9))
+            .unwrap();
+        // Adjacent to 192.168.0.0/16, but the union is not a network
+        set.insert("192.169.0.0/24".parse::<IpNetwork>().unwrap());
+
+        let conditions = set
+            .conditions(IpFamily::V4, IpAddressConditionBuilder::remote())
+            .unwrap();
+        let types: Vec<_> = conditions
+            .iter()
+            .map(|condition| condition.value_type())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                Some(DataType::UInt32),
+                Some(DataType::UInt32),
+                Some(DataType::UInt32)
+            ]
+        );
+        // The range conditions have the range match type, and the address is an exact match
+        let match_types: Vec<_> = conditions
+            .iter()
+            .map(|condition| condition.raw_condition().matchType)
+            .collect();
+        assert_eq!(
+            match_types,
+            [
+                crate::MatchType::Equal as i32,
+                crate::MatchType::Range as i32,
+                crate::MatchType::Range as i32
+            ]
+        );
+
+        let conditions = set
+            .conditions(IpFamily::V6, IpAddressConditionBuilder::local())
+            .unwrap();
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].value_type(), Some(DataType::V6AddrMask));
+
+        assert!(
+            IpSet::all_v6()
+                .conditions(IpFamily::V4, IpAddressConditionBuilder::remote())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_ip_set_conditions_unmap_v4() {
+        use crate::condition::DataType;
+
+        // A range that crosses into the IPv4-mapped block ::ffff:0:0/96
+        let mut set = IpSet::new();
+        set.insert_range(
+            "::fffe:ffff:ffff".parse::<Ipv6Addr>().unwrap(),
+            "::ffff:1.2.3.4".parse::<Ipv6Addr>().unwrap(),
+        )
+        .unwrap();
+        // An IPv4-mapped network
+        set.insert("::ffff:10.0.0.0/104".parse::<IpNetwork>().unwrap());
+
+        let conditions = set
+            .conditions(IpFamily::V6, IpAddressConditionBuilder::remote())
+            .unwrap();
+        let types: Vec<_> = conditions
+            .iter()
+            .map(|condition| condition.value_type())
+            .collect();
+        // Both the range and the network are matched as IPv6 addresses
+        assert_eq!(
+            types,
+            [Some(DataType::ByteArray16), Some(DataType::V6AddrMask)]
+        );
+
+        // Unmapping is rejected rather than ignored
+        for family in [IpFamily::V4, IpFamily::V6] {
+            assert!(matches!(
+                set.conditions(family, IpAddressConditionBuilder::remote().unmap_v4()),
+                Err(WfpError::InvalidParameter)
+            ));
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Engine-wide options.
+
+use std::time::Duration;
+
+use windows_sys::Win32::Foundation::FWP_E_TYPE_MISMATCH;
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWP_UINT32, FWP_VALUE0, FWPM_ENGINE_COLLECT_NET_EVENTS, FWPM_ENGINE_MONITOR_IPSEC_CONNECTIONS,
+    FWPM_ENGINE_NET_EVENT_MATCH_ANY_KEYWORDS, FWPM_ENGINE_OPTION,
+    FWPM_ENGINE_OPTION_PACKET_BATCH_INBOUND, FWPM_ENGINE_OPTION_PACKET_QUEUE_FORWARD,
+    FWPM_ENGINE_OPTION_PACKET_QUEUE_INBOUND, FWPM_ENGINE_OPTION_PACKET_QUEUE_NONE,
+    FWPM_ENGINE_PACKET_QUEUING, FWPM_ENGINE_TXN_WATCHDOG_TIMEOUT_IN_MSEC,
+    FWPM_NET_EVENT_KEYWORD_CAPABILITY_ALLOW, FWPM_NET_EVENT_KEYWORD_CAPABILITY_DROP,
+    FWPM_NET_EVENT_KEYWORD_CLASSIFY_ALLOW, FWPM_NET_EVENT_KEYWORD_INBOUND_BCAST,
+    FWPM_NET_EVENT_KEYWORD_INBOUND_MCAST, FWPM_NET_EVENT_KEYWORD_PORT_SCANNING_DROP,
+};
+
+use crate::error::{Result, WfpError};
+use crate::util::flags_type;
+
+/// Identifies an engine option.
+///
+/// These correspond to the [`FWPM_ENGINE_OPTION`] enumeration values.
+///
+/// [`FWPM_ENGINE_OPTION`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ne-fwpmtypes-fwpm_engine_option
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EngineOptionKind {
+    /// See [`EngineOption::CollectNetEvents`].
+    CollectNetEvents = FWPM_ENGINE_COLLECT_NET_EVENTS,
+    /// See [`EngineOption::NetEventMatchAnyKeywords`].
+    NetEventMatchAnyKeywords = FWPM_ENGINE_NET_EVENT_MATCH_ANY_KEYWORDS,
+    /// See [`EngineOption::MonitorIpsecConnections`].
+    MonitorIpsecConnections = FWPM_ENGINE_MONITOR_IPSEC_CONNECTIONS,
+    /// See [`EngineOption::PacketQueuing`].
+    PacketQueuing = FWPM_ENGINE_PACKET_QUEUING,
+    /// See [`EngineOption::TxnWatchdogTimeout`].
+    TxnWatchdogTimeout = FWPM_ENGINE_TXN_WATCHDOG_TIMEOUT_IN_MSEC,
+}
+
+impl EngineOptionKind {
+    /// Returns the raw `FWPM_ENGINE_OPTION` value.
+    pub fn as_raw(self) -> FWPM_ENGINE_OPTION {
+        self as FWPM_ENGINE_OPTION
+    }
+}
+
+/// An engine option along with its value.
+///
+/// Options apply to the whole engine rather than to a single session. They are read using
+/// [`FilterEngine::option`](crate::FilterEngine::option) and changed using
+/// [`FilterEngine::set_option`](crate::FilterEngine::set_option).
+///
+/// # Example
+///
+/// ```
+/// use wfp::{EngineOption, EngineOptionKind, FilterEngineBuilder, MemoryBfe, NetEventKeywords};
+///
+/// let bfe = MemoryBfe::new();
+/// let engine = FilterEngineBuilder::default().dynamic().open_in_memory(&bfe).unwrap();
+///
+/// engine.set_option(EngineOption::CollectNetEvents(true)).unwrap();
+/// engine
+///     .set_option(EngineOption::NetEventMatchAnyKeywords(
+///         NetEventKeywords::INBOUND_MCAST | NetEventKeywords::INBOUND_BCAST,
+///     ))
+///     .unwrap();
+///
+/// assert_eq!(
+///     engine.option(EngineOptionKind::CollectNetEvents).unwrap(),
+///     EngineOption::CollectNetEvents(true),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EngineOption {
+    /// Whether the engine collects network events
+    /// (`FWPM_ENGINE_COLLECT_NET_EVENTS`).
+    CollectNetEvents(bool),
+    /// Network events that are collected in addition to the default ones
+    /// (`FWPM_ENGINE_NET_EVENT_MATCH_ANY_KEYWORDS`).
+    NetEventMatchAnyKeywords(NetEventKeywords),
+    /// Whether the engine monitors IPsec connections
+    /// (`FWPM_ENGINE_MONITOR_IPSEC_CONNECTIONS`).
+    MonitorIpsecConnections(bool),
+    /// Which packets may be queued by the engine (`FWPM_ENGINE_PACKET_QUEUING`).
+    PacketQueuing(PacketQueuing),
+    /// How long a transaction may be held before it is aborted by the engine
+    /// (`FWPM_ENGINE_TXN_WATCHDOG_TIMEOUT_IN_MSEC`).
+    ///
+    /// The value is stored in milliseconds. Longer timeouts are saturated.
+    TxnWatchdogTimeout(Duration),
+}
+
+impl EngineOption {
+    /// Returns the kind of the option.
+    pub fn kind(&self) -> EngineOptionKind {
+        match self {
+            Self::CollectNetEvents(_) => EngineOptionKind::CollectNetEvents,
+            Self::NetEventMatchAnyKeywords(_) => EngineOptionKind::NetEventMatchAnyKeywords,
+            Self::MonitorIpsecConnections(_) => EngineOptionKind::MonitorIpsecConnections,
+            Self::PacketQueuing(_) => EngineOptionKind::PacketQueuing,
+            Self::TxnWatchdogTimeout(_) => EngineOptionKind::TxnWatchdogTimeout,
+        }
+    }
+
+    /// Converts the value of the option to an `FWP_VALUE0`.
+    ///
+    /// Every option is stored as an `FWP_UINT32`.
+    pub(crate) fn to_value(self) -> FWP_VALUE0 {
+        let val = match self {
+            Self::CollectNetEvents(enabled) | Self::MonitorIpsecConnections(enabled) => {
+                u32::from(enabled)
+            }
+            Self::NetEventMatchAnyKeywords(keywords) => keywords.bits(),
+            Self::PacketQueuing(queuing) => queuing.bits(),
+            Self::TxnWatchdogTimeout(timeout) => {
+                u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX)
+            }
+        };
+        let mut value = FWP_VALUE0 {
+            r#type: FWP_UINT32,
+            ..Default::default()
+        };
+        value.Anonymous.uint32 = val;
+        value
+    }
+
+    /// Converts an `FWP_VALUE0` returned by the engine to an option of the given kind.
+    ///
+    /// Fails with the error corresponding to `FWP_E_TYPE_MISMATCH` if the value is not an
+    /// `FWP_UINT32`.
+    pub(crate) fn from_value(kind: EngineOptionKind, value: &FWP_VALUE0) -> Result<Self> {
+        if value.r#type != FWP_UINT32 {
+            return Err(WfpError::from_code(FWP_E_TYPE_MISMATCH));
+        }
+        // SAFETY: The type is FWP_UINT32
+        let val = unsafe { value.Anonymous.uint32 };
+        Ok(match kind {
+            EngineOptionKind::CollectNetEvents => Self::CollectNetEvents(val != 0),
+            EngineOptionKind::NetEventMatchAnyKeywords => {
+                Self::NetEventMatchAnyKeywords(NetEventKeywords::from_bits_retain(val))
+            }
+            EngineOptionKind::MonitorIpsecConnections => Self::MonitorIpsecConnections(val != 0),
+            EngineOptionKind::PacketQueuing => {
+                Self::PacketQueuing(PacketQueuing::from_bits_retain(val))
+            }
+            EngineOptionKind::TxnWatchdogTimeout => {
+                Self::TxnWatchdogTimeout(Duration::from_millis(u64::from(val)))
+            }
+        })
+    }
+}
+
+flags_type! {
+    /// Network events to collect in addition to the default ones.
+    ///
+    /// These correspond to the `FWPM_NET_EVENT_KEYWORD_*` flags. See
+    /// [`FWPM_ENGINE_OPTION`] for details.
+    ///
+    /// [`FWPM_ENGINE_OPTION`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ne-fwpmtypes-fwpm_engine_option
+    pub struct NetEventKeywords {
+        /// Inbound multicast traffic that is dropped (`FWPM_NET_EVENT_KEYWORD_INBOUND_MCAST`).
+        const INBOUND_MCAST = FWPM_NET_EVENT_KEYWORD_INBOUND_MCAST;
+        /// Inbound broadcast traffic that is dropped (`FWPM_NET_EVENT_KEYWORD_INBOUND_BCAST`).
+        const INBOUND_BCAST = FWPM_NET_EVENT_KEYWORD_INBOUND_BCAST;
+        /// Traffic dropped by AppContainer capability checks
+        /// (`FWPM_NET_EVENT_KEYWORD_CAPABILITY_DROP`).
+        const CAPABILITY_DROP = FWPM_NET_EVENT_KEYWORD_CAPABILITY_DROP;
+        /// Traffic allowed by AppContainer capability checks
+        /// (`FWPM_NET_EVENT_KEYWORD_CAPABILITY_ALLOW`).
+        const CAPABILITY_ALLOW = FWPM_NET_EVENT_KEYWORD_CAPABILITY_ALLOW;
+        /// Traffic allowed by a filter (`FWPM_NET_EVENT_KEYWORD_CLASSIFY_ALLOW`).
+        const CLASSIFY_ALLOW = FWPM_NET_EVENT_KEYWORD_CLASSIFY_ALLOW;
+        /// Traffic dropped by port scanning prevention
+        /// (`FWPM_NET_EVENT_KEYWORD_PORT_SCANNING_DROP`).
+        const PORT_SCANNING_DROP = FWPM_NET_EVENT_KEYWORD_PORT_SCANNING_DROP;
+    }
+}
+
+flags_type! {
+    /// Packets that the engine may queue.
+    ///
+    /// These correspond to the `FWPM_ENGINE_OPTION_PACKET_*` flags. See
+    /// [`FWPM_ENGINE_OPTION`] for details.
+    ///
+    /// [`FWPM_ENGINE_OPTION`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ne-fwpmtypes-fwpm_engine_option
+    pub struct PacketQueuing {
+        /// Do not queue packets (`FWPM_ENGINE_OPTION_PACKET_QUEUE_NONE`).
+        const NONE = FWPM_ENGINE_OPTION_PACKET_QUEUE_NONE;
+        /// Queue inbound packets (`FWPM_ENGINE_OPTION_PACKET_QUEUE_INBOUND`).
+        const INBOUND = FWPM_ENGINE_OPTION_PACKET_QUEUE_INBOUND;
+        /// Queue forwarded packets (`FWPM_ENGINE_OPTION_PACKET_QUEUE_FORWARD`).
+        const FORWARD = FWPM_ENGINE_OPTION_PACKET_QUEUE_FORWARD;
+        /// Batch inbound packets (`FWPM_ENGINE_OPTION_PACKET_BATCH_INBOUND`).
+        const BATCH_INBOUND = FWPM_ENGINE_OPTION_PACKET_BATCH_INBOUND;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_option_value_round_trip() {
+        let options = [
+            EngineOption::CollectNetEvents(true),
+            EngineOption::NetEventMatchAnyKeywords(
+                NetEventKeywords::INBOUND_MCAST | NetEventKeywords::CLASSIFY_ALLOW,
+            ),
+            EngineOption::MonitorIpsecConnections(false),
+            EngineOption::PacketQueuing(PacketQueuing::INBOUND | PacketQueuing::FORWARD),
+            EngineOption::TxnWatchdogTimeout(Duration::from_millis(2500)),
+        ];
+        for option in options {
+            let value = option.to_value();
+            assert_eq!(value.r#type, FWP_UINT32);
+            assert_eq!(
+                EngineOption::from_value(option.kind(), &value).unwrap(),
+                option
+            );
+        }
+    }
+
+    #[test]
+    fn test_option_type_mismatch() {
+        let value = FWP_VALUE0::default();
+        let err = EngineOption::from_value(EngineOptionKind::CollectNetEvents, &value).unwrap_err();
+        assert_eq!(err.code(), Some(FWP_E_TYPE_MISMATCH));
+    }
+
+    #[test]
+    fn test_flags_debug() {
+        assert_eq!(
+            format!(
+                "{:?}",
+                NetEventKeywords::INBOUND_MCAST | NetEventKeywords::from_bits_retain(0x100)
+            ),
+            "NetEventKeywords(INBOUND_MCAST | 0x100)"
+        );
+        assert_eq!(format!("{:?}", PacketQueuing::NONE), "PacketQueuing(0x0)");
+    }
+}
@@ -0,0 +1,400 @@
+//! Sets of ports.
+
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::condition::{Condition, PortConditionBuilder, PortConditionBuilderMissingValue};
+use crate::error::ParseError;
+use crate::range_set::RangeSet;
+
+/// A set of ports, for building port conditions.
+///
+/// A set can be parsed from a comma-separated list of ports and inclusive port ranges, such as
+/// `"22, 80, 443, 8000-8100"`. Items prefixed with `!` are excluded from the set, regardless of
+/// their position in the list. A list that only contains exclusions, such as `"!53"`, contains
+/// all other ports.
+///
+/// [`conditions`](Self::conditions) returns the fewest port conditions that together match
+/// exactly the ports in the set.
+///
+/// # Example
+///
+/// ```
+/// use wfp::{PortConditionBuilder, PortSet};
+///
+/// # fn main() -> Result<(), wfp::ParseError> {
+/// let ports: PortSet = "22, 80, 443, 8000-8100, !8080".parse()?;
+/// assert!(ports.contains(8081));
+/// assert!(!ports.contains(8080));
+/// assert_eq!(ports.to_string(), "22, 80, 443, 8000-8079, 8081-8100");
+///
+/// // 3 equality conditions and 2 range conditions
+/// let conditions = ports.conditions(PortConditionBuilder::remote());
+/// assert_eq!(conditions.len(), 5);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PortSet {
+    ports: RangeSet<u16>,
+}
+
+impl PortSet {
+    /// Returns an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the set of all ports.
+    pub fn all() -> Self {
+        Self {
+            ports: RangeSet::full(),
+        }
+    }
+
+    /// Adds `port` to the set.
+    pub fn insert(&mut self, port: u16) {
+        self.ports.insert(port, port);
+    }
+
+    /// Adds the ports in `ports` to the set. Does nothing if `ports` is empty.
+    pub fn insert_range(&mut self, ports: RangeInclusive<u16>) {
+        self.ports.insert(*ports.start(), *ports.end());
+    }
+
+    /// Removes `port` from the set.
+    pub fn remove(&mut self, port: u16) {
+        self.ports.remove(port, port);
+    }
+
+    /// Removes the ports in `ports` from the set. Does nothing if `ports` is empty.
+    pub fn remove_range(&mut self, ports: RangeInclusive<u16>) {
+        self.ports.remove(*ports.start(), *ports.end());
+    }
+
+    /// Returns whether `port` is in the set.
+    pub fn contains(&self, port: u16) -> bool {
+        self.ports.contains(port)
+    }
+
+    /// Returns whether the set contains no ports.
+    pub fn is_empty(&self) -> bool {
+        self.ports.is_empty()
+    }
+
+    /// Returns the ports that are in `self`, `other` or both.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            ports: self.ports.union(&other.ports),
+        }
+    }
+
+    /// Returns the ports that are in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            ports: self.ports.intersection(&other.ports),
+        }
+    }
+
+    /// Returns the ports that are in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            ports: self.ports.difference(&other.ports),
+        }
+    }
+
+    /// Returns the ports that are not in `self`.
+    pub fn complement(&self) -> Self {
+        Self {
+            ports: self.ports.complement(),
+        }
+    }
+
+    /// Returns the contiguous ranges of ports in the set, in ascending order.
+    pub fn ranges(&self) -> Vec<RangeInclusive<u16>> {
+        self.ports
+            .ranges()
+            .iter()
+            .map(|&(start, end)| start..=end)
+            .collect()
+    }
+
+    /// Returns the fewest conditions that together match exactly the ports in the set.
+    ///
+    /// Single ports become equality conditions and other contiguous ranges become range
+    /// conditions. A set that contains all ports but one becomes a single `not_equal`
+    /// condition. Conditions on the same field are combined with logical OR, so all of the
+    /// conditions should be added to the same filter, or split across filters with the same
+    /// action using [`split_conditions`](Self::split_conditions).
+    ///
+    /// If the set is empty, no conditions are returned. A filter without port conditions
+    /// matches every port, so check for this before adding a filter that is meant to match the
+    /// set.
+    pub fn conditions(
+        &self,
+        builder: PortConditionBuilder<PortConditionBuilderMissingValue>,
+    ) -> Vec<Condition> {
+        // A `not_equal` condition only works on its own, since it is OR'ed with the others
+        if let [(start, end)] = self.ports.complement().ranges()
+            && start == end
+        {
+            return vec![builder.not_equal(*start).build()];
+        }
+
+        self.ports
+            .ranges()
+            .iter()
+            .map(|&(start, end)| {
+                let builder = builder.clone();
+                if start == end {
+                    builder.equal(start).build()
+                } else {
+                    builder
+                        .range(start..=end)
+                        .expect("ranges in the set are not empty")
+                        .build()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the conditions from [`conditions`](Self::conditions), split into groups of at
+    /// most `max_per_filter` conditions.
+    ///
+    /// Each group is meant for a separate filter. Since the filters match the union of their
+    /// ports, they should be identical apart from their port conditions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use wfp::{PortConditionBuilder, PortSet};
+    ///
+    /// let ports: PortSet = "22, 80, 443, 8000-8100".parse().unwrap();
+    /// let groups = ports.split_conditions(PortConditionBuilder::local(), NonZeroUsize::new(3).unwrap());
+    /// assert_eq!(groups.len(), 2);
+    /// assert_eq!(groups[0].len(), 3);
+    /// assert_eq!(groups[1].len(), 1);
+    /// ```
+    pub fn split_conditions(
+        &self,
+        builder: PortConditionBuilder<PortConditionBuilderMissingValue>,
+        max_per_filter: NonZeroUsize,
+    ) -> Vec<Vec<Condition>> {
+        self.conditions(builder)
+            .chunks(max_per_filter.get())
+            .map(<[Condition]>::to_vec)
+            .collect()
+    }
+}
+
+impl From<u16> for PortSet {
+    fn from(port: u16) -> Self {
+        let mut set = Self::new();
+        set.insert(port);
+        set
+    }
+}
+
+impl From<RangeInclusive<u16>> for PortSet {
+    fn from(ports: RangeInclusive<u16>) -> Self {
+        let mut set = Self::new();
+        set.insert_range(ports);
+        set
+    }
+}
+
+impl FromIterator<u16> for PortSet {
+    fn from_iter<I: IntoIterator<Item = u16>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<u16> for PortSet {
+    fn extend<I: IntoIterator<Item = u16>>(&mut self, iter: I) {
+        let ports = iter.into_iter().map(|port| (port, port)).collect();
+        self.ports = self.ports.union(&ports);
+    }
+}
+
+impl fmt::Display for PortSet {
+    /// Writes the set in the format accepted by [`FromStr`], without exclusions.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, &(start, end)) in self.ports.ranges().iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            if start == end {
+                write!(f, "{start}")?;
+            } else {
+                write!(f, "{start}-{end}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PortSet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut included = RangeSet::new();
+        let mut excluded = RangeSet::new();
+        let mut has_inclusions = false;
+
+        for item in s.split(',') {
+            let item = item.trim();
+            let (is_exclusion, ports) = match item.strip_prefix('!') {
+                Some(ports) => (true, ports.trim_start()),
+                None => (false, item),
+            };
+            let (start, end) = match ports.split_once('-') {
+                Some((start, end)) => (parse_port(start.trim())?, parse_port(end.trim())?),
+                None => {
+                    let port = parse_port(ports)?;
+                    (port, port)
+                }
+            };
+            if start > end {
+                return Err(ParseError::new(
+                    "port set",
+                    format!("empty range: \"{item}\""),
+                ));
+            }
+
+            if is_exclusion {
+                excluded.insert(start, end);
+            } else {
+                included.insert(start, end);
+                has_inclusions = true;
+            }
+        }
+
+        if !has_inclusions {
+            included = RangeSet::full();
+        }
+        Ok(Self {
+            ports: included.difference(&excluded),
+        })
+    }
+}
+
+/// Parses a port number, rejecting signs and whitespace.
+fn parse_port(s: &str) -> Result<u16, ParseError> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::new("port set", format!("bad port: \"{s}\"")));
+    }
+    s.parse()
+        .map_err(|_| ParseError::new("port set", format!("port exceeds 65535: \"{s}\"")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MatchType;
+    use crate::condition::DataType;
+
+    #[test]
+    fn test_parse_port_set() {
+        let ports: PortSet = "22, 80, 443, 8000-8100, !8080".parse().unwrap();
+        assert_eq!(
+            ports.ranges(),
+            [22..=22, 80..=80, 443..=443, 8000..=8079, 8081..=8100]
+        );
+
+        // Exclusions apply regardless of their position
+        let ports: PortSet = "!8080,8000-8100,!8000 - 8009".parse().unwrap();
+        assert_eq!(ports.ranges(), [8010..=8079, 8081..=8100]);
+
+        // Only exclusions means all other ports
+        let ports: PortSet = "!53".parse().unwrap();
+        assert_eq!(ports.ranges(), [0..=52, 54..=65535]);
+        assert_eq!(ports.to_string(), "0-52, 54-65535");
+
+        // Adjacent items are merged
+        let ports: PortSet = "1, 2, 3-9, 10".parse().unwrap();
+        assert_eq!(ports.ranges(), [1..=10]);
+
+        // Excluding everything that is included leaves an empty set
+        assert!("80, !80".parse::<PortSet>().unwrap().is_empty());
+
+        for invalid in [
+            "", "22,", ",22", "x", "65536", "-1", "+22", "10-", "-10", "20-10", "!", "!!22", "1 2",
+        ] {
+            assert!(invalid.parse::<PortSet>().is_err(), "{invalid}");
+        }
+    }
+
+    fn match_types(conditions: &[Condition]) -> Vec<i32> {
+        conditions
+            .iter()
+            .map(|condition| condition.raw_condition().matchType)
+            .collect()
+    }
+
+    #[test]
+    fn test_port_set_conditions() {
+        let ports: PortSet = "22, 80, 443, 8000-8100, !8080".parse().unwrap();
+        let conditions = ports.conditions(PortConditionBuilder::remote());
+        assert_eq!(
+            match_types(&conditions),
+            [
+                MatchType::Equal as i32,
+                MatchType::Equal as i32,
+                MatchType::Equal as i32,
+                MatchType::Range as i32,
+                MatchType::Range as i32,
+            ]
+        );
+        assert!(
+            conditions
+                .iter()
+                .all(|condition| condition.value_type() == Some(DataType::UInt16))
+        );
+
+        // All ports but one
+        let conditions = "!53"
+            .parse::<PortSet>()
+            .unwrap()
+            .conditions(PortConditionBuilder::local());
+        assert_eq!(match_types(&conditions), [MatchType::NotEqual as i32]);
+
+        let conditions = PortSet::all().conditions(PortConditionBuilder::local());
+        assert_eq!(match_types(&conditions), [MatchType::Range as i32]);
+
+        assert!(
+            PortSet::new()
+                .conditions(PortConditionBuilder::local())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_split_port_set_conditions() {
+        let ports: PortSet = (1..=10).map(|port| port * 2).collect();
+        let groups = ports.split_conditions(
+            PortConditionBuilder::remote(),
+            NonZeroUsize::new(4).unwrap(),
+        );
+        let sizes: Vec<_> = groups.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [4, 4, 2]);
+    }
+
+    #[test]
+    fn test_port_set_algebra() {
+        let web = PortSet::from(80).union(&PortSet::from(443));
+        let high = PortSet::from(1024..=65535);
+        assert!(web.intersection(&high).is_empty());
+        assert_eq!(web.union(&high).complement().ranges().len(), 3);
+        assert_eq!(PortSet::all().difference(&web).complement(), web);
+
+        let mut ports = PortSet::all();
+        ports.remove_range(0..=1023);
+        ports.remove(8080);
+        assert_eq!(ports.to_string(), "1024-8079, 8081-65535");
+    }
+}
@@ -1,19 +1,19 @@
 //! Provider creation and management.
 
 use std::ffi::OsStr;
-use std::io;
-use std::os::windows::io::AsRawHandle;
-use std::ptr;
+use std::fmt;
 use std::sync::Arc;
 
-use windows_sys::Win32::Foundation::ERROR_SUCCESS;
 use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
-    FWPM_PROVIDER_FLAG_PERSISTENT, FWPM_PROVIDER0, FwpmProviderAdd0, FwpmProviderDeleteByKey0,
+    FWPM_PROVIDER_FLAG_DISABLED, FWPM_PROVIDER_FLAG_PERSISTENT, FWPM_PROVIDER0,
 };
 use windows_sys::core::GUID;
 
+use crate::error::Result;
 use crate::transaction::Transaction;
-use crate::util::string_to_null_terminated_utf16;
+use crate::util::{
+    GuidDisplay, byte_blob_as_slice, display_string, flags_type, string_to_null_terminated_utf16,
+};
 
 /// Builder for creating Windows Filtering Platform providers.
 ///
@@ -178,26 +178,13 @@ impl ProviderBuilder<ProviderBuilderHasName> {
     /// It calls [`FwpmProviderAdd0`] to add the provider to the engine.
     ///
     /// [`FwpmProviderAdd0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmprovideradd0
-    pub fn add<'a>(&self, transaction: &Transaction<'a>) -> io::Result<()> {
+    pub fn add<'a>(&self, transaction: &Transaction<'a>) -> Result<()> {
         // SAFETY:
-        // - transaction.engine.as_raw_handle() returns a valid engine handle
         // - &self.provider is a valid pointer to a properly initialized FWPM_PROVIDER0 structure
         // - The required name field has been set by the type system
         // - The display data and service name buffers are kept alive by self,
         //   ensuring all string pointers remain valid for the duration of the call
-        // - NULL security descriptor pointer is acceptable (uses default security)
-        let status = unsafe {
-            FwpmProviderAdd0(
-                transaction.engine.as_raw_handle(),
-                &self.provider,
-                ptr::null_mut(),
-            )
-        };
-        if status != ERROR_SUCCESS {
-            return Err(io::Error::from_raw_os_error(status as i32));
-        }
-
-        Ok(())
+        unsafe { transaction.engine.backend().provider_add(&self.provider) }
     }
 }
 
@@ -207,16 +194,131 @@ impl ProviderBuilder<ProviderBuilderHasName> {
 /// [`FWPM_PROVIDER0`] structure.
 ///
 /// This calls [`FwpmProviderDeleteByKey0`]. It returns
-/// `FWP_E_PROVIDER_REFERENCED` (surfaced as an [`io::Error`]) if any filter or
+/// [`WfpError::InUse`](crate::WfpError::InUse) if any filter or
 /// sublayer is still attached to the provider; remove those first.
 ///
 /// [`FWPM_PROVIDER0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_provider0
 /// [`FwpmProviderDeleteByKey0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmproviderdeletebykey0
-pub fn delete_provider<'a>(transaction: &Transaction<'a>, guid: &GUID) -> io::Result<()> {
-    // SAFETY: The handle and GUID are valid
-    let status = unsafe { FwpmProviderDeleteByKey0(transaction.engine.as_raw_handle(), guid) };
-    if status != ERROR_SUCCESS {
-        return Err(io::Error::from_raw_os_error(status as i32));
+pub fn delete_provider<'a>(transaction: &Transaction<'a>, guid: &GUID) -> Result<()> {
+    transaction.engine.backend().provider_delete_by_key(guid)
+}
+
+/// Return an owned copy of the provider with the given GUID.
+///
+/// This calls [`FwpmProviderGetByKey0`]. It returns
+/// [`WfpError::NotFound`](crate::WfpError::NotFound) if there is no such provider.
+/// `transaction` may be a [read-only](Transaction::read_only) transaction.
+///
+/// [`FwpmProviderGetByKey0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmprovidergetbykey0
+pub fn get_provider_by_key<Mode>(
+    transaction: &Transaction<'_, Mode>,
+    guid: &GUID,
+) -> Result<ProviderInfo> {
+    let provider = transaction.engine.backend().provider_get_by_key(guid)?;
+    // SAFETY: The provider was returned by the backend, so its pointers are valid
+    unsafe { ProviderInfo::from_raw(provider.get()) }
+}
+
+flags_type! {
+    /// Flags of a provider.
+    ///
+    /// These correspond to the `FWPM_PROVIDER_FLAG_*` values of the `flags` field in the
+    /// underlying [`FWPM_PROVIDER0`] structure.
+    ///
+    /// [`FWPM_PROVIDER0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_provider0
+    pub struct ProviderFlags {
+        /// The provider survives a BFE restart (`FWPM_PROVIDER_FLAG_PERSISTENT`).
+        const PERSISTENT = FWPM_PROVIDER_FLAG_PERSISTENT;
+        /// The service associated with the provider is not running, so its filters are
+        /// disabled (`FWPM_PROVIDER_FLAG_DISABLED`).
+        const DISABLED = FWPM_PROVIDER_FLAG_DISABLED;
+    }
+}
+
+/// An owned copy of a provider.
+///
+/// This is returned by [`ProviderEnumerator`](crate::ProviderEnumerator) and
+/// [`get_provider_by_key`].
+///
+/// This corresponds to the underlying [`FWPM_PROVIDER0`] structure.
+///
+/// [`FWPM_PROVIDER0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_provider0
+#[derive(Clone)]
+pub struct ProviderInfo {
+    key: GUID,
+    name: Option<String>,
+    description: Option<String>,
+    flags: ProviderFlags,
+    provider_data: Vec<u8>,
+    service_name: Option<String>,
+}
+
+impl ProviderInfo {
+    /// Deep-copies a raw provider.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `provider` must be valid.
+    pub(crate) unsafe fn from_raw(provider: &FWPM_PROVIDER0) -> Result<Self> {
+        // SAFETY: The caller guarantees that the pointers are valid
+        unsafe {
+            Ok(Self {
+                key: provider.providerKey,
+                name: display_string(provider.displayData.name, "invalid provider name")?,
+                description: display_string(
+                    provider.displayData.description,
+                    "invalid provider description",
+                )?,
+                flags: ProviderFlags::from_bits_retain(provider.flags),
+                provider_data: byte_blob_as_slice(&provider.providerData).to_vec(),
+                service_name: display_string(
+                    provider.serviceName,
+                    "invalid provider service name",
+                )?,
+            })
+        }
+    }
+
+    /// Return the provider GUID.
+    pub fn guid(&self) -> GUID {
+        self.key
+    }
+
+    /// Return the provider name, if set.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Return the provider description, if set.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return the provider flags.
+    pub fn flags(&self) -> ProviderFlags {
+        self.flags
+    }
+
+    /// Return the provider data of the provider. This is empty if none is set.
+    pub fn provider_data(&self) -> &[u8] {
+        &self.provider_data
+    }
+
+    /// Return the name of the Windows service associated with the provider, if set.
+    pub fn service_name(&self) -> Option<&str> {
+        self.service_name.as_deref()
+    }
+}
+
+impl fmt::Debug for ProviderInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderInfo")
+            .field("key", &GuidDisplay(&self.key))
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("flags", &self.flags)
+            .field("provider_data", &self.provider_data)
+            .field("service_name", &self.service_name)
+            .finish()
     }
-    Ok(())
 }
@@ -0,0 +1,220 @@
+//! Provider contexts
+
+use std::fmt;
+
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWPM_CLASSIFY_OPTIONS_CONTEXT, FWPM_GENERAL_CONTEXT, FWPM_IPSEC_AUTHIP_MM_CONTEXT,
+    FWPM_IPSEC_AUTHIP_QM_TRANSPORT_CONTEXT, FWPM_IPSEC_AUTHIP_QM_TUNNEL_CONTEXT,
+    FWPM_IPSEC_DOSP_CONTEXT, FWPM_IPSEC_IKE_MM_CONTEXT, FWPM_IPSEC_IKE_QM_TRANSPORT_CONTEXT,
+    FWPM_IPSEC_IKE_QM_TUNNEL_CONTEXT, FWPM_IPSEC_IKEV2_MM_CONTEXT,
+    FWPM_IPSEC_IKEV2_QM_TRANSPORT_CONTEXT, FWPM_IPSEC_IKEV2_QM_TUNNEL_CONTEXT,
+    FWPM_IPSEC_KEYING_CONTEXT, FWPM_NETWORK_CONNECTION_POLICY_CONTEXT,
+    FWPM_PROVIDER_CONTEXT_FLAG_DOWNLEVEL, FWPM_PROVIDER_CONTEXT_FLAG_PERSISTENT,
+    FWPM_PROVIDER_CONTEXT_TYPE, FWPM_PROVIDER_CONTEXT0,
+};
+use windows_sys::core::GUID;
+
+use crate::error::Result;
+use crate::util::{GuidDisplay, byte_blob_as_slice, display_string, flags_type};
+
+flags_type! {
+    /// Flags of a provider context.
+    ///
+    /// These correspond to the `FWPM_PROVIDER_CONTEXT_FLAG_*` values of the `flags` field in
+    /// the underlying [`FWPM_PROVIDER_CONTEXT0`] structure.
+    ///
+    /// [`FWPM_PROVIDER_CONTEXT0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_provider_context0
+    pub struct ProviderContextFlags {
+        /// The provider context survives a BFE restart
+        /// (`FWPM_PROVIDER_CONTEXT_FLAG_PERSISTENT`).
+        const PERSISTENT = FWPM_PROVIDER_CONTEXT_FLAG_PERSISTENT;
+        /// The provider context is used by a down-level IPsec policy
+        /// (`FWPM_PROVIDER_CONTEXT_FLAG_DOWNLEVEL`).
+        const DOWNLEVEL = FWPM_PROVIDER_CONTEXT_FLAG_DOWNLEVEL;
+    }
+}
+
+/// The type of data stored in a provider context.
+///
+/// These correspond to the [`FWPM_PROVIDER_CONTEXT_TYPE`] enumeration values.
+///
+/// [`FWPM_PROVIDER_CONTEXT_TYPE`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ne-fwpmtypes-fwpm_provider_context_type
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ProviderContextType {
+    /// IPsec keying modules (`FWPM_IPSEC_KEYING_CONTEXT`).
+    IpsecKeying = FWPM_IPSEC_KEYING_CONTEXT,
+    /// IKE quick mode transport policy (`FWPM_IPSEC_IKE_QM_TRANSPORT_CONTEXT`).
+    IpsecIkeQmTransport = FWPM_IPSEC_IKE_QM_TRANSPORT_CONTEXT,
+    /// IKE quick mode tunnel policy (`FWPM_IPSEC_IKE_QM_TUNNEL_CONTEXT`).
+    IpsecIkeQmTunnel = FWPM_IPSEC_IKE_QM_TUNNEL_CONTEXT,
+    /// AuthIP quick mode transport policy (`FWPM_IPSEC_AUTHIP_QM_TRANSPORT_CONTEXT`).
+    IpsecAuthIpQmTransport = FWPM_IPSEC_AUTHIP_QM_TRANSPORT_CONTEXT,
+    /// AuthIP quick mode tunnel policy (`FWPM_IPSEC_AUTHIP_QM_TUNNEL_CONTEXT`).
+    IpsecAuthIpQmTunnel = FWPM_IPSEC_AUTHIP_QM_TUNNEL_CONTEXT,
+    /// IKE main mode policy (`FWPM_IPSEC_IKE_MM_CONTEXT`).
+    IpsecIkeMm = FWPM_IPSEC_IKE_MM_CONTEXT,
+    /// AuthIP main mode policy (`FWPM_IPSEC_AUTHIP_MM_CONTEXT`).
+    IpsecAuthIpMm = FWPM_IPSEC_AUTHIP_MM_CONTEXT,
+    /// Classify options (`FWPM_CLASSIFY_OPTIONS_CONTEXT`).
+    ClassifyOptions = FWPM_CLASSIFY_OPTIONS_CONTEXT,
+    /// Arbitrary data that is passed to a callout (`FWPM_GENERAL_CONTEXT`).
+    General = FWPM_GENERAL_CONTEXT,
+    /// IKEv2 quick mode tunnel policy (`FWPM_IPSEC_IKEV2_QM_TUNNEL_CONTEXT`).
+    IpsecIkev2QmTunnel = FWPM_IPSEC_IKEV2_QM_TUNNEL_CONTEXT,
+    /// IKEv2 main mode policy (`FWPM_IPSEC_IKEV2_MM_CONTEXT`).
+    IpsecIkev2Mm = FWPM_IPSEC_IKEV2_MM_CONTEXT,
+    /// IPsec denial of service protection policy (`FWPM_IPSEC_DOSP_CONTEXT`).
+    IpsecDosp = FWPM_IPSEC_DOSP_CONTEXT,
+    /// IKEv2 quick mode transport policy (`FWPM_IPSEC_IKEV2_QM_TRANSPORT_CONTEXT`).
+    IpsecIkev2QmTransport = FWPM_IPSEC_IKEV2_QM_TRANSPORT_CONTEXT,
+    /// Network connection policy (`FWPM_NETWORK_CONNECTION_POLICY_CONTEXT`).
+    NetworkConnectionPolicy = FWPM_NETWORK_CONNECTION_POLICY_CONTEXT,
+}
+
+impl ProviderContextType {
+    const ALL: &[Self] = &[
+        Self::IpsecKeying,
+        Self::IpsecIkeQmTransport,
+        Self::IpsecIkeQmTunnel,
+        Self::IpsecAuthIpQmTransport,
+        Self::IpsecAuthIpQmTunnel,
+        Self::IpsecIkeMm,
+        Self::IpsecAuthIpMm,
+        Self::ClassifyOptions,
+        Self::General,
+        Self::IpsecIkev2QmTunnel,
+        Self::IpsecIkev2Mm,
+        Self::IpsecDosp,
+        Self::IpsecIkev2QmTransport,
+        Self::NetworkConnectionPolicy,
+    ];
+
+    /// Returns the type that corresponds to a raw `FWPM_PROVIDER_CONTEXT_TYPE` value, if any.
+    pub fn from_raw(raw: FWPM_PROVIDER_CONTEXT_TYPE) -> Option<Self> {
+        Self::ALL.iter().copied().find(|ty| *ty as i32 == raw)
+    }
+}
+
+/// An owned copy of a provider context.
+///
+/// A provider context stores data that is passed to callouts and keying modules, such as
+/// IPsec policy. This is returned by
+/// [`ProviderContextEnumerator`](crate::ProviderContextEnumerator). Only the data of
+/// [general](ProviderContextType::General) contexts is copied.
+///
+/// This corresponds to the underlying [`FWPM_PROVIDER_CONTEXT0`] structure.
+///
+/// [`FWPM_PROVIDER_CONTEXT0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_provider_context0
+#[derive(Clone)]
+pub struct ProviderContextInfo {
+    key: GUID,
+    name: Option<String>,
+    description: Option<String>,
+    flags: ProviderContextFlags,
+    provider: Option<GUID>,
+    provider_data: Vec<u8>,
+    context_type: Option<ProviderContextType>,
+    data: Option<Vec<u8>>,
+    id: u64,
+}
+
+impl ProviderContextInfo {
+    /// Deep-copies a raw provider context.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `context` must be valid.
+    pub(crate) unsafe fn from_raw(context: &FWPM_PROVIDER_CONTEXT0) -> Result<Self> {
+        let context_type = ProviderContextType::from_raw(context.r#type);
+        // SAFETY: The caller guarantees that the pointers are valid, and `dataBuffer` is the
+        //         active union field for general contexts
+        unsafe {
+            let data = match context_type {
+                Some(ProviderContextType::General) => context
+                    .Anonymous
+                    .dataBuffer
+                    .as_ref()
+                    .map(|blob| byte_blob_as_slice(blob).to_vec()),
+                _ => None,
+            };
+            Ok(Self {
+                key: context.providerContextKey,
+                name: display_string(context.displayData.name, "invalid provider context name")?,
+                description: display_string(
+                    context.displayData.description,
+                    "invalid provider context description",
+                )?,
+                flags: ProviderContextFlags::from_bits_retain(context.flags),
+                provider: context.providerKey.as_ref().copied(),
+                provider_data: byte_blob_as_slice(&context.providerData).to_vec(),
+                context_type,
+                data,
+                id: context.providerContextId,
+            })
+        }
+    }
+
+    /// Return the provider context GUID.
+    pub fn guid(&self) -> GUID {
+        self.key
+    }
+
+    /// Return the provider context name, if set.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Return the provider context description, if set.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return the provider context flags.
+    pub fn flags(&self) -> ProviderContextFlags {
+        self.flags
+    }
+
+    /// Return the provider that the provider context belongs to, if set.
+    pub fn provider(&self) -> Option<GUID> {
+        self.provider
+    }
+
+    /// Return the provider data of the provider context. This is empty if none is set.
+    pub fn provider_data(&self) -> &[u8] {
+        &self.provider_data
+    }
+
+    /// Return the type of the provider context, or `None` if it is not a known type.
+    pub fn context_type(&self) -> Option<ProviderContextType> {
+        self.context_type
+    }
+
+    /// Return the data of a [general](ProviderContextType::General) provider context, or
+    /// `None` for other types.
+    pub fn data(&self) -> Option<&[u8]> {
+        self.data.as_deref()
+    }
+
+    /// Return the runtime ID of the provider context.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl fmt::Debug for ProviderContextInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderContextInfo")
+            .field("key", &GuidDisplay(&self.key))
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("flags", &self.flags)
+            .field("provider", &self.provider.as_ref().map(GuidDisplay))
+            .field("provider_data", &self.provider_data)
+            .field("context_type", &self.context_type)
+            .field("data", &self.data)
+            .field("id", &self.id)
+            .finish()
+    }
+}
@@ -0,0 +1,231 @@
+//! Sets of integers, stored as sorted, disjoint inclusive ranges.
+
+/// An integer type that can be stored in a [`RangeSet`].
+pub(crate) trait Bound: Copy + Ord {
+    const MIN: Self;
+    const MAX: Self;
+
+    /// Returns `self + 1`, or `None` if `self` is the maximum value.
+    fn next(self) -> Option<Self>;
+
+    /// Returns `self - 1`, or `None` if `self` is the minimum value.
+    fn prev(self) -> Option<Self>;
+}
+
+macro_rules! impl_bound {
+    ($($t:ty),*) => {
+        $(
+            impl Bound for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn next(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn prev(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_bound!(u16, u32, u128);
+
+/// A set of integers, stored as the smallest list of inclusive ranges.
+///
+/// The ranges are sorted, and no two ranges overlap or are adjacent.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct RangeSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Bound> RangeSet<T> {
+    /// Returns an empty set.
+    pub fn new() -> Self {
+        Self { ranges: vec![] }
+    }
+
+    /// Returns the set of all values of `T`.
+    pub fn full() -> Self {
+        Self {
+            ranges: vec![(T::MIN, T::MAX)],
+        }
+    }
+
+    /// Returns the ranges of the set, in ascending order.
+    pub fn ranges(&self) -> &[(T, T)] {
+        &self.ranges
+    }
+
+    /// Returns whether the set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns whether `value` is in the set.
+    pub fn contains(&self, value: T) -> bool {
+        // The ranges are sorted by both their start and their end
+        let i = self.ranges.partition_point(|&(_, end)| end < value);
+        self.ranges.get(i).is_some_and(|&(start, _)| start <= value)
+    }
+
+    /// Adds `start..=end` to the set. Does nothing if `start > end`.
+    pub fn insert(&mut self, start: T, end: T) {
+        if start <= end {
+            self.ranges.push((start, end));
+            self.normalize();
+        }
+    }
+
+    /// Removes `start..=end` from the set. Does nothing if `start > end`.
+    pub fn remove(&mut self, start: T, end: T) {
+        if start <= end {
+            let mut removed = Self::new();
+            removed.insert(start, end);
+            *self = self.difference(&removed);
+        }
+    }
+
+    /// Returns the values that are in `self`, `other` or both.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges = self.ranges.clone();
+        ranges.extend_from_slice(&other.ranges);
+        let mut set = Self { ranges };
+        set.normalize();
+        set
+    }
+
+    /// Returns the values that are in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(&&(a_start, a_end)), Some(&&(b_start, b_end))) = (a.peek(), b.peek()) {
+            let start = a_start.max(b_start);
+            let end = a_end.min(b_end);
+            if start <= end {
+                ranges.push((start, end));
+            }
+            // Advance past the range that ends first, since it cannot overlap anything else
+            if a_end < b_end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Returns the values that are not in `self`.
+    pub fn complement(&self) -> Self {
+        let mut ranges = vec![];
+        let mut next_start = Some(T::MIN);
+        for &(start, end) in &self.ranges {
+            if let Some(gap_start) = next_start
+                && let Some(gap_end) = start.prev()
+                && gap_start <= gap_end
+            {
+                ranges.push((gap_start, gap_end));
+            }
+            next_start = end.next();
+        }
+        if let Some(gap_start) = next_start {
+            ranges.push((gap_start, T::MAX));
+        }
+        Self { ranges }
+    }
+
+    /// Returns the values that are in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement())
+    }
+
+    /// Sorts the ranges and merges those that overlap or are adjacent.
+    fn normalize(&mut self) {
+        self.ranges.sort_unstable();
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(self.ranges.len());
+        for &(start, end) in &self.ranges {
+            if let Some(last) = merged.last_mut()
+                && last.1.next().is_none_or(|after_last| start <= after_last)
+            {
+                last.1 = last.1.max(end);
+                continue;
+            }
+            merged.push((start, end));
+        }
+        self.ranges = merged;
+    }
+}
+
+impl<T: Bound> FromIterator<(T, T)> for RangeSet<T> {
+    /// Collects inclusive ranges into a set, ignoring empty ranges.
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut set = Self {
+            ranges: iter
+                .into_iter()
+                .filter(|(start, end)| start <= end)
+                .collect(),
+        };
+        set.normalize();
+        set
+    }
+}
+
+impl<T: Bound> Default for RangeSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(ranges: &[(u16, u16)]) -> RangeSet<u16> {
+        let mut set = RangeSet::new();
+        for &(start, end) in ranges {
+            set.insert(start, end);
+        }
+        set
+    }
+
+    #[test]
+    fn test_insert_merges_ranges() {
+        let ports = set(&[(10, 20), (5, 7), (21, 30), (8, 9), (100, 200), (150, 160)]);
+        assert_eq!(ports.ranges(), [(5, 30), (100, 200)]);
+        assert!(ports.contains(5));
+        assert!(ports.contains(30));
+        assert!(!ports.contains(31));
+        assert!(!ports.contains(4));
+
+        // Ranges ending at the maximum value do not overflow
+        let full = set(&[(u16::MAX, u16::MAX), (0, u16::MAX - 1)]);
+        assert_eq!(full, RangeSet::full());
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25)]);
+
+        assert_eq!(a.union(&b).ranges(), [(0, 30)]);
+        assert_eq!(a.intersection(&b).ranges(), [(5, 10), (20, 25)]);
+        assert_eq!(a.difference(&b).ranges(), [(0, 4), (26, 30)]);
+        assert_eq!(b.difference(&a).ranges(), [(11, 19)]);
+        assert_eq!(a.complement().ranges(), [(11, 19), (31, u16::MAX)]);
+
+        assert_eq!(RangeSet::<u16>::full().complement(), RangeSet::new());
+        assert_eq!(RangeSet::<u16>::new().complement(), RangeSet::full());
+        assert!(a.intersection(&a.complement()).is_empty());
+
+        let mut c = a.clone();
+        c.remove(0, u16::MAX);
+        assert!(c.is_empty());
+        c.insert(10, 5);
+        assert!(c.is_empty());
+    }
+}
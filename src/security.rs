@@ -0,0 +1,691 @@
+//! Security descriptors.
+
+use std::str::FromStr;
+
+use windows_sys::Win32::Security::{
+    ACL_REVISION, CONTAINER_INHERIT_ACE, INHERIT_ONLY_ACE, INHERITED_ACE, NO_PROPAGATE_INHERIT_ACE,
+    OBJECT_INHERIT_ACE, SE_DACL_AUTO_INHERIT_REQ, SE_DACL_AUTO_INHERITED, SE_DACL_PRESENT,
+    SE_DACL_PROTECTED, SE_SELF_RELATIVE,
+};
+
+use crate::error::ParseError;
+use crate::sid::Sid;
+
+// These are defined in `Win32_System_SystemServices`, which is not enabled
+const ACCESS_ALLOWED_ACE_TYPE: u8 = 0;
+const ACCESS_DENIED_ACE_TYPE: u8 = 1;
+const SECURITY_DESCRIPTOR_REVISION: u8 = 1;
+
+/// The size of `SECURITY_DESCRIPTOR_RELATIVE`.
+const HEADER_LEN: usize = 20;
+/// The size of `ACL`.
+const ACL_HEADER_LEN: usize = 8;
+/// The size of `ACE_HEADER` and the access mask.
+const ACE_HEADER_LEN: usize = 8;
+
+/// Access rights that can be written as two-letter codes in SDDL.
+const ACCESS_RIGHTS: &[(&str, u32)] = &[
+    ("GA", 0x1000_0000),
+    ("GR", 0x8000_0000),
+    ("GW", 0x4000_0000),
+    ("GX", 0x2000_0000),
+    ("RC", 0x0002_0000),
+    ("SD", 0x0001_0000),
+    ("WD", 0x0004_0000),
+    ("WO", 0x0008_0000),
+    ("RP", 0x0000_0010),
+    ("WP", 0x0000_0020),
+    ("CC", 0x0000_0001),
+    ("DC", 0x0000_0002),
+    ("LC", 0x0000_0004),
+    ("SW", 0x0000_0008),
+    ("LO", 0x0000_0080),
+    ("DT", 0x0000_0040),
+    ("CR", 0x0000_0100),
+    ("FA", 0x001f_01ff),
+    ("FR", 0x0012_0089),
+    ("FW", 0x0012_0116),
+    ("FX", 0x0012_00a0),
+    ("KA", 0x000f_003f),
+    ("KR", 0x0002_0019),
+    ("KW", 0x0002_0006),
+    ("KX", 0x0002_0019),
+];
+
+/// ACE flags that can be written as two-letter codes in SDDL.
+const ACE_FLAGS: &[(&str, u32)] = &[
+    ("CI", CONTAINER_INHERIT_ACE),
+    ("OI", OBJECT_INHERIT_ACE),
+    ("NP", NO_PROPAGATE_INHERIT_ACE),
+    ("IO", INHERIT_ONLY_ACE),
+    ("ID", INHERITED_ACE),
+];
+
+/// SID aliases that do not depend on the domain, with their identifier authority and
+/// sub-authorities.
+const SID_ALIASES: &[(&str, u64, &[u32])] = &[
+    ("AA", 5, &[32, 579]),
+    ("AC", 15, &[2, 1]),
+    ("AN", 5, &[7]),
+    ("AO", 5, &[32, 548]),
+    ("AU", 5, &[11]),
+    ("BA", 5, &[32, 544]),
+    ("BG", 5, &[32, 546]),
+    ("BO", 5, &[32, 551]),
+    ("BU", 5, &[32, 545]),
+    ("CG", 3, &[1]),
+    ("CO", 3, &[0]),
+    ("CY", 5, &[32, 569]),
+    ("ED", 5, &[9]),
+    ("ER", 5, &[32, 573]),
+    ("ES", 5, &[32, 576]),
+    ("HA", 5, &[32, 578]),
+    ("HI", 16, &[12288]),
+    ("IS", 5, &[32, 568]),
+    ("IU", 5, &[4]),
+    ("LS", 5, &[19]),
+    ("LU", 5, &[32, 559]),
+    ("LW", 16, &[4096]),
+    ("ME", 16, &[8192]),
+    ("MS", 5, &[32, 577]),
+    ("MU", 5, &[32, 558]),
+    ("NO", 5, &[32, 556]),
+    ("NS", 5, &[20]),
+    ("NU", 5, &[2]),
+    ("OW", 3, &[4]),
+    ("PO", 5, &[32, 550]),
+    ("PS", 5, &[10]),
+    ("PU", 5, &[32, 547]),
+    ("RA", 5, &[32, 575]),
+    ("RC", 5, &[12]),
+    ("RD", 5, &[32, 555]),
+    ("RE", 5, &[32, 552]),
+    ("RM", 5, &[32, 580]),
+    ("RU", 5, &[32, 554]),
+    ("SI", 16, &[16384]),
+    ("SO", 5, &[32, 549]),
+    ("SU", 5, &[6]),
+    ("SY", 5, &[18]),
+    ("WD", 1, &[0]),
+    ("WR", 5, &[33]),
+];
+
+/// A security descriptor with an optional owner, group and discretionary access control list
+/// (DACL).
+///
+/// WFP uses security descriptors to match the user of a connection, e.g. in a
+/// [`UserConditionBuilder`](crate::UserConditionBuilder). A security descriptor is usually
+/// created from a string in the [Security Descriptor Definition Language] (SDDL), and is encoded
+/// in the self-relative format by [`SecurityDescriptor::to_bytes`].
+///
+/// Only the parts of SDDL that apply to WFP are supported: the owner (`O:`), the group (`G:`)
+/// and a DACL (`D:`) made up of access-allowed (`A`) and access-denied (`D`) ACEs. SIDs can be
+/// written in the string format or as aliases that do not depend on the domain, such as `SY` or
+/// `BA`.
+///
+/// # Example
+///
+/// ```
+/// use wfp::SecurityDescriptor;
+///
+/// // Allow LocalService and NetworkService to match the filter
+/// let sd: SecurityDescriptor = "D:(A;;CC;;;LS)(A;;CC;;;NS)".parse().unwrap();
+/// assert_eq!(sd.to_bytes().len(), 68);
+/// ```
+///
+/// [Security Descriptor Definition Language]: https://learn.microsoft.com/en-us/windows/win32/secauthz/security-descriptor-definition-language
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecurityDescriptor {
+    owner: Option<Sid>,
+    group: Option<Sid>,
+    /// DACL-related `SECURITY_DESCRIPTOR_CONTROL` flags
+    control: u16,
+    /// The ACEs of the DACL, or `None` if there is no DACL or it is a null DACL
+    dacl: Option<Vec<Ace>>,
+}
+
+/// An access-allowed or access-denied ACE.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Ace {
+    ace_type: u8,
+    flags: u8,
+    mask: u32,
+    sid: Sid,
+}
+
+impl Ace {
+    fn is_inherited(&self) -> bool {
+        u32::from(self.flags) & INHERITED_ACE != 0
+    }
+
+    fn len(&self) -> usize {
+        ACE_HEADER_LEN + self.sid.as_bytes().len()
+    }
+}
+
+impl SecurityDescriptor {
+    /// Creates a security descriptor whose owner and group are LocalSystem, with an empty DACL
+    /// that denies all access. This is equivalent to `O:SYG:SYD:`.
+    pub(crate) fn system_owned() -> Self {
+        let system = parse_sid("SY").unwrap();
+        Self {
+            owner: Some(system.clone()),
+            group: Some(system),
+            control: SE_DACL_PRESENT,
+            dacl: Some(vec![]),
+        }
+    }
+
+    /// Parses a security descriptor string in the [Security Descriptor Definition Language].
+    ///
+    /// This is equivalent to `sddl.parse()`.
+    ///
+    /// [Security Descriptor Definition Language]: https://learn.microsoft.com/en-us/windows/win32/secauthz/security-descriptor-definition-language
+    pub fn from_sddl(sddl: &str) -> Result<Self, ParseError> {
+        sddl.parse()
+    }
+
+    /// Adds an ACE that grants `mask` to `sid`.
+    ///
+    /// The ACE is added after other explicit ACEs, but before inherited ACEs.
+    pub(crate) fn allow(&mut self, sid: Sid, mask: u32) {
+        let aces = self.dacl_mut();
+        let index = aces
+            .iter()
+            .position(Ace::is_inherited)
+            .unwrap_or(aces.len());
+        aces.insert(
+            index,
+            Ace {
+                ace_type: ACCESS_ALLOWED_ACE_TYPE,
+                flags: 0,
+                mask,
+                sid,
+            },
+        );
+    }
+
+    /// Adds an ACE that denies `mask` to `sid`.
+    ///
+    /// The ACE is added after other explicit access-denied ACEs, so that it takes precedence
+    /// over all access-allowed and inherited ACEs.
+    pub(crate) fn deny(&mut self, sid: Sid, mask: u32) {
+        let aces = self.dacl_mut();
+        let index = aces
+            .iter()
+            .position(|ace| ace.is_inherited() || ace.ace_type != ACCESS_DENIED_ACE_TYPE)
+            .unwrap_or(aces.len());
+        aces.insert(
+            index,
+            Ace {
+                ace_type: ACCESS_DENIED_ACE_TYPE,
+                flags: 0,
+                mask,
+                sid,
+            },
+        );
+    }
+
+    /// Returns the ACEs of the DACL, creating an empty DACL if there is none.
+    fn dacl_mut(&mut self) -> &mut Vec<Ace> {
+        self.control |= SE_DACL_PRESENT;
+        self.dacl.get_or_insert_with(Vec::new)
+    }
+
+    /// Decodes a security descriptor in the self-relative format, as produced by
+    /// [`SecurityDescriptor::to_bytes`].
+    ///
+    /// This has the same limitations as the SDDL parser: security descriptors with a system
+    /// ACL, or with ACEs other than access-allowed and access-denied ACEs, are rejected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wfp::SecurityDescriptor;
+    ///
+    /// let sd: SecurityDescriptor = "O:SYD:(A;;CC;;;BU)".parse().unwrap();
+    /// assert_eq!(SecurityDescriptor::from_bytes(&sd.to_bytes()).unwrap(), sd);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let header = bytes
+            .get(..HEADER_LEN)
+            .ok_or_else(|| error("too short for a header"))?;
+        if header[0] != SECURITY_DESCRIPTOR_REVISION {
+            return Err(error(format!("unsupported revision {}", header[0])));
+        }
+        let control = u16::from_le_bytes([header[2], header[3]]);
+        if control & SE_SELF_RELATIVE == 0 {
+            return Err(error("not in the self-relative format"));
+        }
+        let offset = |i: usize| {
+            usize::try_from(u32::from_le_bytes(header[i..i + 4].try_into().unwrap())).unwrap()
+        };
+        let (owner, group, sacl, dacl) = (offset(4), offset(8), offset(12), offset(16));
+        if sacl != 0 {
+            return Err(error("system ACLs are not supported"));
+        }
+
+        let sid_at = |offset: usize| match offset {
+            0 => Ok(None),
+            offset => bytes
+                .get(offset..)
+                .and_then(Sid::from_prefix)
+                .map(Some)
+                .ok_or_else(|| error(format!("invalid SID at offset {offset}"))),
+        };
+        let dacl = if control & SE_DACL_PRESENT != 0 && dacl != 0 {
+            Some(decode_acl(bytes.get(dacl..).unwrap_or_default())?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            owner: sid_at(owner)?,
+            group: sid_at(group)?,
+            control: control & !SE_SELF_RELATIVE,
+            dacl,
+        })
+    }
+
+    /// Encodes the security descriptor in the self-relative format.
+    ///
+    /// The result starts with a [`SECURITY_DESCRIPTOR_RELATIVE`] structure, followed by the
+    /// DACL, the owner and the group.
+    ///
+    /// [`SECURITY_DESCRIPTOR_RELATIVE`]: https://learn.microsoft.com/en-us/windows-hardware/drivers/ddi/ntifs/ns-ntifs-_security_descriptor_relative
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        let mut offset_of = |data: &[u8]| {
+            let offset = HEADER_LEN + body.len();
+            body.extend_from_slice(data);
+            u32::try_from(offset).expect("security descriptor too large")
+        };
+
+        let dacl = self
+            .dacl
+            .as_ref()
+            .map_or(0, |aces| offset_of(&encode_acl(aces)));
+        let owner = self
+            .owner
+            .as_ref()
+            .map_or(0, |sid| offset_of(sid.as_bytes()));
+        let group = self
+            .group
+            .as_ref()
+            .map_or(0, |sid| offset_of(sid.as_bytes()));
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + body.len());
+        bytes.push(SECURITY_DESCRIPTOR_REVISION);
+        bytes.push(0);
+        bytes.extend_from_slice(&(SE_SELF_RELATIVE | self.control).to_le_bytes());
+        bytes.extend_from_slice(&owner.to_le_bytes());
+        bytes.extend_from_slice(&group.to_le_bytes());
+        // There is no SACL
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&dacl.to_le_bytes());
+        bytes.extend_from_slice(&body);
+        bytes
+    }
+}
+
+/// Encodes an `ACL` structure followed by its ACEs.
+fn encode_acl(aces: &[Ace]) -> Vec<u8> {
+    let len = ACL_HEADER_LEN + aces.iter().map(Ace::len).sum::<usize>();
+    let mut acl = Vec::with_capacity(len);
+    acl.push(u8::try_from(ACL_REVISION).unwrap());
+    acl.push(0);
+    acl.extend_from_slice(&u16::try_from(len).expect("ACL too large").to_le_bytes());
+    acl.extend_from_slice(&u16::try_from(aces.len()).unwrap().to_le_bytes());
+    acl.extend_from_slice(&0u16.to_le_bytes());
+    for ace in aces {
+        acl.push(ace.ace_type);
+        acl.push(ace.flags);
+        acl.extend_from_slice(&u16::try_from(ace.len()).unwrap().to_le_bytes());
+        acl.extend_from_slice(&ace.mask.to_le_bytes());
+        acl.extend_from_slice(ace.sid.as_bytes());
+    }
+    acl
+}
+
+/// Decodes an `ACL` structure followed by its ACEs.
+fn decode_acl(bytes: &[u8]) -> Result<Vec<Ace>, ParseError> {
+    let header = bytes
+        .get(..ACL_HEADER_LEN)
+        .ok_or_else(|| error("ACL is too short"))?;
+    let len = usize::from(u16::from_le_bytes([header[2], header[3]]));
+    let ace_count = u16::from_le_bytes([header[4], header[5]]);
+    let mut rest = bytes
+        .get(ACL_HEADER_LEN..len)
+        .ok_or_else(|| error("ACL is truncated"))?;
+
+    let mut aces = Vec::with_capacity(usize::from(ace_count));
+    for _ in 0..ace_count {
+        let ace_header = rest
+            .get(..ACE_HEADER_LEN)
+            .ok_or_else(|| error("ACE is truncated"))?;
+        let ace_type = ace_header[0];
+        if ace_type != ACCESS_ALLOWED_ACE_TYPE && ace_type != ACCESS_DENIED_ACE_TYPE {
+            return Err(error(format!("unsupported ACE type {ace_type}")));
+        }
+        let ace_len = usize::from(u16::from_le_bytes([ace_header[2], ace_header[3]]));
+        let ace = rest
+            .get(..ace_len)
+            .ok_or_else(|| error("ACE is truncated"))?;
+        aces.push(Ace {
+            ace_type,
+            flags: ace_header[1],
+            mask: u32::from_le_bytes(ace_header[4..8].try_into().unwrap()),
+            sid: Sid::from_prefix(&ace[ACE_HEADER_LEN.min(ace_len)..])
+                .ok_or_else(|| error("invalid SID in ACE"))?,
+        });
+        rest = &rest[ace_len..];
+    }
+    Ok(aces)
+}
+
+impl FromStr for SecurityDescriptor {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sd = Self {
+            owner: None,
+            group: None,
+            control: 0,
+            dacl: None,
+        };
+
+        let mut seen = Vec::new();
+        for (tag, value) in split_components(s.trim())? {
+            if seen.contains(&tag) {
+                return Err(error(format!("duplicate \"{tag}:\" component")));
+            }
+            seen.push(tag);
+
+            match tag {
+                'O' => sd.owner = Some(parse_sid(value)?),
+                'G' => sd.group = Some(parse_sid(value)?),
+                'D' => (sd.control, sd.dacl) = parse_dacl(value)?,
+                _ => return Err(error("system ACLs (\"S:\") are not supported")),
+            }
+        }
+
+        Ok(sd)
+    }
+}
+
+fn error(reason: impl Into<String>) -> ParseError {
+    ParseError::new("security descriptor", reason)
+}
+
+/// Splits an SDDL string into its tagged components, e.g. `O:SY` and `D:(A;;CC;;;WD)`.
+fn split_components(s: &str) -> Result<Vec<(char, &str)>, ParseError> {
+    let bytes = s.as_bytes();
+    let is_tag =
+        |i: usize| matches!(bytes[i], b'O' | b'G' | b'D' | b'S') && bytes.get(i + 1) == Some(&b':');
+
+    if s.is_empty() || !is_tag(0) {
+        return Err(error("expected \"O:\", \"G:\", \"D:\" or \"S:\""));
+    }
+
+    let mut components = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    for i in 2..=bytes.len() {
+        if i == bytes.len() || (depth == 0 && is_tag(i)) {
+            components.push((char::from(bytes[start]), &s[start + 2..i]));
+            start = i;
+            continue;
+        }
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| error("unbalanced \")\""))?
+            }
+            _ => (),
+        }
+    }
+    if depth != 0 {
+        return Err(error("unbalanced \"(\""));
+    }
+    Ok(components)
+}
+
+/// Parses a SID in the string format, or a two-letter alias such as `SY`.
+fn parse_sid(s: &str) -> Result<Sid, ParseError> {
+    if let Some((_, authority, sub_authorities)) = SID_ALIASES
+        .iter()
+        .find(|(alias, ..)| alias.eq_ignore_ascii_case(s))
+    {
+        return Ok(Sid::new(*authority, sub_authorities));
+    }
+    if s.len() > 2 && s.get(..2).is_some_and(|p| p.eq_ignore_ascii_case("S-")) {
+        return s.parse();
+    }
+    Err(error(format!("unknown SID \"{s}\"")))
+}
+
+/// Parses the DACL flags and ACEs that follow `D:`.
+fn parse_dacl(s: &str) -> Result<(u16, Option<Vec<Ace>>), ParseError> {
+    let (mut flags, mut rest) = s.split_at(s.find('(').unwrap_or(s.len()));
+
+    let mut control = SE_DACL_PRESENT;
+    let mut null_dacl = false;
+    while !flags.is_empty() {
+        if let Some(remaining) = flags.strip_prefix("NO_ACCESS_CONTROL") {
+            null_dacl = true;
+            flags = remaining;
+            continue;
+        }
+        let (flag, remaining) = flags
+            .split_at_checked(if flags.starts_with('P') { 1 } else { 2 })
+            .ok_or_else(|| error(format!("invalid DACL flags \"{s}\"")))?;
+        control |= match flag {
+            "P" => SE_DACL_PROTECTED,
+            "AI" => SE_DACL_AUTO_INHERITED,
+            "AR" => SE_DACL_AUTO_INHERIT_REQ,
+            _ => return Err(error(format!("invalid DACL flag \"{flag}\""))),
+        };
+        flags = remaining;
+    }
+
+    let mut aces = vec![];
+    while !rest.is_empty() {
+        let end = rest
+            .find(')')
+            .filter(|_| rest.starts_with('('))
+            .ok_or_else(|| error(format!("invalid ACE \"{rest}\"")))?;
+        aces.push(parse_ace(&rest[1..end])?);
+        rest = &rest[end + 1..];
+    }
+
+    if null_dacl {
+        if !aces.is_empty() {
+            return Err(error("a null DACL cannot contain ACEs"));
+        }
+        return Ok((control, None));
+    }
+    Ok((control, Some(aces)))
+}
+
+/// Parses the fields of an ACE, without the enclosing parentheses.
+fn parse_ace(s: &str) -> Result<Ace, ParseError> {
+    let fields: Vec<&str> = s.split(';').collect();
+    let [
+        ace_type,
+        flags,
+        rights,
+        object_guid,
+        inherit_object_guid,
+        sid,
+    ] = fields[..]
+    else {
+        return Err(error(format!("invalid ACE \"({s})\"")));
+    };
+
+    let ace_type = match ace_type {
+        "A" => ACCESS_ALLOWED_ACE_TYPE,
+        "D" => ACCESS_DENIED_ACE_TYPE,
+        _ => return Err(error(format!("unsupported ACE type \"{ace_type}\""))),
+    };
+    if !object_guid.is_empty() || !inherit_object_guid.is_empty() {
+        return Err(error("object ACEs are not supported"));
+    }
+
+    Ok(Ace {
+        ace_type,
+        flags: u8::try_from(parse_codes(flags, ACE_FLAGS, "ACE flag")?).unwrap(),
+        mask: parse_rights(rights)?,
+        sid: parse_sid(sid)?,
+    })
+}
+
+/// Parses an access mask, written as an integer or as a sequence of two-letter codes.
+fn parse_rights(s: &str) -> Result<u32, ParseError> {
+    let invalid = || error(format!("invalid access rights \"{s}\""));
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        return u32::from_str_radix(hex, 16).map_err(|_| invalid());
+    }
+    if s.starts_with(|c: char| c.is_ascii_digit()) {
+        return s.parse().map_err(|_| invalid());
+    }
+    parse_codes(s, ACCESS_RIGHTS, "access right")
+}
+
+/// Parses a sequence of two-letter codes from `table` and combines their values.
+fn parse_codes(s: &str, table: &[(&str, u32)], what: &str) -> Result<u32, ParseError> {
+    let mut value = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (code, remaining) = rest
+            .split_at_checked(2)
+            .ok_or_else(|| error(format!("invalid {what} \"{rest}\"")))?;
+        let (_, bits) = table
+            .iter()
+            .find(|(name, _)| *name == code)
+            .ok_or_else(|| error(format!("invalid {what} \"{code}\"")))?;
+        value |= bits;
+        rest = remaining;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode_sddl() {
+        let sd: SecurityDescriptor = "O:SYG:BAD:P(D;;CC;;;S-1-5-21-1-2-3-1001)(A;OICI;0x1;;;BU)"
+            .parse()
+            .unwrap();
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            // Header: revision, control, owner, group, SACL and DACL offsets
+            1, 0, 0x04, 0x90, 0x58, 0, 0, 0, 0x64, 0, 0, 0, 0, 0, 0, 0, 0x14, 0, 0, 0,
+            // ACL header: revision, size, ACE count
+            2, 0, 0x44, 0, 2, 0, 0, 0,
+            // Access-denied ACE
+            1, 0, 0x24, 0, 1, 0, 0, 0,
+            1, 5, 0, 0, 0, 0, 0, 5, 21, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0xe9, 3, 0, 0,
+            // Access-allowed ACE
+            0, 3, 0x18, 0, 1, 0, 0, 0,
+            1, 2, 0, 0, 0, 0, 0, 5, 32, 0, 0, 0, 0x21, 2, 0, 0,
+            // Owner
+            1, 1, 0, 0, 0, 0, 0, 5, 18, 0, 0, 0,
+            // Group
+            1, 2, 0, 0, 0, 0, 0, 5, 32, 0, 0, 0, 0x20, 2, 0, 0,
+        ];
+        assert_eq!(sd.to_bytes(), expected);
+    }
+
+    #[test]
+    fn test_decode_round_trip() {
+        for sddl in [
+            "O:SYG:BAD:P(D;;CC;;;S-1-5-21-1-2-3-1001)(A;OICI;0x1;;;BU)",
+            "D:NO_ACCESS_CONTROL",
+            "D:",
+            "O:SYG:SYD:",
+            "G:BA",
+        ] {
+            let sd: SecurityDescriptor = sddl.parse().unwrap();
+            assert_eq!(
+                SecurityDescriptor::from_bytes(&sd.to_bytes()).unwrap(),
+                sd,
+                "{sddl}"
+            );
+        }
+
+        let bytes = "O:SYD:(A;;CC;;;BU)"
+            .parse::<SecurityDescriptor>()
+            .unwrap()
+            .to_bytes();
+        for len in 0..bytes.len() {
+            assert!(
+                SecurityDescriptor::from_bytes(&bytes[..len]).is_err(),
+                "{len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_null_and_empty_dacl() {
+        let sd: SecurityDescriptor = "D:NO_ACCESS_CONTROL".parse().unwrap();
+        let bytes = sd.to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN);
+        assert_eq!(u16::from_le_bytes([bytes[2], bytes[3]]), 0x8004);
+        assert_eq!(&bytes[16..20], &[0, 0, 0, 0]);
+
+        let sd: SecurityDescriptor = "D:".parse().unwrap();
+        assert_eq!(&sd.to_bytes()[HEADER_LEN..], &[2, 0, 8, 0, 0, 0, 0, 0]);
+
+        let sd: SecurityDescriptor = "O:SYG:SYD:".parse().unwrap();
+        assert_eq!(sd, SecurityDescriptor::system_owned());
+    }
+
+    #[test]
+    fn test_allow_and_deny_order() {
+        let mut sd: SecurityDescriptor = "D:(A;;CC;;;SY)(A;ID;CC;;;BA)".parse().unwrap();
+        sd.allow("S-1-5-19".parse().unwrap(), 1);
+        sd.deny("S-1-5-20".parse().unwrap(), 1);
+        sd.deny("S-1-1-0".parse().unwrap(), 1);
+
+        let expected: SecurityDescriptor =
+            "D:(D;;CC;;;NS)(D;;CC;;;WD)(A;;CC;;;SY)(A;;CC;;;LS)(A;ID;CC;;;BA)"
+                .parse()
+                .unwrap();
+        assert_eq!(sd, expected);
+    }
+
+    #[test]
+    fn test_invalid_sddl() {
+        for invalid in [
+            "",
+            "X:",
+            "D:(A;;CC;;;SY",
+            "D:A;;CC;;;SY)",
+            "D:(A;;CC;;SY)",
+            "D:(AU;;CC;;;SY)",
+            "D:(A;;ZZ;;;SY)",
+            "D:(A;XX;CC;;;SY)",
+            "D:(A;;CC;;;XX)",
+            "D:(A;;CC;;;S-1-5-x)",
+            "D:(OA;;CC;bf967aba-0de6-11d0-a285-00aa003049e2;;SY)",
+            "D:PX(A;;CC;;;SY)",
+            "D:NO_ACCESS_CONTROL(A;;CC;;;SY)",
+            "O:SYO:BA",
+            "S:(AU;SA;CC;;;WD)",
+            // Non-ASCII text must not be sliced inside a character
+            "O:€€",
+            "G:S€",
+            "D:(A;;CC;;;a€)",
+            "D:(A;;€;;;SY)",
+            "D:P€(A;;CC;;;SY)",
+            "D:(€;;CC;;;SY)",
+        ] {
+            assert!(invalid.parse::<SecurityDescriptor>().is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Security identifiers (SIDs).
+
+use std::ffi::c_void;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::ParseError;
+use crate::sha256::sha256;
+
+/// The identifier authority of AppContainer SIDs (`SECURITY_APP_PACKAGE_AUTHORITY`).
+const SECURITY_APP_PACKAGE_AUTHORITY: u64 = 15;
+/// The first sub-authority of AppContainer SIDs (`SECURITY_APP_PACKAGE_BASE_RID`).
+const SECURITY_APP_PACKAGE_BASE_RID: u32 = 2;
+
+/// An owned security identifier (SID) in its binary form.
+///
+/// The binary form is described by the [`SID`] structure. The [`Display`](fmt::Display) and
+/// [`FromStr`] implementations use the string format, e.g. `S-1-5-18`.
+///
+/// # Example
+///
+/// ```
+/// use wfp::Sid;
+///
+/// let sid: Sid = "S-1-5-18".parse().unwrap();
+/// assert_eq!(sid.identifier_authority(), 5);
+/// assert_eq!(sid.sub_authorities().collect::<Vec<_>>(), [18]);
+/// ```
+///
+/// [`SID`]: https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-sid
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Sid {
+    bytes: Box<[u8]>,
+}
+
+impl Sid {
+    /// The size of the revision, sub-authority count and identifier authority fields.
+    const HEADER_LEN: usize = 8;
+    /// The only supported SID revision (`SID_REVISION`).
+    const REVISION: u8 = 1;
+    /// The maximum number of sub-authorities (`SID_MAX_SUB_AUTHORITIES`).
+    const MAX_SUB_AUTHORITIES: usize = 15;
+    /// The maximum identifier authority, which is stored as a 48-bit integer.
+    const MAX_AUTHORITY: u64 = (1 << 48) - 1;
+    /// The maximum length of an AppContainer name, in UTF-16 code units.
+    const MAX_APP_CONTAINER_NAME_LEN: usize = 64;
+
+    /// Creates a SID from an identifier authority and its sub-authorities.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `identifier_authority` does not fit in 48 bits, or if there are more than 15
+    /// sub-authorities.
+    pub(crate) fn new(identifier_authority: u64, sub_authorities: &[u32]) -> Self {
+        assert!(identifier_authority <= Self::MAX_AUTHORITY);
+        assert!(sub_authorities.len() <= Self::MAX_SUB_AUTHORITIES);
+
+        let mut bytes = Vec::with_capacity(Self::HEADER_LEN + 4 * sub_authorities.len());
+        bytes.push(Self::REVISION);
+        bytes.push(u8::try_from(sub_authorities.len()).unwrap());
+        bytes.extend_from_slice(&identifier_authority.to_be_bytes()[2..]);
+        for sub_authority in sub_authorities {
+            bytes.extend_from_slice(&sub_authority.to_le_bytes());
+        }
+        Self {
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Derives the SID of an AppContainer from its name, e.g. the package family name
+    /// `Microsoft.WindowsCalculator_8wekyb3d8bbwe`.
+    ///
+    /// This computes the same SID as [`DeriveAppContainerSidFromAppContainerName`], without
+    /// calling into Windows: the sub-authorities following `S-1-15-2` are the first 28 bytes of
+    /// the SHA-256 digest of the lower-case name in UTF-16.
+    ///
+    /// Fails if the name is empty or longer than 64 characters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wfp::Sid;
+    ///
+    /// let sid = Sid::from_app_container_name("Microsoft.WindowsCalculator_8wekyb3d8bbwe").unwrap();
+    /// assert_eq!(
+    ///     sid.to_string(),
+    ///     "S-1-15-2-466767348-3739614953-2700836392-1801644223-4227750657-1087833535-2488631167",
+    /// );
+    /// ```
+    ///
+    /// [`DeriveAppContainerSidFromAppContainerName`]: https://learn.microsoft.com/en-us/windows/win32/api/userenv/nf-userenv-deriveappcontainersidfromappcontainername
+    pub fn from_app_container_name(name: &str) -> Result<Self, ParseError> {
+        let name_utf16: Vec<u16> = name.to_lowercase().encode_utf16().collect();
+        if name_utf16.is_empty() || name_utf16.len() > Self::MAX_APP_CONTAINER_NAME_LEN {
+            return Err(ParseError::new(
+                "AppContainer name",
+                format!("must be 1 to 64 characters long: \"{name}\""),
+            ));
+        }
+
+        let bytes: Vec<u8> = name_utf16.iter().flat_map(|c| c.to_le_bytes()).collect();
+        let digest = sha256(&bytes);
+
+        let mut sub_authorities = vec![SECURITY_APP_PACKAGE_BASE_RID];
+        sub_authorities.extend(
+            digest
+                .chunks_exact(4)
+                .take(7)
+                .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap())),
+        );
+        Ok(Self::new(SECURITY_APP_PACKAGE_AUTHORITY, &sub_authorities))
+    }
+
+    /// Copies a SID from a pointer to a `SID` structure.
+    ///
+    /// Returns `None` if `sid` is null.
+    ///
+    /// # Safety
+    ///
+    /// `sid` must be null or point to a valid SID.
+    pub(crate) unsafe fn from_raw(sid: *const c_void) -> Option<Self> {
+        if sid.is_null() {
+            return None;
+        }
+        let sid = sid.cast::<u8>();
+        // SAFETY: The second byte of a valid SID is the number of sub-authorities
+        let sub_authority_count = usize::from(unsafe { *sid.add(1) });
+        let len = Self::HEADER_LEN + 4 * sub_authority_count;
+        // SAFETY: A valid SID is followed by `sub_authority_count` sub-authorities
+        let bytes = unsafe { std::slice::from_raw_parts(sid, len) };
+        Some(Self {
+            bytes: bytes.into(),
+        })
+    }
+
+    /// Copies the SID at the start of `bytes`, ignoring any bytes that follow it.
+    ///
+    /// Returns `None` if `bytes` does not start with a valid SID.
+    pub(crate) fn from_prefix(bytes: &[u8]) -> Option<Self> {
+        let (&revision, &sub_authority_count) = (bytes.first()?, bytes.get(1)?);
+        let sub_authority_count = usize::from(sub_authority_count);
+        if revision != Self::REVISION || sub_authority_count > Self::MAX_SUB_AUTHORITIES {
+            return None;
+        }
+        let bytes = bytes.get(..Self::HEADER_LEN + 4 * sub_authority_count)?;
+        Some(Self {
+            bytes: bytes.into(),
+        })
+    }
+
+    /// Returns the binary form of the SID.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the identifier authority, e.g. 5 for `SECURITY_NT_AUTHORITY`.
+    pub fn identifier_authority(&self) -> u64 {
+        let mut authority = [0u8; 8];
+        authority[2..].copy_from_slice(&self.bytes[2..Self::HEADER_LEN]);
+        u64::from_be_bytes(authority)
+    }
+
+    /// Returns the sub-authorities, e.g. `[18]` for `S-1-5-18`.
+    pub fn sub_authorities(&self) -> impl Iterator<Item = u32> + '_ {
+        self.bytes[Self::HEADER_LEN..]
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+    }
+}
+
+impl fmt::Display for Sid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S-{}", self.bytes[0])?;
+        // Large authorities are written in hexadecimal, like `ConvertSidToStringSidW` does
+        let authority = self.identifier_authority();
+        if authority >= 1 << 32 {
+            write!(f, "-0x{authority:012X}")?;
+        } else {
+            write!(f, "-{authority}")?;
+        }
+        for sub_authority in self.sub_authorities() {
+            write!(f, "-{sub_authority}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Sid {
+    type Err = ParseError;
+
+    /// Parses a SID in the string format, e.g. `S-1-5-18`.
+    ///
+    /// Like `ConvertStringSidToSidW`, the identifier authority may be written in hexadecimal
+    /// with a `0x` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |reason: &str| ParseError::new("SID", format!("{reason}: \"{s}\""));
+
+        let mut parts = s.split('-');
+        if !parts
+            .next()
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("S"))
+        {
+            return Err(error("missing \"S-\" prefix"));
+        }
+        if parts.next() != Some("1") {
+            return Err(error("unsupported revision"));
+        }
+        let authority = parts
+            .next()
+            .ok_or_else(|| error("missing identifier authority"))?;
+        let authority = match authority
+            .strip_prefix("0x")
+            .or_else(|| authority.strip_prefix("0X"))
+        {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => authority.parse(),
+        }
+        .ok()
+        .filter(|authority| *authority <= Self::MAX_AUTHORITY)
+        .ok_or_else(|| error("invalid identifier authority"))?;
+
+        let sub_authorities = parts
+            .map(|part| part.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| error("invalid sub-authority"))?;
+        if sub_authorities.len() > Self::MAX_SUB_AUTHORITIES {
+            return Err(error("too many sub-authorities"));
+        }
+
+        Ok(Self::new(authority, &sub_authorities))
+    }
+}
+
+impl fmt::Debug for Sid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sid({self})")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sid_from_raw() {
+        // S-1-5-21-1-2-3-1001
+        let raw: [u8; 28] = [
+            1, 5, 0, 0, 0, 0, 0, 5, 21, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0xe9, 3, 0, 0,
+        ];
+        let sid = unsafe { Sid::from_raw(raw.as_ptr().cast()) }.unwrap();
+        assert_eq!(sid.as_bytes(), &raw);
+        assert_eq!(sid.to_string(), "S-1-5-21-1-2-3-1001");
+
+        assert!(unsafe { Sid::from_raw(std::ptr::null()) }.is_none());
+    }
+
+    #[test]
+    fn test_sid_from_prefix() {
+        // S-1-5-18, followed by unrelated data
+        let raw: [u8; 14] = [1, 1, 0, 0, 0, 0, 0, 5, 18, 0, 0, 0, 0xff, 0xff];
+        let sid = Sid::from_prefix(&raw).unwrap();
+        assert_eq!(sid.to_string(), "S-1-5-18");
+
+        assert!(Sid::from_prefix(&raw[..11]).is_none());
+        assert!(Sid::from_prefix(&[2, 0, 0, 0, 0, 0, 0, 5]).is_none());
+        assert!(Sid::from_prefix(&[]).is_none());
+    }
+
+    #[test]
+    fn test_sid_large_authority() {
+        let raw: [u8; 12] = [1, 1, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 7, 0, 0, 0];
+        let sid = unsafe { Sid::from_raw(raw.as_ptr().cast()) }.unwrap();
+        assert_eq!(sid.to_string(), "S-1-0x123456789ABC-7");
+        assert_eq!("S-1-0x123456789ABC-7".parse::<Sid>().unwrap(), sid);
+    }
+
+    #[test]
+    fn test_sid_from_app_container_name() {
+        let sid =
+            Sid::from_app_container_name("microsoft.windowscalculator_8wekyb3d8bbwe").unwrap();
+        // The name is case-insensitive
+        assert_eq!(
+            sid,
+            Sid::from_app_container_name("Microsoft.WindowsCalculator_8wekyb3d8bbwe").unwrap()
+        );
+        assert_eq!(
+            sid.to_string(),
+            "S-1-15-2-466767348-3739614953-2700836392-1801644223-4227750657-1087833535-2488631167"
+        );
+
+        assert!(Sid::from_app_container_name("").is_err());
+        assert!(Sid::from_app_container_name(&"a".repeat(65)).is_err());
+        assert!(Sid::from_app_container_name(&"a".repeat(64)).is_ok());
+    }
+
+    #[test]
+    fn test_sid_from_str() {
+        let sid: Sid = "S-1-5-21-1-2-3-1001".parse().unwrap();
+        assert_eq!(
+            sid.as_bytes(),
+            [
+                1, 5, 0, 0, 0, 0, 0, 5, 21, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0xe9, 3,
+                0, 0
+            ]
+        );
+        assert_eq!(sid.to_string(), "S-1-5-21-1-2-3-1001");
+        assert_eq!("S-1-1".parse::<Sid>().unwrap().sub_authorities().count(), 0);
+
+        for invalid in [
+            "",
+            "S",
+            "1-5-18",
+            "S-2-5-18",
+            "S-1-",
+            "S-1-5-",
+            "S-1-5-x",
+            "S-1-5-4294967296",
+            "S-1-281474976710656-1",
+            "S-1-5-1-2-3-4-5-6-7-8-9-10-11-12-13-14-15-16",
+        ] {
+            assert!(invalid.parse::<Sid>().is_err(), "{invalid}");
+        }
+    }
+}
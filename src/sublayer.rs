@@ -1,18 +1,19 @@
 //! Sublayer creation and management
 
 use std::ffi::OsStr;
-use std::io;
-use std::os::windows::io::AsRawHandle;
-use std::ptr;
+use std::fmt;
 use std::sync::Arc;
 
-use windows_sys::Win32::Foundation::ERROR_SUCCESS;
-use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWPM_SUBLAYER0;
-use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FwpmSubLayerAdd0;
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{
+    FWP_BYTE_BLOB, FWPM_SUBLAYER_FLAG_PERSISTENT, FWPM_SUBLAYER0,
+};
 use windows_sys::core::GUID;
 
+use crate::error::Result;
 use crate::transaction::Transaction;
-use crate::util::string_to_null_terminated_utf16;
+use crate::util::{
+    GuidDisplay, byte_blob_as_slice, display_string, flags_type, string_to_null_terminated_utf16,
+};
 
 /// Builder for creating Windows Filtering Platform sublayers.
 ///
@@ -45,6 +46,12 @@ use crate::util::string_to_null_terminated_utf16;
 /// }
 /// ```
 ///
+/// # Persistent sublayers
+///
+/// [`SubLayerBuilder::persistent`] marks the sublayer as surviving a Base Filtering Engine
+/// restart. Persistent sublayers survive reboots and must be cleaned up explicitly with
+/// [`delete_sublayer_by_guid`].
+///
 /// [`FWPM_SUBLAYER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_sublayer0
 /// [Filter Arbitration]: https://docs.microsoft.com/en-us/windows/win32/fwp/filter-arbitration
 #[derive(Clone)]
@@ -54,6 +61,7 @@ pub struct SubLayerBuilder<Name> {
     display_data_name_buffer: Arc<[u16]>,
     display_data_desc_buffer: Arc<[u16]>,
     provider_key: Option<Arc<GUID>>,
+    provider_data: Option<Arc<[u8]>>,
 
     _pd: std::marker::PhantomData<Name>,
 }
@@ -81,6 +89,7 @@ impl Default for SubLayerBuilder<SubLayerBuilderMissingName> {
             display_data_name_buffer: Default::default(),
             display_data_desc_buffer: Default::default(),
             provider_key: None,
+            provider_data: None,
             _pd: Default::default(),
         }
     }
@@ -103,6 +112,7 @@ impl<Name> SubLayerBuilder<Name> {
             display_data_name_buffer: self.display_data_name_buffer,
             display_data_desc_buffer: self.display_data_desc_buffer,
             provider_key: self.provider_key,
+            provider_data: self.provider_data,
 
             _pd: std::marker::PhantomData,
         }
@@ -124,6 +134,7 @@ impl<Name> SubLayerBuilder<Name> {
             display_data_name_buffer: self.display_data_name_buffer,
             display_data_desc_buffer: self.display_data_desc_buffer,
             provider_key: self.provider_key,
+            provider_data: self.provider_data,
 
             _pd: std::marker::PhantomData,
         }
@@ -167,6 +178,43 @@ impl<Name> SubLayerBuilder<Name> {
         self.provider_key = Some(key);
         self
     }
+
+    /// Marks the sublayer as persistent.
+    ///
+    /// Persistent sublayers survive a Base Filtering Engine restart. They can only be added
+    /// by a non-dynamic session, and only to a persistent [provider](Self::provider).
+    /// Persistent state survives reboots and must be cleaned up explicitly with
+    /// [`delete_sublayer_by_guid`].
+    ///
+    /// This sets the `FWPM_SUBLAYER_FLAG_PERSISTENT` bit in the `flags` field of the
+    /// underlying [`FWPM_SUBLAYER0`] structure.
+    ///
+    /// [`FWPM_SUBLAYER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_sublayer0
+    pub fn persistent(mut self) -> SubLayerBuilder<Name> {
+        self.sublayer.flags |= FWPM_SUBLAYER_FLAG_PERSISTENT;
+        self
+    }
+
+    /// Attaches arbitrary data to the sublayer, which is returned by
+    /// [`SubLayerInfo::provider_data`].
+    ///
+    /// This sets the `providerData` field in the underlying [`FWPM_SUBLAYER0`] structure.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is 4 GiB or larger, since the length is stored as a `u32`.
+    ///
+    /// [`FWPM_SUBLAYER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_sublayer0
+    pub fn provider_data(mut self, data: impl AsRef<[u8]>) -> SubLayerBuilder<Name> {
+        let data: Arc<[u8]> = Arc::from(data.as_ref());
+        // SAFETY: The data is never mutated; the Arc keeps it alive as long as `self` lives.
+        self.sublayer.providerData = FWP_BYTE_BLOB {
+            size: u32::try_from(data.len()).expect("provider data too large"),
+            data: data.as_ptr() as *mut _,
+        };
+        self.provider_data = Some(data);
+        self
+    }
 }
 
 impl SubLayerBuilder<SubLayerBuilderHasName> {
@@ -178,24 +226,150 @@ impl SubLayerBuilder<SubLayerBuilderHasName> {
     /// It calls [`FwpmSubLayerAdd0`] to add the sublayer to the engine.
     ///
     /// [`FwpmSubLayerAdd0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmsublayeradd0
-    pub fn add<'a>(&self, transaction: &Transaction<'a>) -> io::Result<()> {
+    pub fn add<'a>(&self, transaction: &Transaction<'a>) -> Result<()> {
         // SAFETY:
-        // - transaction.engine.as_raw_handle() returns a valid engine handle
         // - &self.sublayer is a valid pointer to a properly initialized FWPM_SUBLAYER0 structure
         // - All required fields (name, description) have been set by the type system
         // - The display data buffers are kept alive by self, ensuring string pointers remain valid
-        // - NULL security descriptor pointer is acceptable (uses default security)
-        let status = unsafe {
-            FwpmSubLayerAdd0(
-                transaction.engine.as_raw_handle(),
-                &self.sublayer,
-                ptr::null_mut(),
-            )
-        };
-        if status != ERROR_SUCCESS {
-            return Err(io::Error::from_raw_os_error(status as i32));
+        unsafe { transaction.engine.backend().sublayer_add(&self.sublayer) }
+    }
+}
+
+/// Delete a sublayer by its GUID.
+///
+/// The GUID corresponds to the `subLayerKey` field in the underlying [`FWPM_SUBLAYER0`]
+/// structure.
+///
+/// This calls [`FwpmSubLayerDeleteByKey0`]. It returns
+/// [`WfpError::InUse`](crate::WfpError::InUse) if any filter still belongs to the sublayer;
+/// remove those first.
+///
+/// [`FWPM_SUBLAYER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_sublayer0
+/// [`FwpmSubLayerDeleteByKey0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmsublayerdeletebykey0
+pub fn delete_sublayer_by_guid<'a>(transaction: &Transaction<'a>, guid: &GUID) -> Result<()> {
+    transaction.engine.backend().sublayer_delete_by_key(guid)
+}
+
+/// Return an owned copy of the sublayer with the given GUID.
+///
+/// This calls [`FwpmSubLayerGetByKey0`]. It returns
+/// [`WfpError::NotFound`](crate::WfpError::NotFound) if there is no such sublayer.
+/// `transaction` may be a [read-only](Transaction::read_only) transaction.
+///
+/// [`FwpmSubLayerGetByKey0`]: https://learn.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmsublayergetbykey0
+pub fn get_sublayer_by_key<Mode>(
+    transaction: &Transaction<'_, Mode>,
+    guid: &GUID,
+) -> Result<SubLayerInfo> {
+    let sublayer = transaction.engine.backend().sublayer_get_by_key(guid)?;
+    // SAFETY: The sublayer was returned by the backend, so its pointers are valid
+    unsafe { SubLayerInfo::from_raw(sublayer.get()) }
+}
+
+flags_type! {
+    /// Flags of a sublayer.
+    ///
+    /// These correspond to the `FWPM_SUBLAYER_FLAG_*` values of the `flags` field in the
+    /// underlying [`FWPM_SUBLAYER0`] structure.
+    ///
+    /// [`FWPM_SUBLAYER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_sublayer0
+    pub struct SubLayerFlags {
+        /// The sublayer survives a BFE restart (`FWPM_SUBLAYER_FLAG_PERSISTENT`).
+        const PERSISTENT = FWPM_SUBLAYER_FLAG_PERSISTENT;
+    }
+}
+
+/// An owned copy of a sublayer.
+///
+/// This is returned by [`SubLayerEnumerator`](crate::SubLayerEnumerator) and
+/// [`get_sublayer_by_key`]. The [weight](Self::weight) determines the order in which
+/// sublayers are evaluated, see [Filter Arbitration].
+///
+/// This corresponds to the underlying [`FWPM_SUBLAYER0`] structure.
+///
+/// [`FWPM_SUBLAYER0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmtypes/ns-fwpmtypes-fwpm_sublayer0
+/// [Filter Arbitration]: https://docs.microsoft.com/en-us/windows/win32/fwp/filter-arbitration
+#[derive(Clone)]
+pub struct SubLayerInfo {
+    key: GUID,
+    name: Option<String>,
+    description: Option<String>,
+    flags: SubLayerFlags,
+    provider: Option<GUID>,
+    provider_data: Vec<u8>,
+    weight: u16,
+}
+
+impl SubLayerInfo {
+    /// Deep-copies a raw sublayer.
+    ///
+    /// # Safety
+    ///
+    /// All pointers in `sublayer` must be valid.
+    pub(crate) unsafe fn from_raw(sublayer: &FWPM_SUBLAYER0) -> Result<Self> {
+        // SAFETY: The caller guarantees that the pointers are valid
+        unsafe {
+            Ok(Self {
+                key: sublayer.subLayerKey,
+                name: display_string(sublayer.displayData.name, "invalid sublayer name")?,
+                description: display_string(
+                    sublayer.displayData.description,
+                    "invalid sublayer description",
+                )?,
+                flags: SubLayerFlags::from_bits_retain(sublayer.flags),
+                provider: sublayer.providerKey.as_ref().copied(),
+                provider_data: byte_blob_as_slice(&sublayer.providerData).to_vec(),
+                weight: sublayer.weight,
+            })
         }
+    }
+
+    /// Return the sublayer GUID.
+    pub fn guid(&self) -> GUID {
+        self.key
+    }
+
+    /// Return the sublayer name, if set.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Return the sublayer description, if set.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Return the sublayer flags.
+    pub fn flags(&self) -> SubLayerFlags {
+        self.flags
+    }
+
+    /// Return the provider that the sublayer belongs to, if set.
+    pub fn provider(&self) -> Option<GUID> {
+        self.provider
+    }
+
+    /// Return the provider data of the sublayer. This is empty if none is set.
+    pub fn provider_data(&self) -> &[u8] {
+        &self.provider_data
+    }
+
+    /// Return the weight of the sublayer. Sublayers with a higher weight are evaluated first.
+    pub fn weight(&self) -> u16 {
+        self.weight
+    }
+}
 
-        Ok(())
+impl fmt::Debug for SubLayerInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubLayerInfo")
+            .field("key", &GuidDisplay(&self.key))
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("flags", &self.flags)
+            .field("provider", &self.provider.as_ref().map(GuidDisplay))
+            .field("provider_data", &self.provider_data)
+            .field("weight", &self.weight)
+            .finish()
     }
 }
@@ -1,14 +1,12 @@
 //! Transaction creation and management
 
-use std::io;
-use std::os::windows::io::AsRawHandle;
+use std::marker::PhantomData;
+use std::mem;
 
-use windows_sys::Win32::Foundation::ERROR_SUCCESS;
-use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FwpmTransactionAbort0;
-use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FwpmTransactionBegin0;
-use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FwpmTransactionCommit0;
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWPM_TXN_READ_ONLY;
 
 use crate::engine::FilterEngine;
+use crate::error::Result;
 
 /// Represents a transactional context for filter operations.
 ///
@@ -16,8 +14,11 @@ use crate::engine::FilterEngine;
 /// If any operation fails, the entire transaction can be rolled back, leaving
 /// the filter state unchanged.
 ///
-/// The transaction holds a mutable reference to the `FilterEngine`, preventing
-/// other transactions on the engine until this one is completed or dropped.
+/// A read-write transaction, created using [`Transaction::new`], holds a mutable reference to
+/// the `FilterEngine`, preventing other transactions on the engine until this one is completed
+/// or dropped. A read-only transaction, created using [`Transaction::read_only`], only needs a
+/// shared reference. It can be used for enumeration and lookups, but objects cannot be added
+/// or deleted through it.
 ///
 /// # Drop behavior
 ///
@@ -27,32 +28,83 @@ use crate::engine::FilterEngine;
 ///
 /// If you wish to explicitly abort a transaction and handle any error, call
 /// [`Transaction::abort`].
-pub struct Transaction<'a> {
-    pub(crate) engine: &'a mut FilterEngine,
+pub struct Transaction<'a, Mode = ReadWrite> {
+    pub(crate) engine: &'a FilterEngine,
+    _mode: PhantomData<Mode>,
 }
 
-// SAFETY: Crossing thread-boundaries is fine
-unsafe impl Send for Transaction<'_> {}
+/// Type-level marker for transactions that may modify the engine.
+pub struct ReadWrite;
 
-impl<'a> Transaction<'a> {
+/// Type-level marker for read-only transactions.
+pub struct ReadOnly;
+
+// SAFETY: Crossing thread-boundaries is fine. A read-write transaction has exclusive access to
+//         the engine.
+unsafe impl Send for Transaction<'_, ReadWrite> {}
+
+impl<'a> Transaction<'a, ReadWrite> {
     /// Creates a new transaction for the given filter engine.
     ///
     /// This method calls [`FwpmTransactionBegin0`] to start a new transaction context.
+    /// It fails with [`WfpError::Timeout`](crate::WfpError::Timeout) if another session holds
+    /// a transaction for longer than the session's transaction wait timeout.
     ///
     /// [`FwpmTransactionBegin0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmtransactionbegin0
-    pub fn new(engine: &'a mut FilterEngine) -> io::Result<Self> {
-        // TODO: read-only
-        // SAFETY:
-        // - engine.as_raw_handle() returns a valid engine handle from FilterEngine
-        // - 0 is a valid flags parameter (no special transaction flags)
-        // - The engine handle remains valid for the lifetime of the transaction
-        let status = unsafe { FwpmTransactionBegin0(engine.as_raw_handle(), 0) };
-        // FIXME: handle other errors
-        if status != ERROR_SUCCESS {
-            return Err(io::Error::from_raw_os_error(status as i32));
-        }
+    pub fn new(engine: &'a mut FilterEngine) -> Result<Self> {
+        // 0 is a valid flags parameter (no special transaction flags)
+        Self::begin(engine, 0)
+    }
+}
 
-        Ok(Self { engine })
+impl<'a> Transaction<'a, ReadOnly> {
+    /// Creates a new read-only transaction for the given filter engine.
+    ///
+    /// This method calls [`FwpmTransactionBegin0`] with `FWPM_TXN_READ_ONLY`. Read-only
+    /// transactions in different sessions do not block each other.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wfp::{FilterEngineBuilder, FilterEnumerator, MemoryBfe, Transaction};
+    ///
+    /// let bfe = MemoryBfe::new();
+    /// let engine = FilterEngineBuilder::default().dynamic().open_in_memory(&bfe).unwrap();
+    ///
+    /// let transaction = Transaction::read_only(&engine).unwrap();
+    /// let mut filters = FilterEnumerator::new(&transaction).unwrap();
+    /// assert!(filters.next().is_none());
+    /// ```
+    ///
+    /// Objects cannot be added through a read-only transaction:
+    ///
+    /// ```compile_fail
+    /// # use wfp::{ActionType, FilterBuilder, FilterEngineBuilder, Layer, MemoryBfe, Transaction};
+    /// # let bfe = MemoryBfe::new();
+    /// # let engine = FilterEngineBuilder::default().dynamic().open_in_memory(&bfe).unwrap();
+    /// let transaction = Transaction::read_only(&engine).unwrap();
+    /// FilterBuilder::default()
+    ///     .name("Filter")
+    ///     .action(ActionType::Block)
+    ///     .layer(Layer::ConnectV4)
+    ///     .add(&transaction)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`FwpmTransactionBegin0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmtransactionbegin0
+    pub fn read_only(engine: &'a FilterEngine) -> Result<Self> {
+        Self::begin(engine, FWPM_TXN_READ_ONLY)
+    }
+}
+
+impl<'a, Mode> Transaction<'a, Mode> {
+    fn begin(engine: &'a FilterEngine, flags: u32) -> Result<Self> {
+        engine.backend().transaction_begin(flags)?;
+
+        Ok(Self {
+            engine,
+            _mode: PhantomData,
+        })
     }
 
     /// Commits all changes made during this transaction.
@@ -61,17 +113,11 @@ impl<'a> Transaction<'a> {
     /// become permanent and visible to the system. This method calls [`FwpmTransactionCommit0`].
     ///
     /// [`FwpmTransactionCommit0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmtransactioncommit0
-    pub fn commit(self) -> io::Result<()> {
-        // SAFETY:
-        // - self.engine.as_raw_handle() returns a valid engine handle
-        // - A transaction was successfully started with FwpmTransactionBegin0
-        // - This consumes self, preventing multiple commits of the same transaction
-        let status = unsafe { FwpmTransactionCommit0(self.engine.as_raw_handle()) };
-        // FIXME: handle other errors
-        if status != ERROR_SUCCESS {
-            return Err(io::Error::from_raw_os_error(status as i32));
-        }
-
+    pub fn commit(self) -> Result<()> {
+        // This consumes self, preventing multiple commits of the same transaction
+        self.engine.backend().transaction_commit()?;
+        // The transaction is complete, so there is nothing left to abort
+        mem::forget(self);
         Ok(())
     }
 
@@ -82,26 +128,19 @@ impl<'a> Transaction<'a> {
     /// This method calls [`FwpmTransactionAbort0`].
     ///
     /// [`FwpmTransactionAbort0`]: https://docs.microsoft.com/en-us/windows/win32/api/fwpmu/nf-fwpmu-fwpmtransactionabort0
-    pub fn abort(self) -> io::Result<()> {
-        self.abort_inner()
+    pub fn abort(self) -> Result<()> {
+        let result = self.abort_inner();
+        // Do not attempt to abort the transaction again when dropped
+        mem::forget(self);
+        result
     }
 
-    fn abort_inner(&self) -> io::Result<()> {
-        // SAFETY:
-        // - self.engine.as_raw_handle() returns a valid engine handle
-        // - A transaction was successfully started with FwpmTransactionBegin0
-        // - FwpmTransactionAbort0 is safe to call multiple times on the same transaction
-        let status = unsafe { FwpmTransactionAbort0(self.engine.as_raw_handle()) };
-        // FIXME: handle other errors
-        if status != ERROR_SUCCESS {
-            return Err(io::Error::from_raw_os_error(status as i32));
-        }
-
-        Ok(())
+    fn abort_inner(&self) -> Result<()> {
+        self.engine.backend().transaction_abort()
     }
 }
 
-impl<'a> Drop for Transaction<'a> {
+impl<'a, Mode> Drop for Transaction<'a, Mode> {
     fn drop(&mut self) {
         if let Err(err) = self.abort_inner() {
             log::error!("Failed to abort dropped transaction: {err}");
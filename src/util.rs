@@ -1,6 +1,228 @@
-use std::{ffi::OsStr, iter, os::windows::ffi::OsStrExt};
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::{ffi::OsStr, fmt, io, iter};
+
+use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::FWP_BYTE_BLOB;
+use windows_sys::core::GUID;
+
+use crate::error::{Result, WfpError};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::ffi::OsStrExt;
 
 /// Convert `s` to a null-terminated UTF-16 string
+#[cfg(target_os = "windows")]
 pub fn string_to_null_terminated_utf16<T: FromIterator<u16>>(s: impl AsRef<OsStr>) -> T {
     s.as_ref().encode_wide().chain(iter::once(0u16)).collect()
 }
+
+/// Convert `s` to a null-terminated UTF-16 string
+///
+/// Outside Windows, `OsStr` has no UTF-16 representation, so invalid Unicode is replaced
+/// with U+FFFD.
+#[cfg(not(target_os = "windows"))]
+pub fn string_to_null_terminated_utf16<T: FromIterator<u16>>(s: impl AsRef<OsStr>) -> T {
+    s.as_ref()
+        .to_string_lossy()
+        .encode_utf16()
+        .chain(iter::once(0u16))
+        .collect()
+}
+
+/// Copy `s`, a null-terminated UTF-16 string, into a `String`.
+///
+/// Returns `None` if `s` is null. Invalid UTF-16 is replaced with U+FFFD.
+///
+/// # Safety
+///
+/// `s` must be null or null-terminated.
+pub unsafe fn wide_to_string(s: *const u16) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+    // SAFETY: The caller guarantees that `s` is null-terminated
+    let slice = unsafe { std::slice::from_raw_parts(s, wcslen(s)) };
+    Some(String::from_utf16_lossy(slice))
+}
+
+/// Copies a display string, returning an error with `error_message` if it is not valid UTF-16.
+///
+/// # Safety
+///
+/// `s` must be null or null-terminated.
+pub unsafe fn display_string(s: *const u16, error_message: &'static str) -> Result<Option<String>> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    // SAFETY: The caller guarantees that `s` is null-terminated
+    let slice = unsafe { std::slice::from_raw_parts(s, wcslen(s)) };
+    String::from_utf16(slice)
+        .map_err(|_err| WfpError::Io(io::Error::other(error_message)))
+        .map(Some)
+}
+
+/// Retrieve the length of `s`, a null-terminated UTF-16 string.
+///
+/// # Safety
+///
+/// `s` must be null-terminated.
+pub unsafe fn wcslen(s: *const u16) -> usize {
+    let mut current = s;
+    while unsafe { std::ptr::read_unaligned(current) } != 0 {
+        current = unsafe { current.add(1) };
+    }
+    usize::try_from(unsafe { current.offset_from(s) }).unwrap()
+}
+
+/// Return the contents of `blob` as a slice.
+///
+/// # Safety
+///
+/// `blob.data` must point to `blob.size` readable bytes, or be null.
+pub unsafe fn byte_blob_as_slice(blob: &FWP_BYTE_BLOB) -> &[u8] {
+    if blob.data.is_null() || blob.size == 0 {
+        return &[];
+    }
+    // SAFETY: The caller guarantees that `data` points to `size` bytes
+    unsafe { std::slice::from_raw_parts(blob.data, usize::try_from(blob.size).unwrap()) }
+}
+
+/// Convert `guid` to a `u128`. This is the inverse of [`GUID::from_u128`].
+pub fn guid_to_u128(guid: &GUID) -> u128 {
+    (u128::from(guid.data1) << 96)
+        | (u128::from(guid.data2) << 80)
+        | (u128::from(guid.data3) << 64)
+        | u128::from(u64::from_be_bytes(guid.data4))
+}
+
+/// Generate a random (version 4) GUID.
+pub fn generate_guid() -> GUID {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u8(0);
+    let high = hasher.finish();
+    hasher.write_u8(1);
+    let low = hasher.finish();
+    let mut key = (u128::from(high) << 64) | u128::from(low);
+    // Make it a valid version 4 (random) UUID
+    key &= !(0xf << 76 | 0x3 << 62);
+    key |= 0x4 << 76 | 0x2 << 62;
+    GUID::from_u128(key)
+}
+
+/// Return whether `a` and `b` are the same GUID.
+pub fn guid_eq(a: &GUID, b: &GUID) -> bool {
+    guid_to_u128(a) == guid_to_u128(b)
+}
+
+/// Formats a GUID in registry format, e.g. `{6b29fc40-ca47-1067-b31d-00dd010662da}`.
+pub struct GuidDisplay<'a>(pub &'a GUID);
+
+impl fmt::Display for GuidDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = guid_to_u128(self.0);
+        write!(
+            f,
+            "{{{:08x}-{:04x}-{:04x}-{:04x}-{:012x}}}",
+            value >> 96,
+            (value >> 80) & 0xffff,
+            (value >> 64) & 0xffff,
+            (value >> 48) & 0xffff,
+            value & 0xffff_ffff_ffff,
+        )
+    }
+}
+
+impl fmt::Debug for GuidDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Defines a set of flags stored as a `u32`.
+macro_rules! flags_type {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$flag_meta:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct $name(u32);
+
+        impl $name {
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: Self = Self($value);
+            )*
+
+            /// Returns a value with no flags set.
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// Returns the raw value.
+            pub const fn bits(self) -> u32 {
+                self.0
+            }
+
+            /// Creates a value from raw bits, including bits that have no named flag.
+            pub const fn from_bits_retain(bits: u32) -> Self {
+                Self(bits)
+            }
+
+            /// Returns whether no flags are set.
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// Returns whether all flags in `other` are set.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+        }
+
+        impl ::std::ops::BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                Self(self.0 | rhs.0)
+            }
+        }
+
+        impl ::std::ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0;
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let mut remaining = self.0;
+                let mut first = true;
+                write!(f, "{}(", stringify!($name))?;
+                $(
+                    if $value != 0 && remaining & $value == $value {
+                        if !first {
+                            f.write_str(" | ")?;
+                        }
+                        f.write_str(stringify!($flag))?;
+                        remaining &= !$value;
+                        first = false;
+                    }
+                )*
+                if remaining != 0 || first {
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "{remaining:#x}")?;
+                }
+                f.write_str(")")
+            }
+        }
+    };
+}
+
+pub(crate) use flags_type;
@@ -1,19 +1,62 @@
 //! Integration tests for the Windows Filtering Platform library.
+//!
+//! With the `wfp-integration-tests` feature enabled on Windows, the tests run against the
+//! Base Filtering Engine. Otherwise, they run against a [`MemoryBfe`].
 
 use std::net::{Ipv4Addr, Ipv6Addr};
+use std::num::NonZeroUsize;
+use std::time::Duration;
 
 use windows_sys::core::GUID;
 
 // Import the library modules we want to test
 use wfp::*;
 
+/// The filter engine that the tests run against.
+#[derive(Default)]
+struct TestBfe {
+    #[cfg(not(all(target_os = "windows", feature = "wfp-integration-tests")))]
+    bfe: MemoryBfe,
+}
+
+impl TestBfe {
+    /// Open a dynamic session.
+    fn open(&self) -> FilterEngine {
+        self.open_with(FilterEngineBuilder::default().dynamic())
+    }
+
+    /// Open a session configured by `builder`.
+    fn open_with(&self, builder: FilterEngineBuilder) -> FilterEngine {
+        #[cfg(all(target_os = "windows", feature = "wfp-integration-tests"))]
+        let engine = builder.open();
+        #[cfg(not(all(target_os = "windows", feature = "wfp-integration-tests")))]
+        let engine = builder.open_in_memory(&self.bfe);
+
+        engine.expect("Should be able to open filter engine")
+    }
+}
+
+/// Open a dynamic session with a new engine.
+fn open_engine() -> FilterEngine {
+    TestBfe::default().open()
+}
+
+/// Return whether a filter with the given key exists.
+fn filter_exists<Mode>(transaction: &Transaction<'_, Mode>, key: GUID) -> bool {
+    match get_filter_by_key(transaction, &key) {
+        Ok(_) => true,
+        Err(WfpError::NotFound(ObjectType::Filter)) => false,
+        Err(error) => panic!("Should be able to look up filter: {error}"),
+    }
+}
+
+fn guid_eq(a: &GUID, b: &GUID) -> bool {
+    (a.data1, a.data2, a.data3, a.data4) == (b.data1, b.data2, b.data3, b.data4)
+}
+
 #[test]
-#[cfg_attr(not(feature = "wfp-integration-tests"), ignore)]
 fn test_add_filters_and_sublayer() {
-    let mut engine = FilterEngineBuilder::default()
-        .dynamic()
-        .open()
-        .expect("Should be able to open filter engine");
+    let mut engine = open_engine();
 
     let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
 
@@ -64,12 +107,8 @@ fn test_add_filters_and_sublayer() {
 }
 
 #[test]
-#[cfg_attr(not(feature = "wfp-integration-tests"), ignore)]
 fn test_add_provider_and_attach_filters() {
-    let mut engine = FilterEngineBuilder::default()
-        .dynamic()
-        .open()
-        .expect("Should be able to open filter engine");
+    let mut engine = open_engine();
 
     let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
 
@@ -109,13 +148,11 @@ fn test_add_provider_and_attach_filters() {
         .expect("Should be able to commit provider transaction");
 }
 
+#[cfg(target_os = "windows")]
 #[test]
 #[cfg_attr(not(feature = "wfp-integration-tests"), ignore)]
 fn test_app_id_condition() {
-    let mut engine = FilterEngineBuilder::default()
-        .dynamic()
-        .open()
-        .expect("Should be able to open filter engine");
+    let mut engine = open_engine();
 
     let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
 
@@ -158,12 +195,8 @@ fn test_app_id_condition() {
 }
 
 #[test]
-#[cfg_attr(not(feature = "wfp-integration-tests"), ignore)]
 fn test_ndp_filter() {
-    let mut engine = FilterEngineBuilder::default()
-        .dynamic()
-        .open()
-        .expect("Should be able to open filter engine");
+    let mut engine = open_engine();
 
     let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
 
@@ -219,13 +252,11 @@ fn test_ndp_filter() {
         .expect("Should be able to commit NDP filter transaction");
 }
 
+#[cfg(target_os = "windows")]
 #[test]
 #[cfg_attr(not(feature = "wfp-integration-tests"), ignore)]
 fn test_local_interface_condition() {
-    let mut engine = FilterEngineBuilder::default()
-        .dynamic()
-        .open()
-        .expect("Should be able to open filter engine");
+    let mut engine = open_engine();
 
     let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
 
@@ -267,12 +298,8 @@ fn test_local_interface_condition() {
 }
 
 #[test]
-#[cfg_attr(not(feature = "wfp-integration-tests"), ignore)]
 fn test_ip_address_subnet_condition() {
-    let mut engine = FilterEngineBuilder::default()
-        .dynamic()
-        .open()
-        .expect("Should be able to open filter engine");
+    let mut engine = open_engine();
 
     let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
 
@@ -294,6 +321,7 @@ fn test_ip_address_subnet_condition() {
         .condition(
             IpAddressConditionBuilder::remote()
                 .subnet_v4(Ipv4Addr::new(192, 168, 0, 0), 16)
+                .expect("Should be a valid prefix")
                 .build(),
         )
         .sublayer(test_guid)
@@ -308,6 +336,7 @@ fn test_ip_address_subnet_condition() {
         .condition(
             IpAddressConditionBuilder::remote()
                 .subnet_v6("fe80::".parse::<Ipv6Addr>().unwrap(), 10)
+                .expect("Should be a valid prefix")
                 .build(),
         )
         .sublayer(test_guid)
@@ -318,3 +347,1241 @@ fn test_ip_address_subnet_condition() {
         .commit()
         .expect("Should be able to commit IP-address filter transaction");
 }
+
+#[test]
+fn test_ip_network_subnet_condition() {
+    let mut engine = open_engine();
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+
+    let v4_filter_guid = GUID::from_u128(0x1a7e001e_1234_5678_9abc_def012345678);
+    let v6_filter_guid = GUID::from_u128(0x1a7e001f_1234_5678_9abc_def012345678);
+
+    FilterBuilder::default()
+        .name("Permit 192.168.0.0/16")
+        .description("Permits the 192.168/16 range")
+        .action(ActionType::Permit)
+        .layer(Layer::ConnectV4)
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .subnet("192.168.0.0/16".parse::<IpNetwork>().unwrap())
+                .build(),
+        )
+        .guid(v4_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add v4 network filter");
+
+    FilterBuilder::default()
+        .name("Permit fe80::/10")
+        .description("Permits the IPv6 link-local range")
+        .action(ActionType::Permit)
+        .layer(Layer::ConnectV6)
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .subnet("fe80::/10".parse::<Ipv6Network>().unwrap())
+                .build(),
+        )
+        .guid(v6_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add v6 network filter");
+
+    assert!(filter_exists(&transaction, v4_filter_guid));
+    assert!(filter_exists(&transaction, v6_filter_guid));
+}
+
+#[test]
+fn test_duplicate_filter_key() {
+    let mut engine = open_engine();
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+
+    let test_filter_guid = GUID::from_u128(0x0dd1e5e5_1234_5678_9abc_def012345678);
+
+    let filter = || {
+        FilterBuilder::default()
+            .name("Duplicate Filter")
+            .description("Filter that is added twice")
+            .action(ActionType::Block)
+            .layer(Layer::ConnectV4)
+            .guid(test_filter_guid)
+    };
+
+    filter()
+        .add(&transaction)
+        .expect("Should be able to add filter");
+    let err = filter()
+        .add(&transaction)
+        .expect_err("Should not be able to add a filter with an existing key");
+    assert!(err.is_already_exists(), "Unexpected error: {err}");
+}
+
+#[test]
+fn test_missing_sublayer_and_provider() {
+    let mut engine = open_engine();
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+
+    let err = FilterBuilder::default()
+        .name("Orphan Sublayer Filter")
+        .description("Filter that refers to a nonexistent sublayer")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .sublayer(GUID::from_u128(0x0bad5b1a_1234_5678_9abc_def012345678))
+        .add(&transaction)
+        .expect_err("Should not be able to add a filter to a nonexistent sublayer");
+    assert!(
+        matches!(err, WfpError::NotFound(ObjectType::SubLayer)),
+        "Unexpected error: {err}"
+    );
+
+    let err = FilterBuilder::default()
+        .name("Orphan Provider Filter")
+        .description("Filter that refers to a nonexistent provider")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .provider(GUID::from_u128(0x0badb0de_1234_5678_9abc_def012345678))
+        .add(&transaction)
+        .expect_err("Should not be able to add a filter with a nonexistent provider");
+    assert!(
+        matches!(err, WfpError::NotFound(ObjectType::Provider)),
+        "Unexpected error: {err}"
+    );
+
+    let err = FilterBuilder::default()
+        .name("Orphan Layer Filter")
+        .description("Filter that does not set a layer")
+        .action(ActionType::Block)
+        .add(&transaction)
+        .expect_err("Should not be able to add a filter without a layer");
+    assert!(
+        matches!(err, WfpError::NotFound(ObjectType::Layer)),
+        "Unexpected error: {err}"
+    );
+    assert!(err.is_layer_not_found());
+}
+
+#[test]
+fn test_transaction_rollback() {
+    let mut engine = open_engine();
+
+    let aborted_guid = GUID::from_u128(0xab0b7ed0_1234_5678_9abc_def012345678);
+    let dropped_guid = GUID::from_u128(0xd0bbed00_1234_5678_9abc_def012345678);
+
+    let add_filter = |transaction: &Transaction<'_>, guid| {
+        FilterBuilder::default()
+            .name("Rollback Filter")
+            .description("Filter that is rolled back")
+            .action(ActionType::Block)
+            .layer(Layer::ConnectV4)
+            .guid(guid)
+            .add(transaction)
+            .expect("Should be able to add filter");
+    };
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    add_filter(&transaction, aborted_guid);
+    assert!(filter_exists(&transaction, aborted_guid));
+    transaction
+        .abort()
+        .expect("Should be able to abort transaction");
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    add_filter(&transaction, dropped_guid);
+    drop(transaction);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    assert!(!filter_exists(&transaction, aborted_guid));
+    assert!(!filter_exists(&transaction, dropped_guid));
+}
+
+#[test]
+fn test_dynamic_session_cleanup() {
+    let bfe = TestBfe::default();
+    let mut dynamic_engine = bfe.open();
+    let mut observer = bfe.open();
+
+    let test_filter_guid = GUID::from_u128(0xdd5e5510_1234_5678_9abc_def012345678);
+
+    let transaction =
+        Transaction::new(&mut dynamic_engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Dynamic Filter")
+        .description("Filter that is removed with its session")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+    transaction
+        .commit()
+        .expect("Should be able to commit transaction");
+
+    {
+        let transaction =
+            Transaction::new(&mut observer).expect("Should be able to create transaction");
+        assert!(filter_exists(&transaction, test_filter_guid));
+    }
+
+    drop(dynamic_engine);
+
+    let transaction =
+        Transaction::new(&mut observer).expect("Should be able to create transaction");
+    assert!(!filter_exists(&transaction, test_filter_guid));
+}
+
+#[test]
+fn test_add_returns_id_and_key() {
+    let mut engine = open_engine();
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+
+    let test_filter_guid = GUID::from_u128(0x1dca11ed_1234_5678_9abc_def012345678);
+
+    let keyed = FilterBuilder::default()
+        .name("Keyed Filter")
+        .description("Filter with a key set by the caller")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+    assert!(guid_eq(&keyed.key(), &test_filter_guid));
+
+    let generated = FilterBuilder::default()
+        .name("Generated Key Filter")
+        .description("Filter with a key generated by the engine")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+    assert!(!guid_eq(&generated.key(), &GUID::default()));
+    assert_ne!(generated.id(), keyed.id());
+    assert!(filter_exists(&transaction, generated.key()));
+
+    delete_filter(&transaction, generated.id()).expect("Should be able to delete filter by ID");
+    assert!(!filter_exists(&transaction, generated.key()));
+
+    let err = delete_filter_by_guid(&transaction, &generated.key())
+        .expect_err("Should not be able to delete a filter twice");
+    assert!(
+        matches!(err, WfpError::NotFound(ObjectType::Filter)),
+        "Unexpected error: {err}"
+    );
+}
+
+#[test]
+fn test_read_only_transaction() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x4ead0417_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Read-only Test Filter")
+        .description("Filter that is enumerated in a read-only transaction")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+    transaction
+        .commit()
+        .expect("Should be able to commit transaction");
+
+    let transaction =
+        Transaction::read_only(&engine).expect("Should be able to create read-only transaction");
+    assert!(filter_exists(&transaction, test_filter_guid));
+
+    let err = Transaction::read_only(&engine)
+        .err()
+        .expect("Should not be able to nest transactions");
+    assert!(err.is_txn_in_progress(), "Unexpected error: {err}");
+
+    transaction
+        .commit()
+        .expect("Should be able to commit read-only transaction");
+}
+
+#[test]
+fn test_session_info() {
+    let bfe = TestBfe::default();
+    let engine = bfe.open_with(
+        FilterEngineBuilder::default()
+            .name("Test Session")
+            .description("Session used to test session enumeration")
+            .txn_wait_timeout(Duration::from_millis(1500))
+            .dynamic(),
+    );
+    let other = bfe.open();
+
+    let session = engine
+        .session()
+        .expect("Should be able to find own session");
+    assert!(guid_eq(&session.key(), &engine.session_key()));
+    assert_eq!(session.name(), Some("Test Session"));
+    assert_eq!(
+        session.description(),
+        Some("Session used to test session enumeration")
+    );
+    assert_eq!(
+        session.txn_wait_timeout(),
+        Some(Duration::from_millis(1500))
+    );
+    assert_eq!(session.process_id(), std::process::id());
+    assert!(session.is_dynamic());
+    assert!(!session.is_kernel_mode());
+
+    let sessions = engine
+        .sessions()
+        .expect("Should be able to enumerate sessions");
+    for key in [engine.session_key(), other.session_key()] {
+        assert!(sessions.iter().any(|session| guid_eq(&session.key(), &key)));
+    }
+
+    let other_key = other.session_key();
+    drop(other);
+    let sessions = engine
+        .sessions()
+        .expect("Should be able to enumerate sessions");
+    assert!(
+        !sessions
+            .iter()
+            .any(|session| guid_eq(&session.key(), &other_key))
+    );
+}
+
+#[test]
+fn test_engine_options() {
+    let bfe = TestBfe::default();
+    let engine = bfe.open();
+    let observer = bfe.open();
+
+    let original = engine
+        .option(EngineOptionKind::NetEventMatchAnyKeywords)
+        .expect("Should be able to get engine option");
+
+    let keywords = NetEventKeywords::INBOUND_MCAST | NetEventKeywords::INBOUND_BCAST;
+    engine
+        .set_option(EngineOption::NetEventMatchAnyKeywords(keywords))
+        .expect("Should be able to set engine option");
+
+    // Engine options are not tied to a session
+    assert_eq!(
+        observer
+            .option(EngineOptionKind::NetEventMatchAnyKeywords)
+            .expect("Should be able to get engine option"),
+        EngineOption::NetEventMatchAnyKeywords(keywords)
+    );
+
+    engine
+        .set_option(original)
+        .expect("Should be able to restore engine option");
+    assert_eq!(
+        engine
+            .option(EngineOptionKind::NetEventMatchAnyKeywords)
+            .expect("Should be able to get engine option"),
+        original
+    );
+}
+
+#[test]
+fn test_enumerated_filter_layer() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e0001_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Bind Filter")
+        .description("Filter at the resource assignment layer")
+        .action(ActionType::Block)
+        .layer(Layer::ResourceAssignmentV4)
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+
+    let mut filter_enum =
+        FilterEnumerator::new(&transaction).expect("Should be able to enumerate filters");
+    let mut found = false;
+    while let Some(filter) = filter_enum.next() {
+        let filter = filter.expect("Should be able to get next filter");
+        if guid_eq(&filter.guid(), &test_filter_guid) {
+            assert_eq!(filter.layer(), Some(Layer::ResourceAssignmentV4));
+            found = true;
+        }
+    }
+    assert!(found, "Filter should be enumerated");
+}
+
+#[test]
+fn test_enumerated_filter_details() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e000d_1234_5678_9abc_def012345678);
+    let test_sublayer_guid = GUID::from_u128(0x1a7e000e_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    SubLayerBuilder::default()
+        .name("Audit Sublayer")
+        .description("Sublayer for enumerated filters")
+        .weight(100)
+        .guid(test_sublayer_guid)
+        .add(&transaction)
+        .expect("Should be able to add sublayer");
+    FilterBuilder::default()
+        .name("Audited Filter")
+        .description("Filter whose fields are read back")
+        .action(ActionType::Permit)
+        .layer(Layer::ConnectV4)
+        .sublayer(test_sublayer_guid)
+        .weight(FilterWeight::Range(WeightRange::try_from(3).unwrap()))
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .subnet_v4(Ipv4Addr::new(192, 168, 0, 0), 16)
+                .expect("Should be a valid prefix")
+                .build(),
+        )
+        .condition(
+            PortConditionBuilder::remote()
+                .range(80..=443)
+                .expect("Should be a valid range")
+                .build(),
+        )
+        .condition(ProtocolConditionBuilder::tcp().build())
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+
+    let mut filter_enum =
+        FilterEnumerator::new(&transaction).expect("Should be able to enumerate filters");
+    let mut found = false;
+    while let Some(filter) = filter_enum.next() {
+        let filter = filter.expect("Should be able to get next filter");
+        if !guid_eq(&filter.guid(), &test_filter_guid) {
+            continue;
+        }
+        found = true;
+
+        assert_eq!(filter.layer(), Some(Layer::ConnectV4));
+        assert!(guid_eq(&filter.layer_key(), Layer::ConnectV4.guid()));
+        assert!(guid_eq(&filter.sublayer(), &test_sublayer_guid));
+        assert_eq!(filter.action(), Some(ActionType::Permit));
+        assert!(filter.callout().is_none());
+        assert_eq!(
+            filter.weight(),
+            Some(FilterWeight::Range(WeightRange::try_from(3).unwrap()))
+        );
+        assert_eq!(filter.effective_weight().map(|w| w >> 60), Some(3));
+        assert!(!filter.flags().contains(FilterFlags::PERSISTENT));
+        assert_eq!(filter.lifetime(), FilterLifetime::Default);
+        assert!(filter.provider_data().is_empty());
+
+        let conditions = filter
+            .conditions()
+            .expect("Should be able to decode conditions");
+        let values: Vec<_> = conditions
+            .iter()
+            .map(|condition| (condition.field(), condition.value()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                (
+                    Some(ConditionField::RemoteAddress),
+                    TypedValue::Network("192.168.0.0/16".parse().unwrap())
+                ),
+                (
+                    Some(ConditionField::RemotePort),
+                    TypedValue::Range(
+                        Box::new(TypedValue::Port(80)),
+                        Box::new(TypedValue::Port(443))
+                    )
+                ),
+                (Some(ConditionField::Protocol), TypedValue::Protocol(6)),
+            ]
+        );
+    }
+    assert!(found, "Filter should be enumerated");
+}
+
+#[test]
+fn test_collect_filter_info() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e000f_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Collected Filter")
+        .description("Filter that is copied out of the enumerator")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV6)
+        .condition(PortConditionBuilder::remote().equal(53).build())
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+
+    let filters = FilterEnumerator::new(&transaction)
+        .expect("Should be able to enumerate filters")
+        .into_iter()
+        .collect::<std::io::Result<Vec<FilterInfo>>>()
+        .expect("Should be able to copy filters");
+
+    // The copies are independent of the enumerator and the transaction
+    let filter = std::thread::spawn(move || {
+        filters
+            .into_iter()
+            .find(|filter| guid_eq(&filter.guid(), &test_filter_guid))
+    })
+    .join()
+    .unwrap()
+    .expect("Filter should be enumerated");
+
+    assert_eq!(filter.name(), Some("Collected Filter"));
+    assert_eq!(
+        filter.description(),
+        Some("Filter that is copied out of the enumerator")
+    );
+    assert_eq!(filter.layer(), Some(Layer::ConnectV6));
+    assert_eq!(filter.action(), Some(ActionType::Block));
+    assert_eq!(filter.weight(), Some(FilterWeight::Auto));
+    assert_eq!(filter.lifetime(), FilterLifetime::Default);
+    assert_eq!(filter.conditions().len(), 1);
+    assert_eq!(filter.conditions()[0].value(), TypedValue::Port(53));
+}
+
+#[test]
+fn test_enum_template() {
+    let mut engine = open_engine();
+
+    let test_provider_guid = GUID::from_u128(0x1a7e0010_1234_5678_9abc_def012345678);
+    let block_filter_guid = GUID::from_u128(0x1a7e0011_1234_5678_9abc_def012345678);
+    let permit_filter_guid = GUID::from_u128(0x1a7e0012_1234_5678_9abc_def012345678);
+    let other_layer_filter_guid = GUID::from_u128(0x1a7e0013_1234_5678_9abc_def012345678);
+    let other_provider_filter_guid = GUID::from_u128(0x1a7e0014_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    ProviderBuilder::default()
+        .name("Template Provider")
+        .description("Provider whose filters are enumerated")
+        .guid(test_provider_guid)
+        .add(&transaction)
+        .expect("Should be able to add provider");
+
+    let filters = [
+        (
+            block_filter_guid,
+            Some(test_provider_guid),
+            Layer::ConnectV4,
+            ActionType::Block,
+            1,
+        ),
+        (
+            permit_filter_guid,
+            Some(test_provider_guid),
+            Layer::ConnectV4,
+            ActionType::Permit,
+            2,
+        ),
+        (
+            other_layer_filter_guid,
+            Some(test_provider_guid),
+            Layer::ConnectV6,
+            ActionType::Block,
+            3,
+        ),
+        (
+            other_provider_filter_guid,
+            None,
+            Layer::ConnectV4,
+            ActionType::Block,
+            4,
+        ),
+    ];
+    for (guid, provider, layer, action, weight) in filters {
+        let mut builder = FilterBuilder::default()
+            .name("Template Filter")
+            .description("Filter selected by an enumeration template")
+            .action(action)
+            .layer(layer)
+            .weight(FilterWeight::Exact(weight))
+            .guid(guid);
+        if let Some(provider) = provider {
+            builder = builder.provider(provider);
+        }
+        builder
+            .add(&transaction)
+            .expect("Should be able to add filter");
+    }
+
+    // Returns the indices of the test filters that are selected by `template`
+    let keys = filters.map(|(guid, ..)| guid);
+    let enumerate = |template: &FilterEnumTemplate| -> Vec<usize> {
+        FilterEnumerator::with_template(&transaction, template)
+            .expect("Should be able to enumerate filters")
+            .into_iter()
+            .filter_map(|filter| {
+                let guid = filter.expect("Should be able to copy filter").guid();
+                keys.iter().position(|key| guid_eq(key, &guid))
+            })
+            .collect()
+    };
+
+    let template = FilterEnumTemplate::new(Layer::ConnectV4)
+        .provider(test_provider_guid)
+        .flags(FilterEnumFlags::SORTED);
+    assert_eq!(enumerate(&template), [1, 0]);
+
+    let template = FilterEnumTemplate::new(Layer::ConnectV4)
+        .provider(test_provider_guid)
+        .action_mask(ActionMask::CALLOUT);
+    assert!(enumerate(&template).is_empty());
+
+    let template = FilterEnumTemplate::new(Layer::ConnectV6).provider(test_provider_guid);
+    assert_eq!(enumerate(&template), [2]);
+
+    let template = FilterEnumTemplate::new(Layer::ConnectV4).flags(FilterEnumFlags::SORTED);
+    assert_eq!(enumerate(&template), [3, 1, 0]);
+}
+
+#[test]
+fn test_enumerate_objects() {
+    let mut engine = open_engine();
+
+    let test_provider_guid = GUID::from_u128(0x1a7e0015_1234_5678_9abc_def012345678);
+    let test_sublayer_guid = GUID::from_u128(0x1a7e0016_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    ProviderBuilder::default()
+        .name("Enumerated Provider")
+        .description("Provider that is enumerated")
+        .service_name("TestService")
+        .guid(test_provider_guid)
+        .add(&transaction)
+        .expect("Should be able to add provider");
+    SubLayerBuilder::default()
+        .name("Enumerated SubLayer")
+        .description("Sublayer that is enumerated")
+        .weight(0x1234)
+        .provider(test_provider_guid)
+        .guid(test_sublayer_guid)
+        .add(&transaction)
+        .expect("Should be able to add sublayer");
+
+    let provider = ProviderEnumerator::new(&transaction)
+        .expect("Should be able to enumerate providers")
+        .map(|provider| provider.expect("Should be able to copy provider"))
+        .find(|provider| guid_eq(&provider.guid(), &test_provider_guid))
+        .expect("Should find the test provider");
+    assert_eq!(provider.name(), Some("Enumerated Provider"));
+    assert_eq!(provider.description(), Some("Provider that is enumerated"));
+    assert_eq!(provider.service_name(), Some("TestService"));
+    assert!(!provider.flags().contains(ProviderFlags::PERSISTENT));
+    assert!(provider.provider_data().is_empty());
+
+    let sublayers = SubLayerEnumerator::new(&transaction)
+        .expect("Should be able to enumerate sublayers")
+        .collect::<wfp::Result<Vec<SubLayerInfo>>>()
+        .expect("Should be able to copy sublayers");
+    let sublayer = sublayers
+        .iter()
+        .find(|sublayer| guid_eq(&sublayer.guid(), &test_sublayer_guid))
+        .expect("Should find the test sublayer");
+    assert_eq!(sublayer.name(), Some("Enumerated SubLayer"));
+    assert_eq!(sublayer.weight(), 0x1234);
+    assert!(
+        sublayer
+            .provider()
+            .is_some_and(|provider| guid_eq(&provider, &test_provider_guid))
+    );
+    assert!(!sublayer.flags().contains(SubLayerFlags::PERSISTENT));
+    let universal = GUID::from_u128(0xeebecc03_ced4_4380_819a_2734397b2b74);
+    assert!(
+        sublayers
+            .iter()
+            .any(|sublayer| guid_eq(&sublayer.guid(), &universal))
+    );
+
+    let layer = LayerEnumerator::new(&transaction)
+        .expect("Should be able to enumerate layers")
+        .map(|layer| layer.expect("Should be able to copy layer"))
+        .find(|layer| layer.layer() == Some(Layer::ConnectV4))
+        .expect("Should find the connect layer");
+    assert!(
+        layer
+            .flags()
+            .contains(LayerFlags::BUILTIN | LayerFlags::KERNEL)
+    );
+    let app_id = layer
+        .fields()
+        .iter()
+        .find(|field| field.field() == Some(ConditionField::AppId))
+        .expect("Should find the application ID field");
+    assert_eq!(app_id.data_type(), Some(DataType::ByteBlob));
+    assert_eq!(app_id.field_type(), Some(FieldType::RawData));
+    let remote_address = layer
+        .fields()
+        .iter()
+        .find(|field| field.field() == Some(ConditionField::RemoteAddress))
+        .expect("Should find the remote address field");
+    assert_eq!(remote_address.field_type(), Some(FieldType::IpAddress));
+
+    // Callouts and provider contexts cannot be added, but they can still be enumerated
+    for callout in
+        CalloutEnumerator::new(&transaction).expect("Should be able to enumerate callouts")
+    {
+        callout.expect("Should be able to copy callout");
+    }
+    for context in ProviderContextEnumerator::new(&transaction)
+        .expect("Should be able to enumerate provider contexts")
+    {
+        context.expect("Should be able to copy provider context");
+    }
+}
+
+#[test]
+fn test_sublayer_lifecycle() {
+    let mut engine = open_engine();
+
+    let test_provider_guid = GUID::from_u128(0x1a7e0017_1234_5678_9abc_def012345678);
+    let test_sublayer_guid = GUID::from_u128(0x1a7e0018_1234_5678_9abc_def012345678);
+    let test_filter_guid = GUID::from_u128(0x1a7e0019_1234_5678_9abc_def012345678);
+    let persistent_sublayer_guid = GUID::from_u128(0x1a7e001a_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    ProviderBuilder::default()
+        .name("Sublayer Provider")
+        .guid(test_provider_guid)
+        .add(&transaction)
+        .expect("Should be able to add provider");
+    SubLayerBuilder::default()
+        .name("Lifecycle SubLayer")
+        .description("Sublayer that is looked up and deleted")
+        .weight(0x4321)
+        .provider(test_provider_guid)
+        .provider_data([1, 2, 3])
+        .guid(test_sublayer_guid)
+        .add(&transaction)
+        .expect("Should be able to add sublayer");
+
+    let sublayer =
+        get_sublayer_by_key(&transaction, &test_sublayer_guid).expect("Should find sublayer");
+    assert!(guid_eq(&sublayer.guid(), &test_sublayer_guid));
+    assert_eq!(sublayer.name(), Some("Lifecycle SubLayer"));
+    assert_eq!(sublayer.weight(), 0x4321);
+    assert_eq!(sublayer.provider_data(), [1, 2, 3]);
+    assert!(!sublayer.flags().contains(SubLayerFlags::PERSISTENT));
+
+    // Persistent sublayers cannot be added by a dynamic session
+    let result = SubLayerBuilder::default()
+        .name("Persistent SubLayer")
+        .persistent()
+        .guid(persistent_sublayer_guid)
+        .add(&transaction);
+    assert!(matches!(result, Err(WfpError::DynamicSessionInProgress)));
+
+    // Sublayers cannot be deleted while they contain filters
+    FilterBuilder::default()
+        .name("Sublayer Filter")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .sublayer(test_sublayer_guid)
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+    let err = delete_sublayer_by_guid(&transaction, &test_sublayer_guid).unwrap_err();
+    assert!(err.is_in_use());
+
+    delete_filter_by_guid(&transaction, &test_filter_guid).expect("Should delete filter");
+    delete_sublayer_by_guid(&transaction, &test_sublayer_guid).expect("Should delete sublayer");
+
+    let err = get_sublayer_by_key(&transaction, &test_sublayer_guid).unwrap_err();
+    assert!(matches!(err, WfpError::NotFound(ObjectType::SubLayer)));
+    let err = delete_sublayer_by_guid(&transaction, &test_sublayer_guid).unwrap_err();
+    assert!(matches!(err, WfpError::NotFound(ObjectType::SubLayer)));
+}
+
+#[test]
+fn test_get_objects_by_key() {
+    let mut engine = open_engine();
+
+    let test_provider_guid = GUID::from_u128(0x1a7e001b_1234_5678_9abc_def012345678);
+    let test_filter_guid = GUID::from_u128(0x1a7e001c_1234_5678_9abc_def012345678);
+    let missing_guid = GUID::from_u128(0x1a7e001d_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    ProviderBuilder::default()
+        .name("Lookup Provider")
+        .description("Provider that is looked up by key")
+        .guid(test_provider_guid)
+        .add(&transaction)
+        .expect("Should be able to add provider");
+    let added = FilterBuilder::default()
+        .name("Lookup Filter")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .provider(test_provider_guid)
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add filter");
+
+    let by_id = get_filter_by_id(&transaction, added.id()).expect("Should find filter by ID");
+    let by_key =
+        get_filter_by_key(&transaction, &test_filter_guid).expect("Should find filter by key");
+    for filter in [&by_id, &by_key] {
+        assert_eq!(filter.id(), added.id());
+        assert!(guid_eq(&filter.guid(), &test_filter_guid));
+        assert_eq!(filter.name(), Some("Lookup Filter"));
+        assert_eq!(filter.layer(), Some(Layer::ConnectV4));
+        assert_eq!(filter.action(), Some(ActionType::Block));
+    }
+
+    let provider =
+        get_provider_by_key(&transaction, &test_provider_guid).expect("Should find provider");
+    assert!(guid_eq(&provider.guid(), &test_provider_guid));
+    assert_eq!(provider.name(), Some("Lookup Provider"));
+    assert_eq!(
+        provider.description(),
+        Some("Provider that is looked up by key")
+    );
+
+    let layer = get_layer_by_key(&transaction, Layer::ConnectV4.guid()).expect("Should find layer");
+    assert_eq!(layer.layer(), Some(Layer::ConnectV4));
+    assert!(
+        layer
+            .fields()
+            .iter()
+            .any(|field| field.field() == Some(ConditionField::RemotePort))
+    );
+
+    // Missing objects are reported as not found
+    let err = get_filter_by_key(&transaction, &missing_guid).unwrap_err();
+    assert!(matches!(err, WfpError::NotFound(ObjectType::Filter)));
+    assert!(err.is_not_found());
+    let err = get_provider_by_key(&transaction, &missing_guid).unwrap_err();
+    assert!(matches!(err, WfpError::NotFound(ObjectType::Provider)));
+    assert!(err.is_not_found());
+    let err = get_layer_by_key(&transaction, &missing_guid).unwrap_err();
+    assert!(matches!(err, WfpError::NotFound(ObjectType::Layer)));
+
+    delete_filter(&transaction, added.id()).expect("Should delete filter");
+    let err = get_filter_by_id(&transaction, added.id()).unwrap_err();
+    assert!(matches!(err, WfpError::NotFound(ObjectType::Filter)));
+}
+
+#[test]
+fn test_incompatible_condition() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e0002_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+
+    // Ports are not available at the IP packet layers
+    let result = FilterBuilder::default()
+        .name("Port Filter")
+        .description("Port condition at the IP packet layer")
+        .action(ActionType::Block)
+        .layer(Layer::InboundIpPacketV4)
+        .condition(PortConditionBuilder::local().equal(80).build())
+        .guid(test_filter_guid)
+        .add(&transaction);
+    assert!(matches!(
+        result,
+        Err(WfpError::IncompatibleCondition {
+            layer: Layer::InboundIpPacketV4,
+            field: Some(ConditionField::LocalPort),
+            reason: ConditionError::FieldNotFound,
+        })
+    ));
+
+    // IPv6 addresses cannot be used at an IPv4 layer
+    let result = FilterBuilder::default()
+        .name("Subnet Filter")
+        .description("IPv6 subnet at an IPv4 layer")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .subnet_v6("fe80::".parse::<Ipv6Addr>().unwrap(), 10)
+                .expect("Should be a valid prefix")
+                .build(),
+        )
+        .guid(test_filter_guid)
+        .add(&transaction);
+    let err = result.expect_err("Should reject v6 subnet at ConnectV4");
+    assert!(matches!(
+        err,
+        WfpError::IncompatibleCondition {
+            reason: ConditionError::AddressFamilyMismatch,
+            ..
+        }
+    ));
+    assert!(err.is_invalid_condition());
+
+    assert!(!filter_exists(&transaction, test_filter_guid));
+}
+
+#[test]
+fn test_range_conditions() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e0003_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Range Filter")
+        .description("Blocks a port range and an address range")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV6)
+        .condition(
+            PortConditionBuilder::remote()
+                .range(1024..=65535)
+                .expect("Should be a valid range")
+                .build(),
+        )
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .range(
+                    "2001:db8::".parse::<Ipv6Addr>().unwrap(),
+                    "2001:db8::ffff".parse::<Ipv6Addr>().unwrap(),
+                )
+                .expect("Should be a valid range")
+                .build(),
+        )
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add range filter");
+
+    assert!(filter_exists(&transaction, test_filter_guid));
+
+    // A v4 range does not match the address family of a v6 layer
+    let result = FilterBuilder::default()
+        .name("Range Filter")
+        .description("IPv4 range at an IPv6 layer")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV6)
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .range(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 9))
+                .expect("Should be a valid range")
+                .build(),
+        )
+        .add(&transaction);
+    assert!(matches!(
+        result,
+        Err(WfpError::IncompatibleCondition {
+            reason: ConditionError::AddressFamilyMismatch,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_flags_and_negated_conditions() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e0004_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Non-loopback Filter")
+        .description("Blocks non-loopback traffic except DNS")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .condition(
+            FlagsConditionBuilder::none_set(
+                ConditionFlags::IS_LOOPBACK | ConditionFlags::IS_REAUTHORIZE,
+            )
+            .build(),
+        )
+        .condition(PortConditionBuilder::remote().not_equal(53).build())
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add flags filter");
+
+    assert!(filter_exists(&transaction, test_filter_guid));
+}
+
+#[test]
+fn test_user_condition() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e0005_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Service Filter")
+        .description("Blocks connections made by NetworkService")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .condition(
+            UserConditionBuilder::local()
+                .sddl("O:SYG:SYD:(A;;CC;;;NS)")
+                .expect("Should be able to parse SDDL")
+                .build(),
+        )
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add user filter");
+    assert!(filter_exists(&transaction, test_filter_guid));
+
+    // The user is not known at the IP packet layers
+    let result = FilterBuilder::default()
+        .name("Service Filter")
+        .description("User condition at the IP packet layer")
+        .action(ActionType::Block)
+        .layer(Layer::OutboundIpPacketV4)
+        .condition(
+            UserConditionBuilder::local()
+                .allow("S-1-5-20".parse().expect("Should be able to parse SID"))
+                .build(),
+        )
+        .add(&transaction);
+    assert!(matches!(
+        result,
+        Err(WfpError::IncompatibleCondition {
+            field: Some(ConditionField::UserId),
+            reason: ConditionError::FieldNotFound,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_address_condition() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e0007_1234_5678_9abc_def012345678);
+    let dns_server = Ipv6Addr::new(0x2620, 0xfe, 0, 0, 0, 0, 0, 0xfe);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Address Filter")
+        .description("Blocks connections to a single IPv6 host")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV6)
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .address(dns_server)
+                .build(),
+        )
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add address filter");
+    assert!(filter_exists(&transaction, test_filter_guid));
+
+    // The IPv6 address does not match the address family of a v4 layer
+    let result = FilterBuilder::default()
+        .name("Address Filter")
+        .description("IPv6 address at an IPv4 layer")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .address(dns_server)
+                .build(),
+        )
+        .add(&transaction);
+    assert!(matches!(
+        result,
+        Err(WfpError::IncompatibleCondition {
+            field: Some(ConditionField::RemoteAddress),
+            reason: ConditionError::AddressFamilyMismatch,
+            ..
+        })
+    ));
+
+    // Unless it is an IPv4-mapped address that is unmapped
+    FilterBuilder::default()
+        .name("Address Filter")
+        .description("IPv4-mapped address at an IPv4 layer")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .condition(
+            IpAddressConditionBuilder::remote()
+                .unmap_v4()
+                .address(Ipv4Addr::new(9, 9, 9, 9).to_ipv6_mapped())
+                .build(),
+        )
+        .add(&transaction)
+        .expect("Should be able to add unmapped address filter");
+}
+
+#[test]
+fn test_ip_set_conditions() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e0008_1234_5678_9abc_def012345678);
+
+    // Block everything except private networks and the VPN server
+    let excluded: IpSet = ["10.0.0.0/8", "192.168.0.0/16", "203.0.113.7"]
+        .iter()
+        .map(|network| network.parse::<IpNetwork>().unwrap())
+        .collect();
+    let blocked = IpSet::all_v4().difference(&excluded);
+    let conditions = blocked
+        .conditions(IpFamily::V4, IpAddressConditionBuilder::remote())
+        .expect("Should be able to build conditions");
+    assert_eq!(conditions.len(), 4);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    let mut filter = FilterBuilder::default()
+        .name("Kill Switch")
+        .description("Blocks everything except private networks and the VPN server")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .guid(test_filter_guid);
+    for condition in conditions {
+        filter = filter.condition(condition);
+    }
+    filter
+        .add(&transaction)
+        .expect("Should be able to add filter with set conditions");
+    assert!(filter_exists(&transaction, test_filter_guid));
+}
+
+#[test]
+fn test_port_set_conditions() {
+    let mut engine = open_engine();
+
+    let ports: PortSet = "22, 80, 443, 8000-8100, !8080"
+        .parse()
+        .expect("Should be able to parse port set");
+    let groups = ports.split_conditions(
+        PortConditionBuilder::remote(),
+        NonZeroUsize::new(3).unwrap(),
+    );
+    assert_eq!(groups.len(), 2);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    for (i, conditions) in groups.into_iter().enumerate() {
+        let guid = GUID::from_u128(0x1a7e0009_1234_5678_9abc_def012345678 + i as u128);
+        let mut filter = FilterBuilder::default()
+            .name("Port Set Filter")
+            .description("Blocks a set of remote ports")
+            .action(ActionType::Block)
+            .layer(Layer::ConnectV4)
+            .guid(guid);
+        for condition in conditions {
+            filter = filter.condition(condition);
+        }
+        filter
+            .add(&transaction)
+            .expect("Should be able to add port set filter");
+        assert!(filter_exists(&transaction, guid));
+    }
+}
+
+#[test]
+fn test_package_condition() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e0006_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Package Filter")
+        .description("Blocks connections made by the Calculator app")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .condition(
+            PackageConditionBuilder::default()
+                .family_name("Microsoft.WindowsCalculator_8wekyb3d8bbwe")
+                .expect("Should be able to derive package SID")
+                .build(),
+        )
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add package filter");
+    assert!(filter_exists(&transaction, test_filter_guid));
+}
+
+#[test]
+fn test_interface_and_direction_conditions() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e000b_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Inbound Teredo Filter")
+        .description("Blocks inbound Teredo datagrams outside of the default compartment")
+        .action(ActionType::Block)
+        .layer(Layer::DatagramDataV6)
+        .condition(DirectionConditionBuilder::equal(Direction::Inbound).build())
+        .condition(InterfaceTypeConditionBuilder::equal(InterfaceType::Tunnel).build())
+        .condition(TunnelTypeConditionBuilder::equal(TunnelType::Teredo).build())
+        .condition(
+            CompartmentIdConditionBuilder::not_equal(
+                CompartmentIdConditionBuilder::DEFAULT_COMPARTMENT_ID,
+            )
+            .build(),
+        )
+        .condition(
+            AddressTypeConditionBuilder::local()
+                .equal(AddressType::Unicast)
+                .build(),
+        )
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add interface filter");
+    assert!(filter_exists(&transaction, test_filter_guid));
+
+    // Direction is not available at the connect layer
+    let result = FilterBuilder::default()
+        .name("Invalid Direction Filter")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV4)
+        .condition(DirectionConditionBuilder::equal(Direction::Outbound).build())
+        .add(&transaction);
+    assert!(matches!(
+        result,
+        Err(WfpError::IncompatibleCondition {
+            reason: ConditionError::FieldNotFound,
+            ..
+        })
+    ));
+
+    // The next hop address must match the IP version of the layer
+    let result = FilterBuilder::default()
+        .name("Invalid Next Hop Filter")
+        .action(ActionType::Block)
+        .layer(Layer::ConnectV6)
+        .condition(
+            IpAddressConditionBuilder::next_hop()
+                .address(Ipv4Addr::new(192, 168, 1, 1))
+                .build(),
+        )
+        .add(&transaction);
+    assert!(matches!(
+        result,
+        Err(WfpError::IncompatibleCondition {
+            reason: ConditionError::AddressFamilyMismatch,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_next_hop_interface_condition() {
+    let mut engine = open_engine();
+
+    let test_filter_guid = GUID::from_u128(0x1a7e000c_1234_5678_9abc_def012345678);
+
+    let transaction = Transaction::new(&mut engine).expect("Should be able to create transaction");
+    FilterBuilder::default()
+        .name("Next Hop Interface Filter")
+        .description("Permits connections that leave through a specific interface")
+        .action(ActionType::Permit)
+        .layer(Layer::ConnectV4)
+        .condition(
+            InterfaceConditionBuilder::next_hop()
+                .luid(NetLuid::new(InterfaceType::Ethernet as u16, 1))
+                .build(),
+        )
+        .guid(test_filter_guid)
+        .add(&transaction)
+        .expect("Should be able to add next hop interface filter");
+    assert!(filter_exists(&transaction, test_filter_guid));
+}